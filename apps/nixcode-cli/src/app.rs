@@ -1,16 +1,29 @@
-use crate::command_popup::CommandPopup;
+use crate::command_popup::{split_command, CommandPopup};
+use crate::env_file;
+use crate::file_picker_popup::{FilePickerPopup, MAX_PICKER_FILES};
 use crate::input_mode::InputMode;
+use crate::notifier::Notifier;
+use crate::recent_projects::{record_recent_project, RecentProject, RecentProjects};
+use crate::status_bar::ToolActivity;
+use crate::throttle::{UpdateThrottle, CHAT_UPDATE_INTERVAL};
 use crate::utils::highlights::THEME;
 use crate::widgets::chat::Chat;
+use crate::widgets::debug_overlay::{DebugInfo, DebugOverlay};
+use crate::widgets::diff_view::DiffView;
 use anyhow::Result;
 use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
+use nixcode::config::Config;
 use nixcode::events::NixcodeEvent;
+use nixcode::project::Project;
 use nixcode::{NewNixcodeResult, Nixcode};
+use nixcode_llm_sdk::errors::llm::LLMError;
 use nixcode_llm_sdk::ErrorContent;
 use ratatui::prelude::{Color, Modifier, Stylize};
 use ratatui::widgets::Block;
 use ratatui::{DefaultTerminal, Frame};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Instant;
 use tokio_stream::StreamExt;
 
 #[allow(dead_code)]
@@ -19,11 +32,13 @@ pub enum AppEvent {
     Command(String),
     UpdateChatWidgets,
     RetryLastMessage,
+    ContinueLastMessage,
     RemoveLastMessage,
     ClearChat,
     Quit,
     Render,
     ChatError(ErrorContent),
+    InsertFilePath(String),
 }
 
 enum AppView {
@@ -44,6 +59,27 @@ pub struct App {
     nixcode: Arc<Nixcode>,
 
     command_popup: CommandPopup,
+    file_picker_popup: FilePickerPopup,
+    tool_activity: ToolActivity,
+    debug_overlay: DebugOverlay,
+    diff_view: DiffView,
+    chat_update_throttle: UpdateThrottle,
+    notifier: Notifier,
+}
+
+/// Creates the client for the project in `project_dir` with its config, using `model`
+/// instead of the configured default when given
+pub fn create_nixcode(
+    project_dir: &Path,
+    model: Option<String>,
+) -> Result<NewNixcodeResult, LLMError> {
+    let project = Project::new(project_dir.to_path_buf());
+    let mut config = Config::load_for_project(project_dir).unwrap_or_else(|_| Config::new());
+    if let Some(model) = model {
+        config.llm.default_model = Some(model);
+    }
+
+    Nixcode::new_with_config(project, config)
 }
 
 impl App {
@@ -54,12 +90,19 @@ impl App {
         let (nixcode_rx, client) = nixcode;
         let nixcode = Arc::new(client);
         let chat = Chat::new(nixcode.clone(), input_mode, tx.clone());
+        let notifier = Notifier::new(&nixcode.get_config().ui);
 
         Ok(App {
             input_mode,
             should_quit: false,
             current_view: AppView::Chat,
             command_popup: CommandPopup::new(tx.clone()),
+            file_picker_popup: FilePickerPopup::new(tx.clone()),
+            tool_activity: ToolActivity::default(),
+            debug_overlay: DebugOverlay::default(),
+            diff_view: DiffView::default(),
+            chat_update_throttle: UpdateThrottle::new(CHAT_UPDATE_INTERVAL),
+            notifier,
             chat_view: chat,
             nixcode,
             rx,
@@ -68,7 +111,21 @@ impl App {
         })
     }
 
+    /// Attaches piped input to the first message and sends the startup prompt
+    pub async fn seed_first_message(
+        &mut self,
+        piped_input: Option<String>,
+        prompt: Option<String>,
+    ) {
+        self.chat_view.seed_first_message(piped_input, prompt).await;
+    }
+
     async fn handle_input_events(&mut self, event: Event) {
+        if self.diff_view.is_visible() && matches!(self.input_mode, InputMode::Normal) {
+            self.handle_diff_view_input_events(&event);
+            return;
+        }
+
         match self.current_view {
             AppView::Chat => {
                 self.chat_view
@@ -82,6 +139,7 @@ impl App {
             InputMode::Insert => self.handle_insert_input_events(&event),
             InputMode::Normal => self.handle_normal_input_events(&event),
             InputMode::Command => self.handle_command_input_events(event),
+            InputMode::FilePicker => self.handle_file_picker_input_events(&event),
         }
     }
 
@@ -99,6 +157,29 @@ impl App {
 
     fn handle_insert_input_events(&mut self, event: &Event) {
         self.handle_esc_normal_mode(event);
+
+        if let Event::Key(key) = event {
+            if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('@') {
+                self.open_file_picker();
+            }
+        }
+    }
+
+    fn handle_file_picker_input_events(&mut self, event: &Event) {
+        if let Event::Key(key) = event {
+            if key.kind == KeyEventKind::Press && key.code == KeyCode::Esc {
+                self.set_input_mode(InputMode::Insert);
+                return;
+            }
+        }
+
+        self.file_picker_popup.handle_input_event(event);
+    }
+
+    fn open_file_picker(&mut self) {
+        let files = self.nixcode.get_project().list_files(MAX_PICKER_FILES);
+        self.file_picker_popup.open(files);
+        self.set_input_mode(InputMode::FilePicker);
     }
 
     fn handle_command_input_events(&mut self, event: Event) {
@@ -106,6 +187,22 @@ impl App {
         self.command_popup.handle_input_event(&event);
     }
 
+    fn handle_diff_view_input_events(&mut self, event: &Event) {
+        if let Event::Key(key) = event {
+            if key.kind != KeyEventKind::Press {
+                return;
+            }
+
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => self.diff_view.scroll_down(),
+                KeyCode::Char('k') | KeyCode::Up => self.diff_view.scroll_up(),
+                KeyCode::Esc | KeyCode::Char('q') => self.diff_view.close(),
+                KeyCode::Char(':') => self.set_input_mode(InputMode::Command),
+                _ => (),
+            }
+        }
+    }
+
     fn handle_normal_input_events(&mut self, event: &Event) {
         match event {
             Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
@@ -128,6 +225,7 @@ impl App {
 
     pub async fn run(&mut self, terminal: &mut DefaultTerminal) -> tokio::io::Result<()> {
         let mut events = EventStream::new();
+        self.record_recent_project().await;
 
         while !self.should_quit {
             self.draw(terminal).await?;
@@ -142,15 +240,175 @@ impl App {
                 Some(nixcode_event) = self.nixcode_rx.recv() => {
                     self.handle_nixcode_event(nixcode_event).await;
                 }
+                _ = tokio::time::sleep(CHAT_UPDATE_INTERVAL), if self.chat_update_throttle.is_pending() => {
+                    if self.chat_update_throttle.take_pending(Instant::now()) {
+                        self.chat_view.update_chat_widgets().await;
+                    }
+                }
+            }
+
+            if self.debug_overlay.is_visible() {
+                self.update_debug_overlay().await;
             }
         }
 
+        self.record_recent_project().await;
+
         Ok(())
     }
 
+    /// Saves the current project with its model and session to the recent projects
+    async fn record_recent_project(&self) {
+        record_recent_project(RecentProject {
+            path: self.nixcode.get_project().get_cwd(),
+            model: Some(self.nixcode.get_model().to_string()),
+            session: Some(self.nixcode.get_active_session().await),
+        });
+    }
+
+    /// Lists the recent projects, or opens project `args` (a number from the list or a
+    /// path) with the model last used there
+    async fn show_or_switch_project(&mut self, args: &str) {
+        let recent_projects = RecentProjects::get_default_path()
+            .map(|path| RecentProjects::load(&path))
+            .unwrap_or_default();
+
+        if args.is_empty() {
+            let current = self.nixcode.get_project().get_cwd();
+            let projects = recent_projects
+                .get_projects()
+                .iter()
+                .enumerate()
+                .map(|(index, project)| {
+                    let marker = if project.path == current { "*" } else { " " };
+                    format!(
+                        "{} {}. {} [{}] session: {}",
+                        marker,
+                        index + 1,
+                        project.path.display(),
+                        project.model.as_deref().unwrap_or("default model"),
+                        project.session.as_deref().unwrap_or("-"),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let notice = format!(
+                "Recent projects (:project <number|path> to switch):\n{}",
+                projects.join("\n")
+            );
+            self.chat_view.show_notice(notice).await;
+            return;
+        }
+
+        let has_tools = !self.nixcode.get_tools_to_execute().await.is_empty();
+        if has_tools || self.nixcode.is_waiting().await {
+            let error = ErrorContent::new(
+                "invalid_command",
+                "Wait for the response and its tools to finish (or :cancel) before switching projects",
+            );
+            self.chat_view.on_error(error).await;
+            return;
+        }
+
+        let (project_dir, model) = match args.parse::<usize>() {
+            Ok(number) => match recent_projects.get_by_number(number) {
+                Some(project) => (project.path.clone(), project.model.clone()),
+                None => {
+                    let message = format!("No recent project number {}", number);
+                    let error = ErrorContent::new("invalid_command", message);
+                    self.chat_view.on_error(error).await;
+                    return;
+                }
+            },
+            Err(_) => {
+                let path = self
+                    .nixcode
+                    .get_project()
+                    .get_cwd()
+                    .join(PathBuf::from(args));
+                let model = recent_projects
+                    .get_projects()
+                    .iter()
+                    .find(|project| path.canonicalize().ok().as_ref() == Some(&project.path))
+                    .and_then(|project| project.model.clone());
+                (path, model)
+            }
+        };
+
+        let project_dir = match project_dir.canonicalize() {
+            Ok(project_dir) if project_dir.is_dir() => project_dir,
+            _ => {
+                let message = format!("Project directory {} does not exist", project_dir.display());
+                let error = ErrorContent::new("invalid_command", message);
+                self.chat_view.on_error(error).await;
+                return;
+            }
+        };
+
+        self.record_recent_project().await;
+        env_file::reload_env_file(&project_dir);
+
+        match create_nixcode(&project_dir, model) {
+            Ok(nixcode) => self.set_nixcode(nixcode),
+            Err(err) => {
+                let message = format!("Failed to open {}: {:?}", project_dir.display(), err);
+                let error = ErrorContent::new("project_failed", message);
+                self.chat_view.on_error(error).await;
+                return;
+            }
+        }
+
+        self.record_recent_project().await;
+        self.chat_view.update_chat_widgets().await;
+    }
+
+    /// Replaces the client and everything bound to it, like on startup
+    fn set_nixcode(&mut self, nixcode: NewNixcodeResult) {
+        let (nixcode_rx, client) = nixcode;
+        self.nixcode = Arc::new(client);
+        self.nixcode_rx = nixcode_rx;
+        self.chat_view = Chat::new(self.nixcode.clone(), self.input_mode, self.tx.clone());
+        self.notifier = Notifier::new(&self.nixcode.get_config().ui);
+        self.tool_activity.reset();
+        self.diff_view.close();
+    }
+
+    /// Sends the messages queued while the agent was busy, returns false when there
+    /// were none
+    async fn flush_queued_messages(&self) -> bool {
+        if self.nixcode.get_queued_messages().await.is_empty() {
+            return false;
+        }
+
+        let nixcode = self.nixcode.clone();
+        tokio::spawn(async move {
+            nixcode.send_queued_messages().await;
+        });
+
+        true
+    }
+
     async fn handle_nixcode_event(&mut self, event: NixcodeEvent) {
         match event {
+            NixcodeEvent::ToolStart(_) => {
+                self.tool_activity.on_tool_start();
+                self.chat_view.update_chat_widgets().await;
+            }
+            NixcodeEvent::ToolEnd(_) => {
+                self.tool_activity.on_tool_end(false);
+                self.chat_view.update_chat_widgets().await;
+            }
+            NixcodeEvent::ToolError(_) => {
+                self.tool_activity.on_tool_end(true);
+                self.chat_view.update_chat_widgets().await;
+            }
+            NixcodeEvent::CommandOutput(line) => {
+                self.tool_activity.on_command_output(&line);
+            }
+            NixcodeEvent::TurnSummary(index, summary) => {
+                self.chat_view.add_turn_summary(index, summary).await;
+            }
             NixcodeEvent::ToolsFinished => {
+                self.tool_activity.on_tools_finished();
                 let nixcode = self.nixcode.clone();
                 tokio::spawn(async move {
                     nixcode.send_tools_results().await;
@@ -158,9 +416,37 @@ impl App {
                 self.chat_view.update_chat_widgets().await;
             }
             NixcodeEvent::Error(error) => {
+                if !self.flush_queued_messages().await {
+                    self.notifier.on_run_end(Instant::now(), "The run failed");
+                }
                 self.tx.send(AppEvent::ChatError(error.into())).ok();
             }
-            _ => self.chat_view.update_chat_widgets().await,
+            NixcodeEvent::GeneratingResponse => {
+                self.notifier.on_run_start(Instant::now());
+                self.chat_update_throttle.mark_updated(Instant::now());
+                self.chat_view.update_chat_widgets().await
+            }
+            NixcodeEvent::GeneratedResponse => {
+                // The run continues while the response has tools to execute, or with
+                // the messages queued meanwhile
+                let has_tools = !self.nixcode.get_tools_to_execute().await.is_empty();
+                if !has_tools && !self.flush_queued_messages().await {
+                    self.notifier
+                        .on_run_end(Instant::now(), "The response is ready");
+                }
+                self.chat_update_throttle.mark_updated(Instant::now());
+                self.chat_view.update_chat_widgets().await
+            }
+            // Streamed deltas re-layout the whole chat, coalesce them while streaming
+            NixcodeEvent::MessageUpdated => {
+                if self.chat_update_throttle.request(Instant::now()) {
+                    self.chat_view.update_chat_widgets().await;
+                }
+            }
+            _ => {
+                self.chat_update_throttle.mark_updated(Instant::now());
+                self.chat_view.update_chat_widgets().await
+            }
         }
     }
 
@@ -172,12 +458,25 @@ impl App {
             AppEvent::UpdateChatWidgets => self.chat_view.update_chat_widgets().await,
             AppEvent::Render => (),
             AppEvent::RetryLastMessage => self.chat_view.retry_last_message().await,
-            AppEvent::ClearChat => self.chat_view.clear_chat().await,
+            AppEvent::ContinueLastMessage => self.chat_view.continue_last_message().await,
+            AppEvent::ClearChat => {
+                self.tool_activity.reset();
+                self.chat_view.clear_chat().await
+            }
             AppEvent::RemoveLastMessage => self.chat_view.remove_last_message().await,
             AppEvent::ChatError(error) => self.chat_view.on_error(error).await,
+            AppEvent::InsertFilePath(path) => {
+                self.chat_view.insert_text(&path);
+                self.set_input_mode(InputMode::Insert);
+            }
         }
     }
 
+    async fn update_debug_overlay(&mut self) {
+        let info = DebugInfo::from_client(&self.nixcode).await;
+        self.debug_overlay.set_info(info);
+    }
+
     async fn draw(&mut self, terminal: &mut DefaultTerminal) -> tokio::io::Result<()> {
         terminal.draw(|frame| self.draw_frame(frame))?;
 
@@ -206,7 +505,15 @@ impl App {
             AppView::Chat => self.chat_view.render_frame(frame, main_area),
         }
 
-        frame.render_widget(StatusBar::new(self.input_mode), status_area);
+        if self.diff_view.is_visible() {
+            frame.render_widget(&self.diff_view, main_area);
+        }
+
+        if self.debug_overlay.is_visible() {
+            frame.render_widget(&self.debug_overlay, main_area);
+        }
+
+        frame.render_widget(StatusBar::new(self.input_mode, self.tool_activity.clone()), status_area);
         let mut cursor_position: Option<Position> = None;
 
         if let InputMode::Command = self.input_mode {
@@ -216,6 +523,13 @@ impl App {
 
             frame.render_widget(Block::new().add_modifier(Modifier::DIM), main_area);
             frame.render_widget(&self.command_popup, popup_area);
+        } else if let InputMode::FilePicker = self.input_mode {
+            let popup_area = crate::popup_utils::popup_area(area, 60);
+            let (x, y) = self.file_picker_popup.get_input_position(popup_area);
+            cursor_position = Some(Position::new(x, y));
+
+            frame.render_widget(Block::new().add_modifier(Modifier::DIM), main_area);
+            frame.render_widget(&self.file_picker_popup, popup_area);
         } else if let InputMode::Insert = self.input_mode {
             let (x, y) = self.chat_view.get_cursor_position(main_area);
             cursor_position = Some(Position::new(x, y));
@@ -227,7 +541,7 @@ impl App {
     }
 
     async fn execute_command(&mut self, command: String) {
-        let command = command.trim();
+        let (command, args) = split_command(&command);
         match command {
             "quit" => self.quit(),
             "clear" => {
@@ -236,6 +550,42 @@ impl App {
             "retry" => {
                 self.tx.send(AppEvent::RetryLastMessage).ok();
             }
+            "continue" => {
+                self.tx.send(AppEvent::ContinueLastMessage).ok();
+            }
+            "cancel" => {
+                self.chat_view.cancel_tools().await;
+                self.tool_activity.on_tools_finished();
+                self.flush_queued_messages().await;
+            }
+            "pin" | "unpin" => {
+                let pinned = command == "pin";
+                self.chat_view.set_message_pinned(args, pinned).await;
+            }
+            "collapse" | "expand" => {
+                let collapsed = command == "collapse";
+                self.chat_view.set_message_collapsed(args, collapsed).await;
+            }
+            "new" => self.chat_view.new_session(args).await,
+            "sessions" => self.chat_view.show_or_switch_session(args).await,
+            "project" => self.show_or_switch_project(args).await,
+            "export" => self.chat_view.export_markdown(args).await,
+            "template" => self.chat_view.send_template(args).await,
+            "why" => self.chat_view.explain_last_error().await,
+            "cache" => self.chat_view.show_cache_stats().await,
+            "image" => self.chat_view.attach_image(args).await,
+            "toolchoice" => self.chat_view.set_tool_choice(args).await,
+            "diff" => match self.nixcode.get_working_tree_diff().await {
+                Ok(diff) => self.diff_view.open(&diff),
+                Err(error) => {
+                    let error = ErrorContent::new("diff_failed", error);
+                    self.chat_view.on_error(error).await;
+                }
+            },
+            "debug" => {
+                self.debug_overlay.toggle();
+                self.update_debug_overlay().await;
+            }
             "remove-last-message" => {
                 self.tx.send(AppEvent::RemoveLastMessage).ok();
             },
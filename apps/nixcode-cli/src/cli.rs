@@ -0,0 +1,24 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tracing_subscriber::filter::LevelFilter;
+
+/// Command line arguments of the nixcode terminal app
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    /// Log level for the log file in .nixcode/logs (off, error, warn, info, debug, trace)
+    #[arg(long, default_value = "info")]
+    pub log_level: LevelFilter,
+
+    /// Model to use instead of the configured default (aliases from [models.aliases] are accepted)
+    #[arg(long)]
+    pub model: Option<String>,
+
+    /// Project directory to open instead of the current directory
+    #[arg(long)]
+    pub project: Option<PathBuf>,
+
+    /// Message sent on startup, together with input piped to stdin when there is any
+    #[arg(long)]
+    pub prompt: Option<String>,
+}
@@ -1,4 +1,5 @@
 use crate::app::AppEvent;
+use crate::popup_utils::text_width;
 use crate::user_input::UserSingleLineInput;
 use crate::utils::highlights::THEME;
 use crossterm::event::{Event, KeyCode, KeyEventKind};
@@ -27,11 +28,96 @@ const AVAILABLE_COMMANDS: &[CommandInfo] = &[
         aliases: &[],
         description: "Clear the chat history",
     },
+    CommandInfo {
+        name: "new",
+        aliases: &[],
+        description: "Start a new session (:new [name]), the current one is kept",
+    },
+    CommandInfo {
+        name: "sessions",
+        aliases: &["session"],
+        description: "List the sessions or switch to one (:sessions <name>)",
+    },
+    CommandInfo {
+        name: "project",
+        aliases: &["projects"],
+        description: "List recent projects or open one (:project <number|path>)",
+    },
     CommandInfo {
         name: "retry",
         aliases: &[],
         description: "Retry the last message",
     },
+    CommandInfo {
+        name: "continue",
+        aliases: &["cont"],
+        description: "Continue a response truncated by max tokens",
+    },
+    CommandInfo {
+        name: "cancel",
+        aliases: &[],
+        description: "Stop the running tools, their commands are killed and files are not changed",
+    },
+    CommandInfo {
+        name: "pin",
+        aliases: &[],
+        description: "Pin message N (default: last) so it is always kept in context",
+    },
+    CommandInfo {
+        name: "unpin",
+        aliases: &[],
+        description: "Unpin message N (default: last)",
+    },
+    CommandInfo {
+        name: "collapse",
+        aliases: &["fold"],
+        description: "Collapse message N (default: last) or all messages to one line",
+    },
+    CommandInfo {
+        name: "expand",
+        aliases: &["unfold"],
+        description: "Expand message N (default: last) or all messages",
+    },
+    CommandInfo {
+        name: "toolchoice",
+        aliases: &["tc"],
+        description: "Set tool use: auto, none (text only), required or a tool name (next request)",
+    },
+    CommandInfo {
+        name: "cache",
+        aliases: &[],
+        description: "Show the prompt cache hit ratio and estimated savings",
+    },
+    CommandInfo {
+        name: "why",
+        aliases: &[],
+        description: "Explain the last error and how to fix it",
+    },
+    CommandInfo {
+        name: "diff",
+        aliases: &[],
+        description: "Show all working tree changes (Esc to close)",
+    },
+    CommandInfo {
+        name: "image",
+        aliases: &["img"],
+        description: "Attach an image to the next message (:image <path>)",
+    },
+    CommandInfo {
+        name: "template",
+        aliases: &[],
+        description: "Send a prompt template from .nixcode/templates (:template <name>)",
+    },
+    CommandInfo {
+        name: "export",
+        aliases: &[],
+        description: "Export the conversation to a new Markdown file (:export <file>)",
+    },
+    CommandInfo {
+        name: "debug",
+        aliases: &[],
+        description: "Toggle the debug overlay",
+    },
     CommandInfo {
         name: "remove-last-message",
         aliases: &["remove-last", "remove-last-msg", "remove-msg", "rlm"],
@@ -39,6 +125,15 @@ const AVAILABLE_COMMANDS: &[CommandInfo] = &[
     },
 ];
 
+/// Splits command input into the command name and its arguments
+pub fn split_command(input: &str) -> (&str, &str) {
+    let input = input.trim();
+    match input.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (input, ""),
+    }
+}
+
 /// Represents a command suggestion shown in the popup
 struct CommandSuggestion {
     display_name: String,
@@ -75,12 +170,14 @@ impl CommandPopup {
 
     fn execute_command(&mut self) {
         // Get the command to execute (either from selection or input)
+        let input = self.command.as_string();
+        let (_, args) = split_command(&input);
         let command_to_execute = match self.selected_suggestion {
             Some(index) => self.suggestions.get(index).map_or_else(
-                || self.command.as_string(),
-                |suggestion| suggestion.display_name.clone(),
+                || input.clone(),
+                |suggestion| format!("{} {}", suggestion.display_name, args),
             ),
-            None => self.command.as_string(),
+            None => input.clone(),
         };
 
         // Check if the command is valid before executing
@@ -100,32 +197,31 @@ impl CommandPopup {
 
     // Check if a command exists (either as primary command or alias)
     fn is_valid_command(&self, input: &str) -> bool {
-        let input = input.trim();
-        if input.is_empty() {
+        let (name, _) = split_command(input);
+        if name.is_empty() {
             return false;
         }
 
         AVAILABLE_COMMANDS
             .iter()
-            .any(|cmd| cmd.name == input || cmd.aliases.contains(&input))
+            .any(|cmd| cmd.name == name || cmd.aliases.contains(&name))
     }
 
-    // Convert aliases to their primary command
+    // Convert aliases to their primary command, keeping arguments
     fn normalize_command(&self, input: &str) -> String {
-        let input = input.trim();
+        let (name, args) = split_command(input);
 
         // Find the primary command for the input (or return input as-is)
-        for cmd in AVAILABLE_COMMANDS {
-            if cmd.name == input {
-                return input.to_string();
-            }
+        let name = AVAILABLE_COMMANDS
+            .iter()
+            .find(|cmd| cmd.name == name || cmd.aliases.contains(&name))
+            .map_or(name, |cmd| cmd.name);
 
-            if cmd.aliases.contains(&input) {
-                return cmd.name.to_string();
-            }
+        if args.is_empty() {
+            name.to_string()
+        } else {
+            format!("{} {}", name, args)
         }
-
-        input.to_string()
     }
 
     pub fn get_input_area(area: Rect) -> Rect {
@@ -145,6 +241,10 @@ impl CommandPopup {
         // Update command validity
         self.command_is_valid = current_input.is_empty() || self.is_valid_command(&current_input);
 
+        // Suggestions only match the command name, not its arguments
+        let (current_input, _) = split_command(&current_input);
+        let current_input = current_input.to_string();
+
         // Build suggestions list with matching commands and aliases
         for cmd in AVAILABLE_COMMANDS {
             // Add main command if it matches
@@ -453,17 +553,17 @@ impl CommandPopup {
             &suggestion.display_name,
             name_style,
         );
-        x_offset += suggestion.display_name.len() as u16;
+        x_offset += text_width(&suggestion.display_name);
 
         // Show alias information if applicable
         if suggestion.is_alias {
             let alias_text = format!(" (alias of {})", suggestion.original_command);
             buf.set_string(area.x + x_offset, area.y, &alias_text, alias_style);
-            x_offset += alias_text.len() as u16;
+            x_offset += text_width(&alias_text);
         }
 
         buf.set_string(area.x + x_offset, area.y, ": ", desc_style);
-        x_offset += 2;
+        x_offset += text_width(": ");
 
         buf.set_string(
             area.x + x_offset,
@@ -0,0 +1,87 @@
+use std::path::{Path, PathBuf};
+
+/// Name of the file with environment variables (e.g. API keys)
+pub const ENV_FILE_NAME: &str = ".env";
+
+/// Returns true for the directory that ends the search: the git root or the
+/// directory holding `.nixcode/`
+fn is_project_root(dir: &Path) -> bool {
+    dir.join(".git").exists() || dir.join(".nixcode").is_dir()
+}
+
+/// Finds the nearest `.env` walking up from `start`, without leaving the project
+/// (the search stops at the git root or at the directory holding `.nixcode/`)
+pub fn find_env_file(start: &Path) -> Option<PathBuf> {
+    for dir in start.ancestors() {
+        let env_file = dir.join(ENV_FILE_NAME);
+        if env_file.is_file() {
+            return Some(env_file);
+        }
+
+        if is_project_root(dir) {
+            break;
+        }
+    }
+
+    None
+}
+
+/// Loads the nearest `.env` of the project, variables already set are kept. Without one
+/// the usual dotenv lookup (the current directory and its parents) is used
+pub fn load_env_file(start: &Path) {
+    if let Some(env_file) = find_env_file(start) {
+        dotenv::from_path(env_file).ok();
+    } else {
+        dotenv::dotenv().ok();
+    }
+}
+
+/// Loads the nearest `.env` of a project switched to, its variables replace the ones
+/// loaded for the previous project (e.g. a different API key)
+pub fn reload_env_file(start: &Path) {
+    let Some(env_file) = find_env_file(start) else {
+        return;
+    };
+
+    // dotenv 0.15 cannot override variables, its iterator gives the values to set
+    #[allow(deprecated)]
+    let vars = dotenv::from_path_iter(&env_file);
+    match vars {
+        Ok(vars) => {
+            for (key, value) in vars.flatten() {
+                std::env::set_var(key, value);
+            }
+        }
+        Err(e) => log::warn!("Cannot read {}: {}", env_file.display(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_file_is_found_in_parent_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        let nested = project.join("crates/core/src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(project.join(".git")).unwrap();
+        std::fs::write(project.join(".env"), "ANTHROPIC_API_KEY=test\n").unwrap();
+
+        assert_eq!(find_env_file(&nested), Some(project.join(".env")));
+
+        std::fs::write(nested.join(".env"), "").unwrap();
+        assert_eq!(find_env_file(&nested), Some(nested.join(".env")));
+    }
+
+    #[test]
+    fn test_env_file_search_stops_at_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(project.join(".nixcode")).unwrap();
+        std::fs::write(dir.path().join(".env"), "OTHER=1\n").unwrap();
+
+        assert_eq!(find_env_file(&project), None);
+    }
+}
@@ -0,0 +1,272 @@
+use crate::app::AppEvent;
+use crate::user_input::UserSingleLineInput;
+use crate::utils::highlights::THEME;
+use crossterm::event::{Event, KeyCode, KeyEventKind};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Margin, Rect};
+use ratatui::prelude::{Color, Modifier, Style, Stylize, Widget};
+use ratatui::widgets::{Block, BorderType, Borders, Clear};
+
+const MAX_DISPLAYED_FILES: usize = 10;
+
+/// Maximum number of project files loaded into the picker
+pub const MAX_PICKER_FILES: usize = 10_000;
+
+/// Scores how well the query fuzzy-matches the candidate path (higher is better).
+/// Returns None if not all query characters appear in order in the candidate.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate_chars: Vec<char> = candidate.chars().collect();
+    let file_name_start = candidate
+        .rfind('/')
+        .map_or(0, |idx| candidate[..=idx].chars().count());
+
+    let mut score = 0i64;
+    let mut position = 0usize;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.chars() {
+        let query_char = query_char.to_ascii_lowercase();
+        let found = candidate_chars[position..]
+            .iter()
+            .position(|c| c.to_ascii_lowercase() == query_char)?;
+        let index = position + found;
+
+        score += 1;
+
+        // Consecutive characters are a strong signal
+        if previous_match.is_some_and(|prev| prev + 1 == index) {
+            score += 5;
+        }
+
+        // Matches at the beginning of a path segment or a word
+        let at_boundary =
+            index == 0 || matches!(candidate_chars[index - 1], '/' | '_' | '-' | '.' | ' ');
+        if at_boundary {
+            score += 3;
+        }
+
+        // Prefer matches inside the file name over the directory part
+        if index >= file_name_start {
+            score += 2;
+        }
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    // Shorter paths are preferred when the match quality is equal
+    score -= (candidate_chars.len() / 10) as i64;
+
+    Some(score)
+}
+
+/// Filters files by fuzzy query and sorts them by score (best first)
+pub fn filter_files(query: &str, files: &[String]) -> Vec<String> {
+    let query = query.trim();
+    let mut scored: Vec<(i64, &String)> = files
+        .iter()
+        .filter_map(|file| fuzzy_score(query, file).map(|score| (score, file)))
+        .collect();
+
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    scored.into_iter().map(|(_, file)| file.clone()).collect()
+}
+
+pub struct FilePickerPopup {
+    query: UserSingleLineInput,
+    tx: tokio::sync::mpsc::UnboundedSender<AppEvent>,
+    files: Vec<String>,
+    matches: Vec<String>,
+    selected: usize,
+}
+
+impl FilePickerPopup {
+    pub(crate) fn new(tx: tokio::sync::mpsc::UnboundedSender<AppEvent>) -> Self {
+        FilePickerPopup {
+            query: UserSingleLineInput::default(),
+            tx,
+            files: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    /// Resets the picker with a fresh list of project files
+    pub fn open(&mut self, files: Vec<String>) {
+        self.query.flush();
+        self.files = files;
+        self.update_matches();
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = filter_files(self.query.get_data(), &self.files);
+        self.selected = 0;
+    }
+
+    fn select_file(&mut self) {
+        if let Some(file) = self.matches.get(self.selected) {
+            self.tx.send(AppEvent::InsertFilePath(file.clone())).ok();
+        }
+    }
+
+    fn next_match(&mut self) {
+        let visible = self.matches.len().min(MAX_DISPLAYED_FILES);
+        if visible > 0 {
+            self.selected = (self.selected + 1) % visible;
+        }
+    }
+
+    fn prev_match(&mut self) {
+        let visible = self.matches.len().min(MAX_DISPLAYED_FILES);
+        if visible > 0 {
+            self.selected = (self.selected + visible - 1) % visible;
+        }
+    }
+
+    pub fn get_input_area(area: Rect) -> Rect {
+        area.inner(Margin::new(1, 1))
+    }
+
+    pub fn get_input_position(&self, area: Rect) -> (u16, u16) {
+        let input_area = Self::get_input_area(area);
+        self.query.get_cursor_position(input_area)
+    }
+
+    pub(crate) fn handle_input_event(&mut self, event: &Event) {
+        if let Event::Key(key) = event {
+            if key.kind == KeyEventKind::Press {
+                match key.code {
+                    KeyCode::Enter | KeyCode::Tab => {
+                        self.select_file();
+                        return;
+                    }
+                    KeyCode::Down => {
+                        self.next_match();
+                        return;
+                    }
+                    KeyCode::Up => {
+                        self.prev_match();
+                        return;
+                    }
+                    KeyCode::Esc => return,
+                    _ => {}
+                }
+            }
+        }
+
+        self.query.handle_input_events(event);
+        self.update_matches();
+    }
+}
+
+impl Widget for &FilePickerPopup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let list_height = self.matches.len().min(MAX_DISPLAYED_FILES) as u16;
+        let popup_area = Rect {
+            x: area.x,
+            y: area.y,
+            width: area.width,
+            height: 3 + list_height + 1,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let title = format!("Files ({}/{})", self.matches.len(), self.files.len());
+        let mut block = Block::bordered()
+            .title(title)
+            .title_style(
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .border_type(BorderType::Rounded)
+            .borders(Borders::ALL);
+
+        if let Some(bg) = THEME.settings.background {
+            block = block.bg(Color::Rgb(bg.r, bg.g, bg.b));
+        }
+
+        block.render(popup_area, buf);
+        self.query
+            .render(FilePickerPopup::get_input_area(popup_area), buf);
+
+        for (i, file) in self.matches.iter().take(MAX_DISPLAYED_FILES).enumerate() {
+            let style = if i == self.selected {
+                Style::default()
+                    .fg(Color::Yellow)
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+
+            buf.set_stringn(
+                popup_area.x + 2,
+                popup_area.y + 3 + i as u16,
+                file,
+                popup_area.width.saturating_sub(4) as usize,
+                style,
+            );
+        }
+
+        let hint = if self.matches.is_empty() {
+            "No matching files"
+        } else {
+            "↑/↓: Navigate  Enter: Insert path  Esc: Cancel"
+        };
+        buf.set_stringn(
+            popup_area.x + 2,
+            popup_area.y + popup_area.height - 1,
+            hint,
+            popup_area.width.saturating_sub(4) as usize,
+            Style::default().fg(Color::DarkGray),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_score_requires_ordered_chars() {
+        assert!(fuzzy_score("app", "apps/nixcode-cli/src/app.rs").is_some());
+        assert!(fuzzy_score("ppa", "app.rs").is_none());
+    }
+
+    #[test]
+    fn test_filter_prefers_file_name_matches() {
+        let candidates = files(&["libs/mod_helpers/lib.rs", "src/config/mod.rs"]);
+
+        let result = filter_files("mod", &candidates);
+
+        assert_eq!(result.first().unwrap(), "src/config/mod.rs");
+    }
+
+    #[test]
+    fn test_filter_prefers_consecutive_matches() {
+        let candidates = files(&["src/a_p_p.rs", "src/app.rs"]);
+
+        let result = filter_files("app", &candidates);
+
+        assert_eq!(result, files(&["src/app.rs", "src/a_p_p.rs"]));
+    }
+
+    #[test]
+    fn test_filter_empty_query_keeps_all_files() {
+        let candidates = files(&["b.rs", "a.rs"]);
+
+        let result = filter_files("", &candidates);
+
+        assert_eq!(result, files(&["a.rs", "b.rs"]));
+    }
+}
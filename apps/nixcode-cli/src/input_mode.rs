@@ -3,6 +3,7 @@ pub enum InputMode {
     Normal,
     Insert,
     Command,
+    FilePicker,
 }
 
 impl InputMode {
@@ -11,6 +12,7 @@ impl InputMode {
             InputMode::Normal => "Normal",
             InputMode::Insert => "Insert",
             InputMode::Command => "Command",
+            InputMode::FilePicker => "Files",
         }
     }
 }
@@ -0,0 +1,52 @@
+use std::path::{Path, PathBuf};
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::filter::LevelFilter;
+
+/// Directory (relative to the project root) where log files are written
+pub const LOGS_DIR: &str = ".nixcode/logs";
+
+/// Base name of the log file, rotated daily
+pub const LOG_FILE_NAME: &str = "nixcode.log";
+
+pub fn get_logs_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(LOGS_DIR)
+}
+
+/// Initializes file logging for both `log` and `tracing` macros.
+/// Output never goes to the terminal, so it does not interfere with the TUI.
+/// The returned guard must be kept alive to flush buffered log lines.
+pub fn init_logging(project_dir: &Path, level: LevelFilter) -> anyhow::Result<WorkerGuard> {
+    let logs_dir = get_logs_dir(project_dir);
+    std::fs::create_dir_all(&logs_dir)?;
+
+    let file_appender = tracing_appender::rolling::daily(&logs_dir, LOG_FILE_NAME);
+    let (writer, guard) = tracing_appender::non_blocking(file_appender);
+
+    // A global logger may already be installed (e.g. in tests), that is not an error
+    let _ = tracing_subscriber::fmt()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_max_level(level)
+        .try_init();
+
+    Ok(guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_init_logging_does_not_panic() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let guard = init_logging(dir.path(), LevelFilter::DEBUG);
+        assert!(guard.is_ok());
+        assert!(get_logs_dir(dir.path()).is_dir());
+
+        // Initializing again must not panic either
+        let guard = init_logging(dir.path(), LevelFilter::TRACE);
+        assert!(guard.is_ok());
+    }
+}
@@ -1,29 +1,58 @@
 use app::App;
-use dotenv::dotenv;
-use nixcode::project::Project;
-use nixcode::Nixcode;
+use clap::Parser;
+use cli::Cli;
 use std::env::current_dir;
 use std::path::PathBuf;
 
 mod app;
+mod cli;
 mod command_popup;
+mod env_file;
+mod file_picker_popup;
 mod input_mode;
+mod logging;
+mod notifier;
+mod panic_hook;
 mod popup_utils;
+mod recent_projects;
 mod status_bar;
+mod stdin_input;
+mod throttle;
 mod user_input;
 mod widgets;
 mod utils;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
-    // Load environment variables from .env file if present
-    dotenv().ok();
+    let cli = Cli::parse();
 
-    // Create project from current directory
-    let project = Project::new(current_dir().unwrap_or(PathBuf::from(".")));
+    // Create project from the given or the current directory
+    let project_dir = match cli.project {
+        Some(project_dir) => match project_dir.canonicalize() {
+            Ok(project_dir) if project_dir.is_dir() => project_dir,
+            _ => {
+                let msg = format!("Project directory {} does not exist", project_dir.display());
+                eprintln!("{}", msg);
+                return Err(std::io::Error::new(std::io::ErrorKind::NotFound, msg));
+            }
+        },
+        None => current_dir().unwrap_or(PathBuf::from(".")),
+    };
+
+    // Load environment variables from the nearest .env of the project if present
+    env_file::load_env_file(&project_dir);
+
+    // Initialize file logging, keep the guard alive until exit to flush logs
+    let _log_guard = match logging::init_logging(&project_dir, cli.log_level) {
+        Ok(guard) => Some(guard),
+        Err(err) => {
+            eprintln!("Failed to initialize logging: {}", err);
+            None
+        }
+    };
 
     // Create Nixcode client with config from environment or files
-    let nixcode_result = Nixcode::new_from_env(project);
+    let nixcode_result = app::create_nixcode(&project_dir, cli.model);
 
     // Check if Nixcode creation was successful
     let nixcode = match nixcode_result {
@@ -35,11 +64,20 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    // Read piped input before the terminal UI takes over, keys are then read from the tty.
+    // It is capped like a file read, so a huge pipe does not fill the context
+    let read_chunk_tokens = nixcode.1.get_project().get_read_chunk_tokens();
+    let piped_input = stdin_input::read_piped_stdin(read_chunk_tokens);
+
     // Initialize terminal UI
     let mut terminal = ratatui::init();
 
+    // Log panics, only those of the main thread restore the terminal and end the app
+    panic_hook::install_panic_hook();
+
     // Create app with the nixcode client
     let mut app = App::new(nixcode).expect("Failed to create app");
+    app.seed_first_message(piped_input, cli.prompt).await;
 
     // Run the application
     let app_result = app.run(&mut terminal).await;
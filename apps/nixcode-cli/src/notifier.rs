@@ -0,0 +1,123 @@
+use nixcode::config::{NotifyMode, UiConfig};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Notifies the user when a run (a user message and all following tool turns)
+/// that took long enough finishes or fails
+#[derive(Debug)]
+pub struct Notifier {
+    mode: NotifyMode,
+    min_duration: Duration,
+    run_started: Option<Instant>,
+}
+
+impl Notifier {
+    pub fn new(config: &UiConfig) -> Self {
+        Self {
+            mode: config.notify,
+            min_duration: Duration::from_secs(config.notify_after_secs),
+            run_started: None,
+        }
+    }
+
+    /// Called for every generated response, only the first one of a run starts the timer
+    pub fn on_run_start(&mut self, now: Instant) {
+        self.run_started.get_or_insert(now);
+    }
+
+    /// Returns true if notifications are enabled and the current run took long enough
+    pub fn should_notify(&self, now: Instant) -> bool {
+        if self.mode == NotifyMode::Off {
+            return false;
+        }
+
+        self.run_started
+            .is_some_and(|started| now.duration_since(started) >= self.min_duration)
+    }
+
+    /// Ends the current run, notifying with the message if it took long enough
+    pub fn on_run_end(&mut self, now: Instant, message: &str) {
+        if self.should_notify(now) {
+            self.notify(message);
+        }
+
+        self.run_started = None;
+    }
+
+    fn notify(&self, message: &str) {
+        if matches!(self.mode, NotifyMode::Bell | NotifyMode::Both) {
+            let mut stdout = std::io::stdout();
+            stdout.write_all(b"\x07").ok();
+            stdout.flush().ok();
+        }
+
+        if matches!(self.mode, NotifyMode::Desktop | NotifyMode::Both) {
+            send_desktop_notification(message);
+        }
+    }
+}
+
+/// Best effort desktop notification, failures are only logged
+fn send_desktop_notification(message: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("osascript");
+        let script = format!("display notification {:?} with title \"nixcode\"", message);
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.arg("nixcode").arg(message);
+        command
+    };
+
+    let result = command
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    match result {
+        // Waited for in the background, so the finished process does not stay a zombie
+        Ok(mut child) => {
+            tokio::task::spawn_blocking(move || child.wait());
+        }
+        Err(e) => log::debug!("Cannot send desktop notification: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notifier(mode: NotifyMode, notify_after_secs: u64) -> Notifier {
+        let config = UiConfig {
+            notify: mode,
+            notify_after_secs,
+            ..UiConfig::default()
+        };
+
+        Notifier::new(&config)
+    }
+
+    #[test]
+    fn test_notifies_only_after_min_duration() {
+        let start = Instant::now();
+        let mut notifier = notifier(NotifyMode::Bell, 30);
+        assert!(!notifier.should_notify(start));
+
+        notifier.on_run_start(start);
+        notifier.on_run_start(start + Duration::from_secs(20));
+        assert!(!notifier.should_notify(start + Duration::from_secs(29)));
+        assert!(notifier.should_notify(start + Duration::from_secs(30)));
+
+        notifier.run_started = None;
+        assert!(!notifier.should_notify(start + Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_disabled_notifier_never_notifies() {
+        let start = Instant::now();
+        let mut notifier = notifier(NotifyMode::Off, 0);
+
+        notifier.on_run_start(start);
+        assert!(!notifier.should_notify(start + Duration::from_secs(3600)));
+    }
+}
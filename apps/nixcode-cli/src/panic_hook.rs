@@ -0,0 +1,41 @@
+/// Installs a panic hook that writes the panic to the log file. Panics of the main
+/// thread end the app and go on to the previous hook, which `ratatui::init` installs
+/// to restore the terminal before the message is printed. Panics of other threads
+/// (e.g. spawned tasks) are only logged, so the terminal UI keeps running.
+/// Call it on the main thread after `ratatui::init`.
+pub fn install_panic_hook() {
+    let main_thread = std::thread::current().id();
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("{}", info);
+        if std::thread::current().id() == main_thread {
+            previous_hook(info);
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static PREVIOUS_HOOK_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    #[test]
+    fn test_only_main_thread_panics_reach_previous_hook() {
+        std::panic::set_hook(Box::new(|_| {
+            PREVIOUS_HOOK_CALLS.fetch_add(1, Ordering::SeqCst);
+        }));
+        install_panic_hook();
+
+        let spawned = std::thread::spawn(|| panic!("task panicked")).join();
+        assert!(spawned.is_err());
+        assert_eq!(PREVIOUS_HOOK_CALLS.load(Ordering::SeqCst), 0);
+
+        let result = std::panic::catch_unwind(|| panic!("widget panicked"));
+        let _ = std::panic::take_hook();
+
+        assert!(result.is_err());
+        assert_eq!(PREVIOUS_HOOK_CALLS.load(Ordering::SeqCst), 1);
+    }
+}
@@ -1,5 +1,6 @@
 use ratatui::layout::Constraint::Length;
 use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use unicode_width::UnicodeWidthStr;
 
 pub fn popup_area(area: Rect, percent_x: u16) -> Rect {
     let vertical = Layout::vertical([Length(3)]).flex(Flex::Center);
@@ -8,3 +9,21 @@ pub fn popup_area(area: Rect, percent_x: u16) -> Rect {
     let [area] = horizontal.areas(area);
     area
 }
+
+/// Number of terminal columns taken by `text` (wide characters take two)
+pub fn text_width(text: &str) -> u16 {
+    text.width().min(u16::MAX as usize) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_width_uses_display_width() {
+        assert_eq!(text_width("abcd"), 4);
+        // Four wide characters take eight columns, but twelve bytes
+        assert_eq!(text_width("模型名称"), 8);
+        assert_eq!(text_width("🚀 fast"), 7);
+    }
+}
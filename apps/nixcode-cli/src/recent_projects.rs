@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the file in the user config directory holding the recent projects
+pub const RECENT_PROJECTS_FILE_NAME: &str = "recent_projects.json";
+
+/// Number of projects kept, the least recently opened ones are dropped
+const MAX_RECENT_PROJECTS: usize = 10;
+
+/// Project opened in nixcode with what was used there last
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentProject {
+    pub path: PathBuf,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub session: Option<String>,
+}
+
+/// Recently opened projects, the most recent first
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentProjects {
+    projects: Vec<RecentProject>,
+}
+
+impl RecentProjects {
+    /// Default location in the user config directory
+    pub fn get_default_path() -> Option<PathBuf> {
+        nixcode::config::get_user_config_dir().map(|dir| dir.join(RECENT_PROJECTS_FILE_NAME))
+    }
+
+    /// Reads the list, a missing or unreadable file gives an empty list
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, content)
+    }
+
+    /// Moves the project to the top of the list with its current model and session
+    pub fn record(&mut self, project: RecentProject) {
+        self.projects.retain(|recent| recent.path != project.path);
+        self.projects.insert(0, project);
+        self.projects.truncate(MAX_RECENT_PROJECTS);
+    }
+
+    pub fn get_projects(&self) -> &[RecentProject] {
+        &self.projects
+    }
+
+    /// Finds a project by its 1-based position in the list
+    pub fn get_by_number(&self, number: usize) -> Option<&RecentProject> {
+        self.projects.get(number.checked_sub(1)?)
+    }
+}
+
+/// Records the project in the default recent projects file
+pub fn record_recent_project(project: RecentProject) {
+    let Some(path) = RecentProjects::get_default_path() else {
+        return;
+    };
+
+    let mut recent_projects = RecentProjects::load(&path);
+    recent_projects.record(project);
+    if let Err(err) = recent_projects.save(&path) {
+        log::warn!("Failed to save recent projects: {}", err);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recent_project(path: &str, model: &str) -> RecentProject {
+        RecentProject {
+            path: PathBuf::from(path),
+            model: Some(model.to_string()),
+            session: Some("main".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_recent_projects_are_recorded_and_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir
+            .path()
+            .join("nixcode-ai")
+            .join(RECENT_PROJECTS_FILE_NAME);
+
+        let mut recent_projects = RecentProjects::load(&path);
+        assert!(recent_projects.get_projects().is_empty());
+
+        recent_projects.record(recent_project("/work/api", "claude-3-7-sonnet"));
+        recent_projects.record(recent_project("/work/web", "gpt-4o"));
+        recent_projects.record(recent_project("/work/api", "claude-3-5-haiku"));
+        recent_projects.save(&path).unwrap();
+
+        let loaded = RecentProjects::load(&path);
+        assert_eq!(loaded, recent_projects);
+        assert_eq!(
+            loaded.get_projects(),
+            &[
+                recent_project("/work/api", "claude-3-5-haiku"),
+                recent_project("/work/web", "gpt-4o"),
+            ]
+        );
+        assert_eq!(
+            loaded.get_by_number(2).unwrap().path,
+            PathBuf::from("/work/web")
+        );
+        assert!(loaded.get_by_number(0).is_none());
+    }
+}
@@ -1,19 +1,117 @@
 use crate::input_mode::InputMode;
+use crate::popup_utils::text_width;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Constraint::{Fill, Length};
 use ratatui::layout::{Layout, Margin, Rect};
 use ratatui::prelude::{Color, Line, Modifier, Span, Style, Stylize, Widget};
 use ratatui::widgets::Block;
 
+/// Characters of the last command output line shown in the status bar
+const MAX_OUTPUT_CHARS: usize = 60;
+
+/// Tracks tool execution state for the status bar indicators
+#[derive(Debug, Default, Clone)]
+pub struct ToolActivity {
+    running: usize,
+    last_turn_failed: bool,
+    turn_finished: bool,
+    last_output: Option<String>,
+}
+
+impl ToolActivity {
+    pub fn on_tool_start(&mut self) {
+        // First tool of a new turn clears the failure from the previous one
+        if self.turn_finished {
+            self.turn_finished = false;
+            self.last_turn_failed = false;
+        }
+        self.running += 1;
+    }
+
+    pub fn on_tool_end(&mut self, failed: bool) {
+        self.running = self.running.saturating_sub(1);
+        self.last_turn_failed |= failed;
+    }
+
+    pub fn on_tools_finished(&mut self) {
+        self.running = 0;
+        self.turn_finished = true;
+        self.last_output = None;
+    }
+
+    /// Keeps the last non-empty line printed by a running command
+    pub fn on_command_output(&mut self, line: &str) {
+        let line = line.trim();
+        if !line.is_empty() {
+            self.last_output = Some(line.chars().take(MAX_OUTPUT_CHARS).collect());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn running(&self) -> usize {
+        self.running
+    }
+
+    pub fn last_turn_failed(&self) -> bool {
+        self.last_turn_failed
+    }
+
+    /// Last output line of a command, only while tools are running
+    pub fn last_output(&self) -> Option<&str> {
+        self.last_output.as_deref().filter(|_| self.running > 0)
+    }
+}
+
 pub struct StatusBar {
     current_mode: InputMode,
+    tool_activity: ToolActivity,
 }
 
 impl StatusBar {
-    pub(crate) fn new(status: InputMode) -> Self {
+    pub(crate) fn new(status: InputMode, tool_activity: ToolActivity) -> Self {
         StatusBar {
             current_mode: status,
+            tool_activity,
+        }
+    }
+
+    fn tool_activity_spans(&self) -> Vec<Span<'static>> {
+        let mut spans = Vec::new();
+
+        if self.tool_activity.running() > 0 {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!(" ⚙ {} running ", self.tool_activity.running()),
+                Style::new()
+                    .fg(Color::Black)
+                    .bg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
         }
+
+        if let Some(output) = self.tool_activity.last_output() {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                output.to_string(),
+                Style::new().fg(Color::Gray).add_modifier(Modifier::ITALIC),
+            ));
+        }
+
+        if self.tool_activity.last_turn_failed() {
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                " ⚠ tool failed ",
+                Style::new()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+
+        spans
     }
 }
 
@@ -30,16 +128,16 @@ impl Widget for StatusBar {
         let version_text = format!("v{}", VERSION);
 
         // Calculate total length of the right side content (date + version)
-        let right_content_length = formatted_date.len() + 1 + version_text.len();
+        let right_content_length = text_width(&formatted_date) + 1 + text_width(&version_text);
 
         // Create layout with three sections: mode info, fill space, date+version
-        let horizontal = Layout::horizontal([Fill(1), Length(right_content_length as u16)]);
+        let horizontal = Layout::horizontal([Fill(1), Length(right_content_length)]);
         let [inner_area, right_area] = horizontal.areas(area.inner(Margin::new(1, 0)));
 
         Block::new().bg(Color::DarkGray).render(area, buf);
 
-        // Render the mode info on the left
-        Line::from(vec![
+        // Render the mode info and tool activity on the left
+        let mut left = vec![
             Span::raw("Mode: "),
             Span::styled(
                 format!(" {} ", self.current_mode.to_string()),
@@ -48,8 +146,9 @@ impl Widget for StatusBar {
                     .bg(Color::Green)
                     .add_modifier(Modifier::BOLD),
             ),
-        ])
-        .render(inner_area, buf);
+        ];
+        left.extend(self.tool_activity_spans());
+        Line::from(left).render(inner_area, buf);
 
         // Render date and version on the right
         Line::from(vec![
@@ -65,3 +164,47 @@ impl Widget for StatusBar {
         .render(right_area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_activity_counts_running_tools() {
+        let mut activity = ToolActivity::default();
+
+        activity.on_tool_start();
+        activity.on_tool_start();
+        activity.on_tool_end(false);
+
+        assert_eq!(activity.running(), 1);
+        assert!(!activity.last_turn_failed());
+    }
+
+    #[test]
+    fn test_tool_activity_failure_cleared_on_next_turn() {
+        let mut activity = ToolActivity::default();
+
+        activity.on_tool_start();
+        activity.on_tool_end(true);
+        activity.on_tools_finished();
+        assert!(activity.last_turn_failed());
+
+        activity.on_tool_start();
+        assert!(!activity.last_turn_failed());
+        assert_eq!(activity.running(), 1);
+    }
+
+    #[test]
+    fn test_command_output_shown_while_tools_run() {
+        let mut activity = ToolActivity::default();
+
+        activity.on_tool_start();
+        activity.on_command_output("   Compiling nixcode v0.4.0");
+        activity.on_command_output("");
+        assert_eq!(activity.last_output(), Some("Compiling nixcode v0.4.0"));
+
+        activity.on_tools_finished();
+        assert_eq!(activity.last_output(), None);
+    }
+}
@@ -0,0 +1,82 @@
+use nixcode_llm_sdk::message::content::Content;
+use std::io::{IsTerminal, Read};
+
+/// Rough number of characters per token, the estimate used by the read tools
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Reads the input piped into nixcode (e.g. `git diff | nixcode-cli`), None when
+/// stdin is a terminal. Like a file read, at most `max_tokens` are kept, the rest
+/// is replaced by a note
+pub fn read_piped_stdin(max_tokens: usize) -> Option<String> {
+    let stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return None;
+    }
+
+    match read_capped(stdin, max_tokens * CHARS_PER_TOKEN) {
+        Ok(input) => Some(input),
+        Err(err) => {
+            log::warn!("Failed to read piped input: {}", err);
+            None
+        }
+    }
+}
+
+/// Reads at most `max_bytes` of the input, noting when more was piped
+fn read_capped(reader: impl Read, max_bytes: usize) -> std::io::Result<String> {
+    let mut bytes = Vec::new();
+    reader.take(max_bytes as u64 + 1).read_to_end(&mut bytes)?;
+
+    if bytes.len() <= max_bytes {
+        return Ok(String::from_utf8_lossy(&bytes).into_owned());
+    }
+
+    bytes.truncate(max_bytes);
+    Ok(format!(
+        "{}\n[Input truncated after {} bytes, pipe less or pass a file path instead]",
+        String::from_utf8_lossy(&bytes),
+        max_bytes
+    ))
+}
+
+/// Context attached to the first message, None for empty input
+pub fn stdin_context(input: &str) -> Option<Content> {
+    let input = input.trim_end();
+    if input.trim().is_empty() {
+        return None;
+    }
+
+    Some(Content::new_text(format!(
+        "Input piped to nixcode:\n```\n{}\n```",
+        input
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_piped_input_is_wrapped_as_context() {
+        let diff = "diff --git a/README.md b/README.md\n+New line\n\n";
+
+        let context = stdin_context(diff).unwrap();
+
+        assert_eq!(
+            context.get_text().unwrap().get_text(),
+            "Input piped to nixcode:\n```\ndiff --git a/README.md b/README.md\n+New line\n```"
+        );
+        assert!(stdin_context(" \n\n").is_none());
+    }
+
+    #[test]
+    fn test_piped_input_is_capped() {
+        assert_eq!(read_capped("short".as_bytes(), 5).unwrap(), "short");
+
+        let input = read_capped("a long input".as_bytes(), 6).unwrap();
+        assert_eq!(
+            input,
+            "a long\n[Input truncated after 6 bytes, pipe less or pass a file path instead]"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+/// Minimal time between chat re-layouts while a response is streaming (~20fps)
+pub const CHAT_UPDATE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Coalesces rapid update requests so expensive work runs at most once per interval
+#[derive(Debug)]
+pub struct UpdateThrottle {
+    interval: Duration,
+    last_update: Option<Instant>,
+    pending: bool,
+}
+
+impl UpdateThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_update: None,
+            pending: false,
+        }
+    }
+
+    /// Returns true if the update should run now, otherwise it is kept pending
+    pub fn request(&mut self, now: Instant) -> bool {
+        if self.is_due(now) {
+            self.mark_updated(now);
+            return true;
+        }
+
+        self.pending = true;
+        false
+    }
+
+    /// Returns true once for a pending update whose interval has elapsed
+    pub fn take_pending(&mut self, now: Instant) -> bool {
+        if self.pending && self.is_due(now) {
+            self.mark_updated(now);
+            return true;
+        }
+
+        false
+    }
+
+    /// Records an update done outside of the throttle, it covers pending requests
+    pub fn mark_updated(&mut self, now: Instant) {
+        self.last_update = Some(now);
+        self.pending = false;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.last_update
+            .is_none_or(|last_update| now.duration_since(last_update) >= self.interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rapid_requests_are_coalesced() {
+        let mut throttle = UpdateThrottle::new(CHAT_UPDATE_INTERVAL);
+        let start = Instant::now();
+
+        let updates = (0..100)
+            .filter(|i| throttle.request(start + Duration::from_millis(*i / 10)))
+            .count();
+
+        assert_eq!(updates, 1);
+        assert!(throttle.is_pending());
+        assert!(!throttle.take_pending(start + Duration::from_millis(20)));
+        assert!(throttle.take_pending(start + CHAT_UPDATE_INTERVAL));
+        assert!(!throttle.is_pending());
+        assert!(!throttle.take_pending(start + CHAT_UPDATE_INTERVAL * 2));
+    }
+
+    #[test]
+    fn test_spaced_requests_run_immediately() {
+        let mut throttle = UpdateThrottle::new(CHAT_UPDATE_INTERVAL);
+        let start = Instant::now();
+
+        assert!(throttle.request(start));
+        assert!(throttle.request(start + CHAT_UPDATE_INTERVAL));
+        assert!(!throttle.is_pending());
+    }
+}
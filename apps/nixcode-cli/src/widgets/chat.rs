@@ -1,13 +1,19 @@
 use crate::app::AppEvent;
 use crate::input_mode::InputMode;
+use crate::stdin_input::stdin_context;
 use crate::user_input::UserSingleLineInput;
+use crate::widgets::chat_scroll::ChatScroll;
+use crate::widgets::line_cache::MessageLinesCache;
 use crate::widgets::message_widget::MessageWidget;
 use crossterm::event::{Event, KeyCode, KeyEventKind};
+use nixcode::thinking::ThinkingUsage;
+use nixcode::turn_summary::TurnSummary;
 use nixcode::Nixcode;
 use nixcode_llm_sdk::message::content::Content;
 use nixcode_llm_sdk::message::message::Message;
 use nixcode_llm_sdk::message::message::Message::User;
 use nixcode_llm_sdk::message::usage::Usage;
+use nixcode_llm_sdk::tools::ToolChoice;
 use nixcode_llm_sdk::ErrorContent;
 use ratatui::layout::{Constraint, Layout, Margin, Rect};
 use ratatui::prelude::{Modifier, Style, Stylize};
@@ -16,7 +22,9 @@ use ratatui::widgets::{
     Block, BorderType, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap,
 };
 use ratatui::Frame;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::mpsc::UnboundedSender;
 
 pub struct Chat {
@@ -28,12 +36,18 @@ pub struct Chat {
     app_event: UnboundedSender<AppEvent>,
     prompt: UserSingleLineInput,
     area_size: (u16, u16), // (width, height)
-    stick_to_bottom: bool,
-    scroll: usize,      // Simplified to a single value for vertical scrolling
-    total_lines: usize, // Keep track of total line count
+    scroll: ChatScroll,
+    pending_g: bool, // First key of `gg` was pressed
     usage: Usage,
+    thinking_usage: ThinkingUsage,
     waiting: bool,
     error: Option<ErrorContent>,
+    collapsed: HashMap<usize, bool>, // Explicit collapse state by message index
+    turn_summaries: HashMap<usize, TurnSummary>, // Tools run after the message at the index
+    attachments: Vec<Content>,       // Images and piped input sent with the next message
+    lines_cache: MessageLinesCache,
+    session: Option<String>, // Active session name, shown once there are several
+    notice: Option<String>,  // Command output shown below the messages
 }
 
 impl Chat {
@@ -48,15 +62,21 @@ impl Chat {
             input_mode,
             app_event,
             prompt: Default::default(),
-            scroll: 0,
+            scroll: ChatScroll::default(),
+            pending_g: false,
             lines: Vec::new(),
             paragraph: Paragraph::new(Vec::new()),
-            stick_to_bottom: true,
             area_size: (0, 0),
-            total_lines: 0,
             usage: Usage::default(),
+            thinking_usage: ThinkingUsage::default(),
             waiting: false,
             error: None,
+            collapsed: HashMap::new(),
+            turn_summaries: HashMap::new(),
+            attachments: Vec::new(),
+            lines_cache: MessageLinesCache::default(),
+            session: None,
+            notice: None,
         }
     }
 
@@ -76,11 +96,20 @@ impl Chat {
 
     async fn handle_normal_input_events(&mut self, event: &Event) {
         match event {
-            Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
-                KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
-                KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
-                _ => (),
-            },
+            Event::Key(key) if key.kind == KeyEventKind::Press => {
+                let pending_g = std::mem::take(&mut self.pending_g);
+                match key.code {
+                    KeyCode::Char('j') | KeyCode::Down => self.scroll_down(),
+                    KeyCode::Char('k') | KeyCode::Up => self.scroll_up(),
+                    KeyCode::PageUp => self.page_up(),
+                    KeyCode::PageDown => self.page_down(),
+                    KeyCode::Char('G') | KeyCode::End => self.scroll_to_bottom(),
+                    KeyCode::Home => self.scroll_to_top(),
+                    KeyCode::Char('g') if pending_g => self.scroll_to_top(),
+                    KeyCode::Char('g') => self.pending_g = true,
+                    _ => (),
+                }
+            }
             _ => (),
         }
     }
@@ -101,18 +130,66 @@ impl Chat {
 
     pub async fn update_chat_widgets(&mut self) {
         let messages = self.client.get_messages().await;
+        let metadata = self.client.get_messages_metadata().await;
         let llm_error = self.client.get_error().await;
         self.usage = self.client.get_usage().await;
-        let mut lines: Vec<Line> = messages
-            .clone()
-            .into_iter()
-            .flat_map(MessageWidget::get_lines)
-            .collect();
-
+        self.thinking_usage = self.client.get_thinking_usage().await;
         self.waiting = self.client.is_waiting().await;
+        self.session = match self.client.get_session_names().await.len() {
+            1 => None,
+            _ => Some(self.client.get_active_session().await),
+        };
 
-        if let Some(error) = llm_error {
-            lines.push(Line::raw(format!("Error: {:?}", error)).red().bold());
+        let messages_count = messages.len();
+        self.lines_cache.truncate(messages_count);
+        // A summary belongs to the response with its tool calls, which may have been removed
+        self.turn_summaries.retain(|index, summary| {
+            messages
+                .get(*index)
+                .is_some_and(|message| summary.is_for(message))
+        });
+        let assistant_label = self.client.get_config().ui.assistant_label.clone();
+        let mut lines: Vec<Line> = Vec::new();
+        for (index, (message, metadata)) in messages.into_iter().zip(&metadata).enumerate() {
+            let message_lines = self.lines_cache.get_or_render(index, message, |message| {
+                MessageWidget::get_lines(message, &assistant_label)
+            });
+            let completed = !self.waiting || index + 1 < messages_count;
+            let collapsed = self.is_collapsed(index, message_lines.len(), completed);
+            lines.extend(MessageWidget::decorate_lines(
+                index + 1,
+                message_lines,
+                metadata,
+                collapsed,
+            ));
+            if let Some(summary) = self.turn_summaries.get(&index) {
+                let expanded = self.collapsed.get(&index) == Some(&false);
+                lines.extend(MessageWidget::get_turn_summary_lines(
+                    index + 1,
+                    summary,
+                    expanded,
+                ));
+            }
+        }
+
+        for message in self.client.get_queued_messages().await {
+            let text = message
+                .get_content()
+                .iter()
+                .filter_map(|content| content.get_text().map(|text| text.get_text()))
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(Line::raw(format!("Queued > {}", text)).dark_gray().italic());
+        }
+
+        if let Some(notice) = &self.notice {
+            for line in notice.lines() {
+                lines.push(Line::raw(line.to_string()).dark_gray());
+            }
+        }
+
+        if let Some(error) = llm_error.or(self.error.clone()) {
+            lines.push(Line::raw(format!("Error: {}", error)).red().bold());
         }
 
         self.paragraph = Paragraph::new(lines.clone()).wrap(Wrap { trim: false });
@@ -125,106 +202,158 @@ impl Chat {
             lines.len()
         };
 
-        self.total_lines = total_lines;
         self.lines = lines;
 
-        // Update scrollbar state with new content length
-        self.vertical_scroll_state = self
-            .vertical_scroll_state
-            .content_length(self.total_lines.saturating_sub(self.area_size.1 as usize))
-            .viewport_content_length(self.area_size.1 as usize);
+        // Follows the new content only when the user has not scrolled up
+        self.scroll.set_total_lines(total_lines);
+        self.update_scrollbar();
+    }
+
+    /// Shows the summary of the tools run after the message at `index`
+    pub async fn add_turn_summary(&mut self, index: usize, summary: TurnSummary) {
+        self.turn_summaries.insert(index, summary);
+        self.update_chat_widgets().await;
+    }
+
+    /// Explicit state wins, otherwise long completed messages follow `ui.auto_collapse_lines`
+    fn is_collapsed(&self, index: usize, lines_count: usize, completed: bool) -> bool {
+        if let Some(collapsed) = self.collapsed.get(&index) {
+            return *collapsed;
+        }
+
+        let auto_collapse_lines = self.client.get_config().ui.auto_collapse_lines;
+        completed && auto_collapse_lines.is_some_and(|max_lines| lines_count > max_lines)
+    }
 
-        // If sticking to bottom, update scroll position
-        if self.stick_to_bottom {
-            self.scroll_to_bottom();
+    /// Collapses or expands message number `args` (1-based), `all` messages,
+    /// or the last message if empty
+    pub async fn set_message_collapsed(&mut self, args: &str, collapsed: bool) {
+        let messages_count = self.client.get_messages().await.len();
+
+        if args == "all" {
+            self.collapsed = (0..messages_count).map(|i| (i, collapsed)).collect();
+            self.error = None;
+        } else {
+            let number = if args.is_empty() {
+                Ok(messages_count)
+            } else {
+                args.parse::<usize>()
+            };
+
+            match number {
+                Ok(number) if number > 0 && number <= messages_count => {
+                    self.collapsed.insert(number - 1, collapsed);
+                    self.error = None;
+                }
+                _ => {
+                    let message = format!("Invalid message number: {}", args);
+                    self.error = Some(ErrorContent::new("invalid_command", message));
+                }
+            }
         }
+
+        self.update_chat_widgets().await;
     }
 
-    // Simplified to use a single scroll value
-    pub fn set_vertical_scroll(&mut self, scroll: usize) {
-        let max_scroll = self.get_max_scroll();
-        self.scroll = scroll.min(max_scroll);
-        self.vertical_scroll_state = self.vertical_scroll_state.position(self.scroll);
+    fn update_scrollbar(&mut self) {
+        self.vertical_scroll_state = self
+            .vertical_scroll_state
+            .content_length(self.scroll.get_max_scroll())
+            .viewport_content_length(self.area_size.1 as usize)
+            .position(self.scroll.get_position());
     }
 
     pub fn scroll_up(&mut self) {
-        if self.scroll > 0 {
-            self.set_vertical_scroll(self.scroll - 1);
-            // Only update stick_to_bottom if we're not at the bottom anymore
-            self.stick_to_bottom = self.scroll >= self.get_max_scroll();
-        }
+        self.scroll.scroll_up();
+        self.update_scrollbar();
     }
 
     pub fn scroll_down(&mut self) {
-        let max_scroll = self.get_max_scroll();
-        if self.scroll < max_scroll {
-            self.set_vertical_scroll(self.scroll + 1);
-            // Check if we reached the bottom
-            self.stick_to_bottom = self.scroll >= max_scroll;
-        }
+        self.scroll.scroll_down();
+        self.update_scrollbar();
     }
 
-    pub fn scroll_to_bottom(&mut self) {
-        let max_scroll = self.get_max_scroll();
-        self.set_vertical_scroll(max_scroll);
-        self.stick_to_bottom = true;
+    pub fn page_up(&mut self) {
+        self.scroll.page_up();
+        self.update_scrollbar();
     }
 
-    // Calculate maximum valid scroll position
-    fn get_max_scroll(&self) -> usize {
-        let viewport_height = self.area_size.1 as usize;
-        if self.total_lines <= viewport_height {
-            0
-        } else {
-            self.total_lines - viewport_height
-        }
+    pub fn page_down(&mut self) {
+        self.scroll.page_down();
+        self.update_scrollbar();
     }
 
-    pub fn set_area_size(&mut self, size: (u16, u16)) {
-        let old_size = self.area_size;
-        self.area_size = size;
-
-        // If the area width changed, we need to recalculate line wrapping
-        if old_size.0 != size.0 {
-            return;
-        }
+    pub fn scroll_to_top(&mut self) {
+        self.scroll.scroll_to_top();
+        self.update_scrollbar();
+    }
 
-        // Just update the scrollbar viewport height
-        self.vertical_scroll_state = self
-            .vertical_scroll_state
-            .viewport_content_length(size.1 as usize);
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll.scroll_to_bottom();
+        self.update_scrollbar();
+    }
 
-        // Check if we need to adjust scroll position
-        if self.stick_to_bottom {
-            self.scroll_to_bottom();
-        } else {
-            // Make sure scroll position is still valid
-            self.set_vertical_scroll(self.scroll);
-        }
+    pub fn set_area_size(&mut self, size: (u16, u16)) {
+        self.area_size = size;
+        self.scroll.set_viewport_height(size.1 as usize);
+        self.update_scrollbar();
     }
 
-    async fn send_message(&mut self, message: Option<Message>) {
+    /// Sends the message, or queues it until the current response and its tools finish
+    async fn send_message(&mut self, message: Message) {
+        self.error = None;
+        self.notice = None;
         let client = self.client.clone();
 
         tokio::spawn(async move {
-            client.send_message(message).await;
+            client.send_or_queue_message(message).await;
         });
     }
 
-    async fn send_user_message(&mut self) {
-        if self.client.is_waiting().await {
-            return;
+    /// Attaches piped input to the first message, which is sent right away when a
+    /// prompt is given and otherwise with the first message typed
+    pub async fn seed_first_message(
+        &mut self,
+        piped_input: Option<String>,
+        prompt: Option<String>,
+    ) {
+        if let Some(context) = piped_input.as_deref().and_then(stdin_context) {
+            self.attachments.push(context);
         }
 
+        match prompt {
+            Some(prompt) => {
+                self.insert_text(&prompt);
+                self.send_user_message().await;
+            }
+            None if !self.attachments.is_empty() => {
+                self.notice = Some("Piped input attached, type a message to send it".into());
+                self.update_chat_widgets().await;
+            }
+            None => {}
+        }
+    }
+
+    /// Inserts text at the prompt cursor position
+    pub fn insert_text(&mut self, text: &str) {
+        text.chars().for_each(|c| self.prompt.insert(c));
+    }
+
+    async fn send_user_message(&mut self) {
         let message = self.prompt.as_string().trim().to_string();
-        if message.is_empty() {
+        if message.is_empty() && self.attachments.is_empty() {
             return;
         }
 
-        let message = User(Content::new_text(message).into());
+        let mut content = std::mem::take(&mut self.attachments);
+        if !message.is_empty() {
+            content.push(Content::new_text(message));
+        }
+
+        let message = User(content);
         self.prompt.flush();
 
-        self.send_message(Some(message)).await;
+        self.send_message(message).await;
     }
 
     fn get_layout(&self, area: Rect) -> [Rect; 3] {
@@ -244,24 +373,30 @@ impl Chat {
         let inner = area.inner(Margin::new(1, 1));
         self.set_area_size((inner.width, inner.height));
 
-        let create_input_cache_cost =
-            self.usage.cache_creation_input_tokens.unwrap_or(0) as f64 / 1_000_000.0 * 3.75;
-        let read_input_cache_cost =
-            self.usage.cache_read_input_tokens.unwrap_or(0) as f64 / 1_000_000.0 * 0.30;
-        let input_cost = self.usage.input_tokens as f64 / 1_000_000.0 * 3.0;
-        let output_cost = self.usage.output_tokens as f64 / 1_000_000.0 * 15.0;
-        let total_cost = create_input_cache_cost + read_input_cache_cost + input_cost + output_cost;
+        let total_cost = self.usage.estimated_cost();
 
         let cache_write_tokens = self.usage.cache_creation_input_tokens.unwrap_or(0);
         let cache_read_tokens = self.usage.cache_read_input_tokens.unwrap_or(0);
         let input_tokens = self.usage.input_tokens;
         let output_tokens = self.usage.output_tokens;
+        let cache_hit = match self.usage.cache_hit_ratio() {
+            Some(ratio) => format!(" {:.0}% hit", ratio * 100.0),
+            None => String::new(),
+        };
+        let thinking_tokens = if self.client.is_thinking_enabled() {
+            format!(", Thinking: {}", self.thinking_usage.total_tokens)
+        } else {
+            String::new()
+        };
 
         // Add provider info to the title
         let provider = &self.client.get_config().llm.default_provider;
         let model = &self.client.get_model();
 
         let mut title_line_spans = vec![Span::from(format!(" Chat [{}/{}]", provider, model))];
+        if let Some(session) = &self.session {
+            title_line_spans.push(Span::styled(format!(" ({})", session), Style::new().bold()));
+        }
 
         if self.client.get_project().has_repo_path() {
             title_line_spans.push(Span::styled(" [git] ", Style::new().green().bold()))
@@ -275,12 +410,29 @@ impl Chat {
             .title_bottom(Line::raw(format!(" ${:.4} ", total_cost)).right_aligned())
             .title_bottom(
                 Line::raw(format!(
-                    " Cache (R/W): ({}, {}), Input: {}, Output: {} ",
-                    cache_read_tokens, cache_write_tokens, input_tokens, output_tokens
+                    " Cache (R/W): ({}, {}){}, Input: {}, Output: {}{} ",
+                    cache_read_tokens,
+                    cache_write_tokens,
+                    cache_hit,
+                    input_tokens,
+                    output_tokens,
+                    thinking_tokens
                 ))
                 .centered(),
             );
 
+        if self.thinking_usage.exceeds_warning_threshold() {
+            main_area = main_area.title_bottom(
+                Line::from(format!(
+                    " Thinking used {}% of max tokens ",
+                    self.thinking_usage.last_response_percent()
+                ))
+                .red()
+                .bold()
+                .left_aligned(),
+            );
+        }
+
         if !self.client.has_init_analysis() {
             main_area = main_area.title(
                 Line::from(" Project analysis not initialized ")
@@ -305,6 +457,12 @@ impl Chat {
             );
         }
 
+        if !self.scroll.is_stuck_to_bottom() {
+            main_area = main_area.title_bottom(
+                Line::from(" G: jump to latest ").dark_gray().right_aligned(),
+            );
+        }
+
         let scroll = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("↑"))
             .end_symbol(Some("↓"));
@@ -312,7 +470,10 @@ impl Chat {
         frame.render_widget(main_area, area);
 
         // Apply the scroll to the paragraph
-        let scrolled_paragraph = self.paragraph.clone().scroll((self.scroll as u16, 0));
+        let scrolled_paragraph = self
+            .paragraph
+            .clone()
+            .scroll((self.scroll.get_position().min(u16::MAX as usize) as u16, 0));
         frame.render_widget(scrolled_paragraph, inner);
 
         frame.render_stateful_widget(scroll, inner, &mut self.vertical_scroll_state);
@@ -323,9 +484,23 @@ impl Chat {
 
         self.render_chat(frame, chat_area);
 
+        let mut attached = Vec::new();
+        if self.attachments.iter().any(Content::is_text) {
+            attached.push(String::from("piped input"));
+        }
+        match self.attachments.iter().filter(|c| !c.is_text()).count() {
+            0 => {}
+            1 => attached.push(String::from("1 image")),
+            count => attached.push(format!("{} images", count)),
+        }
+        let title = match attached.is_empty() {
+            true => String::from(" Input "),
+            false => format!(" Input ({} attached) ", attached.join(", ")),
+        };
+
         frame.render_widget(
             Block::bordered()
-                .title(" Input ")
+                .title(title)
                 .border_type(BorderType::Rounded),
             input_area,
         );
@@ -337,12 +512,126 @@ impl Chat {
             return;
         }
 
+        self.reset_view();
+    }
+
+    /// Drops everything rendered for the previous conversation
+    fn reset_view(&mut self) {
+        self.notice = None;
         self.lines.clear();
+        self.collapsed.clear();
+        self.turn_summaries.clear();
+        self.attachments.clear();
+        self.lines_cache.clear();
         self.paragraph = Paragraph::new(Vec::new());
         self.vertical_scroll_state = ScrollbarState::default();
-        self.scroll = 0;
-        self.total_lines = 0;
+        self.scroll.reset();
         self.usage = Usage::default();
+        self.thinking_usage = ThinkingUsage::default();
+    }
+
+    /// Saves the conversation and starts a new session named `args` (default: session-N)
+    pub async fn new_session(&mut self, args: &str) {
+        let name = (!args.is_empty()).then_some(args);
+        match self.client.new_session(name).await {
+            Ok(_) => {
+                self.reset_view();
+                self.error = None;
+            }
+            Err(err) => self.error = Some(ErrorContent::new("invalid_command", err.to_string())),
+        }
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Lists the sessions of this run, or switches to session `args`
+    pub async fn show_or_switch_session(&mut self, args: &str) {
+        if args.is_empty() {
+            let active = self.client.get_active_session().await;
+            let sessions = self
+                .client
+                .get_session_names()
+                .await
+                .into_iter()
+                .map(|name| {
+                    let marker = if name == active { "*" } else { " " };
+                    format!("{} {}", marker, name)
+                })
+                .collect::<Vec<_>>();
+            self.notice = Some(format!(
+                "Sessions (:sessions <name> to switch):\n{}",
+                sessions.join("\n")
+            ));
+            self.error = None;
+        } else {
+            match self.client.switch_session(args).await {
+                Ok(()) => {
+                    self.reset_view();
+                    self.error = None;
+                }
+                Err(err) => {
+                    self.error = Some(ErrorContent::new("invalid_command", err.to_string()))
+                }
+            }
+        }
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Shows command output below the messages
+    pub async fn show_notice(&mut self, notice: String) {
+        self.notice = Some(notice);
+        self.error = None;
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Shows how much of the prompts was read from the cache and what it saved
+    pub async fn show_cache_stats(&mut self) {
+        let usage = &self.usage;
+        let notice = match usage.cache_hit_ratio() {
+            None => "No requests sent yet".to_string(),
+            Some(ratio) => format!(
+                "Cache reads: {}, cache writes: {}, uncached input: {}\nHit ratio: {:.1}%, estimated savings: ${:.4}",
+                usage.cache_read_input_tokens.unwrap_or(0),
+                usage.cache_creation_input_tokens.unwrap_or(0),
+                usage.input_tokens,
+                ratio * 100.0,
+                usage.estimated_cache_savings()
+            ),
+        };
+
+        self.show_notice(notice).await;
+    }
+
+    /// Explains the last error in plain English with a suggested fix
+    pub async fn explain_last_error(&mut self) {
+        let error = match &self.error {
+            Some(error) => Some(error.clone()),
+            None => self.client.get_error().await,
+        };
+
+        self.notice = Some(match error {
+            None => "No error to explain".to_string(),
+            Some(error) => match error.explain() {
+                Some(explanation) => explanation.to_string(),
+                None => format!("No explanation for {} errors", error.get_type()),
+            },
+        });
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Stops the tools of the current turn
+    pub async fn cancel_tools(&mut self) {
+        let running = self.client.cancel_tools().await;
+        self.notice = Some(format!(
+            "Cancelled {} running tools, send a message to continue",
+            running
+        ));
+        self.error = None;
+
+        self.update_chat_widgets().await;
     }
 
     /// Retry last message that was sent by the user
@@ -354,6 +643,143 @@ impl Chat {
         self.update_chat_widgets().await;
     }
 
+    /// Continue the last response if it was truncated by max tokens
+    pub async fn continue_last_message(&mut self) {
+        if !self.client.can_continue().await {
+            let error = ErrorContent::new(
+                "invalid_command",
+                "Nothing to continue, the last response was not truncated by max tokens",
+            );
+            self.on_error(error).await;
+            return;
+        }
+
+        self.error = None;
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            client.continue_last_message().await.ok();
+        });
+        self.update_chat_widgets().await;
+    }
+
+    /// Pins or unpins message number `args` (1-based), or the last message if empty
+    pub async fn set_message_pinned(&mut self, args: &str, pinned: bool) {
+        let messages_count = self.client.get_messages().await.len();
+        let number = if args.is_empty() {
+            Ok(messages_count)
+        } else {
+            args.parse::<usize>()
+        };
+
+        let result = match number {
+            Ok(number) if number > 0 => self.client.set_message_pinned(number - 1, pinned).await,
+            _ => Err(anyhow::anyhow!("Invalid message number: {}", args)),
+        };
+
+        match result {
+            Ok(()) => self.error = None,
+            Err(err) => self.error = Some(ErrorContent::new("invalid_command", err.to_string())),
+        }
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Sets how the model may use tools: auto, none, or required or a tool name for the
+    /// next request only
+    pub async fn set_tool_choice(&mut self, args: &str) {
+        match args.parse::<ToolChoice>() {
+            Ok(tool_choice) => match self.client.set_tool_choice(tool_choice).await {
+                Ok(()) => self.error = None,
+                Err(message) => {
+                    self.error = Some(ErrorContent::new("invalid_command", message));
+                }
+            },
+            Err(_) => {
+                let current = self.client.get_tool_choice().await;
+                let message = format!(
+                    "Usage: :toolchoice <auto|none|required|TOOL_NAME> (current: {})",
+                    current
+                );
+                self.error = Some(ErrorContent::new("invalid_command", message));
+            }
+        }
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Writes the conversation as Markdown to `args` (relative to the project directory),
+    /// an existing file is never overwritten
+    pub async fn export_markdown(&mut self, args: &str) {
+        if args.is_empty() {
+            let error = ErrorContent::new("invalid_command", "Usage: :export <file>");
+            self.on_error(error).await;
+            return;
+        }
+
+        let path = self.client.get_project().get_cwd().join(args);
+        let markdown = self.client.export_markdown().await;
+
+        let written = async {
+            let mut file = tokio::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .await?;
+            file.write_all(markdown.as_bytes()).await
+        }
+        .await;
+
+        match written {
+            Ok(()) => self.error = None,
+            Err(err) => {
+                let message = format!("Cannot export to {}: {}", path.display(), err);
+                self.error = Some(ErrorContent::new("export_failed", message));
+            }
+        }
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Sends the prompt template `args` with its variables expanded, together with the
+    /// attachments
+    pub async fn send_template(&mut self, args: &str) {
+        if args.is_empty() {
+            let error = ErrorContent::new("invalid_command", "Usage: :template <name>");
+            self.on_error(error).await;
+            return;
+        }
+
+        match self.client.render_template(args).await {
+            Ok(text) => {
+                let mut content = std::mem::take(&mut self.attachments);
+                content.push(Content::new_text(text));
+                self.send_message(User(content)).await;
+            }
+            Err(error) => self.error = Some(ErrorContent::new("invalid_prompt", error)),
+        }
+
+        self.update_chat_widgets().await;
+    }
+
+    /// Attaches the image at `args` to the next user message
+    pub async fn attach_image(&mut self, args: &str) {
+        if args.is_empty() {
+            let error = ErrorContent::new("invalid_command", "Usage: :image <path>");
+            self.on_error(error).await;
+            return;
+        }
+
+        match self.client.load_image(args) {
+            Ok(image) => {
+                self.attachments.push(image);
+                self.error = None;
+            }
+            Err(message) => self.error = Some(ErrorContent::new("image_failed", message)),
+        }
+
+        self.update_chat_widgets().await;
+    }
+
     pub async fn remove_last_message(&mut self) {
         self.client.remove_last_message().await;
         self.update_chat_widgets().await;
@@ -0,0 +1,177 @@
+/// Vertical scroll position of the chat. Follows new content while at the bottom;
+/// once the user scrolls up the position stays put until they return to the bottom.
+#[derive(Debug, Clone)]
+pub struct ChatScroll {
+    position: usize,
+    total_lines: usize,
+    viewport_height: usize,
+    stick_to_bottom: bool,
+}
+
+impl Default for ChatScroll {
+    fn default() -> Self {
+        Self {
+            position: 0,
+            total_lines: 0,
+            viewport_height: 0,
+            stick_to_bottom: true,
+        }
+    }
+}
+
+impl ChatScroll {
+    pub fn get_position(&self) -> usize {
+        self.position
+    }
+
+    pub fn is_stuck_to_bottom(&self) -> bool {
+        self.stick_to_bottom
+    }
+
+    /// Maximum valid scroll position
+    pub fn get_max_scroll(&self) -> usize {
+        self.total_lines.saturating_sub(self.viewport_height)
+    }
+
+    /// Called when the content changes, e.g. while a response is streamed
+    pub fn set_total_lines(&mut self, total_lines: usize) {
+        self.total_lines = total_lines;
+        self.follow_content();
+    }
+
+    pub fn set_viewport_height(&mut self, viewport_height: usize) {
+        self.viewport_height = viewport_height;
+        self.follow_content();
+    }
+
+    fn follow_content(&mut self) {
+        if self.stick_to_bottom {
+            self.position = self.get_max_scroll();
+        } else {
+            self.position = self.position.min(self.get_max_scroll());
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        if self.position > 0 {
+            self.position -= 1;
+            self.stick_to_bottom = false;
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        let max_scroll = self.get_max_scroll();
+        if self.position < max_scroll {
+            self.position += 1;
+            self.stick_to_bottom = self.position >= max_scroll;
+        }
+    }
+
+    /// Scrolls up by the viewport height
+    pub fn page_up(&mut self) {
+        if self.position > 0 {
+            self.position = self.position.saturating_sub(self.viewport_height.max(1));
+            self.stick_to_bottom = false;
+        }
+    }
+
+    /// Scrolls down by the viewport height, sticking to the bottom when reached
+    pub fn page_down(&mut self) {
+        let max_scroll = self.get_max_scroll();
+        self.position = (self.position + self.viewport_height.max(1)).min(max_scroll);
+        self.stick_to_bottom = self.position >= max_scroll;
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.position = 0;
+        self.stick_to_bottom = self.get_max_scroll() == 0;
+    }
+
+    pub fn scroll_to_bottom(&mut self) {
+        self.position = self.get_max_scroll();
+        self.stick_to_bottom = true;
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self {
+            viewport_height: self.viewport_height,
+            ..Self::default()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scroll_with(total_lines: usize, viewport_height: usize) -> ChatScroll {
+        let mut scroll = ChatScroll::default();
+        scroll.set_viewport_height(viewport_height);
+        scroll.set_total_lines(total_lines);
+        scroll
+    }
+
+    #[test]
+    fn test_follows_new_content_at_bottom() {
+        let mut scroll = scroll_with(30, 10);
+        assert_eq!(scroll.get_position(), 20);
+
+        scroll.set_total_lines(35);
+        assert_eq!(scroll.get_position(), 25);
+    }
+
+    #[test]
+    fn test_scrolled_up_position_is_stable_during_updates() {
+        let mut scroll = scroll_with(30, 10);
+        scroll.scroll_up();
+        scroll.scroll_up();
+        assert_eq!(scroll.get_position(), 18);
+
+        for total_lines in 31..60 {
+            scroll.set_total_lines(total_lines);
+        }
+        assert_eq!(scroll.get_position(), 18);
+        assert!(!scroll.is_stuck_to_bottom());
+
+        scroll.scroll_to_bottom();
+        scroll.set_total_lines(70);
+        assert_eq!(scroll.get_position(), 60);
+    }
+
+    #[test]
+    fn test_page_scrolling() {
+        let mut scroll = scroll_with(100, 10);
+        assert_eq!(scroll.get_position(), 90);
+
+        scroll.page_up();
+        assert_eq!(scroll.get_position(), 80);
+        assert!(!scroll.is_stuck_to_bottom());
+
+        for _ in 0..10 {
+            scroll.page_up();
+        }
+        assert_eq!(scroll.get_position(), 0);
+
+        scroll.page_down();
+        assert_eq!(scroll.get_position(), 10);
+        assert!(!scroll.is_stuck_to_bottom());
+
+        scroll.set_total_lines(25);
+        scroll.page_down();
+        assert_eq!(scroll.get_position(), 15);
+        assert!(scroll.is_stuck_to_bottom());
+    }
+
+    #[test]
+    fn test_scroll_to_top_and_back_to_bottom() {
+        let mut scroll = scroll_with(30, 10);
+
+        scroll.scroll_to_top();
+        scroll.set_total_lines(40);
+        assert_eq!(scroll.get_position(), 0);
+
+        scroll.scroll_to_bottom();
+        assert_eq!(scroll.get_position(), 30);
+        assert!(scroll.is_stuck_to_bottom());
+    }
+}
@@ -0,0 +1,127 @@
+use nixcode::Nixcode;
+use nixcode_llm_sdk::message::usage::Usage;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Margin, Rect};
+use ratatui::prelude::{Line, Span, Style, Stylize, Widget};
+use ratatui::widgets::{Block, BorderType, Clear, Paragraph, Wrap};
+
+/// Snapshot of the client state shown in the debug overlay
+#[derive(Debug, Default, Clone)]
+pub struct DebugInfo {
+    pub provider: String,
+    pub model: String,
+    pub messages_count: usize,
+    pub usage: Usage,
+    pub tool_schema_tokens: usize,
+    pub last_request: Option<String>,
+    pub last_error: Option<String>,
+}
+
+impl DebugInfo {
+    pub async fn from_client(client: &Nixcode) -> Self {
+        DebugInfo {
+            provider: client.get_config().llm.default_provider.clone(),
+            model: client.get_model().to_string(),
+            messages_count: client.get_messages().await.len(),
+            usage: client.get_usage().await,
+            tool_schema_tokens: client.get_tool_schema_tokens(),
+            last_request: client.get_last_request_summary().await,
+            last_error: client.get_error().await.map(|error| format!("{:?}", error)),
+        }
+    }
+}
+
+/// Troubleshooting panel rendered over the chat, toggled with `:debug`
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    visible: bool,
+    info: DebugInfo,
+}
+
+impl DebugOverlay {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_info(&mut self, info: DebugInfo) {
+        self.info = info;
+    }
+
+    fn field(name: &'static str, value: String) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(format!("{:<14}", name), Style::new().bold()),
+            Span::raw(value),
+        ])
+    }
+
+    fn get_lines(&self) -> Vec<Line<'static>> {
+        let info = &self.info;
+        let usage = &info.usage;
+
+        vec![
+            Self::field("Provider", info.provider.clone()),
+            Self::field("Model", info.model.clone()),
+            Self::field("Messages", info.messages_count.to_string()),
+            Self::field(
+                "Tokens",
+                format!(
+                    "input {}, output {}, cache read {}, cache write {}",
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    usage.cache_read_input_tokens.unwrap_or(0),
+                    usage.cache_creation_input_tokens.unwrap_or(0)
+                ),
+            ),
+            Self::field(
+                "Tool schemas",
+                format!("~{} tokens per request", info.tool_schema_tokens),
+            ),
+            Self::field(
+                "Last request",
+                info.last_request.clone().unwrap_or_else(|| "-".into()),
+            ),
+            Self::field(
+                "Last error",
+                info.last_error.clone().unwrap_or_else(|| "-".into()),
+            ),
+        ]
+    }
+}
+
+impl Widget for &DebugOverlay {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.inner(Margin::new(4, 2));
+        Clear.render(area, buf);
+
+        let block = Block::bordered()
+            .title(" Debug (:debug to close) ")
+            .border_type(BorderType::Rounded)
+            .yellow();
+
+        Paragraph::new(self.get_lines())
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_sets_visible_flag() {
+        let mut overlay = DebugOverlay::default();
+        assert!(!overlay.is_visible());
+
+        overlay.toggle();
+        assert!(overlay.is_visible());
+
+        overlay.toggle();
+        assert!(!overlay.is_visible());
+    }
+}
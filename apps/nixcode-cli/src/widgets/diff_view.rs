@@ -0,0 +1,100 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Margin, Rect};
+use ratatui::prelude::{Line, Style, Stylize, Widget};
+use ratatui::widgets::{Block, BorderType, Clear, Paragraph};
+
+/// Working tree diff rendered over the chat, opened with `:diff`
+#[derive(Debug, Default)]
+pub struct DiffView {
+    visible: bool,
+    lines: Vec<Line<'static>>,
+    scroll: usize,
+}
+
+impl DiffView {
+    pub fn open(&mut self, diff: &str) {
+        self.lines = Self::get_diff_lines(diff);
+        self.scroll = 0;
+        self.visible = true;
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.lines.clear();
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll + 1 < self.lines.len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Colors patch lines: file headers, hunk headers, additions and removals
+    pub fn get_diff_lines(diff: &str) -> Vec<Line<'static>> {
+        if diff.trim().is_empty() {
+            return vec![Line::from("No changes in the working tree").dark_gray().italic()];
+        }
+
+        diff.lines()
+            .map(|line| {
+                let style = if line.starts_with("diff --git") {
+                    Style::new().yellow().bold()
+                } else if line.starts_with("+++") || line.starts_with("---") {
+                    Style::new().bold()
+                } else if line.starts_with("@@") {
+                    Style::new().cyan()
+                } else if line.starts_with('+') {
+                    Style::new().green()
+                } else if line.starts_with('-') {
+                    Style::new().red()
+                } else {
+                    Style::new()
+                };
+
+                Line::styled(line.to_string(), style)
+            })
+            .collect()
+    }
+}
+
+impl Widget for &DiffView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.inner(Margin::new(2, 1));
+        Clear.render(area, buf);
+
+        let block = Block::bordered()
+            .title(" Diff (j/k to scroll, Esc to close) ")
+            .border_type(BorderType::Rounded);
+
+        Paragraph::new(self.lines.clone())
+            .scroll((self.scroll.min(u16::MAX as usize) as u16, 0))
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    #[test]
+    fn test_diff_lines_are_colored() {
+        let diff = "diff --git a/a.txt b/a.txt\n@@ -1 +1 @@\n-old\n+new\n context";
+        let lines = DiffView::get_diff_lines(diff);
+
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1].style.fg, Some(Color::Cyan));
+        assert_eq!(lines[2].style.fg, Some(Color::Red));
+        assert_eq!(lines[3].style.fg, Some(Color::Green));
+        assert_eq!(lines[4].style.fg, None);
+    }
+}
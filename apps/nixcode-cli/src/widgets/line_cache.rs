@@ -0,0 +1,129 @@
+use nixcode_llm_sdk::message::content::Content;
+use nixcode_llm_sdk::message::message::Message;
+use ratatui::text::Line;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::discriminant;
+
+/// Rendered lines of a message together with the fingerprint they were rendered from
+struct CachedLines {
+    fingerprint: u64,
+    lines: Vec<Line<'static>>,
+}
+
+/// Caches rendered lines per message index, so only changed messages are rendered again.
+/// While streaming that is just the last message instead of the whole transcript.
+#[derive(Default)]
+pub struct MessageLinesCache {
+    entries: Vec<Option<CachedLines>>,
+}
+
+impl MessageLinesCache {
+    /// Fingerprint of what changes while a message is streamed or its tools run: the
+    /// role, the kind and length of every block and the tool states. Messages only
+    /// grow in place, so this is enough to notice a change without formatting them.
+    fn fingerprint(message: &Message) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        let contents = match message {
+            Message::User(contents) => contents,
+            Message::System(contents) => contents,
+            Message::Assistant(contents) => contents,
+        };
+        discriminant(message).hash(&mut hasher);
+        for content in contents {
+            discriminant(content).hash(&mut hasher);
+            content.text_len().hash(&mut hasher);
+            if let Content::ToolUse(tool_use) = content {
+                discriminant(&tool_use.get_state()).hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Returns cached lines of the message, calling `render` only if it changed
+    pub fn get_or_render(
+        &mut self,
+        index: usize,
+        message: Message,
+        render: impl FnOnce(Message) -> Vec<Line<'static>>,
+    ) -> Vec<Line<'static>> {
+        let fingerprint = Self::fingerprint(&message);
+        if self.entries.len() <= index {
+            self.entries.resize_with(index + 1, || None);
+        }
+
+        match &self.entries[index] {
+            Some(cached) if cached.fingerprint == fingerprint => cached.lines.clone(),
+            _ => {
+                let lines = render(message);
+                self.entries[index] = Some(CachedLines {
+                    fingerprint,
+                    lines: lines.clone(),
+                });
+                lines
+            }
+        }
+    }
+
+    /// Drops entries of messages that no longer exist
+    pub fn truncate(&mut self, messages_count: usize) {
+        self.entries.truncate(messages_count);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nixcode_llm_sdk::message::content::tools::{ToolUseContent, ToolUseState};
+    use serde_json::json;
+
+    #[test]
+    fn test_stable_message_is_not_rendered_again() {
+        let mut cache = MessageLinesCache::default();
+        let mut rendered = Vec::new();
+        let first = Message::User(vec![Content::new_text("Question")]);
+
+        for delta in ["Ans", "Answer", "Answer."] {
+            let last = Message::Assistant(vec![Content::new_text(delta)]);
+            cache.get_or_render(0, first.clone(), |_| {
+                rendered.push(0);
+                vec![Line::from("Question")]
+            });
+            let lines = cache.get_or_render(1, last, |message| {
+                rendered.push(1);
+                vec![Line::from(
+                    message.get_content()[0].get_text().unwrap().get_text(),
+                )]
+            });
+            assert_eq!(lines[0].to_string(), delta);
+        }
+
+        assert_eq!(rendered, vec![0, 1, 1, 1]);
+    }
+
+    #[test]
+    fn test_tool_state_change_renders_again() {
+        let mut cache = MessageLinesCache::default();
+        let mut rendered = 0;
+        let tool_use = ToolUseContent::new("toolu_1", "read_text_file", json!({"path": "a"}));
+        let mut message = Message::Assistant(vec![Content::new_tool_use(tool_use)]);
+
+        for state in [
+            ToolUseState::Executing,
+            ToolUseState::Executing,
+            ToolUseState::Executed,
+        ] {
+            message.set_tool_state("toolu_1".into(), state);
+            cache.get_or_render(0, message.clone(), |_| {
+                rendered += 1;
+                vec![]
+            });
+        }
+
+        assert_eq!(rendered, 2);
+    }
+}
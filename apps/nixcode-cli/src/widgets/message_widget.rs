@@ -1,7 +1,10 @@
 use crate::utils::highlights::highlight_code;
+use nixcode::turn_summary::TurnSummary;
 use nixcode_llm_sdk::message::content::tools::ToolUseState;
 use nixcode_llm_sdk::message::content::Content;
 use nixcode_llm_sdk::message::message::Message;
+use nixcode_llm_sdk::message::metadata::MessageMetadata;
+use nixcode_llm_sdk::stop_reason::StopReason;
 use ratatui::prelude::*;
 use ratatui::text::Line;
 use serde_json::Value;
@@ -34,10 +37,117 @@ impl MessageWidget {
         formatted_params.join(", ")
     }
 
-    pub fn get_lines<'a>(message: Message) -> Vec<Line<'a>> {
+    /// Footer badge for stop reasons worth the user's attention
+    pub fn get_stop_reason_line<'a>(stop_reason: &StopReason) -> Option<Line<'a>> {
+        match stop_reason {
+            StopReason::MaxTokens => Some(
+                Line::from("⚠ truncated (max tokens)")
+                    .style(Style::new().black().on_yellow().bold()),
+            ),
+            StopReason::StopSequence => {
+                Some(Line::from("■ stopped at stop sequence").dark_gray().italic())
+            }
+            StopReason::EndTurn | StopReason::ToolUse => None,
+        }
+    }
+
+    /// Folds message lines to the first one followed by the number of hidden lines
+    pub fn collapse_lines<'a>(number: usize, mut lines: Vec<Line<'a>>) -> Vec<Line<'a>> {
+        let hidden_lines = lines
+            .iter()
+            .skip(1)
+            .filter(|line| !line.spans.is_empty())
+            .count();
+        lines.truncate(1);
+
+        if let Some(first_line) = lines.first_mut() {
+            first_line.spans.push(Span::styled(
+                format!(" … +{} lines (:expand {})", hidden_lines, number),
+                Style::new().dark_gray().italic(),
+            ));
+        }
+        lines.push(Line::from(vec![]));
+
+        lines
+    }
+
+    /// Summary of the tools run after message `number`, one line until it is expanded
+    /// with `:expand`
+    pub fn get_turn_summary_lines<'a>(
+        number: usize,
+        summary: &TurnSummary,
+        expanded: bool,
+    ) -> Vec<Line<'a>> {
+        let style = Style::new().dark_gray().italic();
+        let details = summary.get_details();
+        if !expanded || details.is_empty() {
+            let hint = match details.is_empty() {
+                true => String::new(),
+                false => format!(" (:expand {})", number),
+            };
+            return vec![
+                Line::styled(format!("▸ Turn: {}{}", summary, hint), style),
+                Line::from(vec![]),
+            ];
+        }
+
+        let mut lines = vec![Line::styled(format!("▾ Turn: {}", summary), style)];
+        lines.extend(
+            details
+                .into_iter()
+                .map(|detail| Line::styled(format!("  {}", detail), style)),
+        );
+        lines.push(Line::from(vec![]));
+
+        lines
+    }
+
+    /// Adds the pin marker, model and footer to already generated lines
+    pub fn decorate_lines<'a>(
+        number: usize,
+        mut lines: Vec<Line<'a>>,
+        metadata: &MessageMetadata,
+        collapsed: bool,
+    ) -> Vec<Line<'a>> {
+        if collapsed {
+            lines = Self::collapse_lines(number, lines);
+        }
+
+        // Pin marker and model before the author
+        if let Some(first_line) = lines.first_mut() {
+            let mut prefix = vec![];
+            if metadata.pinned {
+                prefix.push(Span::styled("📌 ", Style::new().yellow()));
+            }
+            if let Some(model) = &metadata.model {
+                prefix.push(Span::styled(
+                    format!("[{}] ", model),
+                    Style::new().dark_gray(),
+                ));
+            }
+            prefix.append(&mut first_line.spans);
+            first_line.spans = prefix;
+        }
+
+        if let Some(footer) = metadata
+            .stop_reason
+            .as_ref()
+            .filter(|_| !collapsed)
+            .and_then(Self::get_stop_reason_line)
+        {
+            lines.push(footer);
+            lines.push(Line::from(vec![]));
+        }
+
+        lines
+    }
+
+    pub fn get_lines<'a>(message: Message, assistant_label: &str) -> Vec<Line<'a>> {
         let author = match message {
             Message::User { .. } => Span::styled("You > ", Style::new().green()),
-            Message::Assistant { .. } => Span::styled("Assistant > ", Style::new().yellow()),
+            Message::Assistant { .. } => {
+                Span::styled(format!("{} > ", assistant_label), Style::new().yellow())
+            }
             Message::System { .. } => Span::styled("System > ", Style::new().dark_gray()),
         }
         .bold();
@@ -139,6 +249,10 @@ impl MessageWidget {
 
                     lines
                 }
+                Content::Image(image) => vec![
+                    Line::from(format!("[image: {}]", image.get_source().media_type)).italic(),
+                    Line::from(vec![]),
+                ],
                 _ => vec![Line::from("Unknown content type".to_string())],
             })
             .collect();
@@ -146,3 +260,121 @@ impl MessageWidget {
         lines
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nixcode_llm_sdk::message::content::Content;
+
+    fn get_lines_with_metadata<'a>(
+        number: usize,
+        message: Message,
+        metadata: &MessageMetadata,
+        collapsed: bool,
+    ) -> Vec<Line<'a>> {
+        let lines = MessageWidget::get_lines(message, "Assistant");
+        MessageWidget::decorate_lines(number, lines, metadata, collapsed)
+    }
+
+    fn assistant_lines(stop_reason: StopReason) -> Vec<String> {
+        let message = Message::Assistant(vec![Content::new_text("Hello")]);
+        let metadata = MessageMetadata {
+            stop_reason: Some(stop_reason),
+            ..Default::default()
+        };
+
+        get_lines_with_metadata(1, message, &metadata, false)
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_max_tokens_renders_truncated_badge() {
+        let lines = assistant_lines(StopReason::MaxTokens);
+
+        assert!(lines.iter().any(|line| line == "⚠ truncated (max tokens)"));
+    }
+
+    #[test]
+    fn test_stop_sequence_renders_badge() {
+        let lines = assistant_lines(StopReason::StopSequence);
+
+        assert!(lines.iter().any(|line| line == "■ stopped at stop sequence"));
+    }
+
+    #[test]
+    fn test_end_turn_and_tool_use_render_no_badge() {
+        let message = Message::Assistant(vec![Content::new_text("Hello")]);
+        let plain = MessageWidget::get_lines(message, "Assistant");
+
+        assert_eq!(assistant_lines(StopReason::EndTurn).len(), plain.len());
+        assert_eq!(assistant_lines(StopReason::ToolUse).len(), plain.len());
+    }
+
+    #[test]
+    fn test_pinned_message_shows_marker() {
+        let message = Message::User(vec![Content::new_text("Requirements")]);
+        let metadata = MessageMetadata {
+            pinned: true,
+            ..Default::default()
+        };
+
+        let lines = get_lines_with_metadata(3, message, &metadata, false);
+
+        assert!(lines[0].to_string().starts_with("📌 You > "));
+    }
+
+    #[test]
+    fn test_assistant_message_shows_label_and_model() {
+        let message = Message::Assistant(vec![Content::new_text("Hello")]);
+        let metadata = MessageMetadata {
+            model: Some("claude-3-7-sonnet".into()),
+            ..Default::default()
+        };
+
+        let lines = MessageWidget::get_lines(message, "nixcode");
+        let lines = MessageWidget::decorate_lines(2, lines, &metadata, false);
+
+        assert_eq!(
+            lines[0].to_string(),
+            "[claude-3-7-sonnet] nixcode > Hello"
+        );
+    }
+
+    #[test]
+    fn test_turn_summary_expands_to_details() {
+        let summary = TurnSummary {
+            tools: 2,
+            files_changed: vec!["src/lib.rs".into()],
+            ..Default::default()
+        };
+
+        let collapsed = MessageWidget::get_turn_summary_lines(4, &summary, false);
+        let expanded = MessageWidget::get_turn_summary_lines(4, &summary, true);
+
+        assert_eq!(
+            collapsed[0].to_string(),
+            "▸ Turn: 2 tool calls, 1 file changed (:expand 4)"
+        );
+        assert_eq!(expanded[1].to_string(), "  changed src/lib.rs");
+    }
+
+    #[test]
+    fn test_collapsed_message_renders_single_line() {
+        let text = (1..=20)
+            .map(|i| format!("Line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let message = Message::Assistant(vec![Content::new_text(text)]);
+        let metadata = MessageMetadata::default();
+
+        let expanded =
+            get_lines_with_metadata(2, message.clone(), &metadata, false);
+        let collapsed = get_lines_with_metadata(2, message, &metadata, true);
+
+        assert!(expanded.len() > 20);
+        assert_eq!(collapsed.len(), 2);
+        assert!(collapsed[0].to_string().ends_with("… +19 lines (:expand 2)"));
+    }
+}
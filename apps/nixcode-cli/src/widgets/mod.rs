@@ -1,2 +1,6 @@
 pub mod chat;
+mod chat_scroll;
+pub mod debug_overlay;
+pub mod diff_view;
+mod line_cache;
 mod message_widget;
@@ -1,14 +1,201 @@
-use secrecy::SecretString;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use secrecy::{ExposeSecret, SecretString};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// OpenAI chat completions endpoint
+pub const OPENAI_CHAT_COMPLETIONS_URL: &str = "https://api.openai.com/v1/chat/completions";
+
+/// Default OpenAI-compatible endpoint of a local Ollama server
+pub const OLLAMA_DEFAULT_BASE_URL: &str = "http://localhost:11434/v1";
 
 #[derive(Debug, Clone)]
 pub struct LLMConfig {
     pub api_key: SecretString,
+    /// Directory where redacted request/response recordings are written (opt-in)
+    pub record_dir: Option<PathBuf>,
+    /// Headers sent with every request, e.g. for LLM gateways and observability proxies
+    pub extra_headers: HashMap<String, String>,
 }
 
 impl LLMConfig {
     pub fn new_anthropic() -> anyhow::Result<Self> {
         let api_key = SecretString::new(std::env::var("ANTHROPIC_API_KEY")?.into());
 
-        Ok(Self { api_key })
+        Ok(Self {
+            api_key,
+            record_dir: None,
+            extra_headers: HashMap::new(),
+        })
+    }
+
+    pub fn with_record_dir(mut self, record_dir: Option<PathBuf>) -> Self {
+        self.record_dir = record_dir;
+        self
+    }
+
+    pub fn with_extra_headers(mut self, extra_headers: HashMap<String, String>) -> Self {
+        self.extra_headers = extra_headers;
+        self
+    }
+}
+
+/// Adds the configured headers, replacing the defaults of the same name. Values are
+/// marked sensitive as gateways often pass keys in them.
+pub fn insert_extra_headers(
+    headers: &mut HeaderMap,
+    extra_headers: &HashMap<String, String>,
+) -> anyhow::Result<()> {
+    for (name, value) in extra_headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Invalid header name {}: {}", name, e))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|e| anyhow::anyhow!("Invalid value of header {}: {}", name, e))?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+
+    Ok(())
+}
+
+/// Endpoint and headers used by OpenAI-compatible clients
+#[derive(Debug, Clone)]
+pub struct HttpClientOptions {
+    pub url: String,
+    pub headers: HeaderMap,
+}
+
+impl HttpClientOptions {
+    fn json_headers() -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers
+    }
+
+    fn sensitive_header(value: &str) -> anyhow::Result<HeaderValue> {
+        let mut value = HeaderValue::from_str(value)?;
+        value.set_sensitive(true);
+        Ok(value)
+    }
+
+    pub fn new_openai(api_key: &SecretString) -> anyhow::Result<Self> {
+        let mut headers = Self::json_headers();
+        let authorization = format!("Bearer {}", api_key.expose_secret());
+        headers.insert(AUTHORIZATION, Self::sensitive_header(&authorization)?);
+
+        Ok(Self {
+            url: OPENAI_CHAT_COMPLETIONS_URL.to_string(),
+            headers,
+        })
+    }
+
+    /// Azure OpenAI uses per-deployment URLs and an `api-key` header
+    pub fn new_azure(
+        endpoint: &str,
+        deployment: &str,
+        api_version: &str,
+        api_key: &SecretString,
+    ) -> anyhow::Result<Self> {
+        let mut headers = Self::json_headers();
+        headers.insert("api-key", Self::sensitive_header(api_key.expose_secret())?);
+
+        let url = format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            endpoint.trim_end_matches('/'),
+            deployment,
+            api_version
+        );
+
+        Ok(Self { url, headers })
+    }
+
+    /// OpenAI-compatible local servers (Ollama, LM Studio); the API key is optional
+    pub fn new_ollama(base_url: &str, api_key: Option<&SecretString>) -> anyhow::Result<Self> {
+        let mut headers = Self::json_headers();
+        if let Some(api_key) = api_key.filter(|key| !key.expose_secret().is_empty()) {
+            let authorization = format!("Bearer {}", api_key.expose_secret());
+            headers.insert(AUTHORIZATION, Self::sensitive_header(&authorization)?);
+        }
+
+        let url = format!("{}/chat/completions", base_url.trim_end_matches('/'));
+
+        Ok(Self { url, headers })
+    }
+
+    pub fn with_extra_headers(
+        mut self,
+        extra_headers: &HashMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        insert_extra_headers(&mut self.headers, extra_headers)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_azure_options_url_and_headers() {
+        let key = SecretString::new("azure-secret".into());
+        let options = HttpClientOptions::new_azure(
+            "https://example.openai.azure.com/",
+            "gpt-4o",
+            "2024-10-21",
+            &key,
+        )
+        .unwrap();
+
+        assert_eq!(
+            options.url,
+            "https://example.openai.azure.com/openai/deployments/gpt-4o/chat/completions?api-version=2024-10-21"
+        );
+        assert_eq!(options.headers.get("api-key").unwrap(), "azure-secret");
+        assert!(options.headers.get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_ollama_options_without_api_key() {
+        let options = HttpClientOptions::new_ollama("http://localhost:11434/v1/", None).unwrap();
+
+        assert_eq!(options.url, "http://localhost:11434/v1/chat/completions");
+        assert!(options.headers.get(AUTHORIZATION).is_none());
+    }
+
+    #[test]
+    fn test_extra_headers_are_added_to_options() {
+        let key = SecretString::new("sk-test".into());
+        let extra_headers = HashMap::from([
+            (
+                "Helicone-Auth".to_string(),
+                "Bearer sk-helicone".to_string(),
+            ),
+            (
+                "Content-Type".to_string(),
+                "application/json; charset=utf-8".to_string(),
+            ),
+        ]);
+
+        let options = HttpClientOptions::new_openai(&key)
+            .unwrap()
+            .with_extra_headers(&extra_headers)
+            .unwrap();
+
+        assert_eq!(
+            options.headers.get("helicone-auth").unwrap(),
+            "Bearer sk-helicone"
+        );
+        assert_eq!(
+            options.headers.get(CONTENT_TYPE).unwrap(),
+            "application/json; charset=utf-8"
+        );
+        assert_eq!(
+            options.headers.get(AUTHORIZATION).unwrap(),
+            "Bearer sk-test"
+        );
+
+        let invalid = HashMap::from([("Bad Header".to_string(), "value".to_string())]);
+        let options = HttpClientOptions::new_openai(&key).unwrap();
+        assert!(options.with_extra_headers(&invalid).is_err());
     }
 }
@@ -0,0 +1,150 @@
+use crate::errors::provider::ProviderError;
+use crate::ErrorContent;
+
+/// Plain-English explanation of an error with a suggested fix
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorExplanation {
+    pub summary: &'static str,
+    pub fix: &'static str,
+}
+
+impl std::fmt::Display for ErrorExplanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}\nFix: {}", self.summary, self.fix)
+    }
+}
+
+const RATE_LIMIT: ErrorExplanation = ErrorExplanation {
+    summary: "The provider rate limit was reached: too many requests or tokens in a short time.",
+    fix: "Wait a minute and :retry, or raise the limits of your account tier.",
+};
+
+const OVERLOADED: ErrorExplanation = ErrorExplanation {
+    summary: "The provider is temporarily overloaded or had an internal error.",
+    fix: "Wait a moment and :retry, the request itself is fine.",
+};
+
+const CONTEXT_LENGTH: ErrorExplanation = ErrorExplanation {
+    summary: "The conversation no longer fits in the context window of the model.",
+    fix: "Start a new session with :new, remove large messages with :remove-last, or set agent.elide_tool_results_after_turns.",
+};
+
+const INVALID_KEY: ErrorExplanation = ErrorExplanation {
+    summary: "The provider rejected the API key, it is missing, mistyped or revoked.",
+    fix: "Check api_key of the provider in config.toml or its environment variable (e.g. ANTHROPIC_API_KEY).",
+};
+
+const PERMISSION: ErrorExplanation = ErrorExplanation {
+    summary: "The API key is valid but not allowed to use this model or feature.",
+    fix: "Pick another model with --model, or enable access in the provider console.",
+};
+
+const MODEL_NOT_FOUND: ErrorExplanation = ErrorExplanation {
+    summary: "The provider does not know the requested model.",
+    fix:
+        "Check the model name (or its alias in [models.aliases]) and pass a valid one with --model.",
+};
+
+const BILLING: ErrorExplanation = ErrorExplanation {
+    summary: "The account has no credits or exceeded its spending quota.",
+    fix: "Add credits or raise the quota in the billing settings of the provider.",
+};
+
+const STREAM_IDLE: ErrorExplanation = ErrorExplanation {
+    summary: "The provider stopped sending the response midway.",
+    fix: ":retry the message, or raise agent.stream_idle_timeout_secs for slow models.",
+};
+
+/// Explains common provider errors by their kind (the OpenAI code or the error
+/// type) and message, None for errors without a known explanation
+pub fn explain_error(kind: &str, message: &str) -> Option<ErrorExplanation> {
+    let message = message.to_lowercase();
+
+    // Anthropic reports a too long prompt as a generic invalid request
+    if kind == "context_length_exceeded"
+        || kind == "input_too_long"
+        || message.contains("prompt is too long")
+        || message.contains("maximum context length")
+    {
+        return Some(CONTEXT_LENGTH);
+    }
+
+    match kind {
+        "rate_limit_error" | "rate_limit_exceeded" | "429" => Some(RATE_LIMIT),
+        "overloaded_error" | "api_error" | "server_error" => Some(OVERLOADED),
+        "authentication_error" | "invalid_api_key" | "missing_api_key" => Some(INVALID_KEY),
+        "permission_error" => Some(PERMISSION),
+        "not_found_error" | "model_not_found" => Some(MODEL_NOT_FOUND),
+        "insufficient_quota" | "billing_error" => Some(BILLING),
+        "stream_idle_timeout" => Some(STREAM_IDLE),
+        _ if message.contains("model") && message.contains("not found") => Some(MODEL_NOT_FOUND),
+        _ => None,
+    }
+}
+
+impl ProviderError {
+    /// Server errors without a known type (e.g. an HTML error page of a proxy) are
+    /// explained by their status
+    pub fn explain(&self) -> Option<ErrorExplanation> {
+        explain_error(self.get_kind(), &self.message)
+            .or_else(|| (self.status >= 500).then_some(OVERLOADED))
+    }
+}
+
+impl ErrorContent {
+    /// Like `ProviderError::explain`, when the error still has the status of the response
+    pub fn explain(&self) -> Option<ErrorExplanation> {
+        let server_error = self.status.is_some_and(|status| status >= 500);
+        explain_error(self.get_type(), self.get_message())
+            .or_else(|| server_error.then_some(OVERLOADED))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::llm::LLMError;
+
+    fn explain_response(status: u16, body: &str) -> Option<ErrorExplanation> {
+        ProviderError::from_response(status, body).explain()
+    }
+
+    #[test]
+    fn test_known_provider_errors_are_explained() {
+        let body = r#"{"type":"error","error":{"type":"rate_limit_error","message":"Number of request tokens has exceeded your per-minute rate limit"}}"#;
+        assert_eq!(explain_response(429, body), Some(RATE_LIMIT));
+
+        let body = r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#;
+        assert_eq!(explain_response(400, body), Some(CONTEXT_LENGTH));
+
+        let body = r#"{"error":{"message":"Incorrect API key provided: sk-abc.","type":"invalid_request_error","param":null,"code":"invalid_api_key"}}"#;
+        assert_eq!(explain_response(401, body), Some(INVALID_KEY));
+
+        let body = r#"{"error":{"message":"The model `gpt-9` does not exist","type":"invalid_request_error","param":null,"code":"model_not_found"}}"#;
+        assert_eq!(explain_response(404, body), Some(MODEL_NOT_FOUND));
+
+        let body = r#"{"error":"model 'llama9' not found, try pulling it first"}"#;
+        assert_eq!(explain_response(404, body), Some(MODEL_NOT_FOUND));
+
+        assert_eq!(
+            explain_response(502, "<html>Bad Gateway</html>"),
+            Some(OVERLOADED)
+        );
+        assert_eq!(explain_response(400, "Bad Request"), None);
+    }
+
+    #[test]
+    fn test_error_content_is_explained_by_type() {
+        let error: ErrorContent = LLMError::MissingAPIKey.into();
+        assert_eq!(error.explain(), Some(INVALID_KEY));
+
+        let error: ErrorContent = LLMError::InputTooLong.into();
+        assert_eq!(error.explain(), Some(CONTEXT_LENGTH));
+
+        let error: ErrorContent = LLMError::from_response(502, "<html>Bad Gateway</html>").into();
+        assert_eq!(error.explain(), Some(OVERLOADED));
+
+        let error: ErrorContent = LLMError::Generic("Unexpected end of stream".into()).into();
+        assert_eq!(error.explain(), None);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::errors::provider::ProviderError;
 use crate::ErrorContent;
 use anyhow::Error;
 
@@ -5,7 +6,8 @@ use anyhow::Error;
 pub enum LLMError {
     CreateClientError(String),
     InvalidRequest,
-    InvalidResponseCode(u16, String),
+    /// The provider answered with an error status, see the parsed body
+    Provider(ProviderError),
     InvalidResponse(String),
     ParseError(String),
     ReqwestError,
@@ -16,15 +18,19 @@ pub enum LLMError {
     Generic(String),
 }
 
+impl LLMError {
+    /// Error for a failed response, with the provider error parsed from the body
+    pub fn from_response(status: u16, body: &str) -> Self {
+        LLMError::Provider(ProviderError::from_response(status, body))
+    }
+}
+
 impl Into<Error> for LLMError {
     fn into(self) -> Error {
         match self {
             LLMError::CreateClientError(e) => Error::msg(e),
             LLMError::InvalidRequest => Error::msg("Invalid request"),
-            LLMError::InvalidResponseCode(code, body) => Error::msg(format!(
-                "Invalid response code: {} with body: {}",
-                code, body
-            )),
+            LLMError::Provider(e) => Error::msg(e.to_string()),
             LLMError::InvalidResponse(e) => Error::msg(e),
             LLMError::ParseError(e) => Error::msg(e),
             LLMError::ReqwestError => Error::msg("Reqwest error"),
@@ -39,11 +45,17 @@ impl Into<Error> for LLMError {
 
 impl Into<ErrorContent> for LLMError {
     fn into(self) -> ErrorContent {
+        let status = match &self {
+            LLMError::Provider(e) => Some(e.status),
+            _ => None,
+        };
+
         ErrorContent {
+            status,
             r#type: match self {
                 LLMError::CreateClientError(_) => "create_client_error".into(),
                 LLMError::InvalidRequest => "invalid_request".into(),
-                LLMError::InvalidResponseCode(_, _) => "invalid_response_code".into(),
+                LLMError::Provider(ref e) => e.get_kind().into(),
                 LLMError::InvalidResponse(_) => "invalid_response".into(),
                 LLMError::ParseError(_) => "parse_error".into(),
                 LLMError::ReqwestError => "reqwest_error".into(),
@@ -56,9 +68,7 @@ impl Into<ErrorContent> for LLMError {
             message: match self {
                 LLMError::CreateClientError(e) => e,
                 LLMError::InvalidRequest => "Invalid request".into(),
-                LLMError::InvalidResponseCode(code, body) => {
-                    format!("Invalid response code: {} with body: {}", code, body)
-                }
+                LLMError::Provider(e) => e.message,
                 LLMError::InvalidResponse(e) => e,
                 LLMError::ParseError(e) => e,
                 LLMError::ReqwestError => "Reqwest error".into(),
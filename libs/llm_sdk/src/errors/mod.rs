@@ -1 +1,3 @@
+pub mod explain;
 pub mod llm;
+pub mod provider;
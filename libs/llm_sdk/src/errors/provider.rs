@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt::Display;
+
+/// Error type used when the body of a failed response is not a known error shape
+pub const UNKNOWN_ERROR_TYPE: &str = "invalid_response_code";
+
+/// Error returned by a provider API for a failed request, parsed from the response
+/// body (Anthropic `{error: {type, message}}`, OpenAI `{error: {message, type, code}}`
+/// or Ollama `{error: message}`)
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProviderError {
+    pub status: u16,
+    pub r#type: String,
+    pub message: String,
+    pub code: Option<String>,
+}
+
+impl ProviderError {
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let error = serde_json::from_str::<Value>(body)
+            .ok()
+            .and_then(|value| value.get("error").cloned());
+
+        let text = |value: &Value, key: &str| match value.get(key) {
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(Value::Number(number)) => Some(number.to_string()),
+            _ => None,
+        };
+
+        match error {
+            Some(Value::String(message)) => Self {
+                status,
+                r#type: UNKNOWN_ERROR_TYPE.into(),
+                message,
+                code: None,
+            },
+            Some(error @ Value::Object(_)) => Self {
+                status,
+                r#type: text(&error, "type").unwrap_or_else(|| UNKNOWN_ERROR_TYPE.into()),
+                message: text(&error, "message").unwrap_or_else(|| error.to_string()),
+                code: text(&error, "code"),
+            },
+            _ => Self {
+                status,
+                r#type: UNKNOWN_ERROR_TYPE.into(),
+                message: match body.trim() {
+                    "" => format!("Invalid response code: {}", status),
+                    body => format!("Invalid response code: {} with body: {}", status, body),
+                },
+                code: None,
+            },
+        }
+    }
+
+    /// The most specific label of the error, the OpenAI code (e.g. `invalid_api_key`)
+    /// or the error type (e.g. `overloaded_error`)
+    pub fn get_kind(&self) -> &str {
+        self.code.as_deref().unwrap_or(&self.r#type)
+    }
+
+    /// Rate limits, overloaded providers and server errors may succeed when retried
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.r#type.as_str(),
+            "rate_limit_error" | "overloaded_error" | "api_error"
+        ) || self.status == 429
+            || self.status >= 500
+    }
+}
+
+impl Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.get_kind(), self.message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anthropic_error() {
+        let body = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let error = ProviderError::from_response(529, body);
+
+        assert_eq!(error.r#type, "overloaded_error");
+        assert_eq!(error.code, None);
+        assert_eq!(error.to_string(), "overloaded_error: Overloaded");
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_openai_error() {
+        let body = r#"{
+            "error": {
+                "message": "Incorrect API key provided: sk-abc.",
+                "type": "invalid_request_error",
+                "param": null,
+                "code": "invalid_api_key"
+            }
+        }"#;
+        let error = ProviderError::from_response(401, body);
+
+        assert_eq!(error.r#type, "invalid_request_error");
+        assert_eq!(
+            error.to_string(),
+            "invalid_api_key: Incorrect API key provided: sk-abc."
+        );
+        assert!(!error.is_retryable());
+
+        let body = r#"{"error":{"message":"Rate limit reached","type":"requests","code":429}}"#;
+        let error = ProviderError::from_response(429, body);
+        assert_eq!(error.get_kind(), "429");
+        assert!(error.is_retryable());
+    }
+
+    #[test]
+    fn test_unknown_error_bodies() {
+        let error = ProviderError::from_response(404, r#"{"error":"model 'llama9' not found"}"#);
+        assert_eq!(
+            error.to_string(),
+            "invalid_response_code: model 'llama9' not found"
+        );
+
+        let error = ProviderError::from_response(502, "<html>Bad Gateway</html>");
+        assert_eq!(
+            error.message,
+            "Invalid response code: 502 with body: <html>Bad Gateway</html>"
+        );
+        assert!(error.is_retryable());
+    }
+}
@@ -2,19 +2,29 @@ pub mod config;
 pub mod errors;
 pub mod json_schema;
 pub mod message;
+pub mod models;
+pub mod openai;
 pub mod providers;
+pub mod recorder;
+pub mod replay;
+pub mod schema;
 pub mod stop_reason;
 pub mod tools;
 
-use crate::tools::Tool;
-use config::LLMConfig;
+use crate::tools::{Tool, ToolChoice};
+use config::{insert_extra_headers, HttpClientOptions, LLMConfig};
 use errors::llm::LLMError;
 use eventsource_stream::{Event, Eventsource};
 use futures::StreamExt;
 use message::content::{Content, ContentDelta};
 use message::message::Message;
 use message::response::MessageResponse;
+use message::tool_message::ToolMessage;
 use message::usage::{Usage, UsageDelta};
+use models::ReasoningEffort;
+pub use openai::OpenAIClient;
+use recorder::Recorder;
+use replay::ReplayClient;
 use secrecy::ExposeSecret;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -63,7 +73,15 @@ pub struct Request {
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<Tool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     system: Option<Vec<Content>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip)]
+    reasoning_effort: Option<ReasoningEffort>,
+    #[serde(skip)]
+    prefill: Option<String>,
     #[serde(skip)]
     _cache: Option<bool>,
 }
@@ -77,7 +95,11 @@ impl Default for Request {
             stream: true,
             thinking: None,
             tools: None,
+            tool_choice: None,
             system: None,
+            temperature: None,
+            reasoning_effort: None,
+            prefill: None,
             _cache: None,
         }
     }
@@ -114,11 +136,48 @@ impl Request {
         self
     }
 
+    /// Tool choice is only sent together with tools
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
     pub fn with_system_prompt(mut self, system: Vec<Content>) -> Self {
         self.system = Some(system);
         self
     }
 
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Reasoning effort for OpenAI reasoning models, ignored by other providers
+    pub fn with_reasoning_effort(mut self, reasoning_effort: ReasoningEffort) -> Self {
+        self.reasoning_effort = Some(reasoning_effort);
+        self
+    }
+
+    /// Start of the assistant response which the model continues (e.g. `{` to force
+    /// JSON), the response does not repeat it. Trailing whitespace is dropped, Anthropic
+    /// rejects a final assistant message ending with it
+    pub fn with_prefill(mut self, prefill: impl Into<String>) -> Self {
+        self.prefill = Some(prefill.into().trim_end().to_string());
+        self
+    }
+
+    pub fn get_prefill(&self) -> Option<&str> {
+        self.prefill.as_deref()
+    }
+
+    pub fn get_temperature(&self) -> Option<f32> {
+        self.temperature
+    }
+
+    pub fn get_tool_choice(&self) -> Option<&ToolChoice> {
+        self.tool_choice.as_ref()
+    }
+
     pub fn with_cache(mut self) -> Self {
         self._cache = Some(true);
         self
@@ -190,6 +249,33 @@ impl MessageDeltaEventContent {
 pub struct ErrorContent {
     r#type: String,
     message: String,
+    /// HTTP status of a failed response, explains server errors without a known type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+}
+
+impl ErrorContent {
+    pub fn new(r#type: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            r#type: r#type.into(),
+            message: message.into(),
+            status: None,
+        }
+    }
+
+    pub fn get_type(&self) -> &str {
+        &self.r#type
+    }
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for ErrorContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.r#type, self.message)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -212,6 +298,7 @@ pub struct AnthropicClient {
     history: Vec<Message>,
     options: LLMConfig,
     client: reqwest::Client,
+    recorder: Option<Recorder>,
 }
 
 impl AnthropicClient {
@@ -264,6 +351,7 @@ struct InputTokens {
 pub enum LLMClient {
     OpenAI(OpenAIClient),
     Anthropic(AnthropicClient),
+    Replay(ReplayClient),
 }
 
 impl LLMClient {
@@ -287,10 +375,49 @@ impl LLMClient {
         Ok(LLMClient::Anthropic(client?))
     }
 
+    /// Creates an OpenAI-compatible client for an Azure OpenAI deployment
+    pub fn new_azure(
+        options: LLMConfig,
+        endpoint: &str,
+        deployment: &str,
+        api_version: &str,
+    ) -> anyhow::Result<Self, LLMError> {
+        let http_options =
+            HttpClientOptions::new_azure(endpoint, deployment, api_version, &options.api_key)
+                .map_err(|e| LLMError::CreateClientError(e.to_string()))?;
+        let client = OpenAIClient::new_with_http_options(options, http_options)?;
+
+        Ok(LLMClient::OpenAI(client))
+    }
+
+    /// Creates an OpenAI-compatible client for a local Ollama/LM Studio server
+    pub fn new_ollama(options: LLMConfig, base_url: &str) -> anyhow::Result<Self, LLMError> {
+        let http_options = HttpClientOptions::new_ollama(base_url, Some(&options.api_key))
+            .map_err(|e| LLMError::CreateClientError(e.to_string()))?;
+        let client = OpenAIClient::new_with_http_options(options, http_options)?;
+
+        Ok(LLMClient::OpenAI(client))
+    }
+
+    /// Creates a client replaying responses recorded in the fixture directory
+    pub fn new_replay(dir: &std::path::Path) -> anyhow::Result<Self, LLMError> {
+        Ok(LLMClient::Replay(ReplayClient::new(dir)?))
+    }
+
+    /// Whether the provider continues a trailing assistant message (prefill)
+    pub fn supports_assistant_prefill(&self) -> bool {
+        match self {
+            LLMClient::OpenAI(_) => false,
+            LLMClient::Anthropic(_) => true,
+            LLMClient::Replay(_) => true,
+        }
+    }
+
     pub async fn count_tokens(&self, request: Request) -> Result<u32, LLMError> {
         match self {
             LLMClient::OpenAI(client) => client.count_tokens(request).await,
             LLMClient::Anthropic(client) => client.count_tokens(request).await,
+            LLMClient::Replay(client) => client.count_tokens(request).await,
         }
     }
 
@@ -301,12 +428,11 @@ impl LLMClient {
         match self {
             LLMClient::OpenAI(client) => client.send(request).await,
             LLMClient::Anthropic(client) => client.send(request).await,
+            LLMClient::Replay(client) => client.send(request).await,
         }
     }
 }
 
-pub struct OpenAIClient {}
-
 pub trait LLMClientImpl {
     fn count_tokens(
         &self,
@@ -322,27 +448,92 @@ pub trait LLMClientImpl {
     fn get_config(&self) -> LLMConfig;
 }
 
-impl LLMClientImpl for OpenAIClient {
-    async fn count_tokens(&self, request: Request) -> Result<u32, LLMError> {
-        todo!()
-    }
+/// Converts a request into an Anthropic messages body, with cache breakpoints and
+/// the prefill sent as a trailing assistant message
+pub fn request_to_anthropic(request: &Request) -> serde_json::Value {
+    let mut body = serde_json::to_value(request).unwrap();
+
+    // Tool results have to come first in their user message
+    let messages = body["messages"].as_array_mut().unwrap();
+    for (message, value) in request.messages.iter().zip(messages) {
+        let tool_messages = message.get_tool_messages();
+        if tool_messages.is_empty() {
+            continue;
+        }
 
-    async fn send(
-        &self,
-        request: Request,
-    ) -> Result<UnboundedReceiver<MessageResponseStreamEvent>, LLMError> {
-        todo!()
+        let other_blocks = value["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|block| block["type"] != "tool_result")
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut content = tool_messages
+            .iter()
+            .map(ToolMessage::to_anthropic)
+            .collect::<Vec<_>>();
+        content.extend(other_blocks);
+        value["content"] = json!(content);
     }
 
-    fn get_config(&self) -> LLMConfig {
-        todo!()
+    if request.is_cache_enabled() && !request.messages.is_empty() {
+        body.as_object_mut()
+            .unwrap()
+            .get_mut("messages")
+            .unwrap()
+            .as_array_mut()
+            .unwrap()
+            .last_mut()
+            .unwrap()
+            .as_object_mut()
+            .unwrap()
+            .get_mut("content")
+            .unwrap()
+            .as_array_mut()
+            .unwrap()
+            .last_mut()
+            .unwrap()
+            .as_object_mut()
+            .unwrap()
+            .insert("cache_control".into(), json!({"type": "ephemeral"}));
+
+        if request.system.is_some() {
+            body.as_object_mut()
+                .unwrap()
+                .get_mut("system")
+                .unwrap()
+                .as_array_mut()
+                .unwrap()
+                .last_mut()
+                .unwrap()
+                .as_object_mut()
+                .unwrap()
+                .insert("cache_control".into(), json!({"type": "ephemeral"}));
+        }
+        if request.tools.is_some() {
+            body.as_object_mut()
+                .unwrap()
+                .get_mut("tools")
+                .unwrap()
+                .as_array_mut()
+                .unwrap()
+                .last_mut()
+                .unwrap()
+                .as_object_mut()
+                .unwrap()
+                .insert("cache_control".into(), json!({"type": "ephemeral"}));
+        }
     }
-}
 
-impl OpenAIClient {
-    pub fn new(options: LLMConfig) -> anyhow::Result<Self, LLMError> {
-        todo!()
+    if let Some(prefill) = &request.prefill {
+        let prefill = json!({
+            "role": "assistant",
+            "content": [{"type": "text", "text": prefill}],
+        });
+        body["messages"].as_array_mut().unwrap().push(prefill);
     }
+
+    body
 }
 
 impl AnthropicClient {
@@ -357,6 +548,8 @@ impl AnthropicClient {
             "application/json".parse().unwrap(),
         );
         headers.insert("anthropic-version", "2023-06-01".parse().unwrap());
+        insert_extra_headers(&mut headers, &options.extra_headers)
+            .map_err(|e| LLMError::CreateClientError(e.to_string()))?;
         // headers.insert(
         //     AUTHORIZATION,
         //     format!("Bearer {}", options.api_key.expose_secret())
@@ -364,6 +557,12 @@ impl AnthropicClient {
         //         .unwrap(),
         // );
 
+        let recorder = options.record_dir.as_ref().map(|dir| {
+            Recorder::new(dir)
+                .with_headers(&headers)
+                .with_secret(options.api_key.expose_secret())
+        });
+
         let reqwest_client = reqwest::Client::builder().default_headers(headers).build();
         if reqwest_client.is_err() {
             return Err(LLMError::CreateClientError(
@@ -376,6 +575,7 @@ impl AnthropicClient {
             client: reqwest_client.unwrap(),
             history: Vec::new(),
             total_usages: Usage::default(),
+            recorder,
         };
 
         Ok(client)
@@ -400,10 +600,9 @@ impl LLMClientImpl for AnthropicClient {
         let response = response.unwrap();
 
         if !response.status().is_success() {
-            return Err(LLMError::InvalidResponseCode(
-                response.status().as_u16(),
-                response.text().await.unwrap(),
-            ));
+            let status = response.status().as_u16();
+            let text = response.text().await.unwrap_or_default();
+            return Err(LLMError::from_response(status, &text));
         }
 
         let body = response.json::<InputTokens>().await;
@@ -418,62 +617,15 @@ impl LLMClientImpl for AnthropicClient {
         &self,
         request: Request,
     ) -> Result<UnboundedReceiver<MessageResponseStreamEvent>, LLMError> {
-        let mut body = serde_json::to_value(&request).unwrap();
-        if request.is_cache_enabled() && !request.messages.is_empty() {
-            body.as_object_mut()
-                .unwrap()
-                .get_mut("messages")
-                .unwrap()
-                .as_array_mut()
-                .unwrap()
-                .last_mut()
-                .unwrap()
-                .as_object_mut()
-                .unwrap()
-                .get_mut("content")
-                .unwrap()
-                .as_array_mut()
-                .unwrap()
-                .last_mut()
-                .unwrap()
-                .as_object_mut()
-                .unwrap()
-                .insert("cache_control".into(), json!({"type": "ephemeral"}));
+        let body = request_to_anthropic(&request);
 
-            if request.system.is_some() {
-                body.as_object_mut()
-                    .unwrap()
-                    .get_mut("system")
-                    .unwrap()
-                    .as_array_mut()
-                    .unwrap()
-                    .last_mut()
-                    .unwrap()
-                    .as_object_mut()
-                    .unwrap()
-                    .insert("cache_control".into(), json!({"type": "ephemeral"}));
-            }
-            if request.tools.is_some() {
-                body.as_object_mut()
-                    .unwrap()
-                    .get_mut("tools")
-                    .unwrap()
-                    .as_array_mut()
-                    .unwrap()
-                    .last_mut()
-                    .unwrap()
-                    .as_object_mut()
-                    .unwrap()
-                    .insert("cache_control".into(), json!({"type": "ephemeral"}));
-            }
-        }
+        let url = "https://api.anthropic.com/v1/messages";
+        let mut recording = self
+            .recorder
+            .as_ref()
+            .and_then(|recorder| recorder.record_request("anthropic", url, &body));
 
-        let result = self
-            .client
-            .post("https://api.anthropic.com/v1/messages")
-            .json(&body)
-            .send()
-            .await;
+        let result = self.client.post(url).json(&body).send().await;
 
         if result.is_err() {
             return Err(LLMError::ReqwestError);
@@ -482,10 +634,13 @@ impl LLMClientImpl for AnthropicClient {
         let response = result.unwrap();
 
         if !response.status().is_success() {
-            return Err(LLMError::InvalidResponseCode(
-                response.status().as_u16(),
-                response.text().await.unwrap(),
-            ));
+            let status = response.status().as_u16();
+            let text = response.text().await.unwrap_or_default();
+            if let Some(recording) = recording.as_mut() {
+                recording.record_error(status, &text);
+            }
+
+            return Err(LLMError::from_response(status, &text));
         }
 
         let (tx, rx) = unbounded_channel::<MessageResponseStreamEvent>();
@@ -495,6 +650,10 @@ impl LLMClientImpl for AnthropicClient {
             while let Some(chunk) = stream.next().await {
                 match chunk {
                     Ok(event) => {
+                        if let Some(recording) = recording.as_mut() {
+                            recording.record_event(&event.data);
+                        }
+
                         let event = MessageResponseStreamEvent::try_from(event);
 
                         if let Err(err) = event {
@@ -507,10 +666,7 @@ impl LLMClientImpl for AnthropicClient {
                     }
                     Err(e) => {
                         tx.send(MessageResponseStreamEvent::Error {
-                            error: ErrorContent {
-                                r#type: "EventStreamError".into(),
-                                message: e.to_string(),
-                            },
+                            error: ErrorContent::new("EventStreamError", e.to_string()),
                         })
                         .ok();
                     }
@@ -525,3 +681,56 @@ impl LLMClientImpl for AnthropicClient {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::content::tools::ToolUseContent;
+
+    #[test]
+    fn test_prefill_is_trailing_assistant_message() {
+        let request = Request::default()
+            .with_max_tokens(1024)
+            .with_messages(vec![Message::User(vec![Content::new_text(
+                "List the files as JSON",
+            )])])
+            .with_prefill("{\n")
+            .with_cache();
+
+        let body = request_to_anthropic(&request);
+
+        let messages = body["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(
+            messages[1],
+            json!({"role": "assistant", "content": [{"type": "text", "text": "{"}]})
+        );
+        assert_eq!(
+            messages[0]["content"][0]["cache_control"],
+            json!({"type": "ephemeral"})
+        );
+        assert!(body.get("prefill").is_none());
+    }
+
+    #[test]
+    fn test_tool_results_are_first_blocks_of_user_message() {
+        let tool_use = ToolUseContent::new("toolu_1", "read_text_file", json!({"path": "a.rs"}));
+        let request = Request::default().with_messages(vec![
+            Message::Assistant(vec![Content::new_tool_use(tool_use.clone())]),
+            Message::User(vec![
+                Content::new_text("Also check b.rs"),
+                Content::new_tool_result(tool_use.create_error_response("Not found")),
+            ]),
+        ]);
+
+        let body = request_to_anthropic(&request);
+
+        assert_eq!(
+            body["messages"][1]["content"],
+            json!([
+                {"type": "tool_result", "tool_use_id": "toolu_1", "content": "Not found", "is_error": true},
+                {"type": "text", "text": "Also check b.rs"},
+            ])
+        );
+    }
+}
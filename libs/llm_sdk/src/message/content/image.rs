@@ -6,3 +6,13 @@ use serde::{Deserialize, Serialize};
 pub struct ImageContent {
     source: ImageSource,
 }
+
+impl ImageContent {
+    pub fn new(source: ImageSource) -> Self {
+        Self { source }
+    }
+
+    pub fn get_source(&self) -> &ImageSource {
+        &self.source
+    }
+}
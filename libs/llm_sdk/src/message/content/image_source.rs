@@ -8,3 +8,19 @@ pub struct ImageSource {
     pub media_type: String,
     pub data: String,
 }
+
+impl ImageSource {
+    /// Image embedded as base64 encoded data
+    pub fn base64(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Self {
+            image_type: "base64".into(),
+            media_type: media_type.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Data URL of the image, as expected by OpenAI
+    pub fn to_data_url(&self) -> String {
+        format!("data:{};base64,{}", self.media_type, self.data)
+    }
+}
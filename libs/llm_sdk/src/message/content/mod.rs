@@ -1,4 +1,5 @@
 use crate::message::content::image::ImageContent;
+use crate::message::content::image_source::ImageSource;
 use crate::message::content::text::{ContentTextDelta, TextContent};
 use crate::message::content::thinking::{
     ContentSignatureDelta, ContentThinkingDelta, RedactedThinkingContent, ThinkingContent,
@@ -46,6 +47,17 @@ impl Content {
         }
     }
 
+    /// Length of the block's text, thinking, tool arguments or tool result, without cloning it
+    pub fn text_len(&self) -> usize {
+        match self {
+            Content::Text(text) => text.text.len(),
+            Content::Thinking(thinking) => thinking.text_len(),
+            Content::ToolUse(tool_use) => tool_use.input_len(),
+            Content::ToolResult(result) => result.content_len(),
+            _ => 0,
+        }
+    }
+
     pub fn is_tool_use(&self) -> bool {
         matches!(self, Content::ToolUse(_))
     }
@@ -72,6 +84,11 @@ impl Content {
         Content::Text(TextContent::new(text.into()))
     }
 
+    /// Image from base64 encoded data, e.g. `image/png`
+    pub fn new_image(media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        Content::Image(ImageContent::new(ImageSource::base64(media_type, data)))
+    }
+
     pub fn new_tool_result(result: ToolResultContent) -> Self {
         Content::ToolResult(result)
     }
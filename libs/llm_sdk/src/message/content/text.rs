@@ -46,3 +46,9 @@ impl AddAssign<ContentTextDelta> for TextContent {
 pub struct ContentTextDelta {
     text: String,
 }
+
+impl ContentTextDelta {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self { text: text.into() }
+    }
+}
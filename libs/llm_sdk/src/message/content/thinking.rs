@@ -13,6 +13,10 @@ impl ThinkingContent {
         self.thinking.clone()
     }
 
+    pub fn text_len(&self) -> usize {
+        self.thinking.len()
+    }
+
     pub fn validate_content(&self) -> bool {
         !self.thinking.is_empty()
     }
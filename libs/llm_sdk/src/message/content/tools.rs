@@ -31,10 +31,28 @@ pub struct ToolUseContent {
 }
 
 impl ToolUseContent {
+    pub fn new(id: impl Into<String>, name: impl Into<String>, input: Value) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            input,
+            ..Default::default()
+        }
+    }
+
     pub fn create_response(&self, content: impl Into<String>) -> ToolResultContent {
         ToolResultContent {
             tool_use_id: self.id.clone(),
             content: content.into(),
+            is_error: false,
+        }
+    }
+
+    /// Creates a result that tells the model the tool failed
+    pub fn create_error_response(&self, content: impl Into<String>) -> ToolResultContent {
+        ToolResultContent {
+            is_error: true,
+            ..self.create_response(content)
         }
     }
 
@@ -54,6 +72,11 @@ impl ToolUseContent {
         self.id.clone()
     }
 
+    /// Length of the streamed arguments, grows with every input delta
+    pub fn input_len(&self) -> usize {
+        self._input_raw.len()
+    }
+
     pub fn get_state(&self) -> ToolUseState {
         self._tool_execution_state.clone()
     }
@@ -65,6 +88,22 @@ impl ToolUseContent {
     pub fn validate_content(&self) -> bool {
         !self.input.is_null()
     }
+
+    /// Describes why the streamed arguments cannot be used, e.g. when the stream was
+    /// interrupted and left incomplete JSON
+    pub fn get_input_error(&self) -> Option<String> {
+        if !self._input_raw.trim().is_empty() {
+            if let Err(e) = serde_json::from_str::<Value>(&self._input_raw) {
+                return Some(format!("incomplete or invalid JSON ({})", e));
+            }
+        }
+
+        if !self.input.is_object() {
+            return Some(String::from("arguments are not a JSON object"));
+        }
+
+        None
+    }
 }
 
 impl AddAssign<ContentInputJsonDelta> for ToolUseContent {
@@ -83,6 +122,8 @@ impl AddAssign<ContentInputJsonDelta> for ToolUseContent {
 pub struct ToolResultContent {
     tool_use_id: String,
     content: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    is_error: bool,
 }
 
 impl ToolResultContent {
@@ -94,9 +135,25 @@ impl ToolResultContent {
         self.content.clone()
     }
 
+    pub fn content_len(&self) -> usize {
+        self.content.len()
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.is_error
+    }
+
     pub fn validate_content(&self) -> bool {
         !self.content.is_empty()
     }
+
+    /// Same result of the same tool call with its content replaced
+    pub fn with_content(&self, content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..self.clone()
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -104,3 +161,44 @@ impl ToolResultContent {
 pub struct ContentInputJsonDelta {
     partial_json: String,
 }
+
+impl ContentInputJsonDelta {
+    pub fn new(partial_json: impl Into<String>) -> Self {
+        Self {
+            partial_json: partial_json.into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_failed_tool_result_serializes_is_error() {
+        let tool_use = ToolUseContent::new("toolu_1", "read_text_file", json!({}));
+
+        let failed = serde_json::to_value(tool_use.create_error_response("Error")).unwrap();
+        let succeeded = serde_json::to_value(tool_use.create_response("Ok")).unwrap();
+
+        assert_eq!(
+            failed,
+            json!({ "tool_use_id": "toolu_1", "content": "Error", "is_error": true })
+        );
+        assert_eq!(succeeded, json!({ "tool_use_id": "toolu_1", "content": "Ok" }));
+    }
+
+    #[test]
+    fn test_partial_input_json_is_reported() {
+        let mut tool_use = ToolUseContent::new("toolu_1", "read_text_file", json!({}));
+        tool_use += ContentInputJsonDelta::new("{\"path\": \"a.r");
+        assert!(tool_use.get_input_error().is_some());
+
+        tool_use += ContentInputJsonDelta::new("s\"}");
+        assert_eq!(tool_use.get_input_error(), None);
+
+        let tool_use = ToolUseContent::new("toolu_2", "read_text_file", Value::Null);
+        assert!(tool_use.get_input_error().is_some());
+    }
+}
@@ -0,0 +1,36 @@
+use crate::stop_reason::StopReason;
+use crate::tools::ToolChoice;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Client-side information about a message that is never sent to the provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MessageMetadata {
+    /// Why the model stopped generating this message (assistant messages only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<StopReason>,
+
+    /// Model that generated this message (assistant messages only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+
+    /// When the message was added to the conversation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+
+    /// When the model finished generating this message (assistant messages only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<Utc>>,
+
+    /// Temperature of the request that generated this message, None for the default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+
+    /// Tool choice of the request that generated this message, None without tools
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+
+    /// Tool results in pinned messages are never elided from requests
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
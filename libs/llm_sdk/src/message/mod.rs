@@ -1,4 +1,6 @@
 pub mod content;
 pub mod message;
+pub mod metadata;
 pub mod response;
+pub mod tool_message;
 pub mod usage;
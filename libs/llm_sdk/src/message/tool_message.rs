@@ -0,0 +1,74 @@
+use crate::message::content::tools::ToolResultContent;
+use crate::message::content::Content;
+use crate::message::message::Message;
+use serde_json::{json, Value};
+
+/// Result of a tool call sent back to the model, independent of the provider.
+///
+/// Tool results are stored in user messages (as Anthropic expects them), chat
+/// completions send each one as a separate message with the `tool` role
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolMessage {
+    pub tool_use_id: String,
+    pub content: String,
+    pub is_error: bool,
+}
+
+impl From<&ToolResultContent> for ToolMessage {
+    fn from(result: &ToolResultContent) -> Self {
+        Self {
+            tool_use_id: result.get_tool_use_id(),
+            content: result.get_content(),
+            is_error: result.is_error(),
+        }
+    }
+}
+
+impl ToolMessage {
+    /// `tool_result` block of an Anthropic user message
+    pub fn to_anthropic(&self) -> Value {
+        let mut block = json!({
+            "type": "tool_result",
+            "tool_use_id": self.tool_use_id,
+            "content": self.content,
+        });
+        if self.is_error {
+            block["is_error"] = json!(true);
+        }
+
+        block
+    }
+
+    /// Chat completions message with the `tool` role, which has no error flag so the
+    /// content of failed tools is marked instead
+    pub fn to_openai(&self) -> Value {
+        let content = if self.is_error {
+            format!("Error: {}", self.content)
+        } else {
+            self.content.clone()
+        };
+
+        json!({
+            "role": "tool",
+            "tool_call_id": self.tool_use_id,
+            "content": content,
+        })
+    }
+}
+
+impl Message {
+    /// Tool results of a user message, in the order of the tool calls they answer
+    pub fn get_tool_messages(&self) -> Vec<ToolMessage> {
+        let Message::User(contents) = self else {
+            return Vec::new();
+        };
+
+        contents
+            .iter()
+            .filter_map(|content| match content {
+                Content::ToolResult(result) => Some(ToolMessage::from(result)),
+                _ => None,
+            })
+            .collect()
+    }
+}
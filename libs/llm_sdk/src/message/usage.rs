@@ -9,19 +9,58 @@ pub struct Usage {
     pub output_tokens: u32,
 }
 
+// Claude 3.7 Sonnet prices in USD per million tokens
+const INPUT_PRICE: f64 = 3.0;
+const OUTPUT_PRICE: f64 = 15.0;
+const CACHE_WRITE_PRICE: f64 = 3.75;
+const CACHE_READ_PRICE: f64 = 0.30;
+
+impl Usage {
+    /// Estimated cost in USD, using Claude 3.7 Sonnet prices per million tokens
+    pub fn estimated_cost(&self) -> f64 {
+        let cache_write = self.cache_creation_input_tokens.unwrap_or(0) as f64 * CACHE_WRITE_PRICE;
+        let cache_read = self.cache_read_input_tokens.unwrap_or(0) as f64 * CACHE_READ_PRICE;
+        let input = self.input_tokens as f64 * INPUT_PRICE;
+        let output = self.output_tokens as f64 * OUTPUT_PRICE;
+
+        (cache_write + cache_read + input + output) / 1_000_000.0
+    }
+
+    /// Share of the prompt tokens read from the cache, None before any prompt was sent
+    pub fn cache_hit_ratio(&self) -> Option<f64> {
+        let cache_read = self.cache_read_input_tokens.unwrap_or(0) as f64;
+        let cache_write = self.cache_creation_input_tokens.unwrap_or(0) as f64;
+        let prompt_tokens = cache_read + cache_write + self.input_tokens as f64;
+        if prompt_tokens == 0.0 {
+            return None;
+        }
+
+        Some(cache_read / prompt_tokens)
+    }
+
+    /// Estimated USD saved by caching compared to sending every prompt token as regular
+    /// input: cheaper cache reads minus the surcharge of cache writes
+    pub fn estimated_cache_savings(&self) -> f64 {
+        let cache_read = self.cache_read_input_tokens.unwrap_or(0) as f64;
+        let cache_write = self.cache_creation_input_tokens.unwrap_or(0) as f64;
+        let saved = cache_read * (INPUT_PRICE - CACHE_READ_PRICE);
+        let surcharge = cache_write * (CACHE_WRITE_PRICE - INPUT_PRICE);
+
+        (saved - surcharge) / 1_000_000.0
+    }
+}
+
 impl AddAssign<Usage> for Usage {
     fn add_assign(&mut self, rhs: Usage) {
         self.output_tokens += rhs.output_tokens;
         self.input_tokens += rhs.input_tokens;
-        self.cache_read_input_tokens = match (
-            self.cache_read_input_tokens,
-            rhs.cache_creation_input_tokens,
-        ) {
-            (Some(a), Some(b)) => Some(a + b),
-            (Some(a), None) => Some(a),
-            (None, Some(b)) => Some(b),
-            (None, None) => None,
-        };
+        self.cache_read_input_tokens =
+            match (self.cache_read_input_tokens, rhs.cache_read_input_tokens) {
+                (Some(a), Some(b)) => Some(a + b),
+                (Some(a), None) => Some(a),
+                (None, Some(b)) => Some(b),
+                (None, None) => None,
+            };
         self.cache_creation_input_tokens = match (
             self.cache_creation_input_tokens,
             rhs.cache_creation_input_tokens,
@@ -50,3 +89,35 @@ impl AddAssign<UsageDelta> for Usage {
         self.output_tokens += rhs.output_tokens;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(input: u32, cache_write: u32, cache_read: u32) -> Usage {
+        Usage {
+            cache_creation_input_tokens: Some(cache_write),
+            cache_read_input_tokens: Some(cache_read),
+            input_tokens: input,
+            output_tokens: 100,
+        }
+    }
+
+    #[test]
+    fn test_cache_hit_ratio_of_accumulated_usage() {
+        let mut total = Usage::default();
+        assert_eq!(total.cache_hit_ratio(), None);
+
+        // The first request writes the cache, the next ones read it
+        total += usage(100, 10_000, 0);
+        total += usage(200, 500, 10_000);
+        total += usage(200, 0, 10_500);
+
+        assert_eq!(total.cache_read_input_tokens, Some(20_500));
+        assert_eq!(total.cache_creation_input_tokens, Some(10_500));
+        assert_eq!(total.cache_hit_ratio(), Some(20_500.0 / 31_500.0));
+
+        let savings = (20_500.0 * 2.7 - 10_500.0 * 0.75) / 1_000_000.0;
+        assert!((total.estimated_cache_savings() - savings).abs() < 1e-9);
+    }
+}
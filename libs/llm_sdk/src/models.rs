@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+/// Reasoning depth for OpenAI reasoning (o-series) models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Low,
+    Medium,
+    High,
+}
+
+/// Request parameters supported by a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelCapabilities {
+    /// Model accepts the `temperature` parameter
+    pub supports_temperature: bool,
+    /// Model accepts the `reasoning_effort` parameter
+    pub supports_reasoning_effort: bool,
+    /// Model accepts images in user messages
+    pub supports_vision: bool,
+}
+
+impl Default for ModelCapabilities {
+    fn default() -> Self {
+        Self {
+            supports_temperature: true,
+            supports_reasoning_effort: false,
+            supports_vision: false,
+        }
+    }
+}
+
+impl ModelCapabilities {
+    /// Capabilities of OpenAI reasoning models (o1, o3, o4-mini, ...)
+    pub fn reasoning() -> Self {
+        Self {
+            supports_temperature: false,
+            supports_reasoning_effort: true,
+            supports_vision: false,
+        }
+    }
+
+    pub fn for_model(model: &str) -> Self {
+        let capabilities = if is_reasoning_model(model) {
+            Self::reasoning()
+        } else {
+            Self::default()
+        };
+
+        Self {
+            supports_vision: is_vision_model(model),
+            ..capabilities
+        }
+    }
+}
+
+/// Returns true for Claude 3+ models and OpenAI models accepting image input
+fn is_vision_model(model: &str) -> bool {
+    let name = model.rsplit('/').next().unwrap_or(model);
+
+    if name.starts_with("claude") {
+        return !name.starts_with("claude-2") && !name.starts_with("claude-instant");
+    }
+
+    const VISION_PREFIXES: &[&str] = &[
+        "gpt-4o",
+        "gpt-4.1",
+        "gpt-4-turbo",
+        "gpt-5",
+        "o1",
+        "o3",
+        "o4",
+    ];
+    const TEXT_ONLY: &[&str] = &["o1-mini", "o1-preview", "o3-mini"];
+
+    VISION_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+        && !TEXT_ONLY.iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// Returns true for OpenAI o-series model names, optionally prefixed with `openai/`
+fn is_reasoning_model(model: &str) -> bool {
+    let name = model.rsplit('/').next().unwrap_or(model);
+    let mut chars = name.chars();
+
+    chars.next() == Some('o') && chars.next().is_some_and(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_reasoning(model: &str) -> bool {
+        let capabilities = ModelCapabilities::for_model(model);
+        capabilities.supports_reasoning_effort && !capabilities.supports_temperature
+    }
+
+    #[test]
+    fn test_o_series_models_are_reasoning_models() {
+        assert!(is_reasoning("o3-mini"));
+        assert!(is_reasoning("o1"));
+        assert!(!is_reasoning("gpt-4o-mini"));
+        assert!(!is_reasoning("omni"));
+    }
+
+    #[test]
+    fn test_vision_models() {
+        assert!(ModelCapabilities::for_model("claude-3-7-sonnet-20250219").supports_vision);
+        assert!(ModelCapabilities::for_model("openai/gpt-4o-mini").supports_vision);
+        assert!(ModelCapabilities::for_model("o1").supports_vision);
+        assert!(!ModelCapabilities::for_model("o3-mini").supports_vision);
+        assert!(!ModelCapabilities::for_model("gpt-3.5-turbo").supports_vision);
+        assert!(!ModelCapabilities::for_model("llama3").supports_vision);
+    }
+}
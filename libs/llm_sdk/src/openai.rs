@@ -0,0 +1,661 @@
+use crate::config::{HttpClientOptions, LLMConfig};
+use crate::errors::llm::LLMError;
+use crate::message::content::text::ContentTextDelta;
+use crate::message::content::tools::{ContentInputJsonDelta, ToolUseContent};
+use crate::message::content::{Content, ContentDelta};
+use crate::message::message::Message;
+use crate::message::response::MessageResponse;
+use crate::message::tool_message::ToolMessage;
+use crate::message::usage::UsageDelta;
+use crate::models::ModelCapabilities;
+use crate::recorder::Recorder;
+use crate::schema::normalize_openai_schema;
+use crate::stop_reason::StopReason;
+use crate::{
+    ContentBlockDeltaEventContent, ContentBlockStartEventContent, ContentBlockStopEventContent,
+    ErrorContent, LLMClientImpl, MessageDelta, MessageDeltaEventContent,
+    MessageResponseStreamEvent, MessageStartEventContent, Request,
+};
+use eventsource_stream::Eventsource;
+use futures::StreamExt;
+use secrecy::ExposeSecret;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+#[derive(Debug)]
+pub struct OpenAIClient {
+    options: LLMConfig,
+    url: String,
+    client: reqwest::Client,
+    recorder: Option<Recorder>,
+}
+
+impl OpenAIClient {
+    pub fn new(options: LLMConfig) -> anyhow::Result<Self, LLMError> {
+        let http_options = HttpClientOptions::new_openai(&options.api_key)
+            .map_err(|_| LLMError::CreateClientError("Invalid API key".to_string()))?;
+
+        Self::new_with_http_options(options, http_options)
+    }
+
+    /// Creates a client for any OpenAI-compatible endpoint (e.g. Azure OpenAI)
+    pub fn new_with_http_options(
+        options: LLMConfig,
+        http_options: HttpClientOptions,
+    ) -> anyhow::Result<Self, LLMError> {
+        let HttpClientOptions { url, headers } = http_options
+            .with_extra_headers(&options.extra_headers)
+            .map_err(|e| LLMError::CreateClientError(e.to_string()))?;
+
+        let recorder = options.record_dir.as_ref().map(|dir| {
+            Recorder::new(dir)
+                .with_headers(&headers)
+                .with_secret(options.api_key.expose_secret())
+        });
+
+        let reqwest_client = reqwest::Client::builder().default_headers(headers).build();
+        if reqwest_client.is_err() {
+            return Err(LLMError::CreateClientError(
+                "Failed to create client".to_string(),
+            ));
+        }
+
+        Ok(OpenAIClient {
+            options,
+            url,
+            client: reqwest_client.unwrap(),
+            recorder,
+        })
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+}
+
+impl LLMClientImpl for OpenAIClient {
+    async fn count_tokens(&self, _request: Request) -> Result<u32, LLMError> {
+        Err(LLMError::Generic(
+            "Token counting is not supported by OpenAI".into(),
+        ))
+    }
+
+    async fn send(
+        &self,
+        request: Request,
+    ) -> Result<UnboundedReceiver<MessageResponseStreamEvent>, LLMError> {
+        let body = request_to_openai(&request);
+        let url = self.url.as_str();
+        let mut recording = self
+            .recorder
+            .as_ref()
+            .and_then(|recorder| recorder.record_request("openai", url, &body));
+
+        let result = self.client.post(url).json(&body).send().await;
+
+        if result.is_err() {
+            return Err(LLMError::ReqwestError);
+        }
+
+        let response = result.unwrap();
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let text = response.text().await.unwrap_or_default();
+            if let Some(recording) = recording.as_mut() {
+                recording.record_error(status, &text);
+            }
+
+            return Err(LLMError::from_response(status, &text));
+        }
+
+        let (tx, rx) = unbounded_channel::<MessageResponseStreamEvent>();
+
+        tokio::spawn(async move {
+            let mut state = OpenAIStreamState::default();
+            let mut stream = response.bytes_stream().eventsource();
+            while let Some(chunk) = stream.next().await {
+                match chunk {
+                    Ok(event) => {
+                        if let Some(recording) = recording.as_mut() {
+                            recording.record_event(&event.data);
+                        }
+
+                        if event.data.trim() == STREAM_DONE {
+                            break;
+                        }
+
+                        match state.process_data(&event.data) {
+                            Ok(events) => {
+                                for event in events {
+                                    tx.send(event).ok();
+                                }
+                            }
+                            Err(error) => {
+                                tx.send(MessageResponseStreamEvent::Error {
+                                    error: error.into(),
+                                })
+                                .ok();
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        tx.send(MessageResponseStreamEvent::Error {
+                            error: ErrorContent::new("EventStreamError", e.to_string()),
+                        })
+                        .ok();
+                    }
+                };
+            }
+
+            for event in state.finish() {
+                tx.send(event).ok();
+            }
+        });
+
+        Ok(rx)
+    }
+
+    fn get_config(&self) -> LLMConfig {
+        self.options.clone()
+    }
+}
+
+fn text_of(contents: &[Content]) -> String {
+    contents
+        .iter()
+        .filter_map(|content| content.get_text().map(|text| text.get_text()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn message_to_openai(message: &Message) -> Vec<Value> {
+    match message {
+        Message::System(contents) => {
+            vec![json!({ "role": "system", "content": text_of(contents) })]
+        }
+        Message::User(contents) => {
+            let mut messages: Vec<Value> = message
+                .get_tool_messages()
+                .iter()
+                .map(ToolMessage::to_openai)
+                .collect();
+
+            let text = text_of(contents);
+            let images: Vec<Value> = contents
+                .iter()
+                .filter_map(|content| match content {
+                    Content::Image(image) => Some(json!({
+                        "type": "image_url",
+                        "image_url": { "url": image.get_source().to_data_url() },
+                    })),
+                    _ => None,
+                })
+                .collect();
+
+            if !images.is_empty() {
+                let mut parts = Vec::new();
+                if !text.is_empty() {
+                    parts.push(json!({ "type": "text", "text": text }));
+                }
+                parts.extend(images);
+                messages.push(json!({ "role": "user", "content": parts }));
+            } else if !text.is_empty() {
+                messages.push(json!({ "role": "user", "content": text }));
+            }
+
+            messages
+        }
+        Message::Assistant(contents) => {
+            let tool_calls: Vec<Value> = contents
+                .iter()
+                .filter_map(|content| match content {
+                    Content::ToolUse(tool_use) => {
+                        let (name, input) = tool_use.get_execute_params();
+                        Some(json!({
+                            "id": tool_use.get_id(),
+                            "type": "function",
+                            "function": { "name": name, "arguments": input.to_string() },
+                        }))
+                    }
+                    _ => None,
+                })
+                .collect();
+
+            let text = text_of(contents);
+            let mut message = json!({ "role": "assistant", "content": text });
+            if !tool_calls.is_empty() {
+                message["tool_calls"] = json!(tool_calls);
+            }
+
+            vec![message]
+        }
+    }
+}
+
+/// Sent after the prefill, chat completions do not continue a trailing assistant message
+pub const PREFILL_PROMPT: &str =
+    "Continue your last message exactly where it ends, without repeating any of it.";
+
+/// Converts a request into an OpenAI chat completions body
+pub fn request_to_openai(request: &Request) -> Value {
+    let capabilities = ModelCapabilities::for_model(&request.model);
+
+    let mut messages = Vec::new();
+    if let Some(system) = &request.system {
+        messages.push(json!({ "role": "system", "content": text_of(system) }));
+    }
+    messages.extend(request.messages.iter().flat_map(message_to_openai));
+
+    if let Some(prefill) = request.get_prefill() {
+        messages.push(json!({ "role": "assistant", "content": prefill }));
+        messages.push(json!({ "role": "user", "content": PREFILL_PROMPT }));
+    }
+
+    let mut body = json!({
+        "model": request.model,
+        "messages": messages,
+        "stream": request.stream,
+    });
+
+    if request.stream {
+        body["stream_options"] = json!({ "include_usage": true });
+    }
+
+    if let Some(max_tokens) = request.max_tokens {
+        // Reasoning models reject max_tokens in favour of max_completion_tokens
+        let key = if capabilities.supports_reasoning_effort {
+            "max_completion_tokens"
+        } else {
+            "max_tokens"
+        };
+        body[key] = json!(max_tokens);
+    }
+
+    if let Some(tools) = &request.tools {
+        let tools: Vec<Value> = tools
+            .iter()
+            .filter_map(|tool| serde_json::to_value(tool).ok())
+            .map(|tool| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool["name"],
+                        "description": tool["description"],
+                        "parameters": normalize_openai_schema(&tool["input_schema"]),
+                    }
+                })
+            })
+            .collect();
+        body["tools"] = json!(tools);
+        body["tool_choice"] = request.tool_choice.clone().unwrap_or_default().to_openai();
+    }
+
+    if capabilities.supports_temperature {
+        if let Some(temperature) = request.temperature {
+            body["temperature"] = json!(temperature);
+        }
+    }
+
+    if capabilities.supports_reasoning_effort {
+        if let Some(reasoning_effort) = request.reasoning_effort {
+            body["reasoning_effort"] = json!(reasoning_effort);
+        }
+    }
+
+    body
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIStreamChunk {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    choices: Vec<OpenAIStreamChoice>,
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIStreamChoice {
+    #[serde(default)]
+    delta: OpenAIDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIDelta {
+    content: Option<String>,
+    tool_calls: Option<Vec<OpenAIToolCallDelta>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIToolCallDelta {
+    index: usize,
+    id: Option<String>,
+    function: Option<OpenAIFunctionDelta>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIFunctionDelta {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OpenAIUsage {
+    #[serde(default)]
+    completion_tokens: u32,
+}
+
+/// Data of the event that ends an OpenAI stream
+pub(crate) const STREAM_DONE: &str = "[DONE]";
+
+/// Translates OpenAI stream chunks into the provider-agnostic stream events
+#[derive(Debug, Default)]
+pub(crate) struct OpenAIStreamState {
+    started: bool,
+    text_block: Option<usize>,
+    tool_blocks: HashMap<usize, usize>,
+    next_block: usize,
+    stop_reason: Option<StopReason>,
+    output_tokens: u32,
+}
+
+impl OpenAIStreamState {
+    /// Events of the data of one stream event (a JSON chunk)
+    pub(crate) fn process_data(
+        &mut self,
+        data: &str,
+    ) -> Result<Vec<MessageResponseStreamEvent>, LLMError> {
+        let chunk = serde_json::from_str::<OpenAIStreamChunk>(data)
+            .map_err(|err| LLMError::ParseError(err.to_string()))?;
+
+        Ok(self.process_chunk(chunk))
+    }
+
+    fn process_chunk(&mut self, chunk: OpenAIStreamChunk) -> Vec<MessageResponseStreamEvent> {
+        let mut events = Vec::new();
+
+        if !self.started {
+            self.started = true;
+            events.push(MessageResponseStreamEvent::MessageStart(
+                MessageStartEventContent {
+                    message: MessageResponse {
+                        id: chunk.id.clone(),
+                        model: chunk.model.clone(),
+                        role: "assistant".into(),
+                        ..Default::default()
+                    },
+                },
+            ));
+        }
+
+        if let Some(usage) = &chunk.usage {
+            self.output_tokens = usage.completion_tokens;
+        }
+
+        for choice in chunk.choices {
+            if let Some(text) = choice.delta.content.filter(|text| !text.is_empty()) {
+                let index = match self.text_block {
+                    Some(index) => index,
+                    None => {
+                        let index = self.open_block(&mut events, Content::new_text(""));
+                        self.text_block = Some(index);
+                        index
+                    }
+                };
+
+                events.push(MessageResponseStreamEvent::ContentBlockDelta(
+                    ContentBlockDeltaEventContent {
+                        index,
+                        delta: ContentDelta::TextDelta(ContentTextDelta::new(text)),
+                    },
+                ));
+            }
+
+            for tool_call in choice.delta.tool_calls.unwrap_or_default() {
+                let function = tool_call.function.unwrap_or_default();
+                let index = match self.tool_blocks.get(&tool_call.index) {
+                    Some(index) => *index,
+                    None => {
+                        let tool_use = ToolUseContent::new(
+                            tool_call.id.unwrap_or_default(),
+                            function.name.unwrap_or_default(),
+                            json!({}),
+                        );
+                        let index = self.open_block(&mut events, Content::new_tool_use(tool_use));
+                        self.tool_blocks.insert(tool_call.index, index);
+                        index
+                    }
+                };
+
+                if let Some(arguments) = function.arguments.filter(|args| !args.is_empty()) {
+                    events.push(MessageResponseStreamEvent::ContentBlockDelta(
+                        ContentBlockDeltaEventContent {
+                            index,
+                            delta: ContentDelta::InputJsonDelta(ContentInputJsonDelta::new(
+                                arguments,
+                            )),
+                        },
+                    ));
+                }
+            }
+
+            if let Some(finish_reason) = choice.finish_reason {
+                self.stop_reason = Some(StopReason::from_finish_reason(&finish_reason));
+            }
+        }
+
+        events
+    }
+
+    fn open_block(
+        &mut self,
+        events: &mut Vec<MessageResponseStreamEvent>,
+        block: Content,
+    ) -> usize {
+        let index = self.next_block;
+        self.next_block += 1;
+
+        events.push(MessageResponseStreamEvent::ContentBlockStart(
+            ContentBlockStartEventContent {
+                index,
+                content_block: block,
+            },
+        ));
+
+        index
+    }
+
+    /// Closes all open content blocks and ends the message
+    pub(crate) fn finish(&mut self) -> Vec<MessageResponseStreamEvent> {
+        if !self.started {
+            return vec![];
+        }
+
+        let mut events: Vec<MessageResponseStreamEvent> = (0..self.next_block)
+            .map(|index| {
+                MessageResponseStreamEvent::ContentBlockStop(ContentBlockStopEventContent { index })
+            })
+            .collect();
+
+        events.push(MessageResponseStreamEvent::MessageDelta(
+            MessageDeltaEventContent {
+                delta: MessageDelta {
+                    stop_reason: self.stop_reason.clone(),
+                    stop_sequence: None,
+                },
+                usage: UsageDelta {
+                    output_tokens: self.output_tokens,
+                },
+            },
+        ));
+        events.push(MessageResponseStreamEvent::MessageStop);
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ReasoningEffort;
+    use crate::tools::{Tool, ToolChoice};
+
+    fn user_request(model: &str) -> Request {
+        Request::default()
+            .with_model(model)
+            .with_max_tokens(1024)
+            .with_temperature(0.2)
+            .with_reasoning_effort(ReasoningEffort::High)
+            .with_messages(vec![Message::User(vec![Content::new_text("Hello")])])
+    }
+
+    #[test]
+    fn test_o_series_request_omits_temperature() {
+        let body = request_to_openai(&user_request("o3-mini"));
+
+        assert!(body.get("temperature").is_none());
+        assert!(body.get("max_tokens").is_none());
+        assert_eq!(body["reasoning_effort"], json!("high"));
+        assert_eq!(body["max_completion_tokens"], json!(1024));
+    }
+
+    #[test]
+    fn test_chat_model_request_omits_reasoning_effort() {
+        let body = request_to_openai(&user_request("gpt-4o-mini"));
+
+        assert!(body.get("reasoning_effort").is_none());
+        assert_eq!(body["temperature"], json!(0.2f32));
+        assert_eq!(body["max_tokens"], json!(1024));
+    }
+
+    #[test]
+    fn test_user_image_is_sent_as_content_part() {
+        let message = Message::User(vec![
+            Content::new_image("image/png", "aGVsbG8="),
+            Content::new_text("What is on the screenshot?"),
+        ]);
+        let request = user_request("gpt-4o").with_messages(vec![message]);
+
+        let body = request_to_openai(&request);
+
+        assert_eq!(
+            body["messages"][0]["content"],
+            json!([
+                { "type": "text", "text": "What is on the screenshot?" },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,aGVsbG8=" } },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_prefill_is_continued_on_request() {
+        let request = user_request("gpt-4o-mini").with_prefill("[");
+
+        let messages = request_to_openai(&request)["messages"].clone();
+
+        assert_eq!(messages[1], json!({ "role": "assistant", "content": "[" }));
+        assert_eq!(
+            messages[2],
+            json!({ "role": "user", "content": PREFILL_PROMPT })
+        );
+    }
+
+    #[test]
+    fn test_tool_results_are_sent_as_tool_messages() {
+        let tool_use = ToolUseContent::new("call_1", "read_text_file", json!({"path": "a.rs"}));
+        let request = user_request("gpt-4o-mini").with_messages(vec![
+            Message::Assistant(vec![Content::new_tool_use(tool_use.clone())]),
+            Message::User(vec![
+                Content::new_text("Also check b.rs"),
+                Content::new_tool_result(tool_use.create_error_response("Not found")),
+            ]),
+        ]);
+
+        let body = request_to_openai(&request);
+
+        assert_eq!(body["messages"][0]["tool_calls"][0]["id"], json!("call_1"));
+        assert_eq!(
+            body["messages"][1],
+            json!({"role": "tool", "tool_call_id": "call_1", "content": "Error: Not found"})
+        );
+        assert_eq!(
+            body["messages"][2],
+            json!({"role": "user", "content": "Also check b.rs"})
+        );
+    }
+
+    #[test]
+    fn test_request_tool_choice() {
+        let tool = Tool::new("read_text_file".into(), "Read".into(), json!({}));
+        let request = user_request("gpt-4o-mini").with_tools(vec![tool]);
+        assert_eq!(request_to_openai(&request)["tool_choice"], json!("auto"));
+
+        let request = request.with_tool_choice(ToolChoice::Tool("read_text_file".into()));
+        assert_eq!(
+            request_to_openai(&request)["tool_choice"],
+            json!({ "type": "function", "function": { "name": "read_text_file" } })
+        );
+
+        let request = request.with_tool_choice(ToolChoice::Required);
+        assert_eq!(request_to_openai(&request)["tool_choice"], json!("required"));
+    }
+
+    #[test]
+    fn test_stream_state_converts_tool_calls() {
+        let mut state = OpenAIStreamState::default();
+        let chunks = [
+            json!({"id": "chatcmpl-1", "model": "gpt-4o-mini", "choices": [{"delta": {"role": "assistant", "tool_calls": [{"index": 0, "id": "call_1", "type": "function", "function": {"name": "read_text_file", "arguments": ""}}]}, "finish_reason": null}]}),
+            json!({"id": "chatcmpl-1", "model": "gpt-4o-mini", "choices": [{"delta": {"tool_calls": [{"index": 0, "function": {"arguments": "{\"path\": \"a.rs\"}"}}]}, "finish_reason": null}]}),
+            json!({"id": "chatcmpl-1", "model": "gpt-4o-mini", "choices": [{"delta": {}, "finish_reason": "tool_calls"}]}),
+            json!({"id": "chatcmpl-1", "model": "gpt-4o-mini", "choices": [], "usage": {"prompt_tokens": 10, "completion_tokens": 7}}),
+        ];
+
+        let mut response = MessageResponse::default();
+        let mut events = Vec::new();
+        for chunk in chunks {
+            events.extend(state.process_chunk(serde_json::from_value(chunk).unwrap()));
+        }
+        events.extend(state.finish());
+
+        for event in events {
+            match event {
+                MessageResponseStreamEvent::MessageStart(start) => response += start,
+                MessageResponseStreamEvent::ContentBlockStart(start) => response += start,
+                MessageResponseStreamEvent::ContentBlockDelta(delta) => response += delta,
+                MessageResponseStreamEvent::MessageDelta(delta) => response += delta,
+                _ => (),
+            }
+        }
+
+        let tools = response.tools_usage();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(
+            tools[0].get_execute_params(),
+            ("read_text_file".to_string(), json!({"path": "a.rs"}))
+        );
+        assert_eq!(response.stop_reason, Some(StopReason::ToolUse));
+        assert_eq!(response.usage.output_tokens, 7);
+    }
+
+    #[test]
+    fn test_ollama_client_uses_local_base_url() {
+        let options = LLMConfig {
+            api_key: secrecy::SecretString::new("".into()),
+            record_dir: None,
+            extra_headers: Default::default(),
+        };
+
+        let client = crate::LLMClient::new_ollama(options, "http://localhost:11434/v1").unwrap();
+
+        let crate::LLMClient::OpenAI(client) = client else {
+            panic!("Expected OpenAI-compatible client");
+        };
+        assert_eq!(client.get_url(), "http://localhost:11434/v1/chat/completions");
+    }
+}
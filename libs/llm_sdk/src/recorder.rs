@@ -0,0 +1,173 @@
+use reqwest::header::HeaderMap;
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Placeholder written instead of secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Headers that carry credentials and must never be written to disk
+const SENSITIVE_HEADERS: [&str; 3] = ["x-api-key", "authorization", "api-key"];
+
+static RECORDING_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes provider requests and streamed responses to disk for debugging.
+/// Credentials are redacted from both headers and payloads.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    dir: PathBuf,
+    headers: Value,
+    secrets: Vec<String>,
+}
+
+impl Recorder {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            headers: json!({}),
+            secrets: Vec::new(),
+        }
+    }
+
+    /// Stores client default headers, with sensitive values redacted
+    pub fn with_headers(mut self, headers: &HeaderMap) -> Self {
+        let mut map = serde_json::Map::new();
+        for (name, value) in headers {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) || value.is_sensitive() {
+                REDACTED.to_string()
+            } else {
+                value.to_str().unwrap_or_default().to_string()
+            };
+            map.insert(name.to_string(), Value::String(value));
+        }
+
+        self.headers = Value::Object(map);
+        self
+    }
+
+    /// Registers a secret that is scrubbed from everything written to disk
+    pub fn with_secret(mut self, secret: impl Into<String>) -> Self {
+        let secret = secret.into();
+        if !secret.is_empty() {
+            self.secrets.push(secret);
+        }
+        self
+    }
+
+    fn redact(&self, text: &str) -> String {
+        self.secrets.iter().fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+    }
+
+    /// Writes the request to `<timestamp>-<provider>-request.json` and returns
+    /// a handle for recording the response
+    pub fn record_request(&self, provider: &str, url: &str, body: &Value) -> Option<Recording> {
+        std::fs::create_dir_all(&self.dir).ok()?;
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let counter = RECORDING_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let stem = format!("{}-{:04}-{}", millis, counter, provider);
+
+        let request = json!({
+            "url": url,
+            "headers": self.headers,
+            "body": body,
+        });
+        let request = serde_json::to_string_pretty(&request).ok()?;
+        let request_path = self.dir.join(format!("{}-request.json", stem));
+        std::fs::write(&request_path, self.redact(&request)).ok()?;
+
+        let response_path = self.dir.join(format!("{}-response.jsonl", stem));
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&response_path)
+            .ok()?;
+
+        Some(Recording {
+            recorder: self.clone(),
+            file,
+            request_path,
+            response_path,
+        })
+    }
+}
+
+/// An in-progress recording of a single request/response exchange
+#[derive(Debug)]
+pub struct Recording {
+    recorder: Recorder,
+    file: File,
+    request_path: PathBuf,
+    response_path: PathBuf,
+}
+
+impl Recording {
+    pub fn request_path(&self) -> &Path {
+        &self.request_path
+    }
+
+    pub fn response_path(&self) -> &Path {
+        &self.response_path
+    }
+
+    /// Appends a raw streamed event (one line per event)
+    pub fn record_event(&mut self, data: &str) {
+        let line = data.replace('\n', " ");
+        writeln!(self.file, "{}", self.recorder.redact(&line)).ok();
+    }
+
+    /// Appends a non-successful HTTP response
+    pub fn record_error(&mut self, status: u16, body: &str) {
+        let line = json!({ "status": status, "body": body }).to_string();
+        writeln!(self.file, "{}", self.recorder.redact(&line)).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    const SECRET: &str = "sk-ant-test-secret-123";
+
+    #[test]
+    fn test_recording_contains_no_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", HeaderValue::from_static(SECRET));
+        headers.insert(
+            "authorization",
+            HeaderValue::from_str(&format!("Bearer {}", SECRET)).unwrap(),
+        );
+        headers.insert("anthropic-version", HeaderValue::from_static("2023-06-01"));
+
+        let recorder = Recorder::new(dir.path())
+            .with_headers(&headers)
+            .with_secret(SECRET);
+
+        let body =
+            json!({ "messages": [{ "role": "user", "content": format!("my key is {}", SECRET) }] });
+        let mut recording = recorder
+            .record_request("anthropic", "https://api.anthropic.com/v1/messages", &body)
+            .unwrap();
+        recording.record_event(&format!("{{\"type\":\"ping\",\"echo\":\"{}\"}}", SECRET));
+        recording.record_error(401, &format!("invalid key {}", SECRET));
+
+        let request = std::fs::read_to_string(recording.request_path()).unwrap();
+        let response = std::fs::read_to_string(recording.response_path()).unwrap();
+
+        assert!(!request.contains(SECRET));
+        assert!(!response.contains(SECRET));
+        assert!(request.contains("anthropic-version"));
+        assert!(request.contains(REDACTED));
+        assert_eq!(response.lines().count(), 2);
+    }
+}
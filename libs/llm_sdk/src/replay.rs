@@ -0,0 +1,218 @@
+use crate::config::LLMConfig;
+use crate::errors::llm::LLMError;
+use crate::openai::{OpenAIStreamState, STREAM_DONE};
+use crate::{LLMClientImpl, MessageResponseStreamEvent, Request};
+use secrecy::SecretString;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+/// Suffix of response files written by the recorder
+pub const RESPONSE_FIXTURE_SUFFIX: &str = "-response.jsonl";
+
+/// Suffix of response files recorded from OpenAI compatible providers, their lines are
+/// OpenAI stream chunks. Other response files hold Anthropic stream events
+pub const OPENAI_FIXTURE_SUFFIX: &str = "-openai-response.jsonl";
+
+/// Error line written by the recorder for non-successful responses
+#[derive(Debug, Deserialize)]
+struct RecordedError {
+    status: u16,
+    body: String,
+}
+
+/// Marker line (`{"stall": true}`) of a response whose stream stays open after its
+/// events, used to reproduce providers that stop sending without closing the stream
+#[derive(Debug, Deserialize)]
+struct RecordedStall {
+    stall: bool,
+}
+
+/// Events of a recorded response
+#[derive(Debug, Default)]
+pub struct RecordedResponse {
+    pub events: Vec<MessageResponseStreamEvent>,
+    /// The stream is kept open after the events
+    pub stalled: bool,
+}
+
+/// Reads recorded responses from a fixture directory, in file name order
+#[derive(Debug)]
+pub struct FixtureReader {
+    responses: Vec<PathBuf>,
+    next: AtomicUsize,
+}
+
+impl FixtureReader {
+    pub fn new(dir: &Path) -> Result<Self, LLMError> {
+        let entries = std::fs::read_dir(dir).map_err(|e| {
+            LLMError::CreateClientError(format!(
+                "Failed to read replay directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        let mut responses: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.ends_with(RESPONSE_FIXTURE_SUFFIX))
+            })
+            .collect();
+        responses.sort();
+
+        Ok(Self {
+            responses,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the number of recorded responses that were not replayed yet
+    pub fn remaining(&self) -> usize {
+        self.responses
+            .len()
+            .saturating_sub(self.next.load(Ordering::SeqCst))
+    }
+
+    /// Reads the next recorded response as a list of stream events
+    pub fn next_response(&self) -> Result<RecordedResponse, LLMError> {
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+        let path = self.responses.get(index).ok_or_else(|| {
+            LLMError::InvalidResponse("No more recorded responses to replay".into())
+        })?;
+
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| LLMError::InvalidResponse(format!("{}: {}", path.display(), e)))?;
+
+        // Recorded OpenAI chunks go through the same parser as the live stream
+        let is_openai = path.to_string_lossy().ends_with(OPENAI_FIXTURE_SUFFIX);
+        let mut openai_state = is_openai.then(OpenAIStreamState::default);
+
+        let mut response = RecordedResponse::default();
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            if let Ok(error) = serde_json::from_str::<RecordedError>(line) {
+                return Err(LLMError::from_response(error.status, &error.body));
+            }
+
+            if let Ok(stall) = serde_json::from_str::<RecordedStall>(line) {
+                response.stalled = stall.stall;
+                continue;
+            }
+
+            let events = match openai_state.as_mut() {
+                Some(_) if line.trim() == STREAM_DONE => Ok(vec![]),
+                Some(state) => state.process_data(line),
+                None => serde_json::from_str::<MessageResponseStreamEvent>(line)
+                    .map(|event| vec![event])
+                    .map_err(|err| LLMError::ParseError(err.to_string())),
+            };
+            match events {
+                Ok(events) => response.events.extend(events),
+                Err(_) => {
+                    return Err(LLMError::ParseError(format!(
+                        "Invalid recorded event in {}: {}",
+                        path.display(),
+                        line
+                    )))
+                }
+            }
+        }
+
+        // The live stream ends the message when the provider closes it
+        if let Some(state) = openai_state.as_mut().filter(|_| !response.stalled) {
+            response.events.extend(state.finish());
+        }
+
+        Ok(response)
+    }
+}
+
+/// Client that replays recorded responses instead of calling a provider
+#[derive(Debug)]
+pub struct ReplayClient {
+    reader: FixtureReader,
+}
+
+impl ReplayClient {
+    pub fn new(dir: &Path) -> Result<Self, LLMError> {
+        Ok(Self {
+            reader: FixtureReader::new(dir)?,
+        })
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.reader.remaining()
+    }
+}
+
+impl LLMClientImpl for ReplayClient {
+    async fn count_tokens(&self, _request: Request) -> Result<u32, LLMError> {
+        Ok(0)
+    }
+
+    async fn send(
+        &self,
+        _request: Request,
+    ) -> Result<UnboundedReceiver<MessageResponseStreamEvent>, LLMError> {
+        let response = self.reader.next_response()?;
+        let (tx, rx) = unbounded_channel::<MessageResponseStreamEvent>();
+
+        for event in response.events {
+            tx.send(event).ok();
+        }
+
+        if response.stalled {
+            // Keep the stream open until the receiver gives up
+            tokio::spawn(async move { tx.closed().await });
+        }
+
+        Ok(rx)
+    }
+
+    fn get_config(&self) -> LLMConfig {
+        LLMConfig {
+            api_key: SecretString::new("".into()),
+            record_dir: None,
+            extra_headers: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openai_fixture_is_parsed_as_openai_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunks = [
+            r#"{"id":"chatcmpl-1","model":"gpt-4o","choices":[{"delta":{"content":"Hello"}}]}"#,
+            r#"{"id":"chatcmpl-1","model":"gpt-4o","choices":[{"delta":{},"finish_reason":"stop"}]}"#,
+            "[DONE]",
+        ];
+        std::fs::write(
+            dir.path().join("0001-0000-openai-response.jsonl"),
+            chunks.join("\n"),
+        )
+        .unwrap();
+
+        let reader = FixtureReader::new(dir.path()).unwrap();
+        let response = reader.next_response().unwrap();
+
+        assert!(matches!(
+            response.events.first(),
+            Some(MessageResponseStreamEvent::MessageStart(_))
+        ));
+        assert!(response
+            .events
+            .iter()
+            .any(|event| matches!(event, MessageResponseStreamEvent::ContentBlockDelta(_))));
+        assert!(matches!(
+            response.events.last(),
+            Some(MessageResponseStreamEvent::MessageStop)
+        ));
+    }
+}
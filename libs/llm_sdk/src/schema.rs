@@ -0,0 +1,304 @@
+use serde_json::{json, Map, Value};
+
+/// String formats accepted in OpenAI function parameters, others (like `uint` of
+/// generated integer schemas) are rejected
+const OPENAI_FORMATS: &[&str] = &[
+    "date-time",
+    "time",
+    "date",
+    "duration",
+    "email",
+    "hostname",
+    "ipv4",
+    "ipv6",
+    "uuid",
+];
+
+/// Keywords without meaning for function parameters, dropped everywhere
+const IGNORED_KEYWORDS: &[&str] = &["$schema", "title", "default"];
+
+/// Nesting limit of inlined references, deeper (recursive) references become `{}`
+const MAX_REF_DEPTH: usize = 16;
+
+/// Rewrites a generated tool schema into the subset OpenAI accepts for function
+/// parameters: references are inlined, `anyOf` with null becomes a nullable type,
+/// `oneOf` of single values becomes an enum and unsupported formats are dropped.
+pub fn normalize_openai_schema(schema: &Value) -> Value {
+    let definitions = schema
+        .get("$defs")
+        .or_else(|| schema.get("definitions"))
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+
+    let mut normalized = normalize(schema, &definitions, 0);
+    if let Value::Object(object) = &mut normalized {
+        object.remove("$defs");
+        object.remove("definitions");
+        object.insert("type".into(), json!("object"));
+        object.entry("properties").or_insert_with(|| json!({}));
+    }
+
+    normalized
+}
+
+fn normalize(schema: &Value, definitions: &Value, depth: usize) -> Value {
+    match schema {
+        Value::Object(object) => normalize_object(object, definitions, depth),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| normalize(item, definitions, depth))
+                .collect(),
+        ),
+        _ => schema.clone(),
+    }
+}
+
+fn normalize_object(object: &Map<String, Value>, definitions: &Value, depth: usize) -> Value {
+    if let Some(Value::String(reference)) = object.get("$ref") {
+        let mut resolved = resolve_ref(reference, definitions, depth);
+        // Keywords next to the reference (e.g. its description) take precedence
+        if let Value::Object(resolved) = &mut resolved {
+            for (key, value) in object.iter().filter(|(key, _)| *key != "$ref") {
+                resolved.insert(key.clone(), normalize(value, definitions, depth));
+            }
+        }
+        return resolved;
+    }
+
+    let mut normalized = Map::new();
+    for (key, value) in object {
+        if IGNORED_KEYWORDS.contains(&key.as_str()) {
+            continue;
+        }
+
+        match key.as_str() {
+            "format" if !value.as_str().is_some_and(|f| OPENAI_FORMATS.contains(&f)) => {
+                log::debug!("Dropping unsupported format {} from a tool schema", value);
+            }
+            // Property names are not schemas, an ignored keyword may be a parameter
+            "properties" => {
+                let properties = value
+                    .as_object()
+                    .map(|properties| {
+                        properties
+                            .iter()
+                            .map(|(name, schema)| {
+                                (name.clone(), normalize(schema, definitions, depth))
+                            })
+                            .collect::<Map<_, _>>()
+                    })
+                    .unwrap_or_default();
+                normalized.insert(key.clone(), Value::Object(properties));
+            }
+            _ => {
+                normalized.insert(key.clone(), normalize(value, definitions, depth));
+            }
+        }
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(variants)) = normalized.get(keyword).cloned() {
+            if let Some(simplified) = simplify_variants(&variants) {
+                log::debug!("Simplifying {} of a tool schema", keyword);
+                normalized.remove(keyword);
+                for (key, value) in simplified {
+                    normalized.entry(key).or_insert(value);
+                }
+            }
+        }
+    }
+
+    Value::Object(normalized)
+}
+
+fn resolve_ref(reference: &str, definitions: &Value, depth: usize) -> Value {
+    let name = reference
+        .strip_prefix("#/$defs/")
+        .or_else(|| reference.strip_prefix("#/definitions/"));
+
+    match name.and_then(|name| definitions.get(name)) {
+        Some(definition) if depth < MAX_REF_DEPTH => normalize(definition, definitions, depth + 1),
+        _ => {
+            log::debug!(
+                "Replacing unresolvable reference {} in a tool schema",
+                reference
+            );
+            json!({})
+        }
+    }
+}
+
+/// Single schema equivalent to the variants: one schema with null becomes nullable,
+/// single-value string variants become an enum. None when they cannot be merged.
+fn simplify_variants(variants: &[Value]) -> Option<Map<String, Value>> {
+    let is_null = |variant: &Value| variant.get("type") == Some(&json!("null"));
+    let non_null = variants.iter().filter(|v| !is_null(v)).collect::<Vec<_>>();
+    let nullable = non_null.len() < variants.len();
+
+    let mut merged = match non_null.as_slice() {
+        [single] => single.as_object()?.clone(),
+        _ => {
+            let values = non_null
+                .iter()
+                .map(
+                    |variant| match (variant.get("const"), variant.get("enum")) {
+                        (Some(value), _) => Some(vec![value.clone()]),
+                        (None, Some(Value::Array(values))) => Some(values.clone()),
+                        _ => None,
+                    },
+                )
+                .collect::<Option<Vec<_>>>()?
+                .concat();
+            if !values.iter().all(Value::is_string) {
+                return None;
+            }
+
+            let mut merged = Map::new();
+            merged.insert("type".into(), json!("string"));
+            merged.insert("enum".into(), Value::Array(values));
+            merged
+        }
+    };
+
+    if nullable {
+        match merged.get("type").cloned() {
+            Some(Value::String(kind)) => {
+                merged.insert("type".into(), json!([kind, "null"]));
+            }
+            Some(Value::Array(mut kinds)) if !kinds.contains(&json!("null")) => {
+                kinds.push(json!("null"));
+                merged.insert("type".into(), Value::Array(kinds));
+            }
+            _ => {}
+        }
+        if let Some(Value::Array(values)) = merged.get_mut("enum") {
+            values.push(Value::Null);
+        }
+    }
+
+    Some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[test]
+    fn test_enum_reference_is_inlined_as_nullable_enum() {
+        let schema = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Params",
+            "type": "object",
+            "$defs": {
+                "Side": { "type": "string", "enum": ["ours", "theirs"] }
+            },
+            "properties": {
+                "side": {
+                    "anyOf": [{ "$ref": "#/$defs/Side" }, { "type": "null" }],
+                    "default": null,
+                    "description": "Side to keep"
+                },
+                "offset": { "type": ["integer", "null"], "format": "uint", "minimum": 0 },
+                "title": { "type": "string", "format": "email" }
+            },
+            "required": ["title"]
+        });
+
+        assert_eq!(
+            normalize_openai_schema(&schema),
+            json!({
+                "type": "object",
+                "properties": {
+                    "side": {
+                        "type": ["string", "null"],
+                        "enum": ["ours", "theirs", null],
+                        "description": "Side to keep"
+                    },
+                    "offset": { "type": ["integer", "null"], "minimum": 0 },
+                    "title": { "type": "string", "format": "email" }
+                },
+                "required": ["title"]
+            })
+        );
+    }
+
+    #[test]
+    fn test_one_of_single_values_becomes_enum() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "mode": {
+                    "oneOf": [
+                        { "type": "string", "const": "fast", "description": "Quick" },
+                        { "type": "string", "enum": ["slow"], "description": "Thorough" }
+                    ]
+                }
+            }
+        });
+
+        let normalized = normalize_openai_schema(&schema);
+
+        assert_eq!(
+            normalized["properties"]["mode"],
+            json!({ "type": "string", "enum": ["fast", "slow"] })
+        );
+    }
+
+    #[test]
+    fn test_recursive_reference_is_cut() {
+        let schema = json!({
+            "type": "object",
+            "$defs": { "Node": { "type": "object", "properties": { "next": { "$ref": "#/$defs/Node" } } } },
+            "properties": { "root": { "$ref": "#/$defs/Node" } }
+        });
+
+        let normalized = normalize_openai_schema(&schema);
+
+        assert!(!normalized.to_string().contains("$ref"));
+        assert!(normalized.get("$defs").is_none());
+    }
+
+    #[test]
+    fn test_derived_enum_param_is_accepted_by_openai() {
+        #[derive(schemars::JsonSchema, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        #[allow(dead_code)]
+        enum Side {
+            Ours,
+            Theirs,
+        }
+
+        #[derive(schemars::JsonSchema, Deserialize)]
+        #[allow(dead_code)]
+        struct Params {
+            path: String,
+            #[schemars(description = "Side to keep")]
+            #[serde(default)]
+            side: Option<Side>,
+        }
+
+        let schema = serde_json::to_value(schemars::schema_for!(Params)).unwrap();
+        let parameters = normalize_openai_schema(&schema);
+
+        let serialized = parameters.to_string();
+        for keyword in ["$ref", "$defs", "$schema", "anyOf", "oneOf"] {
+            assert!(
+                !serialized.contains(keyword),
+                "{} in {}",
+                keyword,
+                serialized
+            );
+        }
+        assert_eq!(parameters["type"], "object");
+        assert_eq!(
+            parameters["properties"]["side"]["enum"],
+            json!(["ours", "theirs", null])
+        );
+        assert_eq!(
+            parameters["properties"]["side"]["description"],
+            "Side to keep"
+        );
+    }
+}
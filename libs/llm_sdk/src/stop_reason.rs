@@ -8,3 +8,43 @@ pub enum StopReason {
     StopSequence,
     ToolUse,
 }
+
+impl StopReason {
+    /// Canonical stop reason for an OpenAI-compatible `finish_reason`, every
+    /// provider speaking that API maps it here
+    pub fn from_finish_reason(finish_reason: &str) -> StopReason {
+        match finish_reason {
+            "length" => StopReason::MaxTokens,
+            "tool_calls" | "function_call" => StopReason::ToolUse,
+            // A filtered response is complete from the conversation's point of view
+            "stop" | "content_filter" => StopReason::EndTurn,
+            _ => StopReason::EndTurn,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finish_reason_mapping() {
+        let cases = [
+            ("stop", StopReason::EndTurn),
+            ("length", StopReason::MaxTokens),
+            ("tool_calls", StopReason::ToolUse),
+            ("function_call", StopReason::ToolUse),
+            ("content_filter", StopReason::EndTurn),
+            ("unknown", StopReason::EndTurn),
+        ];
+
+        for (finish_reason, expected) in cases {
+            assert_eq!(
+                StopReason::from_finish_reason(finish_reason),
+                expected,
+                "finish_reason {}",
+                finish_reason
+            );
+        }
+    }
+}
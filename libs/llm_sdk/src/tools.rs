@@ -1,5 +1,7 @@
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
@@ -18,6 +20,14 @@ impl Tool {
         }
     }
 
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_description(&self) -> &str {
+        &self.description
+    }
+
     pub fn with_name(mut self, name: String) -> Self {
         self.name = name;
         self
@@ -33,3 +43,123 @@ impl Tool {
         self
     }
 }
+
+/// How the model may use the tools sent with a request
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ToolChoice {
+    /// The model decides whether to call tools
+    #[default]
+    Auto,
+    /// The model must answer with text only
+    None,
+    /// The model must call at least one tool
+    Required,
+    /// The model must call the named tool
+    Tool(String),
+}
+
+impl ToolChoice {
+    /// Returns true when the model must call a tool (`required` or a tool name)
+    pub fn is_forced(&self) -> bool {
+        matches!(self, ToolChoice::Required | ToolChoice::Tool(_))
+    }
+
+    /// Value of the `tool_choice` field of the Anthropic Messages API
+    pub fn to_anthropic(&self) -> Value {
+        match self {
+            ToolChoice::Auto => json!({ "type": "auto" }),
+            ToolChoice::None => json!({ "type": "none" }),
+            ToolChoice::Required => json!({ "type": "any" }),
+            ToolChoice::Tool(name) => json!({ "type": "tool", "name": name }),
+        }
+    }
+
+    /// Value of the `tool_choice` field of the OpenAI Chat Completions API
+    pub fn to_openai(&self) -> Value {
+        match self {
+            ToolChoice::Auto => json!("auto"),
+            ToolChoice::None => json!("none"),
+            ToolChoice::Required => json!("required"),
+            ToolChoice::Tool(name) => json!({ "type": "function", "function": { "name": name } }),
+        }
+    }
+}
+
+impl FromStr for ToolChoice {
+    type Err = String;
+
+    /// Parses `auto`, `none`, `required` (or `any`), anything else is a tool name
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "" => Err("Tool choice must not be empty".to_string()),
+            "auto" => Ok(ToolChoice::Auto),
+            "none" => Ok(ToolChoice::None),
+            "required" | "any" => Ok(ToolChoice::Required),
+            name => Ok(ToolChoice::Tool(name.to_string())),
+        }
+    }
+}
+
+impl Display for ToolChoice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToolChoice::Auto => write!(f, "auto"),
+            ToolChoice::None => write!(f, "none"),
+            ToolChoice::Required => write!(f, "required"),
+            ToolChoice::Tool(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl Serialize for ToolChoice {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_anthropic().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ToolChoice {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        match (value["type"].as_str(), value["name"].as_str()) {
+            (Some("auto"), _) => Ok(ToolChoice::Auto),
+            (Some("none"), _) => Ok(ToolChoice::None),
+            (Some("any"), _) => Ok(ToolChoice::Required),
+            (Some("tool"), Some(name)) => Ok(ToolChoice::Tool(name.to_string())),
+            _ => Err(serde::de::Error::custom(format!(
+                "Invalid tool choice: {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_choice() {
+        assert_eq!("auto".parse(), Ok(ToolChoice::Auto));
+        assert_eq!("none".parse(), Ok(ToolChoice::None));
+        assert_eq!("any".parse(), Ok(ToolChoice::Required));
+        assert_eq!(
+            "read_text_file".parse(),
+            Ok(ToolChoice::Tool("read_text_file".into()))
+        );
+        assert!("".parse::<ToolChoice>().is_err());
+    }
+
+    #[test]
+    fn test_anthropic_tool_choice() {
+        let choice = ToolChoice::Tool("read_text_file".into());
+
+        assert_eq!(
+            serde_json::to_value(&choice).unwrap(),
+            json!({ "type": "tool", "name": "read_text_file" })
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoice::Required).unwrap(),
+            json!({ "type": "any" })
+        );
+    }
+}
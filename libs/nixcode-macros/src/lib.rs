@@ -1,16 +1,65 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, Error, FnArg, Ident, ItemFn, LitStr, PatType, Type};
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Error, ExprPath, FnArg, Ident, ItemFn, LitStr, PatType, Token, Type};
+
+/// Arguments of `#[tool("description", summarize = path::to::fn, changed_paths = path::to::fn)]`,
+/// all optional
+struct ToolArgs {
+    description: Option<LitStr>,
+    summarize: Option<ExprPath>,
+    changed_paths: Option<ExprPath>,
+}
+
+impl Parse for ToolArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let description = if input.peek(LitStr) {
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        if description.is_some() && !input.is_empty() {
+            input.parse::<Token![,]>()?;
+        }
+
+        let mut summarize = None;
+        let mut changed_paths = None;
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            match key.to_string().as_str() {
+                "summarize" => summarize = Some(input.parse()?),
+                "changed_paths" => changed_paths = Some(input.parse()?),
+                _ => {
+                    return Err(Error::new_spanned(
+                        key,
+                        "Expected `summarize = function` or `changed_paths = function`",
+                    ))
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(Self {
+            description,
+            summarize,
+            changed_paths,
+        })
+    }
+}
 
 #[proc_macro_attribute]
 pub fn tool(args: TokenStream, input: TokenStream) -> TokenStream {
-    // Parse the description argument
-    let description = if args.is_empty() {
-        None
-    } else {
-        let desc = parse_macro_input!(args as LitStr);
-        Some(desc)
-    };
+    // Parse the description and the optional result summary and changed paths functions
+    let ToolArgs {
+        description,
+        summarize,
+        changed_paths,
+    } = parse_macro_input!(args as ToolArgs);
 
     let func = parse_macro_input!(input as ItemFn);
 
@@ -66,11 +115,46 @@ pub fn tool(args: TokenStream, input: TokenStream) -> TokenStream {
         quote! { String::from(concat!("Use this tool for ", #tool_name, ". Auto generated.")) }
     };
 
+    // Tools keep the default summarize_result of the trait unless a function is given
+    let summarize_fn = summarize.map(|summarize| {
+        quote! {
+            fn summarize_result(&self, result: &serde_json::Value) -> Option<String> {
+                #summarize(result)
+            }
+        }
+    });
+
+    let changed_paths_fn = changed_paths.map(|changed_paths| {
+        quote! {
+            fn changed_paths(&self, params: &serde_json::Value, result: &str) -> Vec<String> {
+                #changed_paths(params, result)
+            }
+        }
+    });
+
     let expanded = quote! {
         #func
 
         pub struct #struct_ident {}
 
+        impl #struct_ident {
+            /// Schema of the tool, generated on the first call and shared by all instances
+            pub fn cached_schema() -> &'static nixcode_llm_sdk::tools::Tool {
+                static SCHEMA: std::sync::OnceLock<nixcode_llm_sdk::tools::Tool> =
+                    std::sync::OnceLock::new();
+
+                SCHEMA.get_or_init(|| {
+                    let schema = schemars::schema_for!(#param_ident);
+                    let parameters = serde_json::to_value(&schema).unwrap();
+
+                    let tool_name = #tool_name.to_string();
+                    let description = #description_expr;
+
+                    nixcode_llm_sdk::tools::Tool::new(tool_name, description, parameters)
+                })
+            }
+        }
+
         #[async_trait::async_trait]
         impl crate::tools::Tool for #struct_ident {
             fn get_name(&self) -> String {
@@ -78,19 +162,17 @@ pub fn tool(args: TokenStream, input: TokenStream) -> TokenStream {
             }
 
             fn get_schema(&self) -> nixcode_llm_sdk::tools::Tool {
-                let schema = schemars::schema_for!(#param_ident);
-                let parameters = serde_json::to_value(&schema).unwrap();
-
-                let tool_name = #tool_name.to_string();
-                let description = #description_expr;
-
-                nixcode_llm_sdk::tools::Tool::new(tool_name, description, parameters)
+                Self::cached_schema().clone()
             }
 
             async fn execute(&self, params: serde_json::Value, project: std::sync::Arc<crate::project::Project>) -> anyhow::Result<serde_json::Value> {
                 let params: #param_ident = serde_json::from_value(params)?;
                 Ok(#func_name(params, project).await)
             }
+
+            #summarize_fn
+
+            #changed_paths_fn
         }
     };
 
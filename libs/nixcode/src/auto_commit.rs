@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use git2::{IndexAddOption, Repository};
+
+use crate::project::CommitAuthor;
+use crate::tools::git::git_commit_message::{get_staged_diff, MAX_STAGED_DIFF_CHARS};
+
+/// Tool whose successful run counts as a passed verification (e.g. build and test tasks)
+pub const VERIFICATION_TOOL: &str = "run_task";
+
+/// Returns true if the tool result reports a successful verification run
+pub fn is_verification_passed(tool_name: &str, result: &str) -> bool {
+    tool_name == VERIFICATION_TOOL && result.starts_with("Exit code: 0\n")
+}
+
+/// Decides whether the changes should be committed after a tool run
+pub fn should_auto_commit(enabled: bool, has_repo: bool, tool_name: &str, result: &str) -> bool {
+    enabled && has_repo && is_verification_passed(tool_name, result)
+}
+
+/// Commit message describing the staged diff: the changed file (or the number of
+/// changed files) in the subject, the files and the verification task in the body
+pub fn get_auto_commit_message(task: &str, staged_diff: &str) -> String {
+    let mut files: Vec<(&str, &str)> = vec![];
+    for line in staged_diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git a/") {
+            if let Some((_, path)) = paths.split_once(" b/") {
+                files.push(("Update", path));
+            }
+        } else if let Some((action, _)) = files.last_mut() {
+            if line.starts_with("new file mode") {
+                *action = "Add";
+            } else if line.starts_with("deleted file mode") {
+                *action = "Delete";
+            }
+        }
+    }
+
+    let subject = match files.as_slice() {
+        [(action, path)] => format!("{} {}", action, path),
+        [(action, _), rest @ ..] if rest.iter().all(|(other, _)| other == action) => {
+            format!("{} {} files", action, files.len())
+        }
+        _ => format!("Update {} files", files.len()),
+    };
+    let list = files
+        .iter()
+        .map(|(action, path)| format!("- {} {}", action, path))
+        .collect::<Vec<_>>();
+
+    format!(
+        "{}\n\n{}\n\nVerified by the {} task\n",
+        subject,
+        list.join("\n"),
+        task
+    )
+}
+
+/// Stages all changes and commits them, returns the short id of the commit
+/// or None when there was nothing to commit
+pub fn commit_all(
+    repo_path: &Path,
+    task: &str,
+    author: &CommitAuthor,
+) -> Result<Option<String>, String> {
+    let repo = Repository::open(repo_path).map_err(|e| format!("Cannot open repository: {}", e))?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Cannot get index: {}", e))?;
+    index
+        .add_all(["*"], IndexAddOption::DEFAULT, None)
+        .and_then(|_| index.update_all(["*"], None))
+        .and_then(|_| index.write())
+        .map_err(|e| format!("Cannot stage changes: {}", e))?;
+
+    let diff = get_staged_diff(&repo, MAX_STAGED_DIFF_CHARS)?;
+    if diff.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let tree = index
+        .write_tree()
+        .and_then(|oid| repo.find_tree(oid))
+        .map_err(|e| format!("Cannot write index tree: {}", e))?;
+    let signature = author
+        .get_signature(&repo)
+        .map_err(|e| format!("Cannot get signature for commiter: {}", e))?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents = parent.iter().collect::<Vec<_>>();
+
+    let oid = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &get_auto_commit_message(task, &diff),
+            &tree,
+            &parents,
+        )
+        .map_err(|e| format!("Can't commit: {}", e))?;
+
+    Ok(Some(oid.to_string()[..7].to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_commit_only_after_passing_verification() {
+        let passed = "Exit code: 0\n\nstdout:\ntest result: ok\n";
+        let failed = "Exit code: 101\n\nstderr:\nerror[E0425]\n";
+
+        assert!(should_auto_commit(true, true, "run_task", passed));
+        assert!(!should_auto_commit(true, true, "run_task", failed));
+        assert!(!should_auto_commit(false, true, "run_task", passed));
+        assert!(!should_auto_commit(true, false, "run_task", passed));
+        assert!(!should_auto_commit(true, true, "read_text_file", passed));
+    }
+
+    #[test]
+    fn test_auto_commit_commits_all_changes() {
+        let workdir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(workdir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "nixcode").unwrap();
+        config.set_str("user.email", "nixcode@example.com").unwrap();
+        std::fs::write(workdir.path().join("lib.rs"), "fn main() {}\n").unwrap();
+
+        let author = CommitAuthor::default();
+        let id = commit_all(workdir.path(), "test", &author).unwrap();
+        assert!(id.is_some());
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(
+            head.message().unwrap(),
+            "Add lib.rs\n\n- Add lib.rs\n\nVerified by the test task\n"
+        );
+        assert_eq!(commit_all(workdir.path(), "test", &author).unwrap(), None);
+
+        std::fs::write(workdir.path().join("lib.rs"), "fn main() { run() }\n").unwrap();
+        std::fs::write(workdir.path().join("run.rs"), "fn run() {}\n").unwrap();
+        commit_all(workdir.path(), "test", &author).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(
+            head.message().unwrap(),
+            "Update 2 files\n\n- Update lib.rs\n- Add run.rs\n\nVerified by the test task\n"
+        );
+    }
+}
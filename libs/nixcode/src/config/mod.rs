@@ -1,17 +1,35 @@
+use crate::project::{CommitAuthor, RemoteDefaults};
+use crate::utils::command::{CommandOptions, DEFAULT_MAX_OUTPUT_BYTES};
 use anyhow::Result;
 use directories::ProjectDirs;
-use secrecy::SecretString;
+use nixcode_llm_sdk::config::OLLAMA_DEFAULT_BASE_URL;
+use nixcode_llm_sdk::models::ReasoningEffort;
+use nixcode_llm_sdk::tools::ToolChoice;
+use secrecy::{ExposeSecret, SecretString};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use toml;
 
+#[cfg(test)]
+mod tests;
+
 /// Default model for Anthropic
 const DEFAULT_ANTHROPIC_MODEL: &str = "claude-3-7-sonnet-20250219";
 /// Default model for OpenAI
 const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+/// Default model for a local Ollama server
+const DEFAULT_OLLAMA_MODEL: &str = "llama3.2";
+/// Default API version for Azure OpenAI
+const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+/// Supported providers, in the order they are offered
+pub const PROVIDERS: [&str; 4] = ["anthropic", "openai", "azure", "ollama"];
 
 /// The Config struct represents the application configuration
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -27,6 +45,26 @@ pub struct Config {
     /// Tool configuration
     #[serde(default)]
     pub tools: ToolsConfig,
+
+    /// Debugging options
+    #[serde(default)]
+    pub debug: DebugConfig,
+
+    /// Model settings shared by all providers
+    #[serde(default)]
+    pub models: ModelsConfig,
+
+    /// Terminal UI settings
+    #[serde(default)]
+    pub ui: UiConfig,
+
+    /// Agent loop settings
+    #[serde(default)]
+    pub agent: AgentConfig,
+
+    /// Git settings of the agent
+    #[serde(default)]
+    pub git: GitConfig,
 }
 
 /// LLM general settings
@@ -54,6 +92,14 @@ pub struct Providers {
     /// OpenAI-specific settings
     #[serde(default)]
     pub openai: ProviderSettings,
+
+    /// Azure OpenAI-specific settings
+    #[serde(default)]
+    pub azure: ProviderSettings,
+
+    /// Local Ollama (or other OpenAI-compatible server) settings
+    #[serde(default)]
+    pub ollama: ProviderSettings,
 }
 
 /// Settings for a specific provider
@@ -64,6 +110,55 @@ pub struct ProviderSettings {
 
     /// Default model for this provider
     pub default_model: Option<String>,
+
+    /// Reasoning effort for reasoning models (low, medium, high)
+    pub reasoning_effort: Option<ReasoningEffort>,
+
+    /// Resource endpoint, e.g. https://my-resource.openai.azure.com (Azure only)
+    pub endpoint: Option<String>,
+
+    /// Deployment name (Azure only)
+    pub deployment: Option<String>,
+
+    /// API version (Azure only)
+    pub api_version: Option<String>,
+
+    /// Base URL of an OpenAI-compatible server (Ollama only)
+    pub base_url: Option<String>,
+
+    /// Headers sent with every request, e.g. for LLM gateways (values can use ${ENV_VAR} syntax)
+    #[serde(default)]
+    pub extra_headers: HashMap<String, String>,
+}
+
+/// Returns the trimmed value, treating empty strings as not configured
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(String::from)
+}
+
+impl ProviderSettings {
+    /// Get the configured default model, ignoring empty values
+    pub fn get_default_model(&self) -> Option<String> {
+        non_empty(&self.default_model)
+    }
+
+    /// Get the Ollama base URL, falling back to the local default
+    pub fn get_base_url(&self) -> String {
+        self.base_url
+            .clone()
+            .unwrap_or_else(|| OLLAMA_DEFAULT_BASE_URL.to_string())
+    }
+
+    /// Get the Azure API version, falling back to the default one
+    pub fn get_api_version(&self) -> String {
+        self.api_version
+            .clone()
+            .unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string())
+    }
 }
 
 /// Tool configuration
@@ -76,6 +171,255 @@ pub struct ToolsConfig {
     /// Override specific tools (true to enable, false to disable)
     #[serde(default)]
     pub overrides: HashMap<String, bool>,
+
+    /// Tools that are always disabled, whatever `enabled` and `overrides` say. Lists of
+    /// the user and the project config are combined, so a project cannot lift a denial
+    #[serde(default)]
+    pub deny: Vec<String>,
+
+    /// Files larger than this (in estimated tokens) are read in chunks by read_text_file
+    pub read_chunk_tokens: Option<usize>,
+
+    /// Files processed by a single call of a multi-file tool (e.g. read_files), the
+    /// remaining paths are reported back
+    pub max_files_per_call: Option<usize>,
+
+    /// Files read at the same time by multi-file tools
+    pub max_concurrent_reads: Option<usize>,
+
+    /// Budget (in estimated tokens) of the tool definitions sent with every request,
+    /// the tools with the largest schemas are left out when it is exceeded
+    pub max_schema_tokens: Option<usize>,
+
+    /// Reuse results of read-only tools (reads, glob and content search) called again
+    /// with the same arguments while their files are unchanged
+    #[serde(default)]
+    pub cache_read_results: bool,
+
+    /// Format files of recognized types (rustfmt for `.rs`) before they are written,
+    /// rejecting writes the formatter cannot parse
+    #[serde(default)]
+    pub format_on_write: bool,
+
+    /// Environment and working directory of commands run by tools
+    #[serde(default)]
+    pub command: CommandConfig,
+}
+
+/// Settings of commands run by tools (e.g. run_task)
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CommandConfig {
+    /// Only these environment variables are passed to commands, when not set
+    /// every variable except sensitive ones (`*_API_KEY`, `*_TOKEN`, ...) is passed
+    pub env_allowlist: Option<Vec<String>>,
+
+    /// Working directory of commands, relative to the project root
+    pub working_dir: Option<String>,
+
+    /// Bytes of stdout (and of stderr) kept from a command, from its start and its end,
+    /// the middle is dropped
+    pub max_output_bytes: Option<usize>,
+}
+
+impl CommandConfig {
+    pub fn get_command_options(&self) -> CommandOptions {
+        CommandOptions {
+            env_allowlist: self.env_allowlist.clone(),
+            working_dir: self.working_dir.as_ref().map(PathBuf::from),
+            max_output_bytes: self.max_output_bytes.unwrap_or(DEFAULT_MAX_OUTPUT_BYTES),
+            output_tx: None,
+        }
+    }
+}
+
+/// Debugging options
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct DebugConfig {
+    /// Directory where provider requests and streamed responses are recorded
+    /// (credentials are redacted). Recording is disabled when not set.
+    pub record_dir: Option<String>,
+
+    /// Directory with recorded responses to replay instead of calling the provider
+    pub replay_dir: Option<String>,
+}
+
+/// Git settings of the agent
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GitConfig {
+    /// Author and committer name of commits made by the agent, from the git config
+    /// of the repository when not set
+    pub author_name: Option<String>,
+
+    /// Author and committer email of commits made by the agent
+    pub author_email: Option<String>,
+
+    /// Remote of git_push and git_pull when the call names none, before the upstream of
+    /// the current branch and origin
+    pub default_remote: Option<String>,
+
+    /// Remote branch of git_push and git_pull when the call names none, before the
+    /// upstream branch and the name of the current branch
+    pub default_branch: Option<String>,
+}
+
+impl GitConfig {
+    pub fn get_commit_author(&self) -> CommitAuthor {
+        CommitAuthor {
+            name: self.author_name.clone(),
+            email: self.author_email.clone(),
+        }
+    }
+
+    pub fn get_remote_defaults(&self) -> RemoteDefaults {
+        RemoteDefaults {
+            remote: self.default_remote.clone(),
+            branch: self.default_branch.clone(),
+        }
+    }
+}
+
+/// Model settings shared by all providers
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ModelsConfig {
+    /// Short names for models, e.g. `fast = "claude-3-5-haiku-latest"`
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+}
+
+/// How the user is notified when a long run finishes
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NotifyMode {
+    #[default]
+    Off,
+    /// Terminal bell
+    Bell,
+    /// Desktop notification (notify-send on Linux, osascript on macOS)
+    Desktop,
+    /// Terminal bell and desktop notification
+    Both,
+}
+
+/// Terminal UI settings
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UiConfig {
+    /// Completed messages longer than this many lines are collapsed to a single line
+    pub auto_collapse_lines: Option<usize>,
+
+    /// Notify when the agent finishes or fails a run
+    #[serde(default)]
+    pub notify: NotifyMode,
+
+    /// Only notify about runs that took at least this many seconds
+    #[serde(default = "default_notify_after_secs")]
+    pub notify_after_secs: u64,
+
+    /// Providers offered for model selection, all available providers when not set
+    pub visible_providers: Option<Vec<String>>,
+
+    /// Label shown before assistant messages in the transcript
+    #[serde(default = "default_assistant_label")]
+    pub assistant_label: String,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            auto_collapse_lines: None,
+            notify: NotifyMode::default(),
+            notify_after_secs: default_notify_after_secs(),
+            visible_providers: None,
+            assistant_label: default_assistant_label(),
+        }
+    }
+}
+
+fn default_notify_after_secs() -> u64 {
+    30
+}
+
+fn default_assistant_label() -> String {
+    "Assistant".to_string()
+}
+
+/// Agent loop settings
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AgentConfig {
+    /// Maximum number of tools executed at the same time within a turn
+    #[serde(default = "default_max_concurrent_tools")]
+    pub max_concurrent_tools: usize,
+
+    /// Tool choice sent with tools: auto, none, required or a tool name. Required and a
+    /// tool name apply to the first request only
+    pub tool_choice: Option<String>,
+
+    /// Token budget for extended thinking, thinking is disabled when not set
+    pub thinking_budget: Option<u32>,
+
+    /// Warn when thinking uses more than this fraction of the response max tokens
+    #[serde(default = "default_thinking_warning_fraction")]
+    pub thinking_warning_fraction: f32,
+
+    /// Stage and commit all changes at the end of a turn in which a verification task
+    /// (run_task) passed
+    #[serde(default)]
+    pub auto_commit: bool,
+
+    /// Start of every response to a user message (e.g. `{` to force JSON), not sent
+    /// with tool results or extended thinking
+    pub prefill: Option<String>,
+
+    /// Seconds without a streamed event after which the response is stopped with an
+    /// error, 0 waits forever
+    #[serde(default = "default_stream_idle_timeout_secs")]
+    pub stream_idle_timeout_secs: u64,
+
+    /// Tool results older than this many responses are sent as a short placeholder,
+    /// 0 always sends them in full
+    #[serde(default)]
+    pub elide_tool_results_after_turns: usize,
+
+    /// Only tool results of at least this many characters are elided
+    #[serde(default = "default_elide_tool_results_min_chars")]
+    pub elide_tool_results_min_chars: usize,
+
+    /// Times an empty response (no text and no tool calls) is retried in a row with a
+    /// nudge, 0 keeps empty responses
+    #[serde(default)]
+    pub retry_empty: u32,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_tools: default_max_concurrent_tools(),
+            tool_choice: None,
+            thinking_budget: None,
+            thinking_warning_fraction: default_thinking_warning_fraction(),
+            auto_commit: false,
+            prefill: None,
+            stream_idle_timeout_secs: default_stream_idle_timeout_secs(),
+            elide_tool_results_after_turns: 0,
+            elide_tool_results_min_chars: default_elide_tool_results_min_chars(),
+            retry_empty: 0,
+        }
+    }
+}
+
+fn default_max_concurrent_tools() -> usize {
+    4
+}
+
+fn default_stream_idle_timeout_secs() -> u64 {
+    300
+}
+
+fn default_elide_tool_results_min_chars() -> usize {
+    2000
+}
+
+fn default_thinking_warning_fraction() -> f32 {
+    0.8
 }
 
 fn default_tools_enabled() -> bool {
@@ -96,13 +440,29 @@ impl Config {
                     default_model: Some(DEFAULT_OPENAI_MODEL.to_string()),
                     ..Default::default()
                 },
+                azure: ProviderSettings::default(),
+                ollama: ProviderSettings {
+                    default_model: Some(DEFAULT_OLLAMA_MODEL.to_string()),
+                    ..Default::default()
+                },
             },
             tools: ToolsConfig::default(),
+            debug: DebugConfig::default(),
+            models: ModelsConfig::default(),
+            ui: UiConfig::default(),
+            agent: AgentConfig::default(),
+            git: GitConfig::default(),
         }
     }
 
     /// Load configuration from files
     pub fn load() -> Result<Self> {
+        let current_dir = env::current_dir()?;
+        Self::load_for_project(&current_dir)
+    }
+
+    /// Loads the user config merged with the config of the project containing `project_dir`
+    pub fn load_for_project(project_dir: &Path) -> Result<Self> {
         // Start with default configuration
         let mut config = Self::new();
 
@@ -114,18 +474,33 @@ impl Config {
         }
 
         // Try to load from project directory (only if we're in a project)
-        if let Some(project_config_path) = find_project_config() {
-            merge_config_from_file(&mut config, &project_config_path)?;
+        if let Some(project_config_path) = find_project_config(project_dir) {
+            merge_project_config_from_file(&mut config, &project_config_path)?;
         }
 
         Ok(config)
     }
 
-    /// Get the model to use for a provider
+    /// Resolve a model alias to the full model name, other names are returned as is
+    pub fn resolve_model_alias(&self, model: &str) -> String {
+        let model = model.trim();
+        self.models
+            .aliases
+            .get(model)
+            .cloned()
+            .unwrap_or_else(|| model.to_string())
+    }
+
+    /// Get the model to use for a provider, with aliases resolved
     pub fn get_model_for_provider(&self, provider: &str) -> String {
+        let model = self.get_configured_model_for_provider(provider);
+        self.resolve_model_alias(&model)
+    }
+
+    fn get_configured_model_for_provider(&self, provider: &str) -> String {
         // First check if there's a default model configured at the top level
-        if let Some(model) = &self.llm.default_model {
-            return model.clone();
+        if let Some(model) = non_empty(&self.llm.default_model) {
+            return model;
         }
 
         // Then check for provider-specific default
@@ -133,26 +508,84 @@ impl Config {
             "anthropic" => self
                 .providers
                 .anthropic
-                .default_model
-                .clone()
+                .get_default_model()
                 .unwrap_or_else(|| DEFAULT_ANTHROPIC_MODEL.to_string()),
             "openai" => self
                 .providers
                 .openai
-                .default_model
-                .clone()
+                .get_default_model()
                 .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+            // Azure routes by deployment, the model name is informational
+            "azure" => self
+                .providers
+                .azure
+                .get_default_model()
+                .or_else(|| non_empty(&self.providers.azure.deployment))
+                .unwrap_or_else(|| DEFAULT_OPENAI_MODEL.to_string()),
+            // Any model pulled into the local server can be configured
+            "ollama" => self
+                .providers
+                .ollama
+                .get_default_model()
+                .unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string()),
             _ => DEFAULT_ANTHROPIC_MODEL.to_string(),
         }
     }
 
-    /// Get the API key for a provider, attempting to resolve environment variables
+    /// Get the configured tool choice, invalid values fall back to auto
+    pub fn get_tool_choice(&self) -> ToolChoice {
+        self.agent
+            .tool_choice
+            .as_deref()
+            .and_then(|tool_choice| tool_choice.parse().ok())
+            .unwrap_or_default()
+    }
+
+    /// Get the configured reasoning effort for a provider
+    pub fn get_reasoning_effort_for_provider(&self, provider: &str) -> Option<ReasoningEffort> {
+        match provider {
+            "anthropic" => self.providers.anthropic.reasoning_effort,
+            "openai" => self.providers.openai.reasoning_effort,
+            "azure" => self.providers.azure.reasoning_effort,
+            "ollama" => self.providers.ollama.reasoning_effort,
+            _ => None,
+        }
+    }
+
+    /// Extra headers of the provider with environment variables resolved
+    pub fn get_extra_headers_for_provider(&self, provider: &str) -> HashMap<String, String> {
+        self.extra_headers_with(provider, |name| env::var(name).ok())
+    }
+
+    /// Extra headers of the provider with variables resolved by `lookup`
+    fn extra_headers_with(
+        &self,
+        provider: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> HashMap<String, String> {
+        let settings = match provider {
+            "anthropic" => &self.providers.anthropic,
+            "openai" => &self.providers.openai,
+            "azure" => &self.providers.azure,
+            "ollama" => &self.providers.ollama,
+            _ => return HashMap::new(),
+        };
+
+        settings
+            .extra_headers
+            .iter()
+            .map(|(name, value)| (name.clone(), expand_vars_with(value, &lookup)))
+            .collect()
+    }
+
+    /// Get the API key for a provider, attempting to resolve environment variables and
+    /// running `cmd:` credential commands
     pub fn get_api_key_for_provider(&self, provider: &str) -> Result<SecretString> {
         let key_value = match provider {
             "anthropic" => {
                 // Try config first
                 if let Some(key) = &self.providers.anthropic.api_key {
-                    expand_env_vars(key)
+                    resolve_api_key(key)?
                 } else {
                     // Fall back to environment variable
                     env::var("ANTHROPIC_API_KEY").map_err(|_| {
@@ -165,7 +598,7 @@ impl Config {
             "openai" => {
                 // Try config first
                 if let Some(key) = &self.providers.openai.api_key {
-                    expand_env_vars(key)
+                    resolve_api_key(key)?
                 } else {
                     // Fall back to environment variable
                     env::var("OPENAI_API_KEY").map_err(|_| {
@@ -175,15 +608,105 @@ impl Config {
                     })?
                 }
             }
+            "azure" => {
+                // Try config first
+                if let Some(key) = &self.providers.azure.api_key {
+                    resolve_api_key(key)?
+                } else {
+                    // Fall back to environment variable
+                    env::var("AZURE_OPENAI_API_KEY").map_err(|_| {
+                        anyhow::anyhow!(
+                            "AZURE_OPENAI_API_KEY environment variable not set and not configured"
+                        )
+                    })?
+                }
+            }
+            // Local servers usually don't require an API key
+            "ollama" => self
+                .providers
+                .ollama
+                .api_key
+                .as_deref()
+                .map(resolve_api_key)
+                .transpose()?
+                .unwrap_or_default(),
             _ => return Err(anyhow::anyhow!("Unknown provider: {}", provider)),
         };
 
         Ok(SecretString::new(key_value.into()))
     }
 
+    /// Checks that the provider can be used: its API key resolves to a value, Azure also
+    /// needs an endpoint and a deployment and Ollama must be configured (or the default)
+    pub fn check_provider_available(&self, provider: &str) -> Result<()> {
+        let api_key = self.get_api_key_for_provider(provider)?;
+        let api_key = api_key.expose_secret();
+
+        match provider {
+            "ollama" => {
+                let configured = self.providers.ollama.base_url.is_some();
+                if !configured && self.llm.default_provider != "ollama" {
+                    return Err(anyhow::anyhow!(
+                        "Ollama is not configured, set providers.ollama.base_url"
+                    ));
+                }
+            }
+            _ if api_key.trim().is_empty() || api_key.contains("${") => {
+                return Err(anyhow::anyhow!(
+                    "API key for {} is empty or uses an unset variable",
+                    provider
+                ));
+            }
+            "azure" => {
+                let azure = &self.providers.azure;
+                if non_empty(&azure.endpoint).is_none() || non_empty(&azure.deployment).is_none() {
+                    return Err(anyhow::anyhow!(
+                        "Azure provider requires providers.azure.endpoint and deployment"
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Providers with a usable configuration, limited to `ui.visible_providers` when set
+    pub fn get_visible_providers(&self) -> Vec<String> {
+        PROVIDERS
+            .iter()
+            .filter(|provider| match &self.ui.visible_providers {
+                Some(visible) => visible.iter().any(|visible| visible == *provider),
+                None => true,
+            })
+            .filter(|provider| self.check_provider_available(provider).is_ok())
+            .map(|provider| provider.to_string())
+            .collect()
+    }
+
+    /// Get the directory for request/response recordings, if enabled
+    pub fn get_record_dir(&self) -> Option<PathBuf> {
+        self.debug
+            .record_dir
+            .as_ref()
+            .map(|dir| PathBuf::from(expand_env_vars(dir)))
+    }
+
+    /// Get the directory with recorded responses to replay, if enabled
+    pub fn get_replay_dir(&self) -> Option<PathBuf> {
+        self.debug
+            .replay_dir
+            .as_ref()
+            .map(|dir| PathBuf::from(expand_env_vars(dir)))
+    }
+
     /// Check if a tool is enabled based on configuration
     pub fn is_tool_enabled(&self, tool_name: &str) -> bool {
-        // First check if we have a specific override for this tool
+        if self.tools.is_denied(tool_name) {
+            return false;
+        }
+
+        // Then check if we have a specific override for this tool
         if let Some(enabled) = self.tools.overrides.get(tool_name) {
             return *enabled;
         }
@@ -194,10 +717,16 @@ impl Config {
 }
 
 impl ToolsConfig {
+    /// Returns true for tools in the deny list
+    pub fn is_denied(&self, tool_name: &str) -> bool {
+        self.deny.iter().any(|denied| denied == tool_name)
+    }
+
     /// Get a list of all enabled tool names based on current configuration and available tools
     pub fn get_enabled_tools(&self, all_tools: &[String]) -> Vec<String> {
         all_tools
             .iter()
+            .filter(|tool_name| !self.is_denied(tool_name))
             .filter(|tool_name| {
                 // If we have a specific override for this tool, use that
                 if let Some(enabled) = self.overrides.get(*tool_name) {
@@ -212,8 +741,19 @@ impl ToolsConfig {
     }
 }
 
+/// Name of the user configuration directory in XDG style locations
+const CONFIG_DIR_NAME: &str = "nixcode-ai";
+
 /// Get the path to the user's configuration file
 fn get_user_config_path() -> Option<PathBuf> {
+    // ProjectDirs would use ~/Library/Application Support on macOS, the docs promise
+    // the XDG location there as well
+    if cfg!(target_os = "macos") {
+        let home = directories::BaseDirs::new()?.home_dir().to_path_buf();
+        let config_dir = get_xdg_config_dir(env::var_os("XDG_CONFIG_HOME"), &home);
+        return Some(config_dir.join("config.toml"));
+    }
+
     if let Some(proj_dirs) = ProjectDirs::from("it.nixcode", "nixcode", "nixcode-ai") {
         let config_dir = proj_dirs.config_dir();
         Some(config_dir.join("config.toml"))
@@ -222,9 +762,24 @@ fn get_user_config_path() -> Option<PathBuf> {
     }
 }
 
+/// `$XDG_CONFIG_HOME/nixcode-ai`, or `~/.config/nixcode-ai` when the variable is not set
+/// or not an absolute path (as the XDG specification requires)
+fn get_xdg_config_dir(xdg_config_home: Option<std::ffi::OsString>, home: &Path) -> PathBuf {
+    xdg_config_home
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| home.join(".config"))
+        .join(CONFIG_DIR_NAME)
+}
+
+/// Directory of the user configuration, also used for state kept between runs
+pub fn get_user_config_dir() -> Option<PathBuf> {
+    get_user_config_path()?.parent().map(Path::to_path_buf)
+}
+
 /// Find the project configuration file by walking up the directory tree
-fn find_project_config() -> Option<PathBuf> {
-    let mut current_dir = env::current_dir().ok()?;
+fn find_project_config(project_dir: &Path) -> Option<PathBuf> {
+    let mut current_dir = project_dir.to_path_buf();
 
     loop {
         let config_path = current_dir.join(".nixcode").join("config.toml");
@@ -244,16 +799,176 @@ fn find_project_config() -> Option<PathBuf> {
 fn merge_config_from_file(config: &mut Config, path: &Path) -> Result<()> {
     let content = fs::read_to_string(path)?;
     let file_config: Config = toml::from_str(&content)?;
+    merge_config(config, file_config);
+
+    Ok(())
+}
+
+/// Merge the config of a project, which comes with the repository and is not trusted:
+/// its `cmd:` API keys are ignored so opening a cloned repository runs no commands
+fn merge_project_config_from_file(config: &mut Config, path: &Path) -> Result<()> {
+    let content = fs::read_to_string(path)?;
+    let mut file_config: Config = toml::from_str(&content)?;
+
+    let (user, project) = (&config.providers, &mut file_config.providers);
+    ignore_credential_command(path, "anthropic", &user.anthropic, &mut project.anthropic);
+    ignore_credential_command(path, "openai", &user.openai, &mut project.openai);
+    ignore_credential_command(path, "azure", &user.azure, &mut project.azure);
+    ignore_credential_command(path, "ollama", &user.ollama, &mut project.ollama);
+
+    merge_config(config, file_config);
+
+    Ok(())
+}
+
+/// Keeps the API key of the user config when the project config sets a `cmd:` key
+fn ignore_credential_command(
+    path: &Path,
+    provider: &str,
+    user: &ProviderSettings,
+    project: &mut ProviderSettings,
+) {
+    let Some(api_key) = &project.api_key else {
+        return;
+    };
+    if !is_credential_command(api_key) {
+        return;
+    }
+
+    log::warn!(
+        "Ignoring the cmd: api_key of {} in {}, credential commands are only run from the user config",
+        provider,
+        path.display()
+    );
+    project.api_key = user.api_key.clone();
+}
+
+fn merge_config(config: &mut Config, mut file_config: Config) {
+    // Denied tools stay denied by the configs merged later
+    for denied in std::mem::take(&mut config.tools.deny) {
+        if !file_config.tools.deny.contains(&denied) {
+            file_config.tools.deny.push(denied);
+        }
+    }
 
     // For now, we just completely override with the file config
     // In a more complex implementation, we would need to do a deep merge
     *config = file_config;
+}
 
-    Ok(())
+/// Prefix of API keys printed by a command, e.g. `cmd:op read op://vault/anthropic/key`
+const CREDENTIAL_COMMAND_PREFIX: &str = "cmd:";
+
+/// Credential commands still running after this long are killed
+const CREDENTIAL_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+
+fn is_credential_command(key: &str) -> bool {
+    key.trim_start().starts_with(CREDENTIAL_COMMAND_PREFIX)
+}
+
+/// Resolves a configured API key: the output of a `cmd:` command, otherwise the value
+/// with environment variables expanded
+fn resolve_api_key(key: &str) -> Result<String> {
+    match key.trim_start().strip_prefix(CREDENTIAL_COMMAND_PREFIX) {
+        Some(command) => run_credential_command(command.trim()),
+        None => Ok(expand_env_vars(key)),
+    }
+}
+
+/// Runs a credential command with the shell and returns its trimmed output. The output
+/// is never logged nor part of an error, it is kept for the run so a password manager
+/// is asked only once. Logs and errors show the command as configured, before
+/// `${ENV}` expansion, since the expanded variables may hold tokens
+fn run_credential_command(configured: &str) -> Result<String> {
+    static CREDENTIALS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+    let credentials = CREDENTIALS.get_or_init(Default::default);
+    if let Some(key) = credentials.lock().unwrap().get(configured) {
+        return Ok(key.clone());
+    }
+
+    let command_line = expand_env_vars(configured);
+
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = std::process::Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    };
+
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = std::process::Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    };
+
+    log::debug!("Running credential command `{}`", configured);
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Cannot run credential command `{}`: {}", configured, e))?;
+
+    // Read on another thread, so a command printing more than the pipe holds can finish
+    let mut stdout = child.stdout.take().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > CREDENTIAL_COMMAND_TIMEOUT {
+            child.kill().ok();
+            child.wait().ok();
+            return Err(anyhow::anyhow!(
+                "Credential command `{}` did not finish within {} seconds",
+                configured,
+                CREDENTIAL_COMMAND_TIMEOUT.as_secs()
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "Credential command `{}` failed with {}",
+            configured,
+            status
+        ));
+    }
+
+    let output = reader.join().ok().and_then(|output| output.ok());
+    let key = output
+        .and_then(|output| String::from_utf8(output).ok())
+        .map(|key| key.trim().to_string())
+        .unwrap_or_default();
+    if key.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Credential command `{}` printed no key",
+            configured
+        ));
+    }
+
+    credentials
+        .lock()
+        .unwrap()
+        .insert(configured.to_string(), key.clone());
+
+    Ok(key)
 }
 
 /// Expand environment variables in a string (format: ${VAR_NAME})
 fn expand_env_vars(input: &str) -> String {
+    expand_vars_with(input, |name| env::var(name).ok())
+}
+
+/// Expand variables in a string (format: ${VAR_NAME}) with values from `lookup`
+fn expand_vars_with(input: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
     let mut result = input.to_string();
 
     // Find all patterns like ${VAR_NAME}
@@ -265,8 +980,8 @@ fn expand_env_vars(input: &str) -> String {
             let var_end = var_start + var_end + 1;
             let var_name = &result[var_start + 2..var_end - 1];
 
-            // Replace with environment variable value if it exists
-            if let Ok(var_value) = env::var(var_name) {
+            // Replace with the variable value if it exists
+            if let Some(var_value) = lookup(var_name) {
                 result.replace_range(var_start..var_end, &var_value);
                 // Continue from the position after the replacement
                 start_idx = var_start + var_value.len();
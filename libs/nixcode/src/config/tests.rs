@@ -0,0 +1,207 @@
+use super::*;
+
+#[test]
+fn test_configured_default_model_is_selected() {
+    let mut config = Config::new();
+    config.providers.anthropic.default_model = Some("claude-3-5-haiku-20241022".into());
+
+    assert_eq!(
+        config.get_model_for_provider("anthropic"),
+        "claude-3-5-haiku-20241022"
+    );
+    assert_eq!(
+        config.get_model_for_provider("openai"),
+        DEFAULT_OPENAI_MODEL
+    );
+}
+
+#[test]
+fn test_empty_default_model_falls_back_to_builtin() {
+    let mut config = Config::new();
+    config.providers.openai.default_model = Some("  ".into());
+    config.llm.default_model = Some("".into());
+
+    assert_eq!(
+        config.get_model_for_provider("openai"),
+        DEFAULT_OPENAI_MODEL
+    );
+}
+
+#[test]
+fn test_top_level_default_model_takes_precedence() {
+    let mut config = Config::new();
+    config.llm.default_model = Some("gpt-4o-mini".into());
+    config.providers.openai.default_model = Some("gpt-4o".into());
+
+    assert_eq!(config.get_model_for_provider("openai"), "gpt-4o-mini");
+}
+
+#[test]
+fn test_denied_tool_stays_disabled_with_project_override() {
+    let dir = tempfile::tempdir().unwrap();
+    let user_config = dir.path().join("user.toml");
+    let project_config = dir.path().join("project.toml");
+    std::fs::write(&user_config, "[tools]\ndeny = [\"delete_file\"]\n").unwrap();
+    std::fs::write(
+        &project_config,
+        "[tools]\nenabled = true\ndeny = []\n\n[tools.overrides]\ndelete_file = true\n",
+    )
+    .unwrap();
+
+    let mut config = Config::new();
+    merge_config_from_file(&mut config, &user_config).unwrap();
+    merge_project_config_from_file(&mut config, &project_config).unwrap();
+
+    assert!(!config.is_tool_enabled("delete_file"));
+    assert!(config.is_tool_enabled("read_text_file"));
+    let tools = ["delete_file".to_string(), "read_text_file".to_string()];
+    assert_eq!(
+        config.tools.get_enabled_tools(&tools),
+        vec!["read_text_file"]
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn test_cmd_api_key_resolves_from_command() {
+    let mut config = Config::new();
+    config.providers.anthropic.api_key = Some("cmd:printf 'sk-from-command\\n'".into());
+
+    let api_key = config.get_api_key_for_provider("anthropic").unwrap();
+    assert_eq!(api_key.expose_secret(), "sk-from-command");
+
+    config.providers.openai.api_key = Some("cmd:exit 3".into());
+    let error = config.get_api_key_for_provider("openai").unwrap_err();
+    assert!(error.to_string().contains("failed with"));
+}
+
+#[test]
+fn test_project_config_cannot_run_credential_commands() {
+    let dir = tempfile::tempdir().unwrap();
+    let user_config = dir.path().join("user.toml");
+    let project_config = dir.path().join("project.toml");
+    std::fs::write(
+        &user_config,
+        "[providers.anthropic]\napi_key = \"cmd:printf user-key\"\n",
+    )
+    .unwrap();
+    std::fs::write(
+        &project_config,
+        "[providers.anthropic]\napi_key = \"cmd:touch pwned\"\n\n[providers.openai]\napi_key = \"cmd:touch pwned\"\n",
+    )
+    .unwrap();
+
+    let mut config = Config::new();
+    merge_config_from_file(&mut config, &user_config).unwrap();
+    merge_project_config_from_file(&mut config, &project_config).unwrap();
+
+    assert_eq!(
+        config.providers.anthropic.api_key.as_deref(),
+        Some("cmd:printf user-key")
+    );
+    assert_eq!(config.providers.openai.api_key, None);
+}
+
+#[test]
+fn test_provider_extra_headers_are_expanded() {
+    let config: Config = toml::from_str(
+        r#"
+        [providers.openai.extra_headers]
+        "Helicone-Auth" = "Bearer ${NIXCODE_TEST_GATEWAY_KEY}"
+        "Helicone-Cache-Enabled" = "true"
+        "#,
+    )
+    .unwrap();
+
+    let headers = config.extra_headers_with("openai", |name| {
+        (name == "NIXCODE_TEST_GATEWAY_KEY").then(|| "gateway-secret".to_string())
+    });
+
+    assert_eq!(headers["Helicone-Auth"], "Bearer gateway-secret");
+    assert_eq!(headers["Helicone-Cache-Enabled"], "true");
+    let anthropic_headers = config.get_extra_headers_for_provider("anthropic");
+    assert!(anthropic_headers.is_empty());
+}
+
+#[test]
+fn test_model_alias_is_resolved() {
+    let mut config: Config = toml::from_str(
+        r#"
+        [llm]
+        default_provider = "anthropic"
+        default_model = "fast"
+
+        [models.aliases]
+        fast = "claude-3-5-haiku-latest"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.get_model_for_provider("anthropic"),
+        "claude-3-5-haiku-latest"
+    );
+    assert_eq!(config.resolve_model_alias("gpt-4o"), "gpt-4o");
+
+    config.llm.default_model = Some("gpt-4o".into());
+    assert_eq!(config.get_model_for_provider("anthropic"), "gpt-4o");
+}
+
+#[test]
+fn test_visible_providers_exclude_providers_without_keys() {
+    let mut config = Config::new();
+    config.providers.anthropic.api_key = Some("sk-ant-test".into());
+    config.providers.openai.api_key = Some("${NIXCODE_TEST_UNSET_OPENAI_KEY}".into());
+    config.providers.azure.api_key = Some("azure-key".into());
+
+    assert_eq!(config.get_visible_providers(), vec!["anthropic"]);
+    assert!(config.check_provider_available("openai").is_err());
+
+    config.providers.azure.endpoint = Some("https://example.openai.azure.com".into());
+    config.providers.azure.deployment = Some("gpt-4o".into());
+    config.providers.ollama.base_url = Some("http://localhost:11434/v1".into());
+    assert_eq!(
+        config.get_visible_providers(),
+        vec!["anthropic", "azure", "ollama"]
+    );
+
+    config.ui.visible_providers = Some(vec!["azure".into(), "openai".into()]);
+    assert_eq!(config.get_visible_providers(), vec!["azure"]);
+}
+
+#[test]
+fn test_xdg_config_dir() {
+    let home = Path::new("/home/user");
+
+    assert_eq!(
+        get_xdg_config_dir(None, home),
+        PathBuf::from("/home/user/.config/nixcode-ai")
+    );
+    assert_eq!(
+        get_xdg_config_dir(Some("/xdg/config".into()), home),
+        PathBuf::from("/xdg/config/nixcode-ai")
+    );
+    assert_eq!(
+        get_xdg_config_dir(Some("relative".into()), home),
+        PathBuf::from("/home/user/.config/nixcode-ai")
+    );
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+#[test]
+fn test_user_config_path_is_xdg_style() {
+    let path = get_user_config_path().unwrap();
+
+    assert!(path.ends_with("nixcode-ai/config.toml"));
+    assert!(!path.to_string_lossy().contains("Library/Application Support"));
+}
+
+#[cfg(windows)]
+#[test]
+fn test_user_config_path_is_in_appdata() {
+    let path = get_user_config_path().unwrap();
+    let appdata = std::env::var("APPDATA").unwrap();
+
+    assert!(path.starts_with(appdata));
+    assert!(path.ends_with("config.toml"));
+}
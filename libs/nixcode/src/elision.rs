@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use nixcode_llm_sdk::message::content::Content;
+use nixcode_llm_sdk::message::message::Message;
+use nixcode_llm_sdk::message::metadata::MessageMetadata;
+
+/// Replaces the content of large tool results older than `after_turns` assistant
+/// responses with a short placeholder.
+///
+/// Results keep their tool_use_id, so every tool call is still answered. Results in
+/// pinned messages are kept verbatim, `after_turns` of 0 keeps everything.
+pub fn elide_old_tool_results(
+    messages: &[Message],
+    metadata: &[MessageMetadata],
+    after_turns: usize,
+    min_chars: usize,
+) -> Vec<Message> {
+    if after_turns == 0 {
+        return messages.to_vec();
+    }
+
+    let tool_names: HashMap<String, String> = messages
+        .iter()
+        .filter(|message| matches!(message, Message::Assistant(_)))
+        .flat_map(|message| message.get_content())
+        .filter_map(|content| match content {
+            Content::ToolUse(tool_use) => Some((tool_use.get_id(), tool_use.get_tool_name())),
+            _ => None,
+        })
+        .collect();
+
+    let mut responses_after = 0;
+    let mut elided = Vec::with_capacity(messages.len());
+    for (index, message) in messages.iter().enumerate().rev() {
+        let pinned = metadata.get(index).is_some_and(|metadata| metadata.pinned);
+        let message = match message {
+            Message::User(contents) if responses_after >= after_turns && !pinned => {
+                let contents = contents
+                    .iter()
+                    .map(|content| match content {
+                        Content::ToolResult(result) if result.get_content().len() >= min_chars => {
+                            let tool_name = tool_names
+                                .get(&result.get_tool_use_id())
+                                .map(String::as_str)
+                                .unwrap_or("tool");
+                            Content::ToolResult(result.with_content(format!(
+                                "[{} result elided, {} characters; run the tool again if it is still needed]",
+                                tool_name,
+                                result.get_content().len()
+                            )))
+                        }
+                        _ => content.clone(),
+                    })
+                    .collect();
+                Message::User(contents)
+            }
+            Message::Assistant(_) => {
+                responses_after += 1;
+                message.clone()
+            }
+            _ => message.clone(),
+        };
+        elided.push(message);
+    }
+    elided.reverse();
+
+    elided
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nixcode_llm_sdk::message::content::tools::{ToolResultContent, ToolUseContent};
+
+    fn read_file_turn(id: &str, result: &str) -> [Message; 2] {
+        let tool_use = ToolUseContent::new(id, "read_text_file", serde_json::json!({}));
+        let tool_result = tool_use.create_response(result);
+
+        [
+            Message::Assistant(vec![Content::ToolUse(tool_use)]),
+            Message::User(vec![Content::ToolResult(tool_result)]),
+        ]
+    }
+
+    #[test]
+    fn test_old_large_tool_results_are_elided() {
+        let large = "x".repeat(5000);
+        let mut messages = vec![Message::User(vec![Content::new_text("Read the files")])];
+        messages.extend(read_file_turn("toolu_1", &large));
+        messages.extend(read_file_turn("toolu_2", &large));
+        messages.extend(read_file_turn("toolu_3", "small"));
+        messages.extend(read_file_turn("toolu_4", &large));
+        let mut metadata = vec![MessageMetadata::default(); messages.len()];
+        metadata[4].pinned = true;
+
+        let elided = elide_old_tool_results(&messages, &metadata, 1, 2000);
+
+        let results: Vec<ToolResultContent> = elided
+            .iter()
+            .flat_map(|message| message.get_content())
+            .filter_map(|content| match content {
+                Content::ToolResult(result) => Some(result),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(results.len(), 4);
+        // Every tool call is still answered
+        assert_eq!(results[0].get_tool_use_id(), "toolu_1");
+        assert!(results[0]
+            .get_content()
+            .starts_with("[read_text_file result elided, 5000 characters"));
+        // Pinned, small and recent results are sent in full
+        assert_eq!(results[1].get_content(), large);
+        assert_eq!(results[2].get_content(), "small");
+        assert_eq!(results[3].get_content(), large);
+
+        let unchanged = elide_old_tool_results(&messages, &metadata, 0, 2000);
+        assert_eq!(unchanged[2].get_tool_messages()[0].content, large);
+    }
+}
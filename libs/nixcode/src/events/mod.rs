@@ -1,6 +1,8 @@
 use nixcode_llm_sdk::errors::llm::LLMError;
 use nixcode_llm_sdk::message::content::tools::{ToolResultContent, ToolUseContent};
 
+use crate::turn_summary::TurnSummary;
+
 pub enum NixcodeEvent {
     GeneratingResponse,
     GeneratedResponse,
@@ -9,5 +11,11 @@ pub enum NixcodeEvent {
     Error(LLMError),
     ToolStart(ToolUseContent),
     ToolEnd(ToolResultContent),
+    ToolError(ToolResultContent),
+    /// Digest of the tool calls of the response at the message index and their
+    /// results, sent right before ToolsFinished
+    TurnSummary(usize, TurnSummary),
     ToolsFinished,
+    /// Line printed by a command of a running tool (e.g. cargo test)
+    CommandOutput(String),
 }
@@ -0,0 +1,102 @@
+use nixcode_llm_sdk::message::content::Content;
+use nixcode_llm_sdk::message::message::Message;
+use nixcode_llm_sdk::message::usage::Usage;
+
+/// Returns a code fence longer than any backtick run in the content
+fn fence_for(content: &str) -> String {
+    let longest_run = content.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+
+    "`".repeat((longest_run + 1).max(3))
+}
+
+fn code_block(language: &str, content: &str) -> String {
+    let fence = fence_for(content);
+    format!(
+        "{}{}\n{}\n{}\n\n",
+        fence,
+        language,
+        content.trim_end(),
+        fence
+    )
+}
+
+fn render_content(content: &Content) -> String {
+    match content {
+        Content::Text(text) => format!("{}\n\n", text.get_text().trim_end()),
+        Content::Thinking(thinking) => {
+            let quoted = thinking
+                .get_text()
+                .lines()
+                .map(|line| format!("> {}", line))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("> **Thinking**\n>\n{}\n\n", quoted)
+        }
+        Content::ToolUse(tool_use) => {
+            let (name, input) = tool_use.get_execute_params();
+            let input = serde_json::to_string_pretty(&input).unwrap_or_default();
+            format!(
+                "### Tool call: `{}`\n\n{}",
+                name,
+                code_block("json", &input)
+            )
+        }
+        Content::ToolResult(result) => {
+            let title = if result.is_error() {
+                "Tool error"
+            } else {
+                "Tool result"
+            };
+            format!(
+                "### {} (`{}`)\n\n{}",
+                title,
+                result.get_tool_use_id(),
+                code_block("text", &result.get_content())
+            )
+        }
+        Content::Image(image) => format!("*[image: {}]*\n\n", image.get_source().media_type),
+        _ => String::new(),
+    }
+}
+
+/// Renders the conversation as a Markdown document
+pub fn render_markdown(messages: &[Message], usage: &Usage, provider: &str, model: &str) -> String {
+    let mut markdown = format!(
+        "# nixcode session\n\n- Provider: {}\n- Model: {}\n\n",
+        provider, model
+    );
+
+    for message in messages {
+        let content = message.get_content();
+        if content.is_empty() {
+            continue;
+        }
+
+        // Messages with only tool results are sent by nixcode, not typed by the user
+        let only_tool_results = content
+            .iter()
+            .all(|content| matches!(content, Content::ToolResult(_)));
+        let author = match message {
+            Message::User(_) if only_tool_results => "Tool results",
+            Message::User(_) => "User",
+            Message::Assistant(_) => "Assistant",
+            Message::System(_) => "System",
+        };
+
+        markdown.push_str(&format!("## {}\n\n", author));
+        content
+            .iter()
+            .for_each(|content| markdown.push_str(&render_content(content)));
+    }
+
+    markdown.push_str(&format!(
+        "## Usage\n\n- Input tokens: {}\n- Output tokens: {}\n- Cache read tokens: {}\n- Cache write tokens: {}\n- Estimated cost: ${:.4}\n",
+        usage.input_tokens,
+        usage.output_tokens,
+        usage.cache_read_input_tokens.unwrap_or(0),
+        usage.cache_creation_input_tokens.unwrap_or(0),
+        usage.estimated_cost()
+    ));
+
+    markdown
+}
@@ -1,37 +1,81 @@
+mod auto_commit;
 pub mod config;
+mod elision;
 pub mod events;
+mod export;
 pub mod project;
 mod prompts;
+mod sessions;
+mod templates;
+pub mod thinking;
+mod tool_cache;
 mod tools;
+pub mod turn_summary;
 mod utils;
 
+#[cfg(test)]
+mod tests;
+
 use crate::config::Config;
+use crate::elision::elide_old_tool_results;
 use crate::events::NixcodeEvent;
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE, INSTRUCTIONS_FILE, MEMORY_FILE};
+use crate::prompts::continuation::CONTINUE_PROMPT;
+use crate::prompts::empty_response::EMPTY_RESPONSE_PROMPT;
 use crate::prompts::system::SYSTEM_PROMPT;
+use crate::sessions::{Session, Sessions};
+use crate::thinking::ThinkingUsage;
+use crate::tool_cache::ToolResultCache;
+use crate::tools::docs::docs_rs::DocsRsTool;
 use crate::tools::fs::create_file::CreateFileTool;
 use crate::tools::fs::delete_file::DeleteFileTool;
+use crate::tools::fs::diff_files::DiffFilesTool;
+use crate::tools::fs::outline_file::OutlineFileTool;
+use crate::tools::fs::read_files::ReadFilesTool;
+use crate::tools::fs::read_symbol::ReadSymbolTool;
 use crate::tools::fs::read_text_file::ReadTextFileTool;
+use crate::tools::fs::tree::TreeTool;
+use crate::tools::fs::update_text_file_partial::UpdateTextFilePartialTool;
 use crate::tools::fs::write_text_file::WriteTextFileTool;
 use crate::tools::git::git_add::GitAddTool;
+use crate::tools::git::git_amend::GitAmendTool;
 use crate::tools::git::git_branch_create::GitBranchCreateTool;
 use crate::tools::git::git_branch_delete::GitBranchDeleteTool;
 use crate::tools::git::git_branches::GitBranchesTool;
+use crate::tools::git::git_clone::GitCloneTool;
 use crate::tools::git::git_commit::GitCommitTool;
-use crate::tools::git::git_diff::GitDiffTool;
+use crate::tools::git::git_commit_message::GitCommitMessageTool;
+use crate::tools::git::git_diff::{get_working_tree_diff, GitDiffTool};
 use crate::tools::git::git_log::GitLogTool;
+use crate::tools::git::git_pull::GitPullTool;
+use crate::tools::git::git_push::GitPushTool;
+use crate::tools::git::git_resolve_conflict::GitResolveConflictTool;
 use crate::tools::git::git_stash_apply::GitStashApplyTool;
 use crate::tools::git::git_stash_drop::GitStashDropTool;
 use crate::tools::git::git_stash_list::GitStashListTool;
 use crate::tools::git::git_stash_save::GitStashSaveTool;
 use crate::tools::git::git_status::GitStatusTool;
+use crate::tools::git::git_tag_checkout::GitTagCheckoutTool;
 use crate::tools::git::git_tag_create::GitTagCreateTool;
 use crate::tools::git::git_tags_list::GitTagsListTool;
+use crate::tools::git::git_upstream::GitUpstreamTool;
 use crate::tools::glob::search_glob_files::SearchGlobFilesTool;
+use crate::tools::memory::append_memory::AppendMemoryTool;
+use crate::tools::memory::read_memory::ReadMemoryTool;
+use crate::tools::meta::get_full_tool_result::{FullToolResults, GetFullToolResultTool};
+use crate::tools::meta::get_project_context::GetProjectContextTool;
+use crate::tools::meta::list_tools::ListToolsTool;
 use crate::tools::prompt::get_project_analysis_prompt::GetProjectAnalysisPromptTool;
 use crate::tools::search::replace_content::ReplaceContentTool;
+use crate::tools::tasks::cargo_metadata::CargoMetadataTool;
+use crate::tools::tasks::cargo_test::CargoTestTool;
+use crate::tools::tasks::coverage::CoverageTool;
+use crate::tools::tasks::error_context::GetErrorContextTool;
+use crate::tools::tasks::run_task::RunTaskTool;
+use crate::tools::tasks::tasks_file::TasksFile;
 use crate::tools::search::search_content::SearchContentTool;
-use crate::tools::Tools;
+use crate::tools::{estimate_schema_tokens, Tools};
+use crate::turn_summary::TurnSummary;
 use anyhow::Result;
 use nixcode_llm_sdk::config::LLMConfig;
 use nixcode_llm_sdk::errors::llm::LLMError;
@@ -39,15 +83,25 @@ use nixcode_llm_sdk::message::content::tools::{ToolResultContent, ToolUseContent
 use nixcode_llm_sdk::message::content::Content;
 use nixcode_llm_sdk::message::message::Message;
 use nixcode_llm_sdk::message::message::Message::Assistant;
+use nixcode_llm_sdk::message::metadata::MessageMetadata;
 use nixcode_llm_sdk::message::response::MessageResponse;
 use nixcode_llm_sdk::message::usage::Usage;
-use nixcode_llm_sdk::{ErrorContent, LLMClient, MessageResponseStreamEvent, Request};
+use nixcode_llm_sdk::models::ModelCapabilities;
+use nixcode_llm_sdk::stop_reason::StopReason;
+use nixcode_llm_sdk::tools::ToolChoice;
+use nixcode_llm_sdk::{
+    ErrorContent, LLMClient, MessageResponseStreamEvent, Request, ThinkingOptions,
+};
+use chrono::Utc;
+use futures::future::BoxFuture;
 use secrecy::SecretString;
 use std::default::Default;
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
+use tokio::task::JoinHandle;
 
 pub struct Nixcode {
     project: Arc<Project>,
@@ -56,15 +110,41 @@ pub struct Nixcode {
     tools: Tools,
     config: Config,
     messages: RwLock<Vec<Message>>,
+    messages_metadata: RwLock<Vec<MessageMetadata>>,
     usage: RwLock<Usage>,
     tools_to_execute: RwLock<Vec<ToolUseContent>>,
     tools_results: RwLock<Vec<ToolResultContent>>,
+    tool_tasks: RwLock<Vec<JoinHandle<()>>>,
+    /// Task reading the response stream of the current request
+    response_task: RwLock<Option<JoinHandle<()>>>,
+    /// Passing verification run of the current turn as (tool use id, task), the
+    /// changes are auto committed once all tools of the turn finished
+    verified_by: RwLock<Option<(String, String)>>,
+    /// User messages sent while the agent was busy, sent once its loop finishes
+    queued_messages: RwLock<Vec<Message>>,
     last_message_response: RwLock<Option<MessageResponse>>,
     llm_error: RwLock<Option<ErrorContent>>,
     is_waiting: RwLock<bool>,
+    continuing: RwLock<bool>,
+    /// Empty responses retried in a row
+    empty_retries: RwLock<u32>,
+    last_request_summary: RwLock<Option<String>>,
+    tools_semaphore: Semaphore,
+    tool_choice: RwLock<ToolChoice>,
+    thinking_usage: RwLock<ThinkingUsage>,
+    sessions: RwLock<Sessions>,
+    full_tool_results: FullToolResults,
+    /// Results of read-only tools, used when `tools.cache_read_results` is enabled
+    tool_cache: ToolResultCache,
     tx: UnboundedSender<NixcodeEvent>,
 }
 
+/// Output token limit of a single response
+const MAX_TOKENS: u32 = 51200;
+
+/// Maximum number of characters of the diff shown by `get_working_tree_diff`
+const MAX_WORKING_TREE_DIFF_CHARS: usize = 200_000;
+
 pub type NewNixcodeResult = (UnboundedReceiver<NixcodeEvent>, Nixcode);
 
 impl Nixcode {
@@ -73,23 +153,61 @@ impl Nixcode {
         client: LLMClient,
         config: Config,
     ) -> Result<NewNixcodeResult, LLMError> {
+        let mut project = project;
+        if let Some(tokens) = config.tools.read_chunk_tokens {
+            project.set_read_chunk_tokens(tokens);
+        }
+        if let Some(files) = config.tools.max_files_per_call {
+            project.set_max_files_per_call(files);
+        }
+        if let Some(reads) = config.tools.max_concurrent_reads {
+            project.set_max_concurrent_reads(reads);
+        }
+        let (tx, rx) = unbounded_channel::<NixcodeEvent>();
+        let mut command_options = config.tools.command.get_command_options();
+        command_options.output_tx = Some(tx.clone());
+        project.set_command_options(command_options);
+        project.set_format_on_write(config.tools.format_on_write);
+        project.set_commit_author(config.git.get_commit_author());
+        project.set_remote_defaults(config.git.get_remote_defaults());
+
         let has_init_analysis = project.has_init_analysis();
         let model = config.get_model_for_provider(&config.llm.default_provider);
         let has_repo_path = project.has_repo_path();
+        let has_tasks_file = TasksFile::exists(&project.get_cwd());
+        let is_rust_project = project.detect_stack().iter().any(|stack| stack == "Rust");
 
-        let (tx, rx) = unbounded_channel::<NixcodeEvent>();
-        let nixcode = Self {
+        let full_tool_results = FullToolResults::default();
+        let mut nixcode = Self {
             project: Arc::new(project),
             client,
             model,
             config: config.clone(),
             messages: RwLock::new(vec![]),
+            messages_metadata: RwLock::new(vec![]),
             usage: RwLock::new(Usage::default()),
             llm_error: RwLock::new(None),
             last_message_response: RwLock::new(None),
             tools_results: RwLock::new(vec![]),
+            tool_tasks: RwLock::new(vec![]),
+            response_task: RwLock::new(None),
+            verified_by: RwLock::new(None),
+            queued_messages: RwLock::new(vec![]),
             tools_to_execute: RwLock::new(vec![]),
             is_waiting: RwLock::new(false),
+            continuing: RwLock::new(false),
+            empty_retries: RwLock::new(0),
+            last_request_summary: RwLock::new(None),
+            tools_semaphore: Semaphore::new(config.agent.max_concurrent_tools.max(1)),
+            tool_choice: RwLock::new(ToolChoice::Auto),
+            thinking_usage: RwLock::new(ThinkingUsage {
+                max_tokens: MAX_TOKENS,
+                warning_fraction: config.agent.thinking_warning_fraction,
+                ..Default::default()
+            }),
+            sessions: RwLock::new(Sessions::default()),
+            full_tool_results: full_tool_results.clone(),
+            tool_cache: ToolResultCache::default(),
             tx,
             tools: {
                 let mut tools = Tools::new();
@@ -98,18 +216,40 @@ impl Nixcode {
                 tools.add_tool(Arc::new(SearchGlobFilesTool {}));
                 tools.add_tool(Arc::new(CreateFileTool {}));
                 tools.add_tool(Arc::new(ReadTextFileTool {}));
+                tools.add_tool(Arc::new(ReadFilesTool {}));
+                tools.add_tool(Arc::new(OutlineFileTool {}));
+                tools.add_tool(Arc::new(ReadSymbolTool {}));
+                tools.add_tool(Arc::new(DiffFilesTool {}));
+                tools.add_tool(Arc::new(TreeTool {}));
                 tools.add_tool(Arc::new(WriteTextFileTool {}));
-                // tools.add_tool(Arc::new(UpdateTextFilePartialTool {}));
+                tools.add_tool(Arc::new(UpdateTextFilePartialTool {}));
                 tools.add_tool(Arc::new(DeleteFileTool {}));
                 // tools.add_tool(Arc::new(DeleteTextFilePartialTool {}));
                 tools.add_tool(Arc::new(SearchContentTool {}));
                 tools.add_tool(Arc::new(ReplaceContentTool {}));
+                tools.add_tool(Arc::new(GitCloneTool {}));
+
+                // Named tasks are opt-in through .nixcode/tasks.toml
+                if has_tasks_file {
+                    tools.add_tool(Arc::new(RunTaskTool {}));
+                }
+
+                if is_rust_project {
+                    tools.add_tool(Arc::new(CargoTestTool {}));
+                    tools.add_tool(Arc::new(CargoMetadataTool {}));
+                    tools.add_tool(Arc::new(CoverageTool {}));
+                    tools.add_tool(Arc::new(GetErrorContextTool {}));
+                    tools.add_tool(Arc::new(DocsRsTool::new()));
+                }
 
                 if has_repo_path {
                     tools.add_tool(Arc::new(GitAddTool {}));
                     tools.add_tool(Arc::new(GitCommitTool {}));
+                    tools.add_tool(Arc::new(GitAmendTool {}));
+                    tools.add_tool(Arc::new(GitCommitMessageTool {}));
                     tools.add_tool(Arc::new(GitStatusTool {}));
                     tools.add_tool(Arc::new(GitDiffTool {}));
+                    tools.add_tool(Arc::new(GitResolveConflictTool {}));
                     tools.add_tool(Arc::new(GitStashSaveTool {}));
                     tools.add_tool(Arc::new(GitStashApplyTool {}));
                     tools.add_tool(Arc::new(GitStashListTool {}));
@@ -120,16 +260,35 @@ impl Nixcode {
                     tools.add_tool(Arc::new(GitBranchDeleteTool {}));
                     tools.add_tool(Arc::new(GitTagCreateTool {}));
                     tools.add_tool(Arc::new(GitTagsListTool {}));
+                    tools.add_tool(Arc::new(GitTagCheckoutTool {}));
+                    tools.add_tool(Arc::new(GitUpstreamTool {}));
+                    tools.add_tool(Arc::new(GitPushTool {}));
+                    tools.add_tool(Arc::new(GitPullTool {}));
                 }
 
+                tools.add_tool(Arc::new(GetProjectContextTool {}));
+                tools.add_tool(Arc::new(AppendMemoryTool {}));
+                tools.add_tool(Arc::new(ReadMemoryTool {}));
+                tools.add_tool(Arc::new(GetFullToolResultTool::new(full_tool_results)));
+
                 if !has_init_analysis {
                     tools.add_tool(Arc::new(GetProjectAnalysisPromptTool {}));
                 }
 
+                // Registered last so it can list every other enabled tool
+                let list_tools = ListToolsTool::new(&tools, &config);
+                tools.add_tool(Arc::new(list_tools));
+
                 tools
             },
         };
 
+        let tool_choice = config.get_tool_choice();
+        match nixcode.check_tool_choice(&tool_choice) {
+            Ok(()) => nixcode.tool_choice = RwLock::new(tool_choice),
+            Err(e) => log::warn!("Ignoring agent.tool_choice: {}", e),
+        }
+
         Ok((rx, nixcode))
     }
 
@@ -145,7 +304,25 @@ impl Nixcode {
         project: Project,
         config: Config,
     ) -> anyhow::Result<NewNixcodeResult, LLMError> {
+        // Replay recorded responses without hitting the network
+        if let Some(replay_dir) = config.get_replay_dir() {
+            let client = LLMClient::new_replay(&replay_dir)?;
+            return Self::new(project, client, config);
+        }
+
         let provider = &config.llm.default_provider;
+        let record_dir = config.get_record_dir();
+        let extra_headers = config.get_extra_headers_for_provider(provider);
+
+        // Fail early instead of sending the provider's model to a fallback client
+        if config::PROVIDERS.contains(&provider.as_str()) {
+            if let Err(e) = config.check_provider_available(provider) {
+                return Err(LLMError::CreateClientError(format!(
+                    "Cannot use provider {}: {}",
+                    provider, e
+                )));
+            }
+        }
 
         // Try to get API key for the provider
         let api_key_result = config.get_api_key_for_provider(provider);
@@ -153,22 +330,67 @@ impl Nixcode {
         match (provider.as_str(), api_key_result) {
             // Anthropic with available API key
             ("anthropic", Ok(api_key)) => {
-                let llm_config = LLMConfig { api_key };
+                let llm_config = LLMConfig {
+                    api_key,
+                    record_dir,
+                    extra_headers,
+                };
                 let client = LLMClient::new_anthropic(llm_config)?;
                 Self::new(project, client, config)
             }
             // OpenAI with available API key
             ("openai", Ok(api_key)) => {
-                let llm_config = LLMConfig { api_key };
+                let llm_config = LLMConfig {
+                    api_key,
+                    record_dir,
+                    extra_headers,
+                };
                 let client = LLMClient::new_openai(llm_config)?;
                 Self::new(project, client, config)
             }
+            // Azure OpenAI with available API key
+            ("azure", Ok(api_key)) => {
+                let azure = &config.providers.azure;
+                let (Some(endpoint), Some(deployment)) = (&azure.endpoint, &azure.deployment)
+                else {
+                    return Err(LLMError::CreateClientError(
+                        "Azure provider requires providers.azure.endpoint and deployment"
+                            .to_string(),
+                    ));
+                };
+
+                let llm_config = LLMConfig {
+                    api_key,
+                    record_dir,
+                    extra_headers,
+                };
+                let client = LLMClient::new_azure(
+                    llm_config,
+                    endpoint,
+                    deployment,
+                    &azure.get_api_version(),
+                )?;
+                Self::new(project, client, config)
+            }
+            // Local Ollama server, API key is optional
+            ("ollama", Ok(api_key)) => {
+                let llm_config = LLMConfig {
+                    api_key,
+                    record_dir,
+                    extra_headers,
+                };
+                let base_url = config.providers.ollama.get_base_url();
+                let client = LLMClient::new_ollama(llm_config, &base_url)?;
+                Self::new(project, client, config)
+            }
             // Fallback to environment variables for Anthropic
             (_, _) => {
                 let api_key = env::var("ANTHROPIC_API_KEY").map_err(|_| LLMError::MissingAPIKey)?;
 
                 let llm_config = LLMConfig {
                     api_key: SecretString::new(api_key.into()),
+                    record_dir,
+                    extra_headers,
                 };
 
                 let client = LLMClient::new_anthropic(llm_config)?;
@@ -197,7 +419,7 @@ impl Nixcode {
     }
 
     pub fn with_model(mut self, model: impl Into<String>) -> Self {
-        self.model = model.into();
+        self.model = self.config.resolve_model_alias(&model.into());
         self
     }
 
@@ -205,7 +427,9 @@ impl Nixcode {
         *self.is_waiting.read().await
     }
 
-    pub async fn send(self: Arc<Self>, messages: Vec<Message>) {
+    /// System prompt blocks: the global prompt, the project analysis, the project instructions
+    /// and the notes the agent recorded in its memory
+    fn get_system_prompt(&self) -> Vec<Content> {
         let mut system_prompt = vec![Content::new_text(SYSTEM_PROMPT)];
         let project_init_analysis_content = self.project.get_project_init_analysis_content();
         if let Some(content) = project_init_analysis_content {
@@ -213,20 +437,99 @@ impl Nixcode {
             system_prompt.push(Content::new_text(content));
         }
 
+        if let Some(instructions) = self.project.get_instructions() {
+            let content = format!("File: {}\n\n{}", INSTRUCTIONS_FILE, instructions);
+            system_prompt.push(Content::new_text(content));
+        }
+
+        if let Some(memory) = self.project.get_memory() {
+            let content = format!("File: {}\n\n{}", MEMORY_FILE, memory);
+            system_prompt.push(Content::new_text(content));
+        }
+
+        system_prompt
+    }
+
+    /// The configured prefill for a request answering a user message. A request sending
+    /// tool results continues the model's own turn and is not prefilled.
+    fn get_user_prefill(&self, messages: &[Message]) -> Option<String> {
+        let Some(Message::User(content)) = messages.last() else {
+            return None;
+        };
+        if content
+            .iter()
+            .any(|content| matches!(content, Content::ToolResult(_)))
+        {
+            return None;
+        }
+
+        self.config.agent.prefill.clone()
+    }
+
+    /// Sends the request, the response starting with `prefill` if given
+    pub async fn send(self: Arc<Self>, messages: Vec<Message>, prefill: Option<String>) {
+        self.project.set_cancelled(false);
+        let messages_count = messages.len();
+        let system_prompt = self.get_system_prompt();
+
+        let system_prompt_count = system_prompt.len();
         let mut request = Request::default()
             .with_model(self.model.clone())
-            .with_max_tokens(51200)
+            .with_max_tokens(MAX_TOKENS)
             .with_messages(messages)
             .with_system_prompt(system_prompt)
-            // .with_thinking(ThinkingOptions::new(8192))
             .with_cache();
 
+        if let Some(budget) = self.config.agent.thinking_budget {
+            request = request.with_thinking(ThinkingOptions::new(budget.min(MAX_TOKENS - 1)));
+        }
+
+        // A continuation already ends with the assistant message, and Anthropic does
+        // not accept a prefill with extended thinking
+        let prefill = match prefill {
+            Some(prefill) if self.config.agent.thinking_budget.is_none() => {
+                let continuing = *self.continuing.read().await;
+                (!continuing).then_some(prefill)
+            }
+            _ => None,
+        };
+        if let Some(prefill) = &prefill {
+            request = request.with_prefill(prefill);
+        }
+
+        let provider = &self.config.llm.default_provider;
+        if let Some(effort) = self.config.get_reasoning_effort_for_provider(provider) {
+            request = request.with_reasoning_effort(effort);
+        }
+
         // Use enabled_tools instead of all tools
-        let enabled_tools = self.tools.get_enabled_tools(&self.config);
+        let enabled_tools = self.tools.get_request_tools(&self.config);
+        let tools_count = enabled_tools.len();
+        let tool_tokens = estimate_schema_tokens(&enabled_tools);
+        let tool_choice = self.tool_choice.read().await.clone();
         if !enabled_tools.is_empty() {
-            request = request.with_tools(enabled_tools);
+            request = request
+                .with_tools(enabled_tools)
+                .with_tool_choice(tool_choice.clone());
         }
+
+        *self.last_request_summary.write().await = Some(format!(
+            "{} model={} messages={} tools={} tool_tokens~{} system_blocks={}",
+            provider,
+            self.model,
+            messages_count,
+            tools_count,
+            tool_tokens,
+            system_prompt_count,
+        ));
         let nixcode_event_sender = self.tx.clone();
+        // Parameters of the request, recorded with the response
+        let metadata = MessageMetadata {
+            model: Some(self.model.clone()),
+            temperature: request.get_temperature(),
+            tool_choice: request.get_tool_choice().cloned(),
+            ..Default::default()
+        };
 
         *self.is_waiting.write().await = true;
         *self.llm_error.write().await = None;
@@ -234,26 +537,74 @@ impl Nixcode {
         nixcode_event_sender
             .send(NixcodeEvent::GeneratingResponse)
             .ok();
+        log::debug!("Sending request with model {}", self.model);
         let response = self.client.send(request).await;
 
         if let Err(err) = response {
+            log::debug!("LLM request failed: {:?}", err);
             *self.is_waiting.write().await = false;
+            *self.continuing.write().await = false;
             *self.llm_error.write().await = Some(err.clone().into());
             nixcode_event_sender.send(NixcodeEvent::Error(err)).ok();
             return;
         }
 
+        // The request was cancelled while it was sent, the response is discarded
+        if self.project.is_cancelled() {
+            *self.is_waiting.write().await = false;
+            *self.continuing.write().await = false;
+            nixcode_event_sender
+                .send(NixcodeEvent::GeneratedResponse)
+                .ok();
+            return;
+        }
+
         let mut stream = response.unwrap();
 
+        // A forced tool choice was used up by this response
+        if tool_choice.is_forced() {
+            *self.tool_choice.write().await = ToolChoice::Auto;
+        }
+
         *self.last_message_response.write().await = Some(MessageResponse::default());
-        self.add_message(Assistant(vec![])).await;
+        self.add_message_with_metadata(Assistant(vec![]), metadata)
+            .await;
 
-        tokio::spawn({
+        // Stored while it is spawned, so `cancel_tools` always sees the current task
+        let nixcode = self.clone();
+        let mut response_task = nixcode.response_task.write().await;
+        *response_task = Some(tokio::spawn({
             let x = self.clone();
 
             async move {
-                while let Some(event) = stream.recv().await {
-                    x.handle_response_event(event.clone()).await;
+                let mut failed = false;
+                let idle_timeout = x.config.agent.stream_idle_timeout_secs;
+                while let Some(event) = Self::receive_event(&mut stream, idle_timeout).await {
+                    failed = matches!(event, MessageResponseStreamEvent::Error { .. });
+                    x.handle_response_event(event).await;
+                    if failed {
+                        break;
+                    }
+                }
+
+                if !failed {
+                    x.update_thinking_usage().await;
+                }
+
+                if failed {
+                    // The response is broken, do not run its tools or merge it as a continuation
+                    log::debug!("Stream failed, ending the turn");
+                    x.tools_to_execute.write().await.clear();
+                    x.remove_tool_uses().await;
+                    *x.continuing.write().await = false;
+                } else if std::mem::take(&mut *x.continuing.write().await) {
+                    x.merge_continuation().await;
+                } else if let Some(prefill) = prefill {
+                    x.prepend_prefill(&prefill).await;
+                }
+
+                if !failed && x.retry_empty_response().await {
+                    return;
                 }
 
                 *self.is_waiting.write().await = false;
@@ -261,9 +612,101 @@ impl Nixcode {
                     .send(NixcodeEvent::GeneratedResponse)
                     .ok();
 
-                x.execute_tools().await;
+                if !failed {
+                    x.execute_tools().await;
+                }
             }
-        });
+        }));
+    }
+
+    /// Boxed `send`, so the response task can send a request again
+    fn send_boxed(
+        self: Arc<Self>,
+        messages: Vec<Message>,
+        prefill: Option<String>,
+    ) -> BoxFuture<'static, ()> {
+        Box::pin(self.send(messages, prefill))
+    }
+
+    /// Drops an empty response (no text and no tool calls) and asks again with a nudge,
+    /// at most `agent.retry_empty` times in a row. Returns true when a retry was sent.
+    async fn retry_empty_response(self: &Arc<Self>) -> bool {
+        let is_empty = match self.messages.read().await.last() {
+            Some(Assistant(content)) => content.iter().all(|content| match content {
+                Content::Text(text) => text.get_text().trim().is_empty(),
+                Content::ToolUse(_) => false,
+                _ => true,
+            }),
+            _ => false,
+        };
+
+        let mut empty_retries = self.empty_retries.write().await;
+        if !is_empty || *empty_retries >= self.config.agent.retry_empty {
+            *empty_retries = 0;
+            return false;
+        }
+        *empty_retries += 1;
+        log::debug!("Empty response, retrying ({})", *empty_retries);
+        drop(empty_retries);
+
+        let mut messages = self.messages.write().await;
+        messages.pop();
+        self.messages_metadata.write().await.truncate(messages.len());
+        drop(messages);
+        self.tx.send(NixcodeEvent::MessageUpdated).ok();
+
+        // The nudge is only sent with this request, the history stays as it was
+        let mut messages = self.get_request_messages().await;
+        let prefill = self.get_user_prefill(&messages);
+        if let Some(Message::User(content)) = messages.last_mut() {
+            content.push(Content::new_text(EMPTY_RESPONSE_PROMPT));
+        }
+        self.clone().send_boxed(messages, prefill).await;
+
+        true
+    }
+
+    /// Sends the message, or queues it while a response is generated or its tools run.
+    /// Returns true when the message was queued.
+    pub async fn send_or_queue_message(self: Arc<Self>, message: Message) -> bool {
+        // The user may have changed files since the last search
+        self.tool_cache.invalidate();
+
+        // The queue stays locked until the message is queued, so the end of the loop
+        // either sees it or has already finished
+        let mut queued_messages = self.queued_messages.write().await;
+        if self.is_waiting().await || !self.get_tools_to_execute().await.is_empty() {
+            queued_messages.push(message);
+            self.tx.send(NixcodeEvent::MessageUpdated).ok();
+            return true;
+        }
+        drop(queued_messages);
+
+        self.send_message(Some(message)).await;
+        false
+    }
+
+    /// Messages waiting for the current agent loop to finish
+    pub async fn get_queued_messages(&self) -> Vec<Message> {
+        self.queued_messages.read().await.clone()
+    }
+
+    /// Sends the queued messages as one user message, the next turn of the conversation.
+    /// Called once a response without tools to run was generated, returns false when
+    /// nothing was queued.
+    pub async fn send_queued_messages(self: Arc<Self>) -> bool {
+        let queued_messages = std::mem::take(&mut *self.queued_messages.write().await);
+        if queued_messages.is_empty() {
+            return false;
+        }
+
+        let content = queued_messages
+            .iter()
+            .flat_map(Message::get_content)
+            .collect::<Vec<_>>();
+        self.send_message(Some(Message::User(content))).await;
+
+        true
     }
 
     pub async fn get_tools_to_execute(self: &Arc<Self>) -> Vec<ToolUseContent> {
@@ -275,7 +718,21 @@ impl Nixcode {
     }
 
     async fn add_message(&self, message: Message) {
-        self.messages.write().await.push(message);
+        self.add_message_with_metadata(message, MessageMetadata::default())
+            .await;
+    }
+
+    async fn add_message_with_metadata(&self, message: Message, mut metadata: MessageMetadata) {
+        metadata.created_at.get_or_insert_with(Utc::now);
+        let mut messages = self.messages.write().await;
+        let mut messages_metadata = self.messages_metadata.write().await;
+        // Drop metadata left behind by removed messages
+        messages_metadata.resize(messages.len(), MessageMetadata::default());
+        messages_metadata.push(metadata);
+        messages.push(message);
+        drop(messages_metadata);
+        drop(messages);
+
         self.tx.send(NixcodeEvent::NewMessage).ok();
     }
 
@@ -284,33 +741,306 @@ impl Nixcode {
             self.add_message(message).await;
         }
 
+        let messages = self.get_request_messages().await;
+        let prefill = self.get_user_prefill(&messages);
+
+        self.send(messages, prefill).await
+    }
+
+    /// History as it is sent to the provider, with old tool results elided
+    async fn get_request_messages(&self) -> Vec<Message> {
         let messages = self.messages.read().await.clone();
+        let metadata = self.get_messages_metadata().await;
+        let agent_config = &self.config.agent;
+
+        elide_old_tool_results(
+            &messages,
+            &metadata,
+            agent_config.elide_tool_results_after_turns,
+            agent_config.elide_tool_results_min_chars,
+        )
+    }
+
+    /// Returns true if the last assistant response was cut off by the token limit
+    pub async fn can_continue(&self) -> bool {
+        if self.is_waiting().await {
+            return false;
+        }
+
+        let last_is_assistant = matches!(self.messages.read().await.last(), Some(Assistant(_)));
+        let truncated = self
+            .get_messages_metadata()
+            .await
+            .last()
+            .is_some_and(|metadata| metadata.stop_reason == Some(StopReason::MaxTokens));
+
+        last_is_assistant && truncated
+    }
+
+    /// Asks the model to resume a response truncated by max tokens.
+    /// The continuation is appended to the truncated assistant message.
+    pub async fn continue_last_message(self: Arc<Self>) -> Result<()> {
+        if !self.can_continue().await {
+            return Err(anyhow::anyhow!(
+                "Nothing to continue, the last response was not truncated by max tokens"
+            ));
+        }
+
+        *self.continuing.write().await = true;
+
+        // A prefilled assistant message must not end with whitespace
+        if let Some(Assistant(content)) = self.messages.write().await.last_mut() {
+            if let Some(Content::Text(text)) = content.iter_mut().rev().find(|c| c.is_text()) {
+                text.text.truncate(text.text.trim_end().len());
+            }
+        }
+
+        // Anthropic continues a trailing assistant message, others need to be asked
+        if !self.client.supports_assistant_prefill() {
+            let prompt = Content::new_text(CONTINUE_PROMPT);
+            self.add_message(Message::User(vec![prompt])).await;
+        }
+
+        self.send_message(None).await;
+
+        Ok(())
+    }
+
+    /// Folds the continuation response into the truncated assistant message
+    async fn merge_continuation(&self) {
+        let mut messages = self.messages.write().await;
+        let mut metadata = self.messages_metadata.write().await;
+        metadata.resize(messages.len(), MessageMetadata::default());
+
+        let continuation = match messages.pop() {
+            Some(Assistant(content)) => content,
+            Some(message) => {
+                messages.push(message);
+                return;
+            }
+            None => return,
+        };
+        let continuation_metadata = metadata.pop().unwrap_or_default();
+
+        // Drop the continue prompt added for providers without prefill support
+        if matches!(messages.last(), Some(Message::User(_))) {
+            messages.pop();
+            metadata.pop();
+        }
+
+        let Some(Assistant(content)) = messages.last_mut() else {
+            return;
+        };
+
+        let mut blocks = continuation.into_iter();
+        if let Some(first) = blocks.next() {
+            let merged = match (content.last_mut(), &first) {
+                (Some(Content::Text(previous)), Content::Text(next)) => {
+                    previous.text.push_str(&next.text);
+                    true
+                }
+                _ => false,
+            };
+
+            if !merged {
+                content.push(first);
+            }
+        }
+        content.extend(blocks);
+
+        if let Some(last) = metadata.last_mut() {
+            last.stop_reason = continuation_metadata.stop_reason;
+            last.completed_at = continuation_metadata.completed_at;
+        }
+    }
+
+    /// Next event of the response stream, or an error event when no event arrives
+    /// within `idle_timeout_secs` (0 waits forever) so a stalled stream ends the turn
+    async fn receive_event(
+        stream: &mut UnboundedReceiver<MessageResponseStreamEvent>,
+        idle_timeout_secs: u64,
+    ) -> Option<MessageResponseStreamEvent> {
+        if idle_timeout_secs == 0 {
+            return stream.recv().await;
+        }
+
+        let idle_timeout = Duration::from_secs(idle_timeout_secs);
+        match tokio::time::timeout(idle_timeout, stream.recv()).await {
+            Ok(event) => event,
+            Err(_) => {
+                log::debug!("No stream event for {} seconds", idle_timeout_secs);
+                let message = format!(
+                    "The provider sent nothing for {} seconds, the response was stopped",
+                    idle_timeout_secs
+                );
+                Some(MessageResponseStreamEvent::Error {
+                    error: ErrorContent::new("stream_idle_timeout", message),
+                })
+            }
+        }
+    }
+
+    /// The response continues the prefill without repeating it, the prefill is
+    /// added to the stored message so the history reads as one response
+    async fn prepend_prefill(&self, prefill: &str) {
+        let prefill = prefill.trim_end();
+        let mut messages = self.messages.write().await;
+        let Some(Assistant(content)) = messages.last_mut() else {
+            return;
+        };
+
+        match content.first_mut() {
+            Some(Content::Text(text)) => text.text.insert_str(0, prefill),
+            _ => content.insert(0, Content::new_text(prefill)),
+        }
+        drop(messages);
 
-        self.send(messages).await
+        self.tx.send(NixcodeEvent::MessageUpdated).ok();
     }
 
     pub async fn execute_tool(self: Arc<Self>, tool: ToolUseContent) {
         let (name, props) = tool.get_execute_params();
 
         if !self.config.is_tool_enabled(name.as_str()) {
+            log::debug!("Tool {} is disabled, not executing it", name);
+            let message = format!("Tool {} is disabled by configuration", name);
+            let response = tool.create_error_response(message);
+            self.clone()
+                .tool_finished(response, ToolUseState::Error)
+                .await;
             return;
         }
 
+        if let Some(error) = tool.get_input_error() {
+            log::debug!("Tool {} has invalid arguments: {}", name, error);
+            let response = tool.create_error_response(format!(
+                "Tool was not executed, the arguments are {}. Call the tool again with complete JSON arguments.",
+                error
+            ));
+            self.clone().tool_finished(response, ToolUseState::Error).await;
+            return;
+        }
+
+        // Tools wait here for a free slot, all results are still gathered before the next turn
+        let _permit = self.tools_semaphore.acquire().await.ok();
+
+        log::debug!("Executing tool {} with params {}", name, props);
         self.clone().start_tool(tool.clone()).await;
 
+        let result = match self.project.is_cancelled() {
+            true => Err(anyhow::anyhow!(CANCELLED_MESSAGE)),
+            false => self.run_tool(&name, &props).await,
+        };
+
+        // The tool may have been stopped part way, the model is told it was cancelled
+        if self.project.is_cancelled() {
+            let response = tool.create_error_response(CANCELLED_MESSAGE);
+            self.clone()
+                .tool_finished(response, ToolUseState::Error)
+                .await;
+            return;
+        }
+
+        let (result, state) = if let Ok(value) = result {
+            let summary = self.tools.summarize_result(&name, &value);
+            let value = tools::result_to_text(value);
+            let summary = summary.filter(|_| value.len() >= tools::MIN_SUMMARIZED_CHARS);
+            self.record_verification(&tool, &props, &value).await;
+
+            let content = match summary {
+                Some(summary) => {
+                    let id = tool.get_id();
+                    let content = format!(
+                        "{}\n(full output available with get_full_tool_result, tool_use_id {})",
+                        summary, id
+                    );
+                    self.full_tool_results.lock().unwrap().insert(id, value);
+                    content
+                }
+                None => value,
+            };
+            (tool.create_response(content), ToolUseState::Executed)
+        } else {
+            log::debug!("Tool {} failed: {:?}", name, result.err());
+            let response = tool.create_error_response("Error executing tool");
+            (response, ToolUseState::Error)
+        };
+
+        self.clone().tool_finished(result, state).await;
+    }
+
+    /// Runs a tool, read-only tools are answered from the result cache when it is
+    /// enabled and their inputs are unchanged
+    async fn run_tool(
+        &self,
+        name: &str,
+        props: &serde_json::Value,
+    ) -> anyhow::Result<serde_json::Value> {
+        let cwd = self.project.get_cwd();
+        let cached = self.config.tools.cache_read_results && ToolResultCache::is_cached_tool(name);
+        if !cached {
+            let result = self
+                .tools
+                .execute_tool(name, props.clone(), self.project.clone())
+                .await;
+            // Any other tool may have changed the project
+            self.tool_cache.invalidate();
+            return result;
+        }
+
+        if let Some(result) = self.tool_cache.get(name, props, &cwd) {
+            log::debug!("Tool {} answered from the result cache", name);
+            return Ok(result);
+        }
+
+        let stamp = self.tool_cache.stamp(props, &cwd);
         let result = self
             .tools
-            .execute_tool(name.as_str(), props, self.project.clone())
-            .await;
+            .execute_tool(name, props.clone(), self.project.clone())
+            .await?;
+        self.tool_cache.insert(name, props, stamp, result.clone());
 
-        let result = if let Ok(value) = result {
-            let value = serde_json::from_value(value).unwrap_or_else(|e| e.to_string());
-            tool.create_response(value)
-        } else {
-            tool.create_response("Error executing tool".to_string())
+        Ok(result)
+    }
+
+    /// Remembers a passing verification run when auto commit is enabled, the changes
+    /// are committed after the other tools of the turn finished too
+    async fn record_verification(
+        &self,
+        tool: &ToolUseContent,
+        props: &serde_json::Value,
+        result: &str,
+    ) {
+        let (name, _) = tool.get_execute_params();
+        let has_repo = self.project.get_repo_path().is_some();
+        let enabled = self.config.agent.auto_commit;
+        if !auto_commit::should_auto_commit(enabled, has_repo, &name, result) {
+            return;
+        }
+
+        let task = props["task"].as_str().unwrap_or(&name).to_string();
+        *self.verified_by.write().await = Some((tool.get_id(), task));
+    }
+
+    /// Commits all changes when a verification passed in the turn, returns the id of
+    /// the verification tool use and a note for its result
+    async fn auto_commit_turn(&self) -> Option<(String, String)> {
+        let (tool_use_id, task) = self.verified_by.write().await.take()?;
+        let repo_path = self.project.get_repo_path()?;
+        let author = self.project.get_commit_author();
+        let committed = tokio::task::spawn_blocking(move || {
+            auto_commit::commit_all(&repo_path, &task, &author)
+        })
+        .await;
+
+        let note = match committed {
+            Ok(Ok(Some(id))) => format!("Auto commit: changes committed as {}", id),
+            Ok(Ok(None)) => return None,
+            Ok(Err(e)) => format!("Auto commit failed: {}", e),
+            Err(e) => format!("Auto commit failed: {}", e),
         };
 
-        self.clone().tool_finished(result).await;
+        Some((tool_use_id, note))
     }
 
     pub fn has_init_analysis(&self) -> bool {
@@ -325,6 +1055,34 @@ impl Nixcode {
         &self.model
     }
 
+    /// Whether the current model accepts images in user messages
+    pub fn supports_vision(&self) -> bool {
+        ModelCapabilities::for_model(&self.model).supports_vision
+    }
+
+    /// Reads an image to attach to the next user message, relative paths are resolved
+    /// against the project directory
+    pub fn load_image(&self, path: &str) -> Result<Content, String> {
+        if !self.supports_vision() {
+            return Err(format!("Model {} does not accept images", self.model));
+        }
+
+        let path = self.project.get_cwd().join(path);
+
+        utils::image::read_image(&path)
+    }
+
+    /// Reads the prompt template `name` from `.nixcode/templates` and expands its
+    /// `${git_diff}` and `${file:path}` variables on a blocking thread
+    pub async fn render_template(&self, name: &str) -> Result<String, String> {
+        let project = self.project.clone();
+        let name = name.to_string();
+        let rendered =
+            tokio::task::spawn_blocking(move || templates::render_template(&name, &project)).await;
+
+        rendered.unwrap_or_else(|e| Err(format!("Cannot render the template: {}", e)))
+    }
+
     pub fn get_project(&self) -> Arc<Project> {
         self.project.clone()
     }
@@ -333,6 +1091,36 @@ impl Nixcode {
         self.messages.read().await.clone()
     }
 
+    /// Returns metadata for each message, aligned with `get_messages`
+    pub async fn get_messages_metadata(&self) -> Vec<MessageMetadata> {
+        let messages_count = self.messages.read().await.len();
+        let mut metadata = self.messages_metadata.read().await.clone();
+        metadata.resize(messages_count, MessageMetadata::default());
+
+        metadata
+    }
+
+    /// Pins or unpins the message at the given (zero-based) index
+    pub async fn set_message_pinned(&self, index: usize, pinned: bool) -> Result<()> {
+        let messages_count = self.messages.read().await.len();
+        if index >= messages_count {
+            return Err(anyhow::anyhow!(
+                "Message #{} does not exist ({} messages)",
+                index + 1,
+                messages_count
+            ));
+        }
+
+        let mut metadata = self.messages_metadata.write().await;
+        metadata.resize(messages_count, MessageMetadata::default());
+        metadata[index].pinned = pinned;
+        drop(metadata);
+
+        self.tx.send(NixcodeEvent::MessageUpdated).ok();
+
+        Ok(())
+    }
+
     pub async fn get_error(&self) -> Option<ErrorContent> {
         self.llm_error.read().await.clone()
     }
@@ -341,7 +1129,111 @@ impl Nixcode {
         self.usage.read().await.clone()
     }
 
+    /// Diff of all working tree changes against HEAD, without involving the model. The
+    /// diff is computed on a blocking thread, large repositories take a while
+    pub async fn get_working_tree_diff(&self) -> Result<String, String> {
+        let repo_path = self.project.get_repo_path();
+        let diff = tokio::task::spawn_blocking(move || {
+            let repo = repo_path
+                .and_then(|path| git2::Repository::open(path).ok())
+                .ok_or_else(|| "Not a git repository".to_string())?;
+
+            get_working_tree_diff(&repo, MAX_WORKING_TREE_DIFF_CHARS)
+        })
+        .await;
+
+        diff.unwrap_or_else(|e| Err(format!("Cannot get the diff: {}", e)))
+    }
+
+    pub async fn get_thinking_usage(&self) -> ThinkingUsage {
+        self.thinking_usage.read().await.clone()
+    }
+
+    pub fn is_thinking_enabled(&self) -> bool {
+        self.config.agent.thinking_budget.is_some()
+    }
+
+    /// Adds the estimated thinking tokens of the last response to the thinking usage
+    async fn update_thinking_usage(&self) {
+        let tokens = match self.messages.read().await.last() {
+            Some(Assistant(content)) => thinking::estimate_thinking_tokens(content),
+            _ => 0,
+        };
+
+        let mut thinking_usage = self.thinking_usage.write().await;
+        thinking_usage.last_response_tokens = tokens;
+        thinking_usage.total_tokens += tokens;
+        if thinking_usage.exceeds_warning_threshold() {
+            log::warn!(
+                "Thinking used {}% of max tokens",
+                thinking_usage.last_response_percent()
+            );
+        }
+    }
+
+    /// Renders the conversation, including tool calls and usage, as Markdown
+    pub async fn export_markdown(&self) -> String {
+        let messages = self.get_messages().await;
+        let usage = self.get_usage().await;
+
+        export::render_markdown(
+            &messages,
+            &usage,
+            &self.config.llm.default_provider,
+            &self.model,
+        )
+    }
+
+    pub async fn get_tool_choice(&self) -> ToolChoice {
+        self.tool_choice.read().await.clone()
+    }
+
+    /// Sets the tool choice of the following requests. A forced choice (`required` or a
+    /// tool name) only applies to the next request and falls back to auto once the model
+    /// responded, otherwise the tool result follow-ups would call tools forever
+    pub async fn set_tool_choice(&self, tool_choice: ToolChoice) -> Result<(), String> {
+        self.check_tool_choice(&tool_choice)?;
+        *self.tool_choice.write().await = tool_choice;
+
+        Ok(())
+    }
+
+    /// Anthropic rejects forced tool use with extended thinking, and a forced tool must
+    /// be sent with the request
+    fn check_tool_choice(&self, tool_choice: &ToolChoice) -> Result<(), String> {
+        if tool_choice.is_forced() && self.config.agent.thinking_budget.is_some() {
+            return Err(format!(
+                "Tool choice {} cannot be used with extended thinking (agent.thinking_budget)",
+                tool_choice
+            ));
+        }
+        if let ToolChoice::Tool(name) = &tool_choice {
+            let enabled = self
+                .tools
+                .get_enabled_tools(&self.config)
+                .iter()
+                .any(|tool| tool.get_name() == name);
+            if !enabled {
+                return Err(format!("Tool {} does not exist or is disabled", name));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Estimated tokens of the tool definitions sent with every request
+    pub fn get_tool_schema_tokens(&self) -> usize {
+        estimate_schema_tokens(&self.tools.get_request_tools(&self.config))
+    }
+
+    /// Short description of the last request sent to the provider
+    pub async fn get_last_request_summary(&self) -> Option<String> {
+        self.last_request_summary.read().await.clone()
+    }
+
     pub async fn send_tools_results(self: Arc<Self>) {
+        // Busy until the next response starts, messages sent meanwhile are queued
+        *self.is_waiting.write().await = true;
         let contents = self.tools_results.read().await.clone();
         self.tools_results.write().await.clear();
         self.tools_to_execute.write().await.clear();
@@ -372,6 +1264,10 @@ impl Nixcode {
             MessageResponseStreamEvent::MessageDelta(delta) => {
                 usage.output_tokens += delta.get_usage().output_tokens;
                 *last_response += delta;
+                if let Some(metadata) = self.messages_metadata.write().await.last_mut() {
+                    metadata.stop_reason = last_response.stop_reason.clone();
+                    metadata.completed_at = Some(Utc::now());
+                }
                 message_updated = true;
             }
             MessageResponseStreamEvent::ContentBlockStart(content) => {
@@ -413,14 +1309,69 @@ impl Nixcode {
             return;
         }
 
+        let mut tool_tasks = self.tool_tasks.write().await;
+        tool_tasks.retain(|task| !task.is_finished());
         for tool in tools {
-            tokio::spawn({
+            tool_tasks.push(tokio::spawn({
                 let nixcode = self.clone();
                 async move {
                     nixcode.execute_tool(tool).await;
                 }
-            });
+            }));
+        }
+    }
+
+    /// Stops a response still streaming and aborts the tools of the current turn (commands
+    /// they run are killed), returns how many tools were still running. No results are recorded for them and the tool calls
+    /// are removed from the response, so the conversation can continue with a new message.
+    pub async fn cancel_tools(&self) -> usize {
+        self.project.set_cancelled(true);
+
+        // A response still streaming is stopped, its remaining tool calls never run
+        let mut streaming = false;
+        let response_task = self.response_task.write().await.take();
+        if let Some(task) = response_task {
+            streaming = !task.is_finished();
+            task.abort();
+            task.await.ok();
+        }
+
+        let tool_tasks = std::mem::take(&mut *self.tool_tasks.write().await);
+        let running = tool_tasks.iter().filter(|task| !task.is_finished()).count();
+        for task in tool_tasks {
+            task.abort();
+            task.await.ok();
+        }
+
+        self.tools_to_execute.write().await.clear();
+        self.tools_results.write().await.clear();
+        *self.verified_by.write().await = None;
+        self.remove_tool_uses().await;
+
+        if streaming {
+            *self.is_waiting.write().await = false;
+            *self.continuing.write().await = false;
+            self.tx.send(NixcodeEvent::GeneratedResponse).ok();
+        }
+
+        running
+    }
+
+    /// Removes the tool calls of the last response, which get no results, and the
+    /// response itself when nothing else is left in it
+    async fn remove_tool_uses(&self) {
+        let mut messages = self.messages.write().await;
+        if let Some(Assistant(content)) = messages.last_mut() {
+            content.retain(|content| !matches!(content, Content::ToolUse(_)));
+            if content.is_empty() {
+                messages.pop();
+                let mut metadata = self.messages_metadata.write().await;
+                metadata.truncate(messages.len());
+            }
         }
+        drop(messages);
+
+        self.tx.send(NixcodeEvent::MessageUpdated).ok();
     }
 
     pub async fn remove_last_message(self: &Arc<Self>) {
@@ -447,13 +1398,86 @@ impl Nixcode {
         *self.last_message_response.write().await = None;
         self.tools_results.write().await.clear();
         self.tools_to_execute.write().await.clear();
+        self.full_tool_results.lock().unwrap().clear();
+        self.queued_messages.write().await.clear();
         self.messages.write().await.clear();
+        self.messages_metadata.write().await.clear();
         *self.usage.write().await = Usage::default();
+        {
+            let mut thinking_usage = self.thinking_usage.write().await;
+            thinking_usage.total_tokens = 0;
+            thinking_usage.last_response_tokens = 0;
+        }
         *self.llm_error.write().await = None;
 
         Ok(())
     }
 
+    /// Name of the active session
+    pub async fn get_active_session(&self) -> String {
+        self.sessions.read().await.get_active().to_string()
+    }
+
+    /// Names of all sessions of this run, including the active one
+    pub async fn get_session_names(&self) -> Vec<String> {
+        self.sessions.read().await.get_names()
+    }
+
+    /// Saves the current conversation and starts an empty session, named `name`
+    /// or `session-N`, returns the name of the new session
+    pub async fn new_session(self: &Arc<Self>, name: Option<&str>) -> Result<String> {
+        let mut sessions = self.sessions.write().await;
+        let name = match name {
+            Some(name) if sessions.exists(name) => {
+                return Err(anyhow::anyhow!("Session {} already exists", name));
+            }
+            Some(name) => name.to_string(),
+            None => sessions.get_next_name(),
+        };
+
+        self.swap_session(&mut sessions, &name).await?;
+
+        Ok(name)
+    }
+
+    /// Saves the current conversation and restores the conversation of session `name`
+    pub async fn switch_session(self: &Arc<Self>, name: &str) -> Result<()> {
+        let mut sessions = self.sessions.write().await;
+        if !sessions.exists(name) {
+            return Err(anyhow::anyhow!("Session {} does not exist", name));
+        }
+
+        if sessions.get_active() == name {
+            return Ok(());
+        }
+
+        self.swap_session(&mut sessions, name).await
+    }
+
+    async fn swap_session(self: &Arc<Self>, sessions: &mut Sessions, name: &str) -> Result<()> {
+        if self.is_waiting().await {
+            return Err(anyhow::anyhow!(
+                "Cannot switch sessions while waiting for response"
+            ));
+        }
+
+        let current = Session {
+            messages: self.get_messages().await,
+            messages_metadata: self.get_messages_metadata().await,
+            usage: self.get_usage().await,
+        };
+        self.reset().await?;
+
+        let next = sessions.switch(name, current);
+        *self.messages.write().await = next.messages;
+        *self.messages_metadata.write().await = next.messages_metadata;
+        *self.usage.write().await = next.usage;
+
+        self.tx.send(NixcodeEvent::MessageUpdated).ok();
+
+        Ok(())
+    }
+
     pub async fn retry_last_message(self: &Arc<Self>) {
         if self.is_waiting().await {
             return;
@@ -490,9 +1514,12 @@ impl Nixcode {
         let mut messages = self.messages.write().await;
         let last_message = messages.last_mut().unwrap();
         last_message.set_tool_state(tool.get_id(), ToolUseState::Executing);
+        drop(messages);
+
+        self.tx.send(NixcodeEvent::ToolStart(tool)).ok();
     }
 
-    pub async fn tool_finished(self: &Arc<Self>, result: ToolResultContent) {
+    pub async fn tool_finished(self: &Arc<Self>, result: ToolResultContent, state: ToolUseState) {
         let tool_id = result.get_tool_use_id();
         self.tools_results.write().await.push(result.clone());
 
@@ -500,14 +1527,35 @@ impl Nixcode {
         let tools_to_execute = self.tools_to_execute.read().await.clone();
         let mut messages = self.messages.write().await;
         let last_message = messages.last_mut().unwrap();
-        last_message.set_tool_state(tool_id, ToolUseState::Executed);
+        let event = match state {
+            ToolUseState::Error => NixcodeEvent::ToolError(result),
+            _ => NixcodeEvent::ToolEnd(result),
+        };
+        last_message.set_tool_state(tool_id, state);
 
-        self.tx.send(NixcodeEvent::ToolEnd(result)).ok();
+        self.tx.send(event).ok();
 
         if tools_results.len() != tools_to_execute.len() {
             return;
         }
+        let message_index = messages.len() - 1;
+        drop(messages);
 
+        // The note of the auto commit goes to the result of the verification run
+        if let Some((tool_use_id, note)) = self.auto_commit_turn().await {
+            let mut results = self.tools_results.write().await;
+            let verification = results
+                .iter_mut()
+                .find(|result| result.get_tool_use_id() == tool_use_id);
+            if let Some(result) = verification {
+                *result = result.with_content(format!("{}\n\n{}", result.get_content(), note));
+            }
+        }
+
+        let summary = TurnSummary::new(&tools_to_execute, &tools_results, &self.tools);
+        self.tx
+            .send(NixcodeEvent::TurnSummary(message_index, summary))
+            .ok();
         self.tx.send(NixcodeEvent::ToolsFinished).ok();
     }
 }
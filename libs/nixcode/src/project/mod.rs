@@ -1,11 +1,100 @@
-use git2::Repository;
-use std::path::PathBuf;
+use crate::utils::command::CommandOptions;
+use crate::utils::format::format_content;
+use crate::utils::fs::resolve_path_in_dir;
+use git2::{Repository, Signature};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Semaphore;
+
+mod stack;
 
 #[derive(Clone, Debug)]
 pub struct Project {
     cwd: PathBuf,
     project_init_analysis_content: Option<String>,
-    repo_path: Option<PathBuf>,
+    instructions: Option<String>,
+    /// Notes of the memory file, read once and extended by append_memory, shared by all clones
+    memory: Arc<Mutex<String>>,
+    /// Work directory of the repository, shared by all clones so a clone into the
+    /// project directory is seen by every tool
+    repo_path: Arc<Mutex<Option<PathBuf>>>,
+    read_chunk_tokens: usize,
+    max_files_per_call: usize,
+    /// Bounds the files read at the same time by multi-file tools, shared by all clones
+    read_semaphore: Arc<Semaphore>,
+    command_options: CommandOptions,
+    commit_author: CommitAuthor,
+    remote_defaults: RemoteDefaults,
+    format_on_write: bool,
+    /// Output of the last cargo command run by a tool, shared by all clones
+    last_cargo_output: Arc<Mutex<Option<String>>>,
+    /// Set when the running tools are cancelled, checked by tools before changing files
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Identity of commits made by the agent, parts that are not set come from the git
+/// config of the repository
+#[derive(Clone, Debug, Default)]
+pub struct CommitAuthor {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl CommitAuthor {
+    /// Author and committer signature of a new commit in `repo`
+    pub fn get_signature(&self, repo: &Repository) -> Result<Signature<'static>, git2::Error> {
+        match (&self.name, &self.email) {
+            (Some(name), Some(email)) => Signature::now(name, email),
+            (None, None) => repo.signature(),
+            (name, email) => {
+                let default = repo.signature()?;
+                let name = name.as_deref().or(default.name()).unwrap_or_default();
+                let email = email.as_deref().or(default.email()).unwrap_or_default();
+                Signature::now(name, email)
+            }
+        }
+    }
+}
+
+/// Remote and branch used by git_push and git_pull when the call names none
+#[derive(Clone, Debug, Default)]
+pub struct RemoteDefaults {
+    pub remote: Option<String>,
+    pub branch: Option<String>,
+}
+
+/// Result of a mutating tool cancelled before its changes were written
+pub const CANCELLED_MESSAGE: &str = "Tool execution was cancelled, nothing was changed";
+
+/// Project-specific instructions written by the team, added to the system prompt
+pub const INSTRUCTIONS_FILE: &str = ".nixcode/instructions.md";
+
+/// Notes recorded by the agent with append_memory, added to the system prompt
+pub const MEMORY_FILE: &str = ".nixcode/memory.md";
+
+/// Characters of memory added to the system prompt, older notes are left out
+const MAX_MEMORY_CHARS: usize = 8_000;
+
+/// Default token budget of a single read_text_file result
+pub const DEFAULT_READ_CHUNK_TOKENS: usize = 10_000;
+
+/// Default number of files a multi-file tool (e.g. read_files) processes per call
+pub const DEFAULT_MAX_FILES_PER_CALL: usize = 50;
+
+/// Default number of files read at the same time by multi-file tools
+pub const DEFAULT_MAX_CONCURRENT_READS: usize = 8;
+
+/// Work directory of the repository containing `cwd`
+fn discover_repo_path(cwd: &Path) -> Option<PathBuf> {
+    let repository = Repository::discover(cwd).ok()?;
+    repository.workdir().map(|path| path.into())
+}
+
+/// Reads a file of the project, None when it is missing or resolves outside the project
+fn read_project_file(cwd: &Path, file: &str) -> Option<String> {
+    let path = resolve_path_in_dir(cwd, file).ok()?;
+    std::fs::read_to_string(path).ok()
 }
 
 impl Project {
@@ -19,16 +108,27 @@ impl Project {
             }
         }
 
-        let repository = if let Some(repository) = Repository::discover(cwd.as_path()).ok() {
-            repository.workdir().map(|path| path.into())
-        } else {
-            None
-        };
+        let instructions =
+            read_project_file(&cwd, INSTRUCTIONS_FILE).filter(|content| !content.trim().is_empty());
+
+        let memory = read_project_file(&cwd, MEMORY_FILE).unwrap_or_default();
+        let repo_path = discover_repo_path(&cwd);
 
         Self {
             cwd,
             project_init_analysis_content,
-            repo_path: repository,
+            instructions,
+            memory: Arc::new(Mutex::new(memory)),
+            repo_path: Arc::new(Mutex::new(repo_path)),
+            read_chunk_tokens: DEFAULT_READ_CHUNK_TOKENS,
+            max_files_per_call: DEFAULT_MAX_FILES_PER_CALL,
+            read_semaphore: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_READS)),
+            command_options: CommandOptions::default(),
+            commit_author: CommitAuthor::default(),
+            remote_defaults: RemoteDefaults::default(),
+            format_on_write: false,
+            last_cargo_output: Arc::new(Mutex::new(None)),
+            cancelled: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -44,11 +144,146 @@ impl Project {
         self.project_init_analysis_content.is_some()
     }
 
+    pub fn get_instructions(&self) -> Option<String> {
+        self.instructions.clone()
+    }
+
+    pub fn has_instructions(&self) -> bool {
+        self.instructions.is_some()
+    }
+
+    /// Memory for the system prompt, the latest notes up to MAX_MEMORY_CHARS characters
+    pub fn get_memory(&self) -> Option<String> {
+        let memory = self.memory.lock().unwrap();
+        if memory.trim().is_empty() {
+            return None;
+        }
+        if memory.len() <= MAX_MEMORY_CHARS {
+            return Some(memory.clone());
+        }
+
+        // Starts at the first whole line within the limit
+        let cut = memory.len() - MAX_MEMORY_CHARS;
+        let start = memory.as_bytes()[cut..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(memory.len(), |newline| cut + newline + 1);
+        Some(format!(
+            "[Older notes are left out, use read_memory to see all of them]\n{}",
+            &memory[start..]
+        ))
+    }
+
+    /// Adds an entry written to the memory file to the memory of the system prompt
+    pub fn append_to_memory(&self, entry: &str) {
+        self.memory.lock().unwrap().push_str(entry);
+    }
+
     pub fn has_repo_path(&self) -> bool {
-        self.repo_path.is_some()
+        self.repo_path.lock().unwrap().is_some()
     }
 
     pub fn get_repo_path(&self) -> Option<PathBuf> {
-        self.repo_path.clone()
+        self.repo_path.lock().unwrap().clone()
+    }
+
+    /// Looks for the repository of the project directory again, e.g. after a clone,
+    /// returns true when the project is in a repository
+    pub fn refresh_repo_path(&self) -> bool {
+        let repo_path = discover_repo_path(&self.cwd);
+        let found = repo_path.is_some();
+        *self.repo_path.lock().unwrap() = repo_path;
+
+        found
+    }
+
+    pub fn get_read_chunk_tokens(&self) -> usize {
+        self.read_chunk_tokens
+    }
+
+    pub fn set_read_chunk_tokens(&mut self, tokens: usize) {
+        self.read_chunk_tokens = tokens.max(1);
+    }
+
+    pub fn get_max_files_per_call(&self) -> usize {
+        self.max_files_per_call
+    }
+
+    pub fn set_max_files_per_call(&mut self, files: usize) {
+        self.max_files_per_call = files.max(1);
+    }
+
+    /// Semaphore a multi-file tool acquires a permit of before reading each file
+    pub fn get_read_semaphore(&self) -> Arc<Semaphore> {
+        self.read_semaphore.clone()
+    }
+
+    pub fn set_max_concurrent_reads(&mut self, reads: usize) {
+        self.read_semaphore = Arc::new(Semaphore::new(reads.max(1)));
+    }
+
+    pub fn get_command_options(&self) -> CommandOptions {
+        self.command_options.clone()
+    }
+
+    pub fn set_command_options(&mut self, options: CommandOptions) {
+        self.command_options = options;
+    }
+
+    pub fn get_commit_author(&self) -> CommitAuthor {
+        self.commit_author.clone()
+    }
+
+    pub fn set_commit_author(&mut self, author: CommitAuthor) {
+        self.commit_author = author;
+    }
+
+    pub fn get_remote_defaults(&self) -> RemoteDefaults {
+        self.remote_defaults.clone()
+    }
+
+    pub fn set_remote_defaults(&mut self, defaults: RemoteDefaults) {
+        self.remote_defaults = defaults;
+    }
+
+    pub fn set_format_on_write(&mut self, enabled: bool) {
+        self.format_on_write = enabled;
+    }
+
+    /// Content to write to `path`, formatted when format_on_write is enabled; an error
+    /// means the formatter rejected the content
+    pub async fn format_for_write(&self, path: &Path, content: String) -> Result<String, String> {
+        if !self.format_on_write {
+            return Ok(content);
+        }
+
+        format_content(&self.cwd, path, &content).await
+    }
+
+    /// Keeps the output of a cargo run for get_error_context
+    pub fn set_last_cargo_output(&self, output: String) {
+        *self.last_cargo_output.lock().unwrap() = Some(output);
+    }
+
+    pub fn get_last_cargo_output(&self) -> Option<String> {
+        self.last_cargo_output.lock().unwrap().clone()
+    }
+
+    pub fn set_cancelled(&self, cancelled: bool) {
+        self.cancelled.store(cancelled, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Languages and build tools detected from marker files in the project root
+    pub fn detect_stack(&self) -> Vec<String> {
+        stack::detect_stack(self.cwd.as_path())
+    }
+
+    /// Lists project files (relative to cwd) that are not hidden or ignored
+    pub fn list_files(&self, limit: usize) -> Vec<String> {
+        crate::utils::fs::list_project_files(self.cwd.as_path(), limit)
     }
 }
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+/// Marker files in the project root and the stack they indicate
+const STACK_MARKERS: [(&str, &str); 12] = [
+    ("Cargo.toml", "Rust"),
+    ("package.json", "Node.js"),
+    ("tsconfig.json", "TypeScript"),
+    ("pyproject.toml", "Python"),
+    ("requirements.txt", "Python"),
+    ("setup.py", "Python"),
+    ("go.mod", "Go"),
+    ("pom.xml", "Java (Maven)"),
+    ("build.gradle", "Java (Gradle)"),
+    ("build.gradle.kts", "Kotlin (Gradle)"),
+    ("Gemfile", "Ruby"),
+    ("composer.json", "PHP"),
+];
+
+/// Detects the languages and build tools used in `dir` from marker files
+pub fn detect_stack(dir: &Path) -> Vec<String> {
+    let mut stack: Vec<String> = Vec::new();
+
+    for (marker, name) in STACK_MARKERS {
+        if dir.join(marker).is_file() && !stack.iter().any(|detected| detected == name) {
+            stack.push(name.to_string());
+        }
+    }
+
+    stack
+}
@@ -0,0 +1,12 @@
+pub const COMMIT_MESSAGE_PROMPT: &str = r#"Write a commit message for the staged changes below using the Conventional Commits format:
+
+<type>(<optional scope>): <short summary>
+
+<optional body>
+
+- type is one of: feat, fix, docs, style, refactor, perf, test, build, ci, chore
+- the summary is in the imperative mood, lowercase, without a trailing period and at most 72 characters
+- the body explains what changed and why, wrapped at 72 characters, and is omitted for trivial changes
+- describe only the changes in the diff
+
+Show the proposed message to the user. Call git_commit with it only after the user accepts it (the user may edit it first)."#;
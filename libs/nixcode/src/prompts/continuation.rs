@@ -0,0 +1 @@
+pub const CONTINUE_PROMPT: &str = "Your previous response was cut off because it reached the maximum output length. Continue exactly where you stopped, without repeating any of the previous text.";
@@ -0,0 +1 @@
+pub const EMPTY_RESPONSE_PROMPT: &str = "Your previous response was empty. Continue with the task: reply to the user or call the tool you need next.";
@@ -1 +1,4 @@
+pub mod commit_message;
+pub mod continuation;
+pub mod empty_response;
 pub mod system;
@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use nixcode_llm_sdk::message::message::Message;
+use nixcode_llm_sdk::message::metadata::MessageMetadata;
+use nixcode_llm_sdk::message::usage::Usage;
+
+/// Name of the session a run starts with
+pub const DEFAULT_SESSION_NAME: &str = "main";
+
+/// Conversation of a session that is not active
+#[derive(Debug, Default)]
+pub struct Session {
+    pub messages: Vec<Message>,
+    pub messages_metadata: Vec<MessageMetadata>,
+    pub usage: Usage,
+}
+
+/// Named conversations of one run, the active one lives in `Nixcode` itself
+#[derive(Debug)]
+pub struct Sessions {
+    active: String,
+    saved: BTreeMap<String, Session>,
+}
+
+impl Default for Sessions {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_SESSION_NAME.into(),
+            saved: BTreeMap::new(),
+        }
+    }
+}
+
+impl Sessions {
+    pub fn get_active(&self) -> &str {
+        &self.active
+    }
+
+    /// Names of all sessions in alphabetical order, including the active one
+    pub fn get_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.saved.keys().cloned().collect();
+        names.push(self.active.clone());
+        names.sort();
+
+        names
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.active == name || self.saved.contains_key(name)
+    }
+
+    /// First free name of the form `session-N`
+    pub fn get_next_name(&self) -> String {
+        (2..)
+            .map(|number| format!("session-{}", number))
+            .find(|name| !self.exists(name))
+            .unwrap()
+    }
+
+    /// Saves the conversation of the active session and makes `name` active,
+    /// returns the saved conversation of `name` (empty for a new session)
+    pub fn switch(&mut self, name: &str, current: Session) -> Session {
+        let active = std::mem::replace(&mut self.active, name.to_string());
+        self.saved.insert(active, current);
+
+        self.saved.remove(name).unwrap_or_default()
+    }
+}
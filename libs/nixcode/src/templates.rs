@@ -0,0 +1,72 @@
+use crate::project::Project;
+use crate::tools::git::git_diff::get_working_tree_diff;
+use crate::utils::fs::resolve_path_in_dir;
+use crate::MAX_WORKING_TREE_DIFF_CHARS;
+
+/// Maximum number of characters inlined by `${file:path}`
+const MAX_FILE_CHARS: usize = 100_000;
+
+/// Directory of the prompt templates, `<name>.md` is sent by `:template <name>`
+pub const TEMPLATES_DIR: &str = ".nixcode/templates";
+
+/// Reads the prompt template `name` and expands its variables
+pub fn render_template(name: &str, project: &Project) -> Result<String, String> {
+    let error = |e: &dyn std::fmt::Display| format!("Template {}: {}", name, e);
+    let file = format!("{}/{}.md", TEMPLATES_DIR, name.trim());
+    let path = resolve_path_in_dir(&project.get_cwd(), file).map_err(|e| error(&e))?;
+    let template = std::fs::read_to_string(&path).map_err(|e| error(&e))?;
+
+    expand_prompt_variables(&template, project)
+}
+
+/// Expands `${git_diff}` (working tree diff) and `${file:path}` (file contents, relative
+/// to the project directory) in a prompt; other `${...}` sequences are kept as they are
+pub fn expand_prompt_variables(text: &str, project: &Project) -> Result<String, String> {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+
+        result.push_str(&rest[..start]);
+        let variable = &rest[start + 2..start + length];
+        match resolve_variable(variable, project)? {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..=start + length]),
+        }
+
+        rest = &rest[start + length + 1..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+fn resolve_variable(variable: &str, project: &Project) -> Result<Option<String>, String> {
+    if variable == "git_diff" {
+        let repo = project
+            .get_repo_path()
+            .and_then(|path| git2::Repository::open(path).ok())
+            .ok_or_else(|| "${git_diff}: not a git repository".to_string())?;
+
+        return get_working_tree_diff(&repo, MAX_WORKING_TREE_DIFF_CHARS).map(Some);
+    }
+
+    let Some(file) = variable.strip_prefix("file:") else {
+        return Ok(None);
+    };
+
+    let error = |e: &dyn std::fmt::Display| format!("${{file:{}}}: {}", file, e);
+    let path = resolve_path_in_dir(&project.get_cwd(), file.trim()).map_err(|e| error(&e))?;
+    let content = std::fs::read_to_string(&path).map_err(|e| error(&e))?;
+
+    if content.chars().count() > MAX_FILE_CHARS {
+        let cut: String = content.chars().take(MAX_FILE_CHARS).collect();
+        return Ok(Some(format!("{}\n[file truncated]", cut)));
+    }
+
+    Ok(Some(content))
+}
@@ -0,0 +1,1046 @@
+use super::*;
+use nixcode_llm_sdk::message::content::tools::ToolUseState;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Writes a recorded response fixture, one stream event per line
+fn write_fixture(dir: &Path, name: &str, events: &[serde_json::Value]) {
+    let content = events
+        .iter()
+        .map(|event| event.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(dir.join(format!("{}-response.jsonl", name)), content).unwrap();
+}
+
+fn message_start(id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "message_start",
+        "message": {
+            "id": id,
+            "model": "replay",
+            "role": "assistant",
+            "stop_reason": null,
+            "content": [],
+            "stop_sequence": null,
+            "usage": { "input_tokens": 10, "output_tokens": 1 }
+        }
+    })
+}
+
+fn message_end(stop_reason: &str) -> Vec<serde_json::Value> {
+    vec![
+        serde_json::json!({
+            "type": "message_delta",
+            "delta": { "stop_reason": stop_reason, "stop_sequence": null },
+            "usage": { "output_tokens": 5 }
+        }),
+        serde_json::json!({ "type": "message_stop" }),
+    ]
+}
+
+/// A single text response ending with the given stop reason
+fn text_turn(id: &str, text: &str, stop_reason: &str) -> Vec<serde_json::Value> {
+    let mut events = vec![
+        message_start(id),
+        serde_json::json!({
+            "type": "content_block_start",
+            "index": 0,
+            "content_block": { "type": "text", "text": "" }
+        }),
+        serde_json::json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": { "type": "text_delta", "text": text }
+        }),
+        serde_json::json!({ "type": "content_block_stop", "index": 0 }),
+    ];
+    events.extend(message_end(stop_reason));
+
+    events
+}
+
+/// A response calling one tool, `input` is the streamed JSON of its arguments and the
+/// tool use id is `id` with `msg` replaced by `toolu`
+fn tool_use_turn(id: &str, name: &str, input: &str) -> Vec<serde_json::Value> {
+    let mut events = vec![
+        message_start(id),
+        serde_json::json!({
+            "type": "content_block_start",
+            "index": 0,
+            "content_block": { "type": "tool_use", "id": id.replace("msg", "toolu"), "name": name, "input": {} }
+        }),
+        serde_json::json!({
+            "type": "content_block_delta",
+            "index": 0,
+            "delta": { "type": "input_json_delta", "partial_json": input }
+        }),
+        serde_json::json!({ "type": "content_block_stop", "index": 0 }),
+    ];
+    events.extend(message_end("tool_use"));
+
+    events
+}
+
+/// Config with the tools enabled
+fn tools_config() -> Config {
+    let mut config = Config::new();
+    config.tools.enabled = true;
+
+    config
+}
+
+/// Agent replaying the responses recorded in `fixtures`. The fixtures directory is also
+/// the project directory, the replay client only reads the response files in it
+fn replay_nixcode(
+    fixtures: &Path,
+    config: Config,
+) -> (Arc<Nixcode>, UnboundedReceiver<NixcodeEvent>) {
+    replay_nixcode_with_tools(fixtures, config, vec![])
+}
+
+/// Like `replay_nixcode`, with `tools` registered next to the built-in ones
+fn replay_nixcode_with_tools(
+    fixtures: &Path,
+    config: Config,
+    tools: Vec<crate::tools::SafeTool>,
+) -> (Arc<Nixcode>, UnboundedReceiver<NixcodeEvent>) {
+    let client = LLMClient::new_replay(fixtures).unwrap();
+    let project = Project::new(fixtures.to_path_buf());
+    let (rx, mut nixcode) = Nixcode::new(project, client, config).unwrap();
+    for tool in tools {
+        nixcode.tools.add_tool(tool);
+    }
+
+    (Arc::new(nixcode), rx)
+}
+
+/// Sends a user message with the text
+async fn send_user_text(nixcode: &Arc<Nixcode>, text: &str) {
+    nixcode
+        .clone()
+        .send_message(Some(Message::User(vec![Content::new_text(text)])))
+        .await;
+}
+
+async fn wait_for(rx: &mut UnboundedReceiver<NixcodeEvent>, expected: fn(&NixcodeEvent) -> bool) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = rx.recv().await {
+            if expected(&event) {
+                return;
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for event");
+}
+
+#[tokio::test]
+async fn test_replay_canned_conversation() {
+    let fixtures = tempfile::tempdir().unwrap();
+    std::fs::write(fixtures.path().join("hello.txt"), "Hello from fixture").unwrap();
+
+    let tool_turn = tool_use_turn("msg_1", "read_text_file", r#"{"path": "hello.txt"}"#);
+    write_fixture(fixtures.path(), "0001", &tool_turn);
+    write_fixture(
+        fixtures.path(),
+        "0002",
+        &text_turn("msg_2", "The file says hello.", "end_turn"),
+    );
+
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), tools_config());
+    send_user_text(&nixcode, "Read hello.txt").await;
+
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::ToolsFinished)
+    })
+    .await;
+    nixcode.clone().send_tools_results().await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    let messages = nixcode.get_messages().await;
+    assert_eq!(messages.len(), 4);
+
+    let Message::Assistant(tool_use) = &messages[1] else {
+        panic!("Expected assistant message");
+    };
+    let Content::ToolUse(tool_use) = &tool_use[0] else {
+        panic!("Expected tool use");
+    };
+    assert!(matches!(tool_use.get_state(), ToolUseState::Executed));
+
+    let Message::User(results) = &messages[2] else {
+        panic!("Expected tool results");
+    };
+    let Content::ToolResult(result) = &results[0] else {
+        panic!("Expected tool result");
+    };
+    assert!(result.get_content().contains("Hello from fixture"));
+
+    let Message::Assistant(answer) = &messages[3] else {
+        panic!("Expected assistant message");
+    };
+    let Content::Text(text) = &answer[0] else {
+        panic!("Expected text");
+    };
+    assert_eq!(text.get_text(), "The file says hello.");
+}
+
+#[tokio::test]
+async fn test_denied_tool_call_returns_error_result() {
+    let fixtures = tempfile::tempdir().unwrap();
+    std::fs::write(fixtures.path().join("hello.txt"), "Hello").unwrap();
+
+    let tool_turn = tool_use_turn("msg_1", "delete_file", r#"{"path": "hello.txt"}"#);
+    write_fixture(fixtures.path(), "0001", &tool_turn);
+    write_fixture(
+        fixtures.path(),
+        "0002",
+        &text_turn("msg_2", "The tool is disabled.", "end_turn"),
+    );
+
+    let mut config = tools_config();
+    config.tools.deny = vec!["delete_file".to_string()];
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), config);
+    send_user_text(&nixcode, "Delete hello.txt").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::ToolsFinished)
+    })
+    .await;
+    nixcode.clone().send_tools_results().await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    assert!(fixtures.path().join("hello.txt").exists());
+    let messages = nixcode.get_messages().await;
+    assert_eq!(messages.len(), 4);
+    let Message::User(results) = &messages[2] else {
+        panic!("Expected tool results");
+    };
+    let Content::ToolResult(result) = &results[0] else {
+        panic!("Expected tool result");
+    };
+    assert!(result.is_error());
+    assert_eq!(
+        result.get_content(),
+        "Tool delete_file is disabled by configuration"
+    );
+}
+
+async fn nixcode_after_first_turn(
+    fixtures: &Path,
+) -> (UnboundedReceiver<NixcodeEvent>, Arc<Nixcode>) {
+    let (nixcode, mut rx) = replay_nixcode(fixtures, Config::new());
+    send_user_text(&nixcode, "Write a story").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    (rx, nixcode)
+}
+
+#[tokio::test]
+async fn test_switching_sessions_swaps_message_history() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(fixtures.path(), "0000", &text_turn("msg_0", "Once upon a time", "end_turn"));
+    let (_rx, nixcode) = nixcode_after_first_turn(fixtures.path()).await;
+    let first_history = nixcode.get_messages().await;
+    assert_eq!(first_history.len(), 2);
+
+    let name = nixcode.new_session(None).await.unwrap();
+    assert_eq!(name, "session-2");
+    assert_eq!(nixcode.get_active_session().await, "session-2");
+    assert!(nixcode.get_messages().await.is_empty());
+    assert!(nixcode.new_session(Some("main")).await.is_err());
+
+    nixcode.switch_session("main").await.unwrap();
+    assert_eq!(nixcode.get_messages().await.len(), first_history.len());
+    assert_eq!(nixcode.get_session_names().await, vec!["main", "session-2"]);
+    assert!(nixcode.switch_session("unknown").await.is_err());
+}
+
+#[tokio::test]
+async fn test_continue_after_max_tokens_appends_to_message() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &text_turn("msg_1", "Once upon \n", "max_tokens"),
+    );
+    write_fixture(
+        fixtures.path(),
+        "0002",
+        &text_turn("msg_2", " a time.", "end_turn"),
+    );
+
+    let (mut rx, nixcode) = nixcode_after_first_turn(fixtures.path()).await;
+    assert!(nixcode.can_continue().await);
+
+    nixcode.clone().continue_last_message().await.unwrap();
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    let messages = nixcode.get_messages().await;
+    assert_eq!(messages.len(), 2);
+    let Message::Assistant(content) = &messages[1] else {
+        panic!("Expected assistant message");
+    };
+    assert_eq!(content.len(), 1);
+    assert_eq!(
+        content[0].get_text().unwrap().get_text(),
+        "Once upon a time."
+    );
+
+    let metadata = nixcode.get_messages_metadata().await;
+    assert_eq!(metadata[1].stop_reason, Some(StopReason::EndTurn));
+    assert!(!nixcode.can_continue().await);
+}
+
+#[tokio::test]
+async fn test_completed_message_carries_model_and_timestamp() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &text_turn("msg_1", "Once upon a time.", "end_turn"),
+    );
+
+    let started = chrono::Utc::now();
+    let (_rx, nixcode) = nixcode_after_first_turn(fixtures.path()).await;
+
+    let metadata = nixcode.get_messages_metadata().await;
+    assert!(metadata[0].created_at.is_some());
+    assert!(metadata[0].model.is_none());
+
+    let response = &metadata[1];
+    assert_eq!(response.model.as_deref(), Some(nixcode.get_model()));
+    assert!(response.created_at.unwrap() >= started);
+    assert!(response.completed_at.unwrap() >= response.created_at.unwrap());
+
+    let serialized = serde_json::to_value(response).unwrap();
+    assert!(serialized["completed_at"].is_string());
+}
+
+#[tokio::test]
+async fn test_continue_rejected_after_end_turn() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &text_turn("msg_1", "The end.", "end_turn"),
+    );
+
+    let (_rx, nixcode) = nixcode_after_first_turn(fixtures.path()).await;
+
+    assert!(!nixcode.can_continue().await);
+    assert!(nixcode.clone().continue_last_message().await.is_err());
+}
+
+#[tokio::test]
+async fn test_prefill_is_prepended_to_response() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0000",
+        &text_turn("msg_0", "\"title\": \"Dune\"}", "end_turn"),
+    );
+    let mut config = Config::new();
+    config.agent.prefill = Some(String::from("{"));
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), config);
+
+    send_user_text(&nixcode, "Extract the book as JSON").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    let messages = nixcode.get_messages().await;
+    assert_eq!(
+        messages[1].get_content()[0].get_text().unwrap().get_text(),
+        "{\"title\": \"Dune\"}"
+    );
+}
+
+#[tokio::test]
+async fn test_prefill_is_not_sent_with_tool_results() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &tool_use_turn("msg_1", "stub_tool", "{}"),
+    );
+    write_fixture(
+        fixtures.path(),
+        "0002",
+        &text_turn("msg_2", "Done.", "end_turn"),
+    );
+    let mut config = tools_config();
+    config.agent.prefill = Some(String::from("Plan:"));
+    let tools: Vec<crate::tools::SafeTool> = vec![Arc::new(StubTool::new(Duration::ZERO))];
+    let (nixcode, mut rx) = replay_nixcode_with_tools(fixtures.path(), config, tools);
+
+    send_user_text(&nixcode, "Go").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::ToolsFinished)
+    })
+    .await;
+    nixcode.clone().send_tools_results().await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    let messages = nixcode.get_messages().await;
+    assert_eq!(messages.len(), 4);
+    assert_eq!(
+        messages[1].get_content()[0].get_text().unwrap().get_text(),
+        "Plan:"
+    );
+    assert_eq!(
+        messages[3].get_content()[0].get_text().unwrap().get_text(),
+        "Done."
+    );
+}
+
+#[tokio::test]
+async fn test_pin_and_unpin_message() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(fixtures.path(), "0001", &text_turn("msg_1", "Noted.", "end_turn"));
+
+    let (_rx, nixcode) = nixcode_after_first_turn(fixtures.path()).await;
+
+    nixcode.set_message_pinned(0, true).await.unwrap();
+    let metadata = nixcode.get_messages_metadata().await;
+    assert!(metadata[0].pinned);
+    assert!(!metadata[1].pinned);
+
+    nixcode.set_message_pinned(0, false).await.unwrap();
+    assert!(!nixcode.get_messages_metadata().await[0].pinned);
+
+    assert!(nixcode.set_message_pinned(2, true).await.is_err());
+}
+
+/// A turn calling read_text_file and the user message with its result
+#[tokio::test]
+async fn test_forced_tool_choice_applies_to_one_request() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &text_turn("msg_1", "Nothing to read.", "end_turn"),
+    );
+
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), tools_config());
+
+    let missing = ToolChoice::Tool("missing_tool".into());
+    assert!(nixcode.set_tool_choice(missing).await.is_err());
+    let forced = ToolChoice::Tool("read_text_file".into());
+    nixcode.set_tool_choice(forced.clone()).await.unwrap();
+
+    send_user_text(&nixcode, "Read hello.txt").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    let metadata = nixcode.get_messages_metadata().await;
+    assert_eq!(metadata[1].tool_choice, Some(forced));
+    assert_eq!(nixcode.get_tool_choice().await, ToolChoice::Auto);
+}
+
+#[tokio::test]
+async fn test_forced_tool_choice_rejected_with_thinking() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let mut config = Config::new();
+    config.agent.thinking_budget = Some(1024);
+    config.agent.tool_choice = Some("required".into());
+    let (nixcode, _rx) = replay_nixcode(fixtures.path(), config);
+
+    assert_eq!(nixcode.get_tool_choice().await, ToolChoice::Auto);
+    assert!(nixcode.set_tool_choice(ToolChoice::Required).await.is_err());
+    assert!(nixcode.set_tool_choice(ToolChoice::None).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_stream_error_ends_turn_without_tools() {
+    let fixtures = tempfile::tempdir().unwrap();
+    // The error arrives after the tool call, before the end of the message
+    let mut events = tool_use_turn("msg_1", "read_text_file", r#"{"path": "hello.txt"}"#);
+    events.insert(
+        4,
+        serde_json::json!({
+            "type": "error",
+            "error": { "type": "overloaded_error", "message": "Overloaded" }
+        }),
+    );
+    write_fixture(fixtures.path(), "0001", &events);
+
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), tools_config());
+    send_user_text(&nixcode, "Read hello.txt").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    assert!(!nixcode.is_waiting().await);
+    assert!(nixcode.get_error().await.is_some());
+    assert!(nixcode.get_tools_to_execute().await.is_empty());
+    // The unanswered tool call is not left in the history
+    let orphaned = nixcode
+        .get_messages()
+        .await
+        .iter()
+        .flat_map(|message| message.get_content())
+        .any(|content| matches!(content, Content::ToolUse(_)));
+    assert!(!orphaned);
+
+    let tool_started = tokio::time::timeout(Duration::from_millis(100), async {
+        while let Some(event) = rx.recv().await {
+            if matches!(event, NixcodeEvent::ToolStart(_)) {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+    assert!(!tool_started);
+}
+
+/// Stub tool that takes `delay` to finish, counts how many of its calls run at once
+/// and records whether a call finished. Clones share the counters. Its result is
+/// `output` and can always be summarized
+#[derive(Clone)]
+struct StubTool {
+    delay: Duration,
+    output: String,
+    running: Arc<AtomicUsize>,
+    max_running: Arc<AtomicUsize>,
+    finished: Arc<AtomicBool>,
+}
+
+impl StubTool {
+    fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            output: String::from("done"),
+            running: Arc::new(AtomicUsize::new(0)),
+            max_running: Arc::new(AtomicUsize::new(0)),
+            finished: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl crate::tools::Tool for StubTool {
+    fn get_name(&self) -> String {
+        "stub_tool".into()
+    }
+
+    fn get_schema(&self) -> nixcode_llm_sdk::tools::Tool {
+        nixcode_llm_sdk::tools::Tool::new(
+            self.get_name(),
+            "Stub tool".into(),
+            serde_json::json!({ "type": "object", "properties": {} }),
+        )
+    }
+
+    async fn execute(
+        &self,
+        _params: serde_json::Value,
+        _project: Arc<Project>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_running.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(self.delay).await;
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        self.finished.store(true, Ordering::SeqCst);
+
+        Ok(serde_json::json!(self.output))
+    }
+
+    fn summarize_result(&self, _result: &serde_json::Value) -> Option<String> {
+        Some(String::from("Stub summary"))
+    }
+}
+
+/// A response calling the stub tool `calls` times, with tool use ids `toolu_1` and up
+fn stub_tool_calls_turn(calls: usize) -> Vec<serde_json::Value> {
+    let mut events = vec![message_start("msg_1")];
+    for index in 0..calls {
+        events.push(serde_json::json!({
+            "type": "content_block_start",
+            "index": index,
+            "content_block": { "type": "tool_use", "id": format!("toolu_{}", index + 1), "name": "stub_tool", "input": {} }
+        }));
+        events.push(serde_json::json!({
+            "type": "content_block_delta",
+            "index": index,
+            "delta": { "type": "input_json_delta", "partial_json": "{}" }
+        }));
+        events.push(serde_json::json!({ "type": "content_block_stop", "index": index }));
+    }
+    events.extend(message_end("tool_use"));
+
+    events
+}
+
+/// Runs `calls` calls of `tool` from one replayed response, at most
+/// `max_concurrent_tools` at once, returns the client once the tools finished
+async fn run_stub_tool(tool: StubTool, calls: usize, max_concurrent_tools: usize) -> Arc<Nixcode> {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(fixtures.path(), "0001", &stub_tool_calls_turn(calls));
+
+    let mut config = tools_config();
+    config.agent.max_concurrent_tools = max_concurrent_tools;
+    let tools: Vec<crate::tools::SafeTool> = vec![Arc::new(tool)];
+    let (nixcode, mut rx) = replay_nixcode_with_tools(fixtures.path(), config, tools);
+    send_user_text(&nixcode, "Go").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::ToolsFinished)
+    })
+    .await;
+
+    nixcode
+}
+
+#[tokio::test]
+async fn test_only_long_results_are_summarized_until_reset() {
+    let mut tool = StubTool::new(Duration::ZERO);
+    tool.output = "x".repeat(crate::tools::MIN_SUMMARIZED_CHARS);
+    let nixcode = run_stub_tool(tool.clone(), 1, 1).await;
+
+    let results = nixcode.tools_results.read().await.clone();
+    assert!(results[0].get_content().starts_with("Stub summary\n"));
+    let full_results = nixcode.full_tool_results.lock().unwrap().clone();
+    assert_eq!(full_results.get("toolu_1"), Some(&tool.output));
+
+    nixcode.reset().await.unwrap();
+    assert!(nixcode.full_tool_results.lock().unwrap().is_empty());
+
+    // A short result costs less than the summary and a get_full_tool_result call
+    let nixcode = run_stub_tool(StubTool::new(Duration::ZERO), 1, 1).await;
+
+    assert_eq!(nixcode.tools_results.read().await[0].get_content(), "done");
+    assert!(nixcode.full_tool_results.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_tool_concurrency_is_bounded() {
+    let tool = StubTool::new(Duration::from_millis(50));
+    let nixcode = run_stub_tool(tool.clone(), 6, 2).await;
+
+    assert_eq!(tool.max_running.load(Ordering::SeqCst), 2);
+    assert_eq!(nixcode.tools_results.read().await.len(), 6);
+}
+
+#[tokio::test]
+async fn test_cancel_aborts_running_tools() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &tool_use_turn("msg_1", "stub_tool", "{}"),
+    );
+
+    let tool = StubTool::new(Duration::from_millis(200));
+    let tools: Vec<crate::tools::SafeTool> = vec![Arc::new(tool.clone())];
+    let (nixcode, mut rx) = replay_nixcode_with_tools(fixtures.path(), tools_config(), tools);
+
+    send_user_text(&nixcode, "Go").await;
+    wait_for(&mut rx, |event| matches!(event, NixcodeEvent::ToolStart(_))).await;
+
+    assert_eq!(nixcode.cancel_tools().await, 1);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+
+    assert!(!tool.finished.load(Ordering::SeqCst));
+    assert!(nixcode.tools_results.read().await.is_empty());
+    assert!(nixcode.get_tools_to_execute().await.is_empty());
+    // The response had only the tool call, so it is removed with it
+    assert_eq!(nixcode.get_messages().await.len(), 1);
+    while let Ok(event) = rx.try_recv() {
+        assert!(!matches!(
+            event,
+            NixcodeEvent::ToolEnd(_) | NixcodeEvent::ToolsFinished
+        ));
+    }
+}
+
+#[tokio::test]
+async fn test_cancel_stops_streaming_response() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let mut stalled_turn = text_turn("msg_0", "Let me", "end_turn");
+    stalled_turn.truncate(3);
+    stalled_turn.push(serde_json::json!({ "stall": true }));
+    write_fixture(fixtures.path(), "0000", &stalled_turn);
+
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), Config::new());
+    send_user_text(&nixcode, "Hi").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::MessageUpdated)
+    })
+    .await;
+
+    assert_eq!(nixcode.cancel_tools().await, 0);
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    assert!(!nixcode.is_waiting().await);
+    assert!(nixcode.get_tools_to_execute().await.is_empty());
+}
+
+#[tokio::test]
+async fn test_empty_response_is_retried_once() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let mut empty_turn = vec![message_start("msg_1")];
+    empty_turn.extend(message_end("end_turn"));
+    write_fixture(fixtures.path(), "0001", &empty_turn);
+    write_fixture(fixtures.path(), "0002", &empty_turn);
+    write_fixture(
+        fixtures.path(),
+        "0003",
+        &text_turn("msg_3", "Not requested", "end_turn"),
+    );
+
+    let mut config = Config::new();
+    config.agent.retry_empty = 1;
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), config);
+    send_user_text(&nixcode, "Go").await;
+
+    let mut requests = 0;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(event) = rx.recv().await {
+            match event {
+                NixcodeEvent::GeneratingResponse => requests += 1,
+                NixcodeEvent::GeneratedResponse => break,
+                _ => {}
+            }
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(requests, 2);
+    let messages = nixcode.get_messages().await;
+    assert_eq!(messages.len(), 2);
+    assert!(messages[1].get_content().is_empty());
+    assert_eq!(nixcode.get_messages_metadata().await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_message_queued_while_waiting_is_sent_after_the_run() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &tool_use_turn("msg_1", "stub_tool", "{}"),
+    );
+    write_fixture(
+        fixtures.path(),
+        "0002",
+        &text_turn("msg_2", "Tool done", "end_turn"),
+    );
+    write_fixture(
+        fixtures.path(),
+        "0003",
+        &text_turn("msg_3", "Tests added", "end_turn"),
+    );
+
+    let tool = StubTool::new(Duration::from_millis(50));
+    let tools: Vec<crate::tools::SafeTool> = vec![Arc::new(tool)];
+    let (nixcode, mut rx) = replay_nixcode_with_tools(fixtures.path(), tools_config(), tools);
+
+    send_user_text(&nixcode, "Go").await;
+    wait_for(&mut rx, |event| matches!(event, NixcodeEvent::ToolStart(_))).await;
+
+    let queued = Message::User(vec![Content::new_text("Add tests too")]);
+    assert!(nixcode.clone().send_or_queue_message(queued).await);
+    assert_eq!(nixcode.get_queued_messages().await.len(), 1);
+
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::ToolsFinished)
+    })
+    .await;
+    nixcode.clone().send_tools_results().await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    // The run is over, so the CLI sends what was queued meanwhile
+    assert!(nixcode.clone().send_queued_messages().await);
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    let messages = nixcode.get_messages().await;
+    let [.., Message::User(queued), Message::Assistant(reply)] = messages.as_slice() else {
+        panic!("unexpected history: {:?}", messages);
+    };
+    assert_eq!(queued[0].get_text().unwrap().get_text(), "Add tests too");
+    assert_eq!(reply[0].get_text().unwrap().get_text(), "Tests added");
+    assert!(nixcode.get_queued_messages().await.is_empty());
+    assert!(!nixcode.clone().send_queued_messages().await);
+}
+
+#[tokio::test]
+async fn test_export_markdown_sections() {
+    let fixtures = tempfile::tempdir().unwrap();
+    write_fixture(
+        fixtures.path(),
+        "0001",
+        &text_turn("msg_1", "Here it is:\n```rust\nfn main() {}\n```", "end_turn"),
+    );
+
+    let (_rx, nixcode) = nixcode_after_first_turn(fixtures.path()).await;
+    let markdown = nixcode.export_markdown().await;
+
+    assert!(markdown.starts_with("# nixcode session\n"));
+    assert!(markdown.contains("## User\n\nWrite a story\n"));
+    assert!(markdown.contains("## Assistant\n\nHere it is:\n```rust\nfn main() {}\n```\n"));
+    assert!(markdown.contains("## Usage\n\n- Input tokens: 10\n"));
+    assert!(markdown.contains("- Estimated cost: $"));
+}
+
+#[test]
+fn test_instructions_are_added_to_system_prompt() {
+    let workdir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(workdir.path().join(".nixcode")).unwrap();
+    std::fs::write(
+        workdir.path().join(".nixcode/instructions.md"),
+        "Always use tabs.",
+    )
+    .unwrap();
+
+    let project = Project::new(workdir.path().to_path_buf());
+    assert!(project.has_instructions());
+    assert!(!project.has_init_analysis());
+
+    let fixtures = tempfile::tempdir().unwrap();
+    let client = LLMClient::new_replay(fixtures.path()).unwrap();
+    let (_rx, nixcode) = Nixcode::new(project, client, Config::new()).unwrap();
+
+    let system_prompt = nixcode.get_system_prompt();
+    assert_eq!(system_prompt.len(), 2);
+    let Content::Text(instructions) = &system_prompt[1] else {
+        panic!("Expected text");
+    };
+    assert!(instructions.get_text().starts_with("File: .nixcode/instructions.md"));
+    assert!(instructions.get_text().contains("Always use tabs."));
+}
+
+#[test]
+fn test_tool_schema_token_footprint() {
+    let workdir = tempfile::tempdir().unwrap();
+    let fixtures = tempfile::tempdir().unwrap();
+    let mut config = Config::new();
+    config.tools.enabled = true;
+
+    let client = LLMClient::new_replay(fixtures.path()).unwrap();
+    let project = Project::new(workdir.path().to_path_buf());
+    let (_rx, nixcode) = Nixcode::new(project, client, config.clone()).unwrap();
+    let all_tools = nixcode.tools.get_request_tools(&config);
+    let footprint = nixcode.get_tool_schema_tokens();
+    assert_eq!(footprint, crate::tools::estimate_schema_tokens(&all_tools));
+    assert!(footprint > 0);
+
+    config.tools.max_schema_tokens = Some(footprint / 2);
+    let capped_tools = nixcode.tools.get_request_tools(&config);
+    assert!(crate::tools::estimate_schema_tokens(&capped_tools) <= footprint / 2);
+    assert!(!capped_tools.is_empty());
+    assert!(capped_tools.len() < all_tools.len());
+
+    config.tools.max_schema_tokens = Some(0);
+    let core_tools = nixcode.tools.get_request_tools(&config);
+    assert!(core_tools.iter().any(|tool| tool.get_name() == "read_text_file"));
+    assert!(core_tools.iter().any(|tool| tool.get_name() == "write_text_file"));
+}
+
+#[tokio::test]
+async fn test_memory_is_added_to_next_system_prompt() {
+    let workdir = tempfile::tempdir().unwrap();
+    let fixtures = tempfile::tempdir().unwrap();
+    let client = LLMClient::new_replay(fixtures.path()).unwrap();
+    let project = Project::new(workdir.path().to_path_buf());
+    let (_rx, nixcode) = Nixcode::new(project, client, Config::new()).unwrap();
+    assert_eq!(nixcode.get_system_prompt().len(), 1);
+
+    let params = crate::tools::memory::append_memory::AppendMemoryParams {
+        note: "Keep the public API unchanged".into(),
+    };
+    crate::tools::memory::append_memory::append_memory(params, nixcode.project.clone()).await;
+
+    let system_prompt = nixcode.get_system_prompt();
+    assert_eq!(system_prompt.len(), 2);
+    let Content::Text(memory) = &system_prompt[1] else {
+        panic!("Expected text");
+    };
+    assert_eq!(
+        memory.get_text(),
+        "File: .nixcode/memory.md\n\n- Keep the public API unchanged"
+    );
+}
+
+#[test]
+fn test_image_is_attached_only_for_vision_models() {
+    let workdir = tempfile::tempdir().unwrap();
+    std::fs::write(workdir.path().join("screenshot.png"), b"\x89PNG").unwrap();
+    let fixtures = tempfile::tempdir().unwrap();
+
+    let client = LLMClient::new_replay(fixtures.path()).unwrap();
+    let project = Project::new(workdir.path().to_path_buf());
+    let (_rx, nixcode) = Nixcode::new(project, client, Config::new()).unwrap();
+    let nixcode = nixcode.with_model("claude-3-7-sonnet-20250219");
+
+    let image = nixcode.load_image("screenshot.png").unwrap();
+    let request = Request::default().with_messages(vec![Message::User(vec![
+        image,
+        Content::new_text("What is wrong here?"),
+    ])]);
+    let body = serde_json::to_value(&request).unwrap();
+    assert_eq!(
+        body["messages"][0]["content"][0],
+        serde_json::json!({
+            "type": "image",
+            "source": { "type": "base64", "media_type": "image/png", "data": "iVBORw==" }
+        })
+    );
+
+    let nixcode = nixcode.with_model("gpt-3.5-turbo");
+    assert!(!nixcode.supports_vision());
+    assert_eq!(
+        nixcode.load_image("screenshot.png").unwrap_err(),
+        "Model gpt-3.5-turbo does not accept images"
+    );
+}
+
+#[tokio::test]
+async fn test_partial_tool_arguments_return_corrective_result() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let tool_turn = tool_use_turn("msg_1", "read_text_file", r#"{"path": "hel"#);
+    write_fixture(fixtures.path(), "0001", &tool_turn);
+
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), tools_config());
+    send_user_text(&nixcode, "Read hello.txt").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::ToolsFinished)
+    })
+    .await;
+
+    let results = nixcode.tools_results.read().await.clone();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].is_error());
+    assert!(results[0]
+        .get_content()
+        .contains("Call the tool again with complete JSON arguments"));
+}
+
+#[test]
+fn test_provider_without_key_is_rejected() {
+    let workdir = tempfile::tempdir().unwrap();
+    let mut config = Config::new();
+    config.llm.default_provider = "openai".into();
+    config.providers.openai.api_key = Some("${NIXCODE_TEST_UNSET_OPENAI_KEY}".into());
+
+    let result = Nixcode::new_with_config(Project::new(workdir.path().to_path_buf()), config);
+
+    let Err(LLMError::CreateClientError(message)) = result else {
+        panic!("Expected the provider to be rejected");
+    };
+    assert_eq!(
+        message,
+        "Cannot use provider openai: API key for openai is empty or uses an unset variable"
+    );
+}
+
+#[test]
+fn test_file_prompt_variable_is_expanded() {
+    let workdir = tempfile::tempdir().unwrap();
+    std::fs::write(workdir.path().join("notes.md"), "Use tabs.\n").unwrap();
+    let project = Project::new(workdir.path().to_path_buf());
+
+    let expanded =
+        templates::expand_prompt_variables("Follow ${file:notes.md}, keep ${HOME}", &project);
+    assert_eq!(expanded.unwrap(), "Follow Use tabs.\n, keep ${HOME}");
+
+    let missing = templates::expand_prompt_variables("${file:missing.md}", &project);
+    assert!(missing.unwrap_err().starts_with("${file:missing.md}: "));
+}
+
+#[tokio::test]
+async fn test_template_variables_are_expanded() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let templates_dir = fixtures.path().join(templates::TEMPLATES_DIR);
+    std::fs::create_dir_all(&templates_dir).unwrap();
+    std::fs::write(
+        templates_dir.join("review.md"),
+        "Review against ${file:notes.md}",
+    )
+    .unwrap();
+    std::fs::write(fixtures.path().join("notes.md"), "Use tabs.").unwrap();
+    let (nixcode, _rx) = replay_nixcode(fixtures.path(), Config::new());
+
+    let rendered = nixcode.render_template("review").await;
+    assert_eq!(rendered.unwrap(), "Review against Use tabs.");
+
+    let missing = nixcode.render_template("missing").await;
+    assert!(missing.unwrap_err().starts_with("Template missing: "));
+    assert!(nixcode.render_template("../notes").await.is_err());
+}
+
+#[test]
+fn test_git_diff_prompt_variable_is_expanded() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = git2::Repository::init(workdir.path()).unwrap();
+    let signature = git2::Signature::now("nixcode", "nixcode@example.com").unwrap();
+    std::fs::write(workdir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("lib.rs")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    repo.commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])
+        .unwrap();
+    std::fs::write(workdir.path().join("lib.rs"), "fn b() {}\n").unwrap();
+    let project = Project::new(workdir.path().to_path_buf());
+
+    let expanded = templates::expand_prompt_variables("Review:\n${git_diff}", &project).unwrap();
+
+    assert!(expanded.starts_with("Review:\ndiff --git a/lib.rs b/lib.rs"));
+    assert!(expanded.contains("-fn a() {}\n+fn b() {}\n"));
+}
+
+#[tokio::test]
+async fn test_stalled_stream_ends_turn_after_idle_timeout() {
+    let fixtures = tempfile::tempdir().unwrap();
+    let mut stalled_turn = text_turn("msg_0", "Let me", "end_turn");
+    stalled_turn.truncate(3);
+    stalled_turn.push(serde_json::json!({ "stall": true }));
+    write_fixture(fixtures.path(), "0000", &stalled_turn);
+
+    let mut config = Config::new();
+    config.agent.stream_idle_timeout_secs = 1;
+    let (nixcode, mut rx) = replay_nixcode(fixtures.path(), config);
+    send_user_text(&nixcode, "Hi").await;
+    wait_for(&mut rx, |event| {
+        matches!(event, NixcodeEvent::GeneratedResponse)
+    })
+    .await;
+
+    assert!(!nixcode.is_waiting().await);
+    assert_eq!(
+        nixcode.get_error().await.unwrap().get_type(),
+        "stream_idle_timeout"
+    );
+}
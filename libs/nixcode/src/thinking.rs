@@ -0,0 +1,85 @@
+use nixcode_llm_sdk::message::content::Content;
+
+/// Rough number of characters per token, the API does not report thinking tokens separately
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimated number of tokens spent on thinking blocks in a message
+pub fn estimate_thinking_tokens(content: &[Content]) -> u32 {
+    let chars: usize = content
+        .iter()
+        .map(|content| match content {
+            Content::Thinking(thinking) => thinking.get_text().len(),
+            _ => 0,
+        })
+        .sum();
+
+    chars.div_ceil(CHARS_PER_TOKEN) as u32
+}
+
+/// Thinking token usage of the conversation
+#[derive(Debug, Clone, Default)]
+pub struct ThinkingUsage {
+    /// Estimated thinking tokens of the whole conversation
+    pub total_tokens: u32,
+    /// Estimated thinking tokens of the last response
+    pub last_response_tokens: u32,
+    /// Output token limit of a single response
+    pub max_tokens: u32,
+    /// Fraction of max tokens thinking may use before a warning is shown
+    pub warning_fraction: f32,
+}
+
+impl ThinkingUsage {
+    /// Returns true if the last response spent more than the allowed fraction of
+    /// its output budget on thinking, leaving little room for the answer
+    pub fn exceeds_warning_threshold(&self) -> bool {
+        if self.max_tokens == 0 {
+            return false;
+        }
+
+        self.last_response_tokens as f32 > self.max_tokens as f32 * self.warning_fraction
+    }
+
+    /// Percentage of max tokens used by thinking in the last response
+    pub fn last_response_percent(&self) -> u32 {
+        if self.max_tokens == 0 {
+            return 0;
+        }
+
+        (self.last_response_tokens as u64 * 100 / self.max_tokens as u64) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thinking_warning_threshold() {
+        let mut usage = ThinkingUsage {
+            total_tokens: 0,
+            last_response_tokens: 800,
+            max_tokens: 1000,
+            warning_fraction: 0.8,
+        };
+        assert!(!usage.exceeds_warning_threshold());
+        assert_eq!(usage.last_response_percent(), 80);
+
+        usage.last_response_tokens = 801;
+        assert!(usage.exceeds_warning_threshold());
+
+        usage.max_tokens = 0;
+        assert!(!usage.exceeds_warning_threshold());
+    }
+
+    #[test]
+    fn test_estimate_thinking_tokens() {
+        let content: Vec<Content> = serde_json::from_value(serde_json::json!([
+            { "type": "thinking", "thinking": "abcdefghi", "signature": "" },
+            { "type": "text", "text": "not counted" }
+        ]))
+        .unwrap();
+
+        assert_eq!(estimate_thinking_tokens(&content), 3);
+    }
+}
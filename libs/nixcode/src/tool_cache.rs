@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+use serde_json::Value;
+
+/// Read-only tools whose results are cached when `tools.cache_read_results` is enabled
+pub const CACHED_TOOLS: &[&str] = &[
+    "read_text_file",
+    "read_files",
+    "read_symbol",
+    "outline_file",
+    "search_glob_files",
+    "search_content",
+];
+
+/// Modification time and size of a file, None when it does not exist
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// State of the inputs of a tool call, taken before the tool runs so changes made
+/// while it runs invalidate its result
+pub struct InputStamp {
+    /// Files named in the arguments
+    files: Vec<(PathBuf, FileStamp)>,
+    generation: u64,
+}
+
+struct CachedResult {
+    stamp: InputStamp,
+    result: Value,
+}
+
+/// Results of read-only tools keyed by tool name and arguments.
+///
+/// Results of tools reading named files (`path` or `paths` arguments) stay valid while
+/// those files are unchanged. Results of tools scanning the project (glob and content
+/// search) stay valid until `invalidate` is called, which happens whenever another
+/// tool runs or the user sends a message.
+#[derive(Default)]
+pub struct ToolResultCache {
+    entries: Mutex<HashMap<String, CachedResult>>,
+    generation: AtomicU64,
+}
+
+impl ToolResultCache {
+    pub fn is_cached_tool(name: &str) -> bool {
+        CACHED_TOOLS.contains(&name)
+    }
+
+    /// Cached result of the call, None when it was not cached or its inputs changed
+    pub fn get(&self, name: &str, params: &Value, cwd: &Path) -> Option<Value> {
+        let entries = self.entries.lock().unwrap();
+        let entry = entries.get(&cache_key(name, params))?;
+
+        let stamp = self.stamp(params, cwd);
+        let is_valid = if stamp.files.is_empty() {
+            entry.stamp.generation == stamp.generation
+        } else {
+            entry.stamp.files == stamp.files
+        };
+
+        is_valid.then(|| entry.result.clone())
+    }
+
+    /// Current state of the inputs of a call, to insert its result with
+    pub fn stamp(&self, params: &Value, cwd: &Path) -> InputStamp {
+        InputStamp {
+            files: stamp_files(params, cwd),
+            generation: self.generation.load(Ordering::SeqCst),
+        }
+    }
+
+    pub fn insert(&self, name: &str, params: &Value, stamp: InputStamp, result: Value) {
+        let entry = CachedResult { stamp, result };
+
+        self.entries
+            .lock()
+            .unwrap()
+            .insert(cache_key(name, params), entry);
+    }
+
+    /// Invalidates the results of tools scanning the project
+    pub fn invalidate(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn cache_key(name: &str, params: &Value) -> String {
+    format!("{}:{}", name, params)
+}
+
+/// Stamps of the files named by the `path` and `paths` arguments
+fn stamp_files(params: &Value, cwd: &Path) -> Vec<(PathBuf, FileStamp)> {
+    let paths = params["path"].as_str().into_iter().chain(
+        params["paths"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str),
+    );
+
+    paths
+        .map(|path| {
+            let path = cwd.join(path);
+            let stamp = std::fs::metadata(&path)
+                .ok()
+                .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+            (path, stamp)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_cache_hits_until_the_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\n").unwrap();
+        let cache = ToolResultCache::default();
+        let params = serde_json::json!({ "path": "lib.rs" });
+
+        let result = serde_json::json!("fn a() {}\n");
+
+        let stamp = cache.stamp(&params, dir.path());
+        cache.insert("read_text_file", &params, stamp, result.clone());
+        assert_eq!(
+            cache.get("read_text_file", &params, dir.path()),
+            Some(result)
+        );
+        assert_eq!(cache.get("read_symbol", &params, dir.path()), None);
+
+        std::fs::write(dir.path().join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        assert_eq!(cache.get("read_text_file", &params, dir.path()), None);
+    }
+
+    #[test]
+    fn test_tool_cache_search_results_are_invalidated() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ToolResultCache::default();
+        let params = serde_json::json!({ "pattern": "fn", "glob_pattern": "**/*.rs" });
+
+        let result = serde_json::json!("No matches found");
+
+        let stamp = cache.stamp(&params, dir.path());
+        cache.insert("search_content", &params, stamp, result);
+        assert!(cache.get("search_content", &params, dir.path()).is_some());
+
+        cache.invalidate();
+        assert_eq!(cache.get("search_content", &params, dir.path()), None);
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::tools::Tool;
+use crate::utils::html::{fetch_html, find_element, html_to_text};
+
+/// Maximum number of characters of documentation returned for an item
+const MAX_DOCS_CHARS: usize = 8_000;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct DocsRsParams {
+    #[schemars(description = "Name of the crate, e.g. serde_json")]
+    pub crate_name: String,
+
+    #[schemars(description = "Version of the crate (default: latest)")]
+    #[serde(default)]
+    pub version: Option<String>,
+
+    #[schemars(
+        description = "Path of the item, e.g. Value or de::Deserializer (default: the crate documentation)"
+    )]
+    #[serde(default)]
+    pub item: Option<String>,
+}
+
+/// Finds the link of an item in a docs.rs `all.html` page. The item matches a listed
+/// path exactly or as its last segments (`Deserializer` matches `de::Deserializer`)
+pub fn find_item_link(all_html: &str, item: &str) -> Option<String> {
+    let suffix = format!("::{}", item);
+    let mut partial_match = None;
+    let mut rest = all_html;
+
+    while let Some(start) = rest.find("<a href=\"") {
+        rest = &rest[start + "<a href=\"".len()..];
+        let Some((href, after_href)) = rest.split_once('"') else {
+            break;
+        };
+        let Some(text_start) = after_href.find('>') else {
+            break;
+        };
+        let Some((text, _)) = after_href[text_start + 1..].split_once("</a>") else {
+            break;
+        };
+
+        let path = html_to_text(text);
+        if path == item {
+            return Some(href.to_string());
+        }
+        if partial_match.is_none() && path.ends_with(&suffix) {
+            partial_match = Some(href.to_string());
+        }
+    }
+
+    partial_match
+}
+
+/// Extracts the declaration and the top-level documentation of a docs.rs item page
+pub fn extract_item_docs(html: &str) -> Option<String> {
+    let content = find_element(html, "<section id=\"main-content\"").unwrap_or(html);
+    let title = find_element(content, "<h1").map(html_to_text);
+    let declaration = find_element(content, "<pre class=\"rust item-decl\"").map(html_to_text);
+    let docs = find_element(content, "<div class=\"docblock\"").map(html_to_text);
+
+    if declaration.is_none() && docs.is_none() {
+        return None;
+    }
+
+    let mut text = String::new();
+    if let Some(title) = title {
+        text.push_str(&format!("{}\n\n", title.replace('\n', " ")));
+    }
+    if let Some(declaration) = declaration {
+        text.push_str(&format!("```rust\n{}\n```\n\n", declaration));
+    }
+    if let Some(docs) = docs {
+        text.push_str(&docs);
+    }
+
+    let text = text.trim_end();
+    if text.chars().count() > MAX_DOCS_CHARS {
+        let cut: String = text.chars().take(MAX_DOCS_CHARS).collect();
+        return Some(format!("{}\n[documentation truncated]", cut));
+    }
+
+    Some(text.to_string())
+}
+
+/// Allows only characters used in crate names and versions, both are part of the URL
+fn is_valid_segment(segment: &str) -> bool {
+    !segment.is_empty()
+        && segment
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+'))
+}
+
+/// Fetches item documentation from docs.rs. Pages are cached for the session, so
+/// looking up several items of the same crate version fetches its index only once.
+#[derive(Default)]
+pub struct DocsRsTool {
+    pub(crate) pages: Mutex<HashMap<String, String>>,
+}
+
+impl DocsRsTool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    async fn fetch_page(&self, url: &str) -> Result<String, String> {
+        if let Some(page) = self.pages.lock().unwrap().get(url) {
+            return Ok(page.clone());
+        }
+
+        let page = fetch_html(url).await?;
+        self.pages
+            .lock()
+            .unwrap()
+            .insert(url.to_string(), page.clone());
+
+        Ok(page)
+    }
+
+    async fn get_docs(&self, params: &DocsRsParams) -> Result<String, String> {
+        let version = params.version.as_deref().unwrap_or("latest");
+        if !is_valid_segment(&params.crate_name) || !is_valid_segment(version) {
+            return Err("Invalid crate name or version".into());
+        }
+
+        let base_url = format!(
+            "https://docs.rs/{}/{}/{}/",
+            params.crate_name,
+            version,
+            params.crate_name.replace('-', "_")
+        );
+
+        let page_url = match params.item.as_deref().map(str::trim) {
+            None | Some("") => format!("{}index.html", base_url),
+            Some(item) => {
+                let all_html = self.fetch_page(&format!("{}all.html", base_url)).await?;
+                let link = find_item_link(&all_html, item).ok_or_else(|| {
+                    format!(
+                        "Item {} not found in {} {}",
+                        item, params.crate_name, version
+                    )
+                })?;
+                format!("{}{}", base_url, link)
+            }
+        };
+
+        let page = self.fetch_page(&page_url).await?;
+        extract_item_docs(&page).ok_or_else(|| format!("No documentation found at {}", page_url))
+    }
+}
+
+#[async_trait]
+impl Tool for DocsRsTool {
+    fn get_name(&self) -> String {
+        "docs_rs".into()
+    }
+
+    fn get_schema(&self) -> nixcode_llm_sdk::tools::Tool {
+        let schema = schemars::schema_for!(DocsRsParams);
+        let parameters = serde_json::to_value(&schema).unwrap();
+
+        nixcode_llm_sdk::tools::Tool::new(
+            self.get_name(),
+            "Get the signature and documentation of a crate item from docs.rs".into(),
+            parameters,
+        )
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _project: Arc<Project>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let params: DocsRsParams = serde_json::from_value(params)?;
+
+        match self.get_docs(&params).await {
+            Ok(docs) => Ok(json!(docs)),
+            Err(e) => Ok(json!(e)),
+        }
+    }
+}
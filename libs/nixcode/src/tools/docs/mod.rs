@@ -0,0 +1,3 @@
+pub mod docs_rs;
+#[cfg(test)]
+mod tests;
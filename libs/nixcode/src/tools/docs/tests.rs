@@ -0,0 +1,67 @@
+use super::*;
+use crate::project::Project;
+use crate::tools::Tool;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+const ALL_ITEMS_HTML: &str = include_str!("fixtures/all.html");
+const FROM_STR_HTML: &str = include_str!("fixtures/fn.from_str.html");
+
+#[test]
+fn test_find_item_link() {
+    assert_eq!(
+        docs_rs::find_item_link(ALL_ITEMS_HTML, "from_str").as_deref(),
+        Some("fn.from_str.html")
+    );
+    assert_eq!(
+        docs_rs::find_item_link(ALL_ITEMS_HTML, "de::Deserializer").as_deref(),
+        Some("de/struct.Deserializer.html")
+    );
+    assert_eq!(
+        docs_rs::find_item_link(ALL_ITEMS_HTML, "from_reader").as_deref(),
+        Some("de/fn.from_reader.html")
+    );
+    assert_eq!(docs_rs::find_item_link(ALL_ITEMS_HTML, "to_string"), None);
+}
+
+#[test]
+fn test_extract_item_docs() {
+    let docs = docs_rs::extract_item_docs(FROM_STR_HTML).unwrap();
+
+    assert!(docs.starts_with("Function serde_json::from_str\n\n```rust\n"));
+    assert!(docs.contains(
+        "pub fn from_str<'a, T>(s: &'a str) -> Result<T>\nwhere\n    T: Deserialize<'a>,"
+    ));
+    assert!(docs.contains("Deserialize an instance of type T from a string of JSON text."));
+    assert!(docs.contains("let v: Value = serde_json::from_str(\"{}\")?;"));
+    assert!(docs.ends_with("does not match the structure expected by T."));
+    assert!(!docs.contains("Copy item path"));
+    assert!(!docs.contains("display:none"));
+}
+
+#[tokio::test]
+async fn test_docs_rs_uses_cached_pages() {
+    let tool = docs_rs::DocsRsTool::new();
+    let base_url = "https://docs.rs/serde_json/1.0.140/serde_json/";
+    {
+        let mut pages = tool.pages.lock().unwrap();
+        pages.insert(format!("{}all.html", base_url), ALL_ITEMS_HTML.into());
+        pages.insert(
+            format!("{}fn.from_str.html", base_url),
+            FROM_STR_HTML.into(),
+        );
+    }
+
+    let project = Arc::new(Project::new(PathBuf::from("/tmp")));
+    let params = serde_json::json!({
+        "crate_name": "serde_json",
+        "version": "1.0.140",
+        "item": "from_str"
+    });
+    let result = tool.execute(params, project.clone()).await.unwrap();
+    assert!(result.as_str().unwrap().contains("pub fn from_str"));
+
+    let params = serde_json::json!({ "crate_name": "serde_json/../x" });
+    let result = tool.execute(params, project).await.unwrap();
+    assert_eq!(result, "Invalid crate name or version");
+}
@@ -5,7 +5,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
+use crate::tools::path_if_succeeded;
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct CreateFileParams {
@@ -13,26 +14,27 @@ pub struct CreateFileParams {
     pub path: String,
 }
 
-#[tool("Create empty file in given path")]
+fn changed_path(params: &serde_json::Value, result: &str) -> Vec<String> {
+    path_if_succeeded(params, result, "File created")
+}
+
+#[tool("Create empty file in given path", changed_paths = changed_path)]
 pub async fn create_file(
     params: CreateFileParams,
     project: std::sync::Arc<Project>,
 ) -> serde_json::Value {
     use crate::utils::fs;
-    use tokio::fs::File;
-    use tokio::io::AsyncWriteExt;
 
     let file_path = PathBuf::from(params.path);
 
     let cwd = project.get_cwd();
-    let path = fs::join_path(cwd.clone(), file_path);
-    if path.is_err() {
-        return json!(path.unwrap_err().to_string());
-    }
+    let path = match fs::resolve_path_in_dir(&cwd, file_path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
 
-    let path = path.unwrap();
-    if !path.starts_with(cwd) {
-        return json!("Path must be inside project directory");
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
     }
 
     // create directories if they don't exist
@@ -42,13 +44,8 @@ pub async fn create_file(
         return json!(create_dirs_result.unwrap_err().to_string());
     }
 
-    let file = File::create(&path).await;
-
-    match file {
-        Ok(mut f) => {
-            f.write_all(b"").await.unwrap();
-            json!("File created")
-        }
+    match fs::write_file_atomic(&path, b"").await {
+        Ok(()) => json!("File created"),
         Err(e) => json!(e.to_string()),
     }
 }
@@ -6,7 +6,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
+use crate::tools::path_if_succeeded;
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct DeleteFileParams {
@@ -14,7 +15,11 @@ pub struct DeleteFileParams {
     pub path: String,
 }
 
-#[tool("Delete file")]
+fn changed_path(params: &serde_json::Value, result: &str) -> Vec<String> {
+    path_if_succeeded(params, result, "File removed")
+}
+
+#[tool("Delete file", changed_paths = changed_path)]
 pub async fn delete_file(params: DeleteFileParams, project: Arc<Project>) -> serde_json::Value {
     use crate::utils::fs;
     use tokio::fs::remove_file;
@@ -22,14 +27,13 @@ pub async fn delete_file(params: DeleteFileParams, project: Arc<Project>) -> ser
     let file_path = PathBuf::from(params.path);
 
     let cwd = project.get_cwd();
-    let path = fs::join_path(cwd.clone(), file_path);
-    if path.is_err() {
-        return json!(path.unwrap_err().to_string());
-    }
+    let path = match fs::resolve_path_in_dir(&cwd, file_path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
 
-    let path = path.unwrap();
-    if !path.starts_with(cwd) {
-        return json!("Path must be inside project directory");
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
     }
 
     let file = remove_file(&path).await;
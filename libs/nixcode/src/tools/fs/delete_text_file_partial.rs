@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
 use crate::tools::fs::file_partial_utils;
 use nixcode_macros::tool;
 use schemars::JsonSchema;
@@ -30,7 +30,7 @@ pub async fn delete_text_file_partial(
     project: Arc<Project>,
 ) -> serde_json::Value {
     // Validate and resolve file path
-    let path = match file_partial_utils::validate_file_path(&params.path, project) {
+    let path = match file_partial_utils::validate_file_path(&params.path, project.clone()) {
         Ok(p) => p,
         Err(e) => return e,
     };
@@ -47,6 +47,15 @@ pub async fn delete_text_file_partial(
         Err(e) => return json!(e),
     };
 
+    let updated_content = match project.format_for_write(&path, updated_content).await {
+        Ok(content) => content,
+        Err(e) => return json!(e),
+    };
+
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     // Write the updated content back to the file
     match file_partial_utils::write_file_content(&path, &updated_content, "deleted").await {
         Ok(message) => json!(message),
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use git2::{DiffOptions, Patch};
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::utils::fs::resolve_path_in_dir;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct DiffFilesParams {
+    #[schemars(description = "Relative path to the original file")]
+    pub path: String,
+
+    #[schemars(description = "Relative path to the file to compare with")]
+    #[serde(default)]
+    pub other_path: Option<String>,
+
+    #[schemars(
+        description = "Text to compare the file with, instead of other_path (e.g. a generated version of the file)"
+    )]
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+/// Unified diff turning `old` into `new`, empty when both are equal
+pub fn unified_diff(
+    old_path: &str,
+    old: &str,
+    new_path: &str,
+    new: &str,
+) -> Result<String, git2::Error> {
+    let mut options = DiffOptions::new();
+    let mut patch = Patch::from_buffers(
+        old.as_bytes(),
+        Some(old_path.as_ref()),
+        new.as_bytes(),
+        Some(new_path.as_ref()),
+        Some(&mut options),
+    )?;
+
+    // A patch of equal buffers still has the file header
+    if patch.num_hunks() == 0 {
+        return Ok(String::new());
+    }
+
+    let buf = patch.to_buf()?;
+    let diff = String::from_utf8_lossy(&buf);
+
+    // The blob ids of the index line do not exist in any repository
+    Ok(diff
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with("index "))
+        .collect())
+}
+
+async fn read_project_file(project: &Project, path: &str) -> Result<String, String> {
+    let resolved = resolve_path_in_dir(&project.get_cwd(), path).map_err(|e| e.to_string())?;
+
+    tokio::fs::read_to_string(resolved)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+#[tool("Get a unified diff between two project files, or between a file and the given content")]
+pub async fn diff_files(params: DiffFilesParams, project: Arc<Project>) -> serde_json::Value {
+    let old = match read_project_file(&project, &params.path).await {
+        Ok(content) => content,
+        Err(e) => return json!(e),
+    };
+
+    let (new_path, new) = match (params.other_path, params.content) {
+        (Some(other_path), None) => match read_project_file(&project, &other_path).await {
+            Ok(content) => (other_path, content),
+            Err(e) => return json!(e),
+        },
+        (None, Some(content)) => (params.path.clone(), content),
+        _ => return json!("Provide either other_path or content"),
+    };
+
+    match unified_diff(&params.path, &old, &new_path, &new) {
+        Ok(diff) if diff.is_empty() => json!("No differences"),
+        Ok(diff) => json!(diff),
+        Err(e) => json!(format!("Failed to compute the diff: {}", e)),
+    }
+}
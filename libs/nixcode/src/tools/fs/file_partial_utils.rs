@@ -1,10 +1,10 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::project::Project;
 use serde_json::json;
-use tokio::fs::{File, OpenOptions};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::fs::File;
+use tokio::io::AsyncReadExt;
 
 /// Helper function to read file content
 pub async fn read_file_content(path: &PathBuf) -> Result<String, String> {
@@ -23,21 +23,11 @@ pub async fn read_file_content(path: &PathBuf) -> Result<String, String> {
 
 /// Helper function to write file content
 pub async fn write_file_content(
-    path: &PathBuf,
+    path: &Path,
     content: &str,
     operation_type: &str,
 ) -> Result<String, String> {
-    let mut file = match OpenOptions::new()
-        .write(true)
-        .truncate(true)
-        .open(path)
-        .await
-    {
-        Ok(f) => f,
-        Err(e) => return Err(format!("Failed to open file for writing: {}", e)),
-    };
-
-    match file.write_all(content.as_bytes()).await {
+    match crate::utils::fs::write_file_atomic(path, content.as_bytes()).await {
         Ok(_) => Ok(format!("File partially {}", operation_type)),
         Err(e) => Err(format!("Failed to write to file: {}", e)),
     }
@@ -53,17 +43,28 @@ pub fn validate_file_path(
     // Validate and resolve file path
     let file_path = PathBuf::from(file_path);
     let cwd = project.get_cwd();
-    let path = match fs::join_path(cwd.clone(), file_path) {
-        Ok(p) => p,
-        Err(e) => return Err(json!(e.to_string())),
-    };
+    // Ensure path is within project directory, also after resolving symlinks
+    fs::resolve_path_in_dir(&cwd, file_path).map_err(|e| json!(e.to_string()))
+}
 
-    // Ensure path is within project directory
-    if !path.starts_with(cwd) {
-        return Err(json!("Path must be inside project directory"));
+/// Helper function to replace a snippet that must occur exactly once in the content
+pub fn replace_unique_snippet(
+    current_content: &str,
+    old_content: &str,
+    new_content: &str,
+) -> Result<String, String> {
+    if old_content.is_empty() {
+        return Err("Snippet to replace must not be empty".to_string());
     }
 
-    Ok(path)
+    match current_content.matches(old_content).count() {
+        0 => Err("Snippet not found in file".to_string()),
+        1 => Ok(current_content.replacen(old_content, new_content, 1)),
+        n => Err(format!(
+            "Snippet is not unique, found {} occurrences. Include more surrounding lines",
+            n
+        )),
+    }
 }
 
 /// Helper function to update content by line range
@@ -1,9 +1,14 @@
 pub mod create_file;
 pub mod delete_file;
 pub mod delete_text_file_partial;
+pub mod diff_files;
 pub mod file_partial_utils;
+pub mod outline_file;
+pub mod read_files;
+pub mod read_symbol;
 pub mod read_text_file;
 #[cfg(test)]
 mod tests;
+pub mod tree;
 pub mod update_text_file_partial;
 pub mod write_text_file;
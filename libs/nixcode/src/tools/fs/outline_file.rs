@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct OutlineFileParams {
+    #[schemars(description = "Relative path to file")]
+    pub path: String,
+}
+
+#[tool("Get the outline of a file (functions, types, impls, headings) with line numbers, cheaper than reading the whole file")]
+pub async fn outline_file(params: OutlineFileParams, project: Arc<Project>) -> serde_json::Value {
+    use crate::utils::fs;
+    use tokio::fs::read_to_string;
+
+    let file_path = PathBuf::from(params.path);
+
+    let cwd = project.get_cwd();
+    let path = match fs::resolve_path_in_dir(&cwd, file_path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let content = match read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let is_rust = path.extension().is_some_and(|ext| ext == "rs");
+    let outline = if is_rust {
+        outline_rust(&content).unwrap_or_else(|| outline_generic(&content))
+    } else {
+        outline_generic(&content)
+    };
+
+    if outline.is_empty() {
+        return json!("No outline items found");
+    }
+
+    json!(outline.join("\n"))
+}
+
+/// Formats the source line of an item as `line: signature`
+fn outline_line(lines: &[&str], line: usize, depth: usize) -> String {
+    let source = lines
+        .get(line.saturating_sub(1))
+        .copied()
+        .unwrap_or_default();
+    let signature = source.trim().trim_end_matches('{').trim_end();
+
+    format!("{}{}: {}", "  ".repeat(depth), line, signature)
+}
+
+/// Outlines top-level Rust items and the functions inside impls and traits
+fn outline_rust(content: &str) -> Option<Vec<String>> {
+    use syn::spanned::Spanned;
+    use syn::{ImplItem, Item, TraitItem};
+
+    let file = syn::parse_file(content).ok()?;
+    let lines: Vec<&str> = content.lines().collect();
+    let mut outline = Vec::new();
+
+    for item in &file.items {
+        let line = match item {
+            Item::Fn(item) => item.sig.ident.span().start().line,
+            Item::Struct(item) => item.ident.span().start().line,
+            Item::Enum(item) => item.ident.span().start().line,
+            Item::Union(item) => item.ident.span().start().line,
+            Item::Trait(item) => item.ident.span().start().line,
+            Item::Mod(item) => item.ident.span().start().line,
+            Item::Type(item) => item.ident.span().start().line,
+            Item::Const(item) => item.ident.span().start().line,
+            Item::Static(item) => item.ident.span().start().line,
+            Item::Impl(item) => item.impl_token.span().start().line,
+            Item::Macro(item) if item.ident.is_some() => item.mac.path.span().start().line,
+            _ => continue,
+        };
+        outline.push(outline_line(&lines, line, 0));
+
+        match item {
+            Item::Impl(item) => {
+                for impl_item in &item.items {
+                    if let ImplItem::Fn(function) = impl_item {
+                        let line = function.sig.ident.span().start().line;
+                        outline.push(outline_line(&lines, line, 1));
+                    }
+                }
+            }
+            Item::Trait(item) => {
+                for trait_item in &item.items {
+                    if let TraitItem::Fn(function) = trait_item {
+                        let line = function.sig.ident.span().start().line;
+                        outline.push(outline_line(&lines, line, 1));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Some(outline)
+}
+
+/// Outlines Markdown headings and common definition keywords of other languages
+fn outline_generic(content: &str) -> Vec<String> {
+    let pattern = Regex::new(
+        r"^\s*(#{1,6}\s+\S|(export\s+)?(default\s+)?(pub\s+)?(async\s+)?(def|class|function|interface|func|fn|struct|enum|trait|impl|module|namespace)\s)",
+    )
+    .unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| pattern.is_match(line))
+        .map(|(index, _)| outline_line(&lines, index + 1, 0))
+        .collect()
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::read_text_file::{read_chunk, CHARS_PER_TOKEN};
+use crate::project::Project;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ReadFilesParams {
+    #[schemars(description = "Relative paths to the files")]
+    pub paths: Vec<String>,
+}
+
+/// Reads one file, holding a permit of the project read semaphore while the file is open
+async fn read_file(project: &Project, path: &str) -> Result<String, String> {
+    let resolved = crate::utils::fs::resolve_path_in_dir(&project.get_cwd(), path)
+        .map_err(|e| e.to_string())?;
+
+    let semaphore = project.get_read_semaphore();
+    let _permit = semaphore.acquire().await;
+    tokio::fs::read_to_string(resolved)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Splits `total` characters between files of the given sizes: small files get what they
+/// need and the rest is shared evenly by the larger ones
+fn split_budget(sizes: &[usize], total: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| sizes[i]);
+
+    let mut budgets = vec![0; sizes.len()];
+    let mut remaining = total;
+    for (done, &i) in order.iter().enumerate() {
+        let share = remaining / (order.len() - done);
+        budgets[i] = sizes[i].min(share);
+        remaining -= budgets[i];
+    }
+
+    budgets
+}
+
+fn format_file(path: &str, content: &str, max_chars: usize) -> String {
+    if content.len() <= max_chars {
+        return format!("==> {} <==\n{}", path, content);
+    }
+
+    let (chunk, next_offset) = read_chunk(content, 0, max_chars);
+    match next_offset {
+        Some(next_offset) => format!(
+            "==> {} <==\n{}\n\n[The file is larger than its share of the read budget, call read_text_file with offset {} to continue]",
+            path, chunk, next_offset
+        ),
+        None => format!("==> {} <==\n{}", path, chunk),
+    }
+}
+
+#[tool("Read the content of several files at once, each preceded by a ==> path <== header")]
+pub async fn read_files(params: ReadFilesParams, project: Arc<Project>) -> serde_json::Value {
+    if params.paths.is_empty() {
+        return json!("No paths given");
+    }
+
+    let max_files = project.get_max_files_per_call();
+    let (paths, remaining) = params.paths.split_at(params.paths.len().min(max_files));
+
+    let files = futures::future::join_all(paths.iter().map(|path| read_file(&project, path))).await;
+    let sizes: Vec<usize> = files
+        .iter()
+        .map(|file| file.as_ref().map_or(0, |content| content.len()))
+        .collect();
+    let budgets = split_budget(&sizes, project.get_read_chunk_tokens() * CHARS_PER_TOKEN);
+
+    let mut result = paths
+        .iter()
+        .zip(files)
+        .zip(budgets)
+        .map(|((path, file), max_chars)| match file {
+            Ok(content) => format_file(path, &content, max_chars),
+            Err(e) => format!("==> {} <==\nError: {}", path, e),
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    if let Some(first) = remaining.first() {
+        result.push_str(&format!(
+            "\n\n[Read {} of {} files, call read_files again with the remaining {} paths, starting with {}]",
+            paths.len(),
+            params.paths.len(),
+            remaining.len(),
+            first
+        ));
+    }
+
+    json!(result)
+}
@@ -0,0 +1,232 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ReadSymbolParams {
+    #[schemars(description = "Relative path to file")]
+    pub path: String,
+
+    #[schemars(
+        description = "Name of the function, type or other item, methods can be qualified with their type (e.g. Point::new)"
+    )]
+    pub name: String,
+}
+
+/// 1-based, inclusive line range of a symbol
+type LineRange = (usize, usize);
+
+#[tool("Read the source of a named function, type or method with its line numbers, cheaper than reading the whole file")]
+pub async fn read_symbol(params: ReadSymbolParams, project: Arc<Project>) -> serde_json::Value {
+    use crate::utils::fs;
+    use tokio::fs::read_to_string;
+
+    let file_path = PathBuf::from(&params.path);
+
+    let cwd = project.get_cwd();
+    let path = match fs::resolve_path_in_dir(&cwd, file_path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let content = match read_to_string(&path).await {
+        Ok(content) => content,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let is_rust = path.extension().is_some_and(|ext| ext == "rs");
+    let ranges = if is_rust {
+        find_rust_symbol(&content, &params.name)
+            .unwrap_or_else(|| find_generic_symbol(&content, &params.name))
+    } else {
+        find_generic_symbol(&content, &params.name)
+    };
+
+    if ranges.is_empty() {
+        return json!(format!(
+            "Symbol {} not found in {}",
+            params.name, params.path
+        ));
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let symbols = ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let source = lines[start - 1..end.min(lines.len())].join("\n");
+            format!("Lines {}-{} of {}:\n{}", start, end, params.path, source)
+        })
+        .collect::<Vec<_>>();
+
+    json!(symbols.join("\n\n"))
+}
+
+/// Line ranges of the Rust items named `name` (with their attributes and doc
+/// comments), `Type::name` only matches items of impls and traits of that type.
+/// None when the file cannot be parsed
+fn find_rust_symbol(content: &str, name: &str) -> Option<Vec<LineRange>> {
+    let file = syn::parse_file(content).ok()?;
+    let (owner, name) = match name.rsplit_once("::") {
+        Some((owner, name)) => (Some(owner.rsplit("::").next().unwrap_or(owner)), name),
+        None => (None, name),
+    };
+
+    let mut ranges = Vec::new();
+    collect_rust_items(&file.items, owner, name, &mut ranges);
+
+    Some(ranges)
+}
+
+fn collect_rust_items(
+    items: &[syn::Item],
+    owner: Option<&str>,
+    name: &str,
+    ranges: &mut Vec<LineRange>,
+) {
+    use syn::spanned::Spanned;
+    use syn::{ImplItem, Item, TraitItem, Type};
+
+    let range = |span: proc_macro2::Span| (span.start().line, span.end().line);
+
+    for item in items {
+        let ident = match item {
+            Item::Fn(item) => Some(&item.sig.ident),
+            Item::Struct(item) => Some(&item.ident),
+            Item::Enum(item) => Some(&item.ident),
+            Item::Union(item) => Some(&item.ident),
+            Item::Trait(item) => Some(&item.ident),
+            Item::Mod(item) => Some(&item.ident),
+            Item::Type(item) => Some(&item.ident),
+            Item::Const(item) => Some(&item.ident),
+            Item::Static(item) => Some(&item.ident),
+            Item::Macro(item) => item.ident.as_ref(),
+            _ => None,
+        };
+        if owner.is_none() && ident.is_some_and(|ident| ident == name) {
+            ranges.push(range(item.span()));
+        }
+
+        match item {
+            Item::Impl(item) => {
+                let self_ty = match item.self_ty.as_ref() {
+                    Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+                    _ => None,
+                };
+                if owner.is_some_and(|owner| self_ty.as_deref() != Some(owner)) {
+                    continue;
+                }
+
+                for impl_item in &item.items {
+                    let ident = match impl_item {
+                        ImplItem::Fn(function) => &function.sig.ident,
+                        ImplItem::Const(constant) => &constant.ident,
+                        ImplItem::Type(alias) => &alias.ident,
+                        _ => continue,
+                    };
+                    if ident == name {
+                        ranges.push(range(impl_item.span()));
+                    }
+                }
+            }
+            Item::Trait(item) => {
+                if owner.is_some_and(|owner| item.ident != owner) {
+                    continue;
+                }
+
+                for trait_item in &item.items {
+                    if let TraitItem::Fn(function) = trait_item {
+                        if function.sig.ident == name {
+                            ranges.push(range(trait_item.span()));
+                        }
+                    }
+                }
+            }
+            Item::Mod(item) => {
+                if let Some((_, items)) = &item.content {
+                    collect_rust_items(items, owner, name, ranges);
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Line ranges of definitions named `name` in other languages, the body ends at the
+/// matching closing brace or, without braces, at the last line indented deeper
+/// than the definition (e.g. Python)
+fn find_generic_symbol(content: &str, name: &str) -> Vec<LineRange> {
+    let name = name.rsplit(['.', ':']).next().unwrap_or(name);
+    let pattern = Regex::new(&format!(
+        r"^\s*(export\s+)?(default\s+)?(pub\s+)?(async\s+)?(def|class|function|interface|func|fn|struct|enum|trait|impl|type|module|namespace)\s+(\([^)]*\)\s*)?{}\b",
+        regex::escape(name)
+    ))
+    .unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| pattern.is_match(line))
+        .map(|(index, _)| {
+            let end =
+                match_braces(&lines, index).unwrap_or_else(|| match_indentation(&lines, index));
+            (index + 1, end + 1)
+        })
+        .collect()
+}
+
+/// Index of the line closing the first brace opened at or after `start`
+fn match_braces(lines: &[&str], start: usize) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut opened = false;
+
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        for character in line.chars() {
+            match character {
+                '{' => {
+                    depth += 1;
+                    opened = true;
+                }
+                '}' if opened => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return Some(index);
+                    }
+                }
+                // A statement ending before any brace has no body (e.g. a declaration)
+                ';' if !opened => return Some(index),
+                _ => (),
+            }
+        }
+
+        // Definitions whose header ends with a colon are indentation based
+        if !opened && line.trim_end().ends_with(':') {
+            return None;
+        }
+    }
+
+    None
+}
+
+/// Index of the last line indented deeper than the line at `start`, ignoring blank lines
+fn match_indentation(lines: &[&str], start: usize) -> usize {
+    let indentation = |line: &str| line.len() - line.trim_start().len();
+    let base = indentation(lines[start]);
+
+    lines
+        .iter()
+        .enumerate()
+        .skip(start + 1)
+        .take_while(|(_, line)| line.trim().is_empty() || indentation(line) > base)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, _)| index)
+        .last()
+        .unwrap_or(start)
+}
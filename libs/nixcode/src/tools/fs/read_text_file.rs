@@ -8,13 +8,56 @@ use serde_json::json;
 
 use crate::project::Project;
 
+/// Rough number of characters per token used to estimate the size of a file
+pub(super) const CHARS_PER_TOKEN: usize = 4;
+
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct ReadTextFileParams {
     #[schemars(description = "Relative path to file")]
     pub path: String,
+
+    #[schemars(
+        description = "Line to continue reading from (0-based), use the offset given by a previous truncated read"
+    )]
+    pub offset: Option<usize>,
 }
 
-#[tool("Read file content")]
+/// Returns the lines starting at `offset` that fit in `max_chars` and the offset of the
+/// first line that did not fit. A single line longer than `max_chars` (e.g. minified
+/// code) is cut with a note, so one line never exceeds the budget
+pub fn read_chunk(content: &str, offset: usize, max_chars: usize) -> (String, Option<usize>) {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut chunk_chars = 0;
+    let mut end = offset;
+
+    while end < lines.len() {
+        let line_chars = lines[end].len() + 1;
+        if end > offset && chunk_chars + line_chars > max_chars {
+            break;
+        }
+        chunk_chars += line_chars;
+        end += 1;
+    }
+
+    let mut chunk = lines[offset.min(lines.len())..end].join("\n");
+    if chunk.len() > max_chars {
+        let cut = (0..=max_chars)
+            .rev()
+            .find(|index| chunk.is_char_boundary(*index))
+            .unwrap_or(0);
+        let omitted = chunk.len() - cut;
+        chunk.truncate(cut);
+        chunk.push_str(&format!(
+            "\n[Line {} is longer than the read budget, {} more bytes omitted]",
+            offset, omitted
+        ));
+    }
+    let next_offset = (end < lines.len()).then_some(end);
+
+    (chunk, next_offset)
+}
+
+#[tool("Read file content, large files are returned in chunks with an offset to continue reading")]
 pub async fn read_text_file(
     params: ReadTextFileParams,
     project: Arc<Project>,
@@ -25,22 +68,38 @@ pub async fn read_text_file(
     let file_path = PathBuf::from(params.path);
 
     let cwd = project.get_cwd();
-    let path = fs::join_path(cwd.clone(), file_path);
-    if path.is_err() {
-        return json!(path.unwrap_err().to_string());
-    }
+    let path = match fs::resolve_path_in_dir(&cwd, file_path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let file = read_to_string(&path).await;
 
-    let path = path.unwrap();
-    if !path.starts_with(cwd) {
-        return json!("Path must be inside project directory");
+    let content = match file {
+        Ok(content) => content,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let max_chars = project.get_read_chunk_tokens() * CHARS_PER_TOKEN;
+    if params.offset.is_none() && content.len() <= max_chars {
+        return json!(content);
     }
 
-    let file = read_to_string(&path).await;
+    let offset = params.offset.unwrap_or(0);
+    let total_lines = content.lines().count();
+    if offset >= total_lines {
+        return json!(format!(
+            "Offset {} is past the end of the file ({} lines)",
+            offset, total_lines
+        ));
+    }
 
-    match file {
-        Ok(content) => {
-            json!(content)
-        }
-        Err(e) => json!(e.to_string()),
+    let (chunk, next_offset) = read_chunk(&content, offset, max_chars);
+    match next_offset {
+        Some(next_offset) => json!(format!(
+            "{}\n\n[Showing lines {}-{} of {}. The file is larger than the read budget, call read_text_file with offset {} to continue]",
+            chunk, offset, next_offset - 1, total_lines, next_offset
+        )),
+        None => json!(chunk),
     }
 }
@@ -84,3 +84,565 @@ async fn test_create_file() {
 
     assert_eq!(result, serde_json::json!("File created"));
 }
+
+#[tokio::test]
+async fn test_update_text_file_partial_replaces_unique_snippet() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("main.rs"), "fn main() {\n    old();\n}\n").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = update_text_file_partial::UpdateTextFilePartialParams {
+        path: String::from("main.rs"),
+        old_content: String::from("    old();"),
+        new_content: String::from("    new();"),
+    };
+
+    let result = update_text_file_partial::update_text_file_partial(params, project).await;
+
+    assert_eq!(result, serde_json::json!("File partially updated"));
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("main.rs")).unwrap(),
+        "fn main() {\n    new();\n}\n"
+    );
+}
+
+#[tokio::test]
+async fn test_update_text_file_partial_rejects_ambiguous_snippet() {
+    let dir = tempfile::tempdir().unwrap();
+    let content = "call();\ncall();\n";
+    std::fs::write(dir.path().join("lib.rs"), content).unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = update_text_file_partial::UpdateTextFilePartialParams {
+        path: String::from("lib.rs"),
+        old_content: String::from("call();"),
+        new_content: String::from("other();"),
+    };
+
+    let result = update_text_file_partial::update_text_file_partial(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "Snippet is not unique, found 2 occurrences. Include more surrounding lines"
+        )
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.path().join("lib.rs")).unwrap(),
+        content
+    );
+}
+
+#[tokio::test]
+async fn test_update_text_file_partial_outside_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = update_text_file_partial::UpdateTextFilePartialParams {
+        path: String::from("./../foo.txt"),
+        old_content: String::from("a"),
+        new_content: String::from("b"),
+    };
+
+    let result = update_text_file_partial::update_text_file_partial(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("Path must be inside project directory")
+    );
+}
+
+#[tokio::test]
+async fn test_write_text_file_is_skipped_when_cancelled() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    project.set_cancelled(true);
+    let params = write_text_file::UpdateTextFileParams {
+        path: String::from("notes.txt"),
+        content: String::from("new"),
+    };
+
+    let result = write_text_file::write_text_file(params, project).await;
+
+    assert_eq!(result, serde_json::json!(crate::project::CANCELLED_MESSAGE));
+    assert!(!dir.path().join("notes.txt").exists());
+}
+
+#[tokio::test]
+async fn test_write_text_file_replaces_content_atomically() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("notes.txt");
+    std::fs::write(&target, "old content that is longer than the new one").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = write_text_file::UpdateTextFileParams {
+        path: String::from("notes.txt"),
+        content: String::from("new"),
+    };
+
+    let result = write_text_file::write_text_file(params, project).await;
+
+    assert_eq!(result, serde_json::json!("File updated"));
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+
+    // No temporary files are left behind next to the target
+    let entries: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_atomic_write_preserves_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("run.sh");
+    std::fs::write(&target, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    crate::utils::fs::write_file_atomic(&target, b"#!/bin/sh\necho hi\n")
+        .await
+        .unwrap();
+
+    let mode = std::fs::metadata(&target).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o755);
+    assert_eq!(
+        std::fs::read_to_string(&target).unwrap(),
+        "#!/bin/sh\necho hi\n"
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_atomic_write_keeps_symlink() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("real.txt");
+    let link = dir.path().join("link.txt");
+    std::fs::write(&target, "old").unwrap();
+    std::os::unix::fs::symlink(&target, &link).unwrap();
+
+    crate::utils::fs::write_file_atomic(&link, b"new")
+        .await
+        .unwrap();
+
+    assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+}
+
+#[tokio::test]
+async fn test_outline_rust_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = r#"use std::fmt;
+
+/// A point
+pub struct Point {
+    x: i32,
+}
+
+impl Point {
+    pub fn new(x: i32) -> Self {
+        Self { x }
+    }
+}
+
+fn helper() -> i32 {
+    1
+}
+"#;
+    std::fs::write(dir.path().join("point.rs"), source).unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = outline_file::OutlineFileParams {
+        path: String::from("point.rs"),
+    };
+
+    let result = outline_file::outline_file(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "4: pub struct Point\n8: impl Point\n  9: pub fn new(x: i32) -> Self\n14: fn helper() -> i32"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_read_symbol_rust_function() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = r#"use std::fmt;
+
+/// A point
+pub struct Point {
+    x: i32,
+}
+
+impl Point {
+    /// Creates a point
+    pub fn new(x: i32) -> Self {
+        Self { x }
+    }
+}
+
+fn helper() -> i32 {
+    1
+}
+"#;
+    std::fs::write(dir.path().join("point.rs"), source).unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+
+    let params = read_symbol::ReadSymbolParams {
+        path: String::from("point.rs"),
+        name: String::from("Point::new"),
+    };
+    let result = read_symbol::read_symbol(params, project.clone()).await;
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "Lines 9-12 of point.rs:\n    /// Creates a point\n    pub fn new(x: i32) -> Self {\n        Self { x }\n    }"
+        )
+    );
+
+    let params = read_symbol::ReadSymbolParams {
+        path: String::from("point.rs"),
+        name: String::from("helper"),
+    };
+    let result = read_symbol::read_symbol(params, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!("Lines 15-17 of point.rs:\nfn helper() -> i32 {\n    1\n}")
+    );
+}
+
+#[tokio::test]
+async fn test_read_symbol_matches_braces_in_other_languages() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = "function a() {\n  if (x) { return 1; }\n}\n\nfunction b() {}\n";
+    std::fs::write(dir.path().join("lib.js"), source).unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = read_symbol::ReadSymbolParams {
+        path: String::from("lib.js"),
+        name: String::from("a"),
+    };
+
+    let result = read_symbol::read_symbol(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("Lines 1-3 of lib.js:\nfunction a() {\n  if (x) { return 1; }\n}")
+    );
+}
+
+#[tokio::test]
+async fn test_outline_markdown_headings() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("README.md"), "# Title\ntext\n## Usage\n").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = outline_file::OutlineFileParams {
+        path: String::from("README.md"),
+    };
+
+    let result = outline_file::outline_file(params, project).await;
+
+    assert_eq!(result, serde_json::json!("1: # Title\n3: ## Usage"));
+}
+
+#[tokio::test]
+async fn test_read_files_stops_at_max_files_per_call() {
+    let dir = tempfile::tempdir().unwrap();
+    for i in 0..5 {
+        std::fs::write(dir.path().join(format!("{}.txt", i)), format!("file {}", i)).unwrap();
+    }
+    let mut project = Project::new(dir.path().to_path_buf());
+    project.set_max_files_per_call(2);
+    project.set_max_concurrent_reads(1);
+    let params = read_files::ReadFilesParams {
+        paths: (0..5).map(|i| format!("{}.txt", i)).collect(),
+    };
+
+    let result = read_files::read_files(params, Arc::new(project)).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "==> 0.txt <==\nfile 0\n\n==> 1.txt <==\nfile 1\n\n[Read 2 of 5 files, call read_files again with the remaining 3 paths, starting with 2.txt]"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_read_files_share_the_read_budget() {
+    let dir = tempfile::tempdir().unwrap();
+    let big = (0..20)
+        .map(|i| format!("line {:02}", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(dir.path().join("big.txt"), big).unwrap();
+    std::fs::write(dir.path().join("small.txt"), "small").unwrap();
+    let mut project = Project::new(dir.path().to_path_buf());
+    project.set_read_chunk_tokens(10);
+    let params = read_files::ReadFilesParams {
+        paths: vec![String::from("big.txt"), String::from("small.txt")],
+    };
+
+    let result = read_files::read_files(params, Arc::new(project)).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "==> big.txt <==\nline 00\nline 01\nline 02\nline 03\n\n[The file is larger than its share of the read budget, call read_text_file with offset 4 to continue]\n\n==> small.txt <==\nsmall"
+        )
+    );
+}
+
+#[test]
+fn test_read_chunk_cuts_long_line() {
+    let content = format!("{}\nnext", "x".repeat(100));
+
+    let (chunk, next_offset) = read_text_file::read_chunk(&content, 0, 40);
+
+    assert_eq!(
+        chunk,
+        format!(
+            "{}\n[Line 0 is longer than the read budget, 60 more bytes omitted]",
+            "x".repeat(40)
+        )
+    );
+    assert_eq!(next_offset, Some(1));
+}
+
+#[tokio::test]
+async fn test_read_large_file_in_chunks() {
+    let dir = tempfile::tempdir().unwrap();
+    let content = (0..20)
+        .map(|i| format!("line {:02}", i))
+        .collect::<Vec<_>>()
+        .join("\n");
+    std::fs::write(dir.path().join("big.txt"), content).unwrap();
+    let mut project = Project::new(dir.path().to_path_buf());
+    project.set_read_chunk_tokens(10);
+    let project = Arc::new(project);
+
+    let params = read_text_file::ReadTextFileParams {
+        path: String::from("big.txt"),
+        offset: None,
+    };
+    let result = read_text_file::read_text_file(params, project.clone()).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("line 00\nline 01\nline 02\nline 03\nline 04\n\n[Showing lines 0-4 of 20. The file is larger than the read budget, call read_text_file with offset 5 to continue]")
+    );
+
+    let params = read_text_file::ReadTextFileParams {
+        path: String::from("big.txt"),
+        offset: Some(15),
+    };
+    let result = read_text_file::read_text_file(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("line 15\nline 16\nline 17\nline 18\nline 19")
+    );
+}
+
+#[tokio::test]
+async fn test_read_small_file_returns_whole_content() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("small.txt"), "Hello\n").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = read_text_file::ReadTextFileParams {
+        path: String::from("small.txt"),
+        offset: None,
+    };
+
+    let result = read_text_file::read_text_file(params, project).await;
+
+    assert_eq!(result, serde_json::json!("Hello\n"));
+}
+
+/// Project directory with `escape` linking to a directory outside of it and
+/// `secret_link` linking to `secret.txt` in that directory
+#[cfg(unix)]
+fn create_escaping_symlinks() -> (tempfile::TempDir, tempfile::TempDir) {
+    let project_dir = tempfile::tempdir().unwrap();
+    let outside_dir = tempfile::tempdir().unwrap();
+    std::fs::write(outside_dir.path().join("secret.txt"), "secret").unwrap();
+    std::os::unix::fs::symlink(outside_dir.path(), project_dir.path().join("escape")).unwrap();
+    std::os::unix::fs::symlink(
+        outside_dir.path().join("secret.txt"),
+        project_dir.path().join("secret_link"),
+    )
+    .unwrap();
+
+    (project_dir, outside_dir)
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_symlink_escaping_project_is_rejected() {
+    let (project_dir, outside_dir) = create_escaping_symlinks();
+    let project = Arc::new(Project::new(project_dir.path().to_path_buf()));
+    let outside = serde_json::json!("Path must be inside project directory");
+
+    for path in ["escape/secret.txt", "secret_link"] {
+        let params = read_text_file::ReadTextFileParams {
+            path: path.to_string(),
+            offset: None,
+        };
+        let result = read_text_file::read_text_file(params, project.clone()).await;
+        assert_eq!(result, outside, "read {}", path);
+    }
+
+    let params = write_text_file::UpdateTextFileParams {
+        path: "escape/new.txt".to_string(),
+        content: "leaked".to_string(),
+    };
+    let result = write_text_file::write_text_file(params, project.clone()).await;
+    assert_eq!(result, outside);
+    assert!(!outside_dir.path().join("new.txt").exists());
+
+    let params = create_file::CreateFileParams {
+        path: "escape/nested/new.txt".to_string(),
+    };
+    let result = create_file::create_file(params, project.clone()).await;
+    assert_eq!(result, outside);
+    assert!(!outside_dir.path().join("nested").exists());
+
+    let params = delete_file::DeleteFileParams {
+        path: "escape/secret.txt".to_string(),
+    };
+    let result = delete_file::delete_file(params, project.clone()).await;
+    assert_eq!(result, outside);
+    assert!(outside_dir.path().join("secret.txt").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_symlink_inside_project_is_allowed() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("docs")).unwrap();
+    std::fs::write(dir.path().join("docs/notes.md"), "notes").unwrap();
+    std::os::unix::fs::symlink(dir.path().join("docs"), dir.path().join("docs_link")).unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+
+    let params = read_text_file::ReadTextFileParams {
+        path: "docs_link/notes.md".to_string(),
+        offset: None,
+    };
+    let result = read_text_file::read_text_file(params, project).await;
+
+    assert_eq!(result, serde_json::json!("notes"));
+}
+
+#[tokio::test]
+async fn test_tree_nested_dirs() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src/tools/fs")).unwrap();
+    std::fs::create_dir_all(dir.path().join(".hidden")).unwrap();
+    std::fs::create_dir_all(dir.path().join("target")).unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+    std::fs::write(dir.path().join("src/tools/mod.rs"), "").unwrap();
+    std::fs::write(dir.path().join("src/tools/fs/tree.rs"), "").unwrap();
+    std::fs::write(dir.path().join(".nixcodeignore"), "target\n").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+
+    let params = tree::TreeParams {
+        path: None,
+        max_depth: None,
+        max_entries: None,
+    };
+    let result = tree::tree(params, project.clone()).await;
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "./\n  src/\n    tools/\n      fs/\n      mod.rs\n    lib.rs\n  Cargo.toml"
+        )
+    );
+
+    let params = tree::TreeParams {
+        path: Some(String::from("src")),
+        max_depth: Some(1),
+        max_entries: Some(1),
+    };
+    let result = tree::tree(params, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "src/\n  tools/\n... and 1 more entries, use a narrower path or a lower max_depth"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_unparseable_rust_write_is_rejected_with_format_on_write() {
+    if std::process::Command::new("rustfmt")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        eprintln!("rustfmt is not installed, skipping");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("main.rs");
+    std::fs::write(&target, "fn main() {}\n").unwrap();
+    let mut project = Project::new(dir.path().to_path_buf());
+    project.set_format_on_write(true);
+    let project = Arc::new(project);
+
+    let params = write_text_file::UpdateTextFileParams {
+        path: String::from("main.rs"),
+        content: String::from("fn main( {\n"),
+    };
+    let result = write_text_file::write_text_file(params, project.clone()).await;
+
+    assert!(result.as_str().unwrap().contains("could not parse"));
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "fn main() {}\n");
+
+    let params = write_text_file::UpdateTextFileParams {
+        path: String::from("main.rs"),
+        content: String::from("fn main(){let x=1;}"),
+    };
+    let result = write_text_file::write_text_file(params, project).await;
+
+    assert_eq!(result, serde_json::json!("File updated"));
+    assert_eq!(
+        std::fs::read_to_string(&target).unwrap(),
+        "fn main() {\n    let x = 1;\n}\n"
+    );
+}
+
+#[tokio::test]
+async fn test_diff_files_returns_unified_diff() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("old.txt"), "one\ntwo\nthree\n").unwrap();
+    std::fs::write(dir.path().join("new.txt"), "one\n2\nthree\n").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+
+    let params = diff_files::DiffFilesParams {
+        path: String::from("old.txt"),
+        other_path: Some(String::from("new.txt")),
+        content: None,
+    };
+    let result = diff_files::diff_files(params, project.clone()).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!(concat!(
+            "diff --git a/old.txt b/new.txt\n",
+            "--- a/old.txt\n",
+            "+++ b/new.txt\n",
+            "@@ -1,3 +1,3 @@\n",
+            " one\n",
+            "-two\n",
+            "+2\n",
+            " three\n"
+        ))
+    );
+
+    let params = diff_files::DiffFilesParams {
+        path: String::from("old.txt"),
+        other_path: None,
+        content: Some(String::from("one\ntwo\nthree\n")),
+    };
+    let result = diff_files::diff_files(params, project).await;
+
+    assert_eq!(result, serde_json::json!("No differences"));
+}
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use walkdir::WalkDir;
+
+use crate::project::Project;
+use crate::utils::fs::{resolve_path_in_dir, ProjectEntryFilter};
+
+const DEFAULT_MAX_DEPTH: usize = 3;
+const DEFAULT_MAX_ENTRIES: usize = 200;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct TreeParams {
+    #[schemars(description = "Relative path to the root directory (default: project root)")]
+    #[serde(default)]
+    pub path: Option<String>,
+
+    #[schemars(description = "Maximum depth of the tree (default: 3)")]
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+
+    #[schemars(description = "Maximum number of entries to show (default: 200)")]
+    #[serde(default)]
+    pub max_entries: Option<usize>,
+}
+
+/// Renders the directory tree of root, one entry per line indented by depth,
+/// directories are suffixed with `/`
+pub fn render_tree(
+    base: &std::path::Path,
+    root: &std::path::Path,
+    max_depth: usize,
+    max_entries: usize,
+) -> String {
+    let filter = ProjectEntryFilter::new(base);
+    let root_name = root
+        .strip_prefix(base)
+        .ok()
+        .filter(|path| !path.as_os_str().is_empty())
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|| String::from("."));
+
+    let mut lines = vec![format!("{}/", root_name)];
+    let mut hidden_entries = 0;
+
+    let entries = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(max_depth)
+        .sort_by(|a, b| {
+            b.file_type()
+                .is_dir()
+                .cmp(&a.file_type().is_dir())
+                .then_with(|| a.file_name().cmp(b.file_name()))
+        })
+        .into_iter()
+        .filter_entry(|entry| filter.is_visible(entry))
+        .filter_map(|entry| entry.ok());
+
+    for entry in entries {
+        if lines.len() > max_entries {
+            hidden_entries += 1;
+            continue;
+        }
+
+        let suffix = if entry.file_type().is_dir() { "/" } else { "" };
+        lines.push(format!(
+            "{}{}{}",
+            "  ".repeat(entry.depth()),
+            entry.file_name().to_string_lossy(),
+            suffix
+        ));
+    }
+
+    if hidden_entries > 0 {
+        lines.push(format!(
+            "... and {} more entries, use a narrower path or a lower max_depth",
+            hidden_entries
+        ));
+    }
+
+    lines.join("\n")
+}
+
+#[tool("Show the directory tree (like `tree`) skipping hidden and ignored files, cheaper than repeated globs")]
+pub async fn tree(params: TreeParams, project: Arc<Project>) -> serde_json::Value {
+    let cwd = project.get_cwd();
+    let path = PathBuf::from(params.path.unwrap_or_else(|| String::from(".")));
+    let root = match resolve_path_in_dir(&cwd, path) {
+        Ok(root) => root,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    if !root.is_dir() {
+        return json!("Path is not a directory");
+    }
+
+    let max_depth = params.max_depth.unwrap_or(DEFAULT_MAX_DEPTH).max(1);
+    let max_entries = params.max_entries.unwrap_or(DEFAULT_MAX_ENTRIES).max(1);
+
+    let tree =
+        tokio::task::spawn_blocking(move || render_tree(&cwd, &root, max_depth, max_entries)).await;
+
+    match tree {
+        Ok(tree) => json!(tree),
+        Err(e) => json!(e.to_string()),
+    }
+}
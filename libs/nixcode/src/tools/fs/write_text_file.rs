@@ -6,9 +6,9 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
+use crate::tools::path_if_succeeded;
 
-// TODO: Optimize this tool to update part of the file instead of rewriting the whole file
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct UpdateTextFileParams {
     #[schemars(description = "Relative path to file")]
@@ -18,37 +18,39 @@ pub struct UpdateTextFileParams {
     pub content: String,
 }
 
+fn changed_path(params: &serde_json::Value, result: &str) -> Vec<String> {
+    path_if_succeeded(params, result, "File updated")
+}
+
 #[tool(
-    "Write file content, overwriting the existing content (use with caution, not for updating part of the file)"
+    "Write file content, overwriting the existing content (use with caution, use update_text_file_partial for updating part of the file)",
+    changed_paths = changed_path
 )]
 pub async fn write_text_file(
     params: UpdateTextFileParams,
     project: Arc<Project>,
 ) -> serde_json::Value {
     use crate::utils::fs;
-    use tokio::fs::File;
-    use tokio::io::AsyncWriteExt;
 
     let file_path = PathBuf::from(params.path);
 
     let cwd = project.get_cwd();
-    let path = fs::join_path(cwd.clone(), file_path);
-    if path.is_err() {
-        return json!(path.unwrap_err().to_string());
+    let path = match fs::resolve_path_in_dir(&cwd, file_path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let content = match project.format_for_write(&path, params.content).await {
+        Ok(content) => content,
+        Err(e) => return json!(e),
+    };
+
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
     }
 
-    let path = path.unwrap();
-    if !path.starts_with(cwd) {
-        return json!("Path must be inside project directory");
-    }
-
-    let file = File::create(&path).await;
-
-    match file {
-        Ok(mut f) => {
-            f.write_all(params.content.as_bytes()).await.unwrap();
-            json!("File updated")
-        }
+    match fs::write_file_atomic(&path, content.as_bytes()).await {
+        Ok(()) => json!("File updated"),
         Err(e) => json!(e.to_string()),
     }
 }
@@ -1,22 +1,33 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 
+use git2::IndexAddOption;
 use nixcode_macros::tool;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use super::utils::resolve_repository;
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitAddParams {
+    #[serde(default)]
     #[schemars(description = "Array of files that will be added to index")]
     pub files: Vec<String>,
+
+    #[schemars(
+        description = "Stage all modifications, new files and deletions (like `git add --all`), files are ignored"
+    )]
+    pub all: Option<bool>,
 }
 
 #[tool("Track changes in git")]
 pub async fn git_add(props: GitAddParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -31,10 +42,43 @@ pub async fn git_add(props: GitAddParams, project: Arc<Project>) -> serde_json::
     }
 
     let mut index = index.unwrap();
+
+    if props.all.unwrap_or(false) || props.files.iter().any(|file| file == ".") {
+        let staged = index
+            .add_all(["*"], IndexAddOption::DEFAULT, None)
+            .and_then(|_| index.update_all(["*"], None))
+            .and_then(|_| index.write());
+
+        return match staged {
+            Ok(()) => json!("Staged all changes"),
+            Err(e) => json!(format!("Cannot stage all changes, reason: {}", e)),
+        };
+    }
+
+    if props.files.is_empty() {
+        return json!("No files given, pass files or set all to true");
+    }
+
     let mut result = String::new();
 
+    let workdir = repository.workdir().map(PathBuf::from).unwrap_or_default();
+
     for file_path in props.files {
         let path = PathBuf::from(file_path.clone());
+
+        // Like `git add`, a tracked file missing on disk stages its deletion
+        let is_deleted = workdir.join(&path).symlink_metadata().is_err()
+            && index.get_path(path.as_path(), 0).is_some();
+        if is_deleted {
+            match index.remove_path(path.as_path()) {
+                Ok(()) => result.push_str(format!("Removed {}\n", file_path).as_str()),
+                Err(e) => result.push_str(
+                    format!("Cannot remove {}, reason: {}\n", file_path, e).as_str(),
+                ),
+            }
+            continue;
+        }
+
         if let Err(e) = index.add_path(path.as_path()) {
             result.push_str(format!("Cannot add {}, reason: {}\n", file_path, e).as_str());
         } else {
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use git2::{Commit, IndexAddOption, Repository};
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::{is_head_unborn, resolve_repository, NO_COMMITS_YET};
+use crate::project::{Project, CANCELLED_MESSAGE};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitAmendParams {
+    #[schemars(description = "New message of the commit, omit to keep the current message")]
+    #[serde(default)]
+    pub message: Option<String>,
+
+    #[schemars(
+        description = "Stage all modifications, new files and deletions before amending (like `git commit --amend -a`); staged changes are always included"
+    )]
+    #[serde(default)]
+    pub stage_all: bool,
+
+    #[schemars(
+        description = "Amend even when HEAD is a merge commit or is already on a remote branch, which then has to be force pushed"
+    )]
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Whether any remote-tracking branch already contains the commit, not only the upstream
+/// of the current branch
+fn is_pushed(repo: &Repository, commit: &Commit) -> bool {
+    let Ok(references) = repo.references_glob("refs/remotes/*") else {
+        return false;
+    };
+
+    references
+        .flatten()
+        .filter_map(|reference| reference.target())
+        .any(|target| {
+            target == commit.id()
+                || repo
+                    .graph_descendant_of(target, commit.id())
+                    .unwrap_or(false)
+        })
+}
+
+#[tool("Amend the last commit with the staged changes and optionally a new message, refused for merge commits and commits already pushed unless forced")]
+pub async fn git_amend(params: GitAmendParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let Some(repo) = resolve_repository(project.get_repo_path()) else {
+        return json!("Not a git repository");
+    };
+    if is_head_unborn(&repo) {
+        return json!(NO_COMMITS_YET);
+    }
+
+    let head = match repo.head().and_then(|head| head.peel_to_commit()) {
+        Ok(head) => head,
+        Err(e) => return json!(format!("Cannot get HEAD commit, reason: {}", e)),
+    };
+    if !params.force && head.parent_count() > 1 {
+        return json!("HEAD is a merge commit, set force to amend it anyway");
+    }
+    if !params.force && is_pushed(&repo, &head) {
+        return json!("HEAD is already on a remote branch, set force to amend it anyway");
+    }
+
+    let mut index = match repo.index() {
+        Ok(index) => index,
+        Err(e) => return json!(format!("Cannot get index, reason: {}", e)),
+    };
+    if params.stage_all {
+        let staged = index
+            .add_all(["*"], IndexAddOption::DEFAULT, None)
+            .and_then(|_| index.update_all(["*"], None))
+            .and_then(|_| index.write());
+        if let Err(e) = staged {
+            return json!(format!("Cannot stage all changes, reason: {}", e));
+        }
+    }
+
+    let tree = match index.write_tree().and_then(|oid| repo.find_tree(oid)) {
+        Ok(tree) => tree,
+        Err(e) => return json!(format!("Cannot write index tree, reason: {}", e)),
+    };
+    let committer = match project.get_commit_author().get_signature(&repo) {
+        Ok(signature) => signature,
+        Err(e) => return json!(format!("Cannot get signature for commiter, reason: {}", e)),
+    };
+
+    let amended = head.amend(
+        Some("HEAD"),
+        None,
+        Some(&committer),
+        None,
+        params.message.as_deref(),
+        Some(&tree),
+    );
+
+    match amended {
+        Ok(oid) => json!(format!("Amended HEAD, now {}", &oid.to_string()[..7])),
+        Err(e) => json!(format!("Can't amend, reason: {}", e)),
+    }
+}
@@ -5,8 +5,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
-use crate::project::Project;
+use super::utils::{is_head_unborn, resolve_repository, NO_COMMITS_YET};
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitBranchCreateParams {
@@ -22,6 +22,10 @@ pub async fn git_branch_create(
     params: GitBranchCreateParams,
     project: Arc<Project>,
 ) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -31,6 +35,13 @@ pub async fn git_branch_create(
     let branch_name = params.branch_name;
     let switch = params.switch.unwrap_or(false);
 
+    if is_head_unborn(&repository) {
+        return json!(format!(
+            "Cannot create branch '{}': {}, create the first commit",
+            branch_name, NO_COMMITS_YET
+        ));
+    }
+
     // Get HEAD commit to branch from
     let head = match repository.head() {
         Ok(head) => head,
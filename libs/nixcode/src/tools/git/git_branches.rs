@@ -5,7 +5,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
+use super::utils::{get_head_branch_name, is_head_unborn, resolve_repository, NO_COMMITS_YET};
 use crate::project::Project;
 
 #[derive(JsonSchema, Serialize, Deserialize, Default)]
@@ -24,6 +24,15 @@ pub async fn git_branches(props: GitBranchesProps, project: Arc<Project>) -> ser
     let repository = repository.unwrap();
     let show_all = props.all.unwrap_or(false);
 
+    if is_head_unborn(&repository) {
+        let branch_name = get_head_branch_name(&repository).unwrap_or_default();
+        return json!(format!(
+            "Branches:\n* {} ({})",
+            branch_name,
+            NO_COMMITS_YET.to_lowercase()
+        ));
+    }
+
     // Get current branch name
     let head = match repository.head() {
         Ok(head) => head,
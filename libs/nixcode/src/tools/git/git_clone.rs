@@ -0,0 +1,127 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use git2::build::RepoBuilder;
+use git2::FetchOptions;
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::remote_callbacks;
+use crate::project::{Project, CANCELLED_MESSAGE};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitCloneParams {
+    #[schemars(description = "Repository URL or local path to clone from")]
+    pub url: String,
+
+    #[schemars(
+        description = "Relative path of the directory to clone into, \".\" clones into the project directory when it is empty"
+    )]
+    pub path: String,
+
+    #[schemars(description = "Branch to check out after cloning (default: remote HEAD)")]
+    pub branch: Option<String>,
+
+    #[schemars(
+        description = "Create a shallow clone with history truncated to given number of commits"
+    )]
+    pub depth: Option<i32>,
+}
+
+/// Returns true if the source looks like a remote URL rather than a local path
+fn is_remote_url(url: &str) -> bool {
+    url.contains("://") || url.starts_with("git@")
+}
+
+/// Resolves local clone sources against the project directory
+fn resolve_source(url: &str, cwd: PathBuf) -> anyhow::Result<String> {
+    if is_remote_url(url) || PathBuf::from(url).is_absolute() {
+        return Ok(url.to_string());
+    }
+
+    let path = crate::utils::fs::join_path(cwd, url)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Builds fetch options, credentials come from `remote_callbacks` which gives up after
+/// a rejected attempt
+fn build_fetch_options<'a>(url: &str, depth: Option<i32>) -> FetchOptions<'a> {
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(remote_callbacks(url));
+
+    if let Some(depth) = depth.filter(|depth| *depth > 0) {
+        fetch_options.depth(depth);
+    }
+
+    fetch_options
+}
+
+#[tool("Clone a git repository (remote URL or local path) into a project subdirectory")]
+pub async fn git_clone(params: GitCloneParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    use crate::utils::fs;
+
+    let cwd = project.get_cwd();
+    let target = match fs::resolve_path_in_dir(&cwd, &params.path) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    if let Ok(mut entries) = std::fs::read_dir(&target) {
+        if entries.next().is_some() {
+            return json!(format!(
+                "Destination path '{}' already exists and is not empty",
+                params.path
+            ));
+        }
+    }
+
+    let source = match resolve_source(&params.url, cwd) {
+        Ok(source) => source,
+        Err(e) => return json!(e.to_string()),
+    };
+
+    let branch = params.branch.clone();
+    let depth = params.depth;
+    let clone_result = tokio::task::spawn_blocking(move || {
+        let mut builder = RepoBuilder::new();
+        builder.fetch_options(build_fetch_options(&source, depth));
+
+        if let Some(branch) = &branch {
+            builder.branch(branch);
+        }
+
+        builder.clone(&source, target.as_path()).map(|repo| {
+            repo.head()
+                .ok()
+                .and_then(|h| h.shorthand().map(String::from))
+        })
+    })
+    .await;
+
+    let had_repo = project.has_repo_path();
+    let cloned = match clone_result {
+        Ok(Ok(Some(branch))) => format!(
+            "Cloned '{}' into '{}' (branch: {})",
+            params.url, params.path, branch
+        ),
+        Ok(Ok(None)) => format!("Cloned '{}' into '{}'", params.url, params.path),
+        Ok(Err(e)) => return json!(format!("Failed to clone '{}': {}", params.url, e)),
+        Err(e) => return json!(format!("Failed to clone '{}': {}", params.url, e)),
+    };
+
+    // Tools registered for repositories only are added when the project is opened again
+    if project.refresh_repo_path() && !had_repo {
+        return json!(format!(
+            "{}\nThe project directory is now a git repository, reopen the project to use all git tools",
+            cloned
+        ));
+    }
+
+    json!(cloned)
+}
@@ -5,8 +5,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
-use crate::project::Project;
+use super::utils::{is_head_unborn, resolve_repository};
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitCommitProps {
@@ -16,6 +16,10 @@ pub struct GitCommitProps {
 
 #[tool("Commit changes")]
 pub async fn git_commit(props: GitCommitProps, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -34,7 +38,7 @@ pub async fn git_commit(props: GitCommitProps, project: Arc<Project>) -> serde_j
     }
     let oid = oid.unwrap();
 
-    let signature = repo.signature();
+    let signature = project.get_commit_author().get_signature(&repo);
     if let Err(e) = signature {
         return json!(format!("Cannot get signature for commiter, reason: {}", e));
     }
@@ -45,16 +49,21 @@ pub async fn git_commit(props: GitCommitProps, project: Arc<Project>) -> serde_j
     }
     let tree = tree.unwrap();
 
-    let parent_commit = repo.head();
-    if let Err(e) = parent_commit {
-        return json!(format!("Cannot get HEAD ref, reason: {}", e));
-    }
+    // The first commit of a repository has no parent
+    let is_root_commit = is_head_unborn(&repo);
+    let mut parents = Vec::new();
+    if !is_root_commit {
+        let parent_commit = repo.head();
+        if let Err(e) = parent_commit {
+            return json!(format!("Cannot get HEAD ref, reason: {}", e));
+        }
 
-    let parent_commit = parent_commit.unwrap().peel_to_commit();
-    if let Err(e) = parent_commit {
-        return json!(format!("Cannot peel to commit, reason: {}", e));
+        let parent_commit = parent_commit.unwrap().peel_to_commit();
+        if let Err(e) = parent_commit {
+            return json!(format!("Cannot peel to commit, reason: {}", e));
+        }
+        parents.push(parent_commit.unwrap());
     }
-    let parent_commit = parent_commit.unwrap();
 
     let result = repo.commit(
         Some("HEAD"),
@@ -62,12 +71,16 @@ pub async fn git_commit(props: GitCommitProps, project: Arc<Project>) -> serde_j
         &signature,
         props.message.as_str(),
         &tree,
-        &[&parent_commit],
+        &parents.iter().collect::<Vec<_>>(),
     );
 
     if let Err(e) = result {
         return json!(format!("Can't commit, reason: {}", e));
     }
 
+    if is_root_commit {
+        return json!("Root commit created");
+    }
+
     json!("Commit created")
 }
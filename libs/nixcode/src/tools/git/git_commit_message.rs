@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use git2::Repository;
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::{diff_to_patch, resolve_repository};
+use crate::project::Project;
+use crate::prompts::commit_message::COMMIT_MESSAGE_PROMPT;
+
+/// Maximum number of characters of the staged diff included in the prompt
+pub const MAX_STAGED_DIFF_CHARS: usize = 20_000;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitCommitMessageParams {}
+
+/// Returns the diff between HEAD (or an empty tree when there are no commits yet)
+/// and the index, cut to `max_chars` characters
+pub fn get_staged_diff(repo: &Repository, max_chars: usize) -> Result<String, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, None)
+        .map_err(|e| format!("Error creating diff: {}", e))?;
+
+    diff_to_patch(&diff, max_chars)
+}
+
+#[tool("Get a prompt with the staged diff to propose a conventional commit message")]
+pub async fn git_commit_message(
+    _params: GitCommitMessageParams,
+    project: Arc<Project>,
+) -> serde_json::Value {
+    let Some(repo) = resolve_repository(project.get_repo_path()) else {
+        return json!("Not a git repository");
+    };
+
+    let diff = match get_staged_diff(&repo, MAX_STAGED_DIFF_CHARS) {
+        Ok(diff) => diff,
+        Err(e) => return json!(e),
+    };
+
+    if diff.trim().is_empty() {
+        return json!("No staged changes, use git_add to stage files first");
+    }
+
+    json!(format!(
+        "{}\n\nStaged diff:\n```diff\n{}```",
+        COMMIT_MESSAGE_PROMPT, diff
+    ))
+}
@@ -2,19 +2,58 @@ use core::str;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use git2::DiffOptions;
+use git2::{DiffOptions, Repository};
 use nixcode_macros::tool;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
+use super::utils::{diff_to_patch, resolve_repository};
 use crate::project::Project;
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitDiffProps {
     #[schemars(description = "Path to the file to show diff for")]
     pub file_path: String,
+
+    #[schemars(
+        description = "Show only the staged changes (index against HEAD), e.g. to review them before committing"
+    )]
+    #[serde(default)]
+    pub staged: bool,
+}
+
+/// Returns the diff of all changes in the working tree (staged, unstaged and
+/// untracked files) against HEAD, cut to `max_chars` characters
+pub fn get_working_tree_diff(repo: &Repository, max_chars: usize) -> Result<String, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let mut diff_options = DiffOptions::new();
+    diff_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+
+    let diff = repo
+        .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut diff_options))
+        .map_err(|e| format!("Error creating diff: {}", e))?;
+
+    diff_to_patch(&diff, max_chars)
+}
+
+/// Returns the staged changes (index against HEAD, or an empty tree when there are
+/// no commits yet) of a single file
+pub fn get_staged_file_diff(repo: &Repository, file_path: &str) -> Result<String, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+
+    let mut diff_options = DiffOptions::new();
+    diff_options.pathspec(file_path);
+
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), None, Some(&mut diff_options))
+        .map_err(|e| format!("Error creating diff: {}", e))?;
+
+    diff_to_patch(&diff, usize::MAX)
 }
 
 #[tool("Get file diff")]
@@ -25,6 +64,18 @@ pub async fn git_diff(props: GitDiffProps, project: Arc<Project>) -> serde_json:
     }
 
     let repo = repository.unwrap();
+
+    // A staged deletion has no file in the working tree
+    if props.staged {
+        return match get_staged_file_diff(&repo, &props.file_path) {
+            Ok(diff) if diff.is_empty() => {
+                json!(format!("No staged changes for file: {}", props.file_path))
+            }
+            Ok(diff) => json!(diff),
+            Err(e) => json!(e),
+        };
+    }
+
     let file_path = PathBuf::from(&props.file_path);
 
     // Check if file exists
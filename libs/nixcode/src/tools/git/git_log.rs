@@ -6,7 +6,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
+use super::utils::{is_head_unborn, resolve_repository, NO_COMMITS_YET};
 use crate::project::Project;
 
 #[derive(JsonSchema, Serialize, Deserialize)]
@@ -88,6 +88,10 @@ pub async fn git_log(props: GitLogProps, project: Arc<Project>) -> serde_json::V
         None => return json!("Not a git repository"),
     };
 
+    if props.to_ref.is_none() && is_head_unborn(&repository) {
+        return json!(NO_COMMITS_YET);
+    }
+
     let limit = props.limit.unwrap_or(50); // Default to 50 commits
 
     // Create a revwalk (iterator over commits)
@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use git2::build::CheckoutBuilder;
+use git2::{FetchOptions, Repository};
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::{
+    find_remote, get_head_branch_name, is_head_unborn, remote_callbacks, resolve_remote_target,
+    NO_COMMITS_YET,
+};
+use crate::project::{Project, RemoteDefaults, CANCELLED_MESSAGE};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitPullParams {
+    #[schemars(
+        description = "Remote to pull from (default: configured default remote, upstream remote or origin)"
+    )]
+    #[serde(default)]
+    pub remote: Option<String>,
+
+    #[schemars(
+        description = "Remote branch to pull (default: configured default branch, upstream branch or the current branch name)"
+    )]
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+fn pull(
+    repo_path: PathBuf,
+    defaults: RemoteDefaults,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> Result<String, String> {
+    let repo = Repository::open(repo_path).map_err(|_| "Not a git repository".to_string())?;
+    if is_head_unborn(&repo) {
+        return Err(NO_COMMITS_YET.to_string());
+    }
+
+    let (remote_name, branch) = resolve_remote_target(&repo, defaults, remote, branch)?;
+    let local = get_head_branch_name(&repo).unwrap_or_default();
+    let mut remote = find_remote(&repo, &remote_name)?;
+    let url = remote.url().unwrap_or_default().to_string();
+
+    let mut options = FetchOptions::new();
+    options.remote_callbacks(remote_callbacks(&url));
+    remote
+        .fetch(&[&branch], Some(&mut options), None)
+        .map_err(|e| format!("Failed to fetch '{}/{}': {}", remote_name, branch, e))?;
+
+    let fetched = repo
+        .find_reference("FETCH_HEAD")
+        .and_then(|fetch_head| repo.reference_to_annotated_commit(&fetch_head))
+        .map_err(|e| format!("Failed to read fetched commit: {}", e))?;
+    let (analysis, _) = repo
+        .merge_analysis(&[&fetched])
+        .map_err(|e| format!("Failed to compare with '{}/{}': {}", remote_name, branch, e))?;
+
+    if analysis.is_up_to_date() {
+        return Ok(format!(
+            "Already up to date with '{}/{}'",
+            remote_name, branch
+        ));
+    }
+    if !analysis.is_fast_forward() {
+        return Err(format!(
+            "'{}' and '{}/{}' have diverged, only fast-forward pulls are supported",
+            local, remote_name, branch
+        ));
+    }
+
+    // Check out the fetched tree first so local changes it would overwrite abort the pull
+    let target = repo.find_object(fetched.id(), None).and_then(|target| {
+        repo.checkout_tree(&target, Some(CheckoutBuilder::new().safe()))?;
+        repo.find_reference(&format!("refs/heads/{}", local))?
+            .set_target(fetched.id(), "pull: fast-forward")
+    });
+    if let Err(e) = target {
+        return Err(format!("Failed to fast-forward '{}': {}", local, e));
+    }
+
+    Ok(format!(
+        "Fast-forwarded '{}' to '{}/{}' ({})",
+        local,
+        remote_name,
+        branch,
+        &fetched.id().to_string()[..7]
+    ))
+}
+
+#[tool("Fetch a remote branch and fast-forward the current branch to it, the remote is checked to exist before connecting")]
+pub async fn git_pull(params: GitPullParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let Some(repo_path) = project.get_repo_path() else {
+        return json!("Not a git repository");
+    };
+
+    let defaults = project.get_remote_defaults();
+    let result = tokio::task::spawn_blocking(move || {
+        pull(repo_path, defaults, params.remote, params.branch)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(message)) | Ok(Err(message)) => json!(message),
+        Err(e) => json!(format!("Failed to pull: {}", e)),
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use git2::{PushOptions, Repository};
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::{
+    find_remote, get_head_branch_name, is_head_unborn, remote_callbacks, resolve_remote_target,
+    NO_COMMITS_YET,
+};
+use crate::project::{Project, RemoteDefaults, CANCELLED_MESSAGE};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitPushParams {
+    #[schemars(
+        description = "Remote to push to (default: configured default remote, upstream remote or origin)"
+    )]
+    #[serde(default)]
+    pub remote: Option<String>,
+
+    #[schemars(
+        description = "Remote branch to push to (default: configured default branch, upstream branch or the current branch name)"
+    )]
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+fn push(
+    repo_path: PathBuf,
+    defaults: RemoteDefaults,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> Result<String, String> {
+    let repo = Repository::open(repo_path).map_err(|_| "Not a git repository".to_string())?;
+    if is_head_unborn(&repo) {
+        return Err(NO_COMMITS_YET.to_string());
+    }
+
+    let (remote_name, branch) = resolve_remote_target(&repo, defaults, remote, branch)?;
+    let local = get_head_branch_name(&repo).unwrap_or_default();
+    let mut remote = find_remote(&repo, &remote_name)?;
+    let url = remote.url().unwrap_or_default().to_string();
+
+    let mut rejected = None;
+    let pushed = {
+        let mut callbacks = remote_callbacks(&url);
+        callbacks.push_update_reference(|_refname, status| {
+            rejected = status.map(str::to_string);
+            Ok(())
+        });
+
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+
+        let refspec = format!("refs/heads/{}:refs/heads/{}", local, branch);
+        remote.push(&[refspec], Some(&mut options))
+    };
+
+    if let Err(e) = pushed {
+        return Err(format!("Failed to push to '{}': {}", remote_name, e));
+    }
+    if let Some(reason) = rejected {
+        return Err(format!(
+            "Push to '{}/{}' was rejected: {}",
+            remote_name, branch, reason
+        ));
+    }
+
+    Ok(format!(
+        "Pushed '{}' to '{}/{}'",
+        local, remote_name, branch
+    ))
+}
+
+#[tool(
+    "Push the current branch to a remote branch, the remote is checked to exist before connecting"
+)]
+pub async fn git_push(params: GitPushParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let Some(repo_path) = project.get_repo_path() else {
+        return json!("Not a git repository");
+    };
+
+    let defaults = project.get_remote_defaults();
+    let result = tokio::task::spawn_blocking(move || {
+        push(repo_path, defaults, params.remote, params.branch)
+    })
+    .await;
+
+    match result {
+        Ok(Ok(message)) | Ok(Err(message)) => json!(message),
+        Err(e) => json!(format!("Failed to push: {}", e)),
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use git2::{IndexConflict, Repository};
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::resolve_repository;
+use crate::project::{Project, CANCELLED_MESSAGE};
+use crate::tools::path_if_succeeded;
+use crate::utils::fs::resolve_path_in_dir;
+
+/// Side of a conflict whose version of the file is kept
+#[derive(JsonSchema, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictSide {
+    Ours,
+    Theirs,
+}
+
+impl ConflictSide {
+    fn name(self) -> &'static str {
+        match self {
+            ConflictSide::Ours => "ours",
+            ConflictSide::Theirs => "theirs",
+        }
+    }
+}
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitResolveConflictParams {
+    #[schemars(description = "Conflicted path relative to the repository root")]
+    pub path: String,
+
+    #[schemars(
+        description = "Keep the version of this side: ours (the current branch) or theirs (the merged, rebased or picked commit)"
+    )]
+    #[serde(default)]
+    pub side: Option<ConflictSide>,
+
+    #[schemars(description = "Resolved content of the file, instead of side")]
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+fn find_conflict(repo: &Repository, path: &str) -> Result<Option<IndexConflict>, git2::Error> {
+    let index = repo.index()?;
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let conflict_path = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+            .next();
+
+        if conflict_path.as_deref() == Some(path) {
+            return Ok(Some(conflict));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Content of one side of the conflict, None when that side deleted the file
+fn get_side_content(
+    repo: &Repository,
+    conflict: &IndexConflict,
+    side: ConflictSide,
+) -> Result<Option<Vec<u8>>, git2::Error> {
+    let entry = match side {
+        ConflictSide::Ours => &conflict.our,
+        ConflictSide::Theirs => &conflict.their,
+    };
+
+    match entry {
+        Some(entry) => Ok(Some(repo.find_blob(entry.id)?.content().to_vec())),
+        None => Ok(None),
+    }
+}
+
+/// Writes the resolution to the working tree and records it in the index, which
+/// removes the conflict entries of the path
+fn resolve_conflict(
+    repo: &Repository,
+    workdir: &Path,
+    path: &str,
+    content: Option<Vec<u8>>,
+) -> anyhow::Result<()> {
+    let full_path = resolve_path_in_dir(workdir, path)?;
+    let mut index = repo.index()?;
+
+    match content {
+        Some(content) => {
+            std::fs::write(&full_path, content)?;
+            index.add_path(Path::new(path))?;
+        }
+        None => {
+            if full_path.exists() {
+                std::fs::remove_file(&full_path)?;
+            }
+            index.conflict_remove(Path::new(path))?;
+            index.remove_path(Path::new(path))?;
+        }
+    }
+
+    index.write()?;
+
+    Ok(())
+}
+
+fn changed_path(params: &serde_json::Value, result: &str) -> Vec<String> {
+    path_if_succeeded(params, result, "Resolved ")
+}
+
+#[tool(
+    "Resolve a merge, rebase or cherry-pick conflict in a file by keeping ours, theirs or the given content, and mark it resolved",
+    changed_paths = changed_path
+)]
+pub async fn git_resolve_conflict(
+    params: GitResolveConflictParams,
+    project: Arc<Project>,
+) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let Some(repo) = resolve_repository(project.get_repo_path()) else {
+        return json!("Not a git repository");
+    };
+    let Some(workdir) = repo.workdir().map(Path::to_path_buf) else {
+        return json!("Repository has no working tree");
+    };
+
+    let conflict = match find_conflict(&repo, &params.path) {
+        Ok(Some(conflict)) => conflict,
+        Ok(None) => return json!(format!("No conflict in {}", params.path)),
+        Err(e) => return json!(format!("Cannot read conflicts, reason: {}", e)),
+    };
+
+    let (content, resolution) = match (params.side, params.content) {
+        (Some(side), None) => match get_side_content(&repo, &conflict, side) {
+            Ok(content) => (content, side.name()),
+            Err(e) => {
+                return json!(format!(
+                    "Cannot read {} of {}, reason: {}",
+                    side.name(),
+                    params.path,
+                    e
+                ))
+            }
+        },
+        (None, Some(content)) => (Some(content.into_bytes()), "the given content"),
+        _ => return json!("Provide either side or content"),
+    };
+
+    let deleted = content.is_none();
+    if let Err(e) = resolve_conflict(&repo, &workdir, &params.path, content) {
+        return json!(format!("Cannot resolve {}, reason: {}", params.path, e));
+    }
+
+    if deleted {
+        json!(format!(
+            "Resolved {} with {}, the file is deleted",
+            params.path, resolution
+        ))
+    } else {
+        json!(format!("Resolved {} with {}", params.path, resolution))
+    }
+}
@@ -7,7 +7,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use super::utils::resolve_repository;
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitStashApplyParams {
@@ -23,6 +23,10 @@ pub async fn git_stash_apply(
     props: GitStashApplyParams,
     project: Arc<Project>,
 ) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::json;
 
 use super::utils::resolve_repository;
-use crate::project::Project;
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitStashDropParams {
@@ -16,6 +16,10 @@ pub struct GitStashDropParams {
 
 #[tool("Drop a stash from git stash list")]
 pub async fn git_stash_drop(props: GitStashDropParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -5,8 +5,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
-use crate::project::Project;
+use super::utils::{is_head_unborn, resolve_repository, NO_COMMITS_YET};
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitStashSaveParams {
@@ -16,6 +16,10 @@ pub struct GitStashSaveParams {
 
 #[tool("Save changes in git stash")]
 pub async fn git_stash_save(props: GitStashSaveParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -23,6 +27,13 @@ pub async fn git_stash_save(props: GitStashSaveParams, project: Arc<Project>) ->
 
     let mut repository = repository.unwrap();
 
+    if is_head_unborn(&repository) {
+        return json!(format!(
+            "{}, create the first commit before stashing",
+            NO_COMMITS_YET
+        ));
+    }
+
     // Get signature for the stash
     let signature = match repository.signature() {
         Ok(sig) => sig,
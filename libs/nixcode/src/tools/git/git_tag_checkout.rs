@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use git2::build::CheckoutBuilder;
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::resolve_repository;
+use crate::project::{Project, CANCELLED_MESSAGE};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitTagCheckoutParams {
+    #[schemars(description = "Name of the tag to check out")]
+    pub tag: String,
+}
+
+#[tool("Check out the commit of a git tag with a detached HEAD, to inspect a release")]
+pub async fn git_tag_checkout(
+    params: GitTagCheckoutParams,
+    project: Arc<Project>,
+) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let Some(repository) = resolve_repository(project.get_repo_path()) else {
+        return json!("Not a git repository");
+    };
+
+    let commit = match repository
+        .find_reference(&format!("refs/tags/{}", params.tag))
+        .and_then(|tag| tag.peel_to_commit())
+    {
+        Ok(commit) => commit,
+        Err(e) => return json!(format!("Failed to find tag '{}': {}", params.tag, e)),
+    };
+
+    // Safe checkout keeps local changes and fails on those the tag would overwrite
+    let checkout = repository
+        .checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+        .and_then(|_| repository.set_head_detached(commit.id()));
+    if let Err(e) = checkout {
+        return json!(format!("Failed to check out tag '{}': {}", params.tag, e));
+    }
+
+    json!(format!(
+        "HEAD is now detached at {} (tag '{}'): {}\nCreate a branch with git_branch_create before committing",
+        &commit.id().to_string()[..7],
+        params.tag,
+        commit.summary().unwrap_or_default()
+    ))
+}
@@ -6,8 +6,8 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use super::utils::resolve_repository;
-use crate::project::Project;
+use super::utils::{is_head_unborn, resolve_repository, NO_COMMITS_YET};
+use crate::project::{Project, CANCELLED_MESSAGE};
 
 #[derive(JsonSchema, Serialize, Deserialize)]
 pub struct GitTagCreateParams {
@@ -29,6 +29,10 @@ pub async fn git_tag_create(
     params: GitTagCreateParams,
     project: Arc<Project>,
 ) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
     let repository = resolve_repository(project.get_repo_path());
     if repository.is_none() {
         return json!("Not a git repository");
@@ -51,6 +55,10 @@ pub async fn git_tag_create(
         }
         None => {
             // Use HEAD
+            if is_head_unborn(&repository) {
+                return json!(format!("{}, nothing to tag", NO_COMMITS_YET));
+            }
+
             match repository.head() {
                 Ok(head) => match head.target() {
                     Some(oid) => oid,
@@ -0,0 +1,152 @@
+use std::sync::Arc;
+
+use git2::{BranchType, Repository};
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::utils::{get_head_branch_name, resolve_repository};
+use crate::project::{Project, CANCELLED_MESSAGE};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GitUpstreamParams {
+    #[schemars(
+        description = "Upstream to set for the current branch as remote/branch (e.g. origin/main), omit to read the current upstream"
+    )]
+    #[serde(default)]
+    pub upstream: Option<String>,
+}
+
+/// Upstream of the branch as `remote/branch`, None when it does not track one
+pub fn get_upstream(repository: &Repository, branch_name: &str) -> Option<String> {
+    let (remote, branch) = get_upstream_target(repository, branch_name)?;
+
+    Some(format!("{}/{}", remote, branch))
+}
+
+/// Remote and remote branch tracked by the branch, read from git instead of split from
+/// `remote/branch`, since both names may contain slashes
+pub fn get_upstream_target(repository: &Repository, branch_name: &str) -> Option<(String, String)> {
+    let refname = format!("refs/heads/{}", branch_name);
+    let remote = repository.branch_upstream_remote(&refname).ok()?;
+    let remote = remote.as_str()?.to_string();
+
+    let branch = repository
+        .find_branch(branch_name, BranchType::Local)
+        .ok()?;
+    let remote_prefix = format!("refs/remotes/{}/", remote);
+    let tracked = match branch.upstream() {
+        Ok(upstream) => upstream
+            .get()
+            .name()
+            .and_then(|name| name.strip_prefix(&remote_prefix))
+            .map(str::to_string),
+        Err(_) => None,
+    };
+
+    // Tracking configured for a branch that was not fetched or pushed yet
+    let tracked = match tracked {
+        Some(tracked) => tracked,
+        None => {
+            let merge = repository.branch_upstream_merge(&refname).ok()?;
+            let merge = merge.as_str()?;
+            merge
+                .strip_prefix("refs/heads/")
+                .unwrap_or(merge)
+                .to_string()
+        }
+    };
+
+    Some((remote, tracked))
+}
+
+/// Sets the upstream of the branch, like `git push --set-upstream` the remote branch
+/// does not have to exist yet
+pub fn set_upstream(
+    repository: &Repository,
+    branch_name: &str,
+    upstream: &str,
+) -> Result<(), String> {
+    let mut branch = repository
+        .find_branch(branch_name, BranchType::Local)
+        .map_err(|e| format!("Failed to find branch '{}': {}", branch_name, e))?;
+
+    if branch.set_upstream(Some(upstream)).is_ok() {
+        return Ok(());
+    }
+
+    if !upstream.contains('/') {
+        return Err(format!(
+            "Invalid upstream '{}', use remote/branch",
+            upstream
+        ));
+    }
+
+    // The longest remote name that prefixes the upstream, remote names may contain slashes
+    let remotes = repository
+        .remotes()
+        .map_err(|e| format!("Failed to list remotes: {}", e))?;
+    let (remote, remote_branch) = remotes
+        .iter()
+        .flatten()
+        .filter_map(|remote| {
+            let branch = upstream.strip_prefix(remote)?.strip_prefix('/')?;
+            (!branch.is_empty()).then_some((remote, branch))
+        })
+        .max_by_key(|(remote, _)| remote.len())
+        .ok_or_else(|| {
+            let remote = upstream.split('/').next().unwrap_or_default();
+            format!("Remote '{}' does not exist", remote)
+        })?;
+
+    let mut config = repository
+        .config()
+        .map_err(|e| format!("Failed to open git config: {}", e))?;
+    config
+        .set_str(&format!("branch.{}.remote", branch_name), remote)
+        .and_then(|_| {
+            config.set_str(
+                &format!("branch.{}.merge", branch_name),
+                &format!("refs/heads/{}", remote_branch),
+            )
+        })
+        .map_err(|e| format!("Failed to set upstream: {}", e))
+}
+
+#[tool("Get the upstream (tracking branch) of the current git branch, or set it like git push --set-upstream")]
+pub async fn git_upstream(params: GitUpstreamParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let Some(repository) = resolve_repository(project.get_repo_path()) else {
+        return json!("Not a git repository");
+    };
+
+    if repository.head_detached().unwrap_or(false) {
+        return json!("HEAD is detached, check out a branch first");
+    }
+
+    let Some(branch_name) = get_head_branch_name(&repository) else {
+        return json!("Failed to get the current branch");
+    };
+
+    if let Some(upstream) = params.upstream {
+        return match set_upstream(&repository, &branch_name, &upstream) {
+            Ok(()) => json!(format!(
+                "Branch '{}' now tracks '{}'",
+                branch_name, upstream
+            )),
+            Err(e) => json!(e),
+        };
+    }
+
+    match get_upstream(&repository, &branch_name) {
+        Some(upstream) => json!(format!("Branch '{}' tracks '{}'", branch_name, upstream)),
+        None => json!(format!(
+            "Branch '{}' has no upstream, set one with git_upstream (e.g. origin/{})",
+            branch_name, branch_name
+        )),
+    }
+}
@@ -1,15 +1,25 @@
 pub mod git_add;
+pub mod git_amend;
 pub mod git_branch_create;
 pub mod git_branch_delete;
 pub mod git_branches;
+pub mod git_clone;
 pub mod git_commit;
+pub mod git_commit_message;
 pub mod git_diff;
 pub mod git_log;
+pub mod git_pull;
+pub mod git_push;
+pub mod git_resolve_conflict;
 pub mod git_stash_apply;
 pub mod git_stash_drop;
 pub mod git_stash_list;
 pub mod git_stash_save;
 pub mod git_status;
+pub mod git_tag_checkout;
 pub mod git_tag_create;
 pub mod git_tags_list;
+pub mod git_upstream;
+#[cfg(test)]
+mod tests;
 mod utils;
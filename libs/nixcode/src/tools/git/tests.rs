@@ -0,0 +1,701 @@
+use super::*;
+use crate::project::Project;
+use git2::{BranchType, Repository, Signature};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Creates a bare repository with a single commit containing README.md
+fn create_bare_fixture(path: &Path) -> Repository {
+    let repo = Repository::init_bare(path).unwrap();
+    {
+        let signature = Signature::now("nixcode", "nixcode@example.com").unwrap();
+        let blob = repo.blob(b"# fixture\n").unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert("README.md", blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+
+        repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            "Initial commit",
+            &tree,
+            &[],
+        )
+        .unwrap();
+    }
+
+    repo
+}
+
+#[tokio::test]
+async fn test_git_clone_local_bare_repository() {
+    let fixture = tempfile::tempdir().unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    create_bare_fixture(&fixture.path().join("origin.git"));
+
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+    let params = git_clone::GitCloneParams {
+        url: fixture.path().join("origin.git").display().to_string(),
+        path: "cloned".to_string(),
+        branch: None,
+        depth: None,
+    };
+
+    let result = git_clone::git_clone(params, project).await;
+
+    assert!(result.as_str().unwrap().starts_with("Cloned"));
+    let readme = std::fs::read_to_string(workdir.path().join("cloned/README.md")).unwrap();
+    assert_eq!(readme, "# fixture\n");
+}
+
+#[tokio::test]
+async fn test_git_clone_into_empty_project_refreshes_repository() {
+    let fixture = tempfile::tempdir().unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    create_bare_fixture(&fixture.path().join("origin.git"));
+
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+    assert!(!project.has_repo_path());
+    let params = git_clone::GitCloneParams {
+        url: fixture.path().join("origin.git").display().to_string(),
+        path: ".".to_string(),
+        branch: None,
+        depth: None,
+    };
+
+    let result = git_clone::git_clone(params, project.clone()).await;
+
+    assert!(result.as_str().unwrap().starts_with("Cloned"));
+    assert!(project.has_repo_path());
+}
+
+#[tokio::test]
+async fn test_git_clone_outside_project() {
+    let workdir = tempfile::tempdir().unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+    let params = git_clone::GitCloneParams {
+        url: "https://github.com/nixcodeit/nixcode-ai.git".to_string(),
+        path: "../cloned".to_string(),
+        branch: None,
+        depth: None,
+    };
+
+    let result = git_clone::git_clone(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("Path must be inside project directory")
+    );
+}
+
+#[tokio::test]
+async fn test_git_clone_through_escaping_symlink() {
+    let workdir = tempfile::tempdir().unwrap();
+    let outside = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(outside.path(), workdir.path().join("escape")).unwrap();
+
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+    let params = git_clone::GitCloneParams {
+        url: "https://github.com/nixcodeit/nixcode-ai.git".to_string(),
+        path: "escape/cloned".to_string(),
+        branch: None,
+        depth: None,
+    };
+
+    let result = git_clone::git_clone(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("Path must be inside project directory")
+    );
+    assert!(!outside.path().join("cloned").exists());
+}
+
+/// Creates a repository with a committed README.md and a staged modification of it
+fn create_staged_fixture(path: &Path) -> Repository {
+    let repo = Repository::init(path).unwrap();
+    let signature = Signature::now("nixcode", "nixcode@example.com").unwrap();
+
+    std::fs::write(path.join("README.md"), "# fixture\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Initial commit",
+        &tree,
+        &[],
+    )
+    .unwrap();
+    drop(tree);
+
+    std::fs::write(path.join("README.md"), "# fixture\n\nStaged line\n").unwrap();
+    std::fs::write(path.join("notes.txt"), "not staged\n").unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+
+    repo
+}
+
+#[test]
+fn test_get_staged_diff() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_staged_fixture(workdir.path());
+
+    let diff = git_commit_message::get_staged_diff(&repo, 10_000).unwrap();
+
+    assert!(diff.contains("README.md"));
+    assert!(diff.contains("+Staged line"));
+    assert!(diff.contains(" # fixture"));
+    assert!(!diff.contains("notes.txt"));
+}
+
+#[test]
+fn test_get_staged_diff_is_bounded() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_staged_fixture(workdir.path());
+
+    let diff = git_commit_message::get_staged_diff(&repo, 20).unwrap();
+
+    assert!(diff.ends_with("[diff truncated]\n"));
+    assert!(diff.len() < 50);
+}
+
+#[tokio::test]
+async fn test_git_commit_message_includes_staged_diff() {
+    let workdir = tempfile::tempdir().unwrap();
+    create_staged_fixture(workdir.path());
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let result = git_commit_message::git_commit_message(
+        git_commit_message::GitCommitMessageParams {},
+        project,
+    )
+    .await;
+
+    let prompt = result.as_str().unwrap();
+    assert!(prompt.contains("Conventional Commits"));
+    assert!(prompt.contains("+Staged line"));
+}
+
+#[test]
+fn test_get_working_tree_diff_includes_all_files() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_staged_fixture(workdir.path());
+    std::fs::create_dir(workdir.path().join("src")).unwrap();
+    std::fs::write(workdir.path().join("src/new.rs"), "fn main() {}\n").unwrap();
+
+    let diff = git_diff::get_working_tree_diff(&repo, 10_000).unwrap();
+
+    assert!(diff.contains("diff --git a/README.md b/README.md"));
+    assert!(diff.contains("+Staged line"));
+    assert!(diff.contains("+not staged"));
+    assert!(diff.contains("+fn main() {}"));
+}
+
+#[tokio::test]
+async fn test_git_diff_staged_shows_only_staged_changes() {
+    let workdir = tempfile::tempdir().unwrap();
+    create_staged_fixture(workdir.path());
+    let readme = workdir.path().join("README.md");
+    std::fs::write(&readme, "# fixture\n\nStaged line\nUnstaged line\n").unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_diff::GitDiffProps {
+        file_path: "README.md".to_string(),
+        staged: true,
+    };
+    let staged = git_diff::git_diff(params, project.clone()).await;
+    let staged = staged.as_str().unwrap();
+    assert!(staged.contains("+Staged line"));
+    assert!(!staged.contains("Unstaged line"));
+
+    let params = git_diff::GitDiffProps {
+        file_path: "notes.txt".to_string(),
+        staged: true,
+    };
+    let result = git_diff::git_diff(params, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!("No staged changes for file: notes.txt")
+    );
+}
+
+#[tokio::test]
+async fn test_git_add_stages_deletion() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_staged_fixture(workdir.path());
+    std::fs::remove_file(workdir.path().join("README.md")).unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_add::GitAddParams {
+        files: vec!["README.md".to_string()],
+        all: None,
+    };
+    let result = git_add::git_add(params, project).await;
+
+    assert_eq!(result, serde_json::json!("Removed README.md\n"));
+    let mut index = repo.index().unwrap();
+    index.read(true).unwrap();
+    assert!(index.get_path(Path::new("README.md"), 0).is_none());
+
+    let diff = git_commit_message::get_staged_diff(&repo, 10_000).unwrap();
+    assert!(diff.contains("deleted file mode"));
+}
+
+#[tokio::test]
+async fn test_git_add_all_stages_every_change() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_staged_fixture(workdir.path());
+    std::fs::remove_file(workdir.path().join("README.md")).unwrap();
+    std::fs::create_dir(workdir.path().join("src")).unwrap();
+    std::fs::write(workdir.path().join("src/lib.rs"), "pub fn lib() {}\n").unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_add::GitAddParams {
+        files: vec![],
+        all: Some(true),
+    };
+    let result = git_add::git_add(params, project).await;
+
+    assert_eq!(result, serde_json::json!("Staged all changes"));
+    let diff = git_commit_message::get_staged_diff(&repo, 10_000).unwrap();
+    assert!(diff.contains("deleted file mode"));
+    assert!(diff.contains("+not staged"));
+    assert!(diff.contains("+pub fn lib() {}"));
+}
+
+/// Initializes a repository without commits and with a committer identity
+fn create_empty_fixture(path: &Path) -> Repository {
+    let repo = Repository::init(path).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "nixcode").unwrap();
+    config.set_str("user.email", "nixcode@example.com").unwrap();
+
+    repo
+}
+
+#[tokio::test]
+async fn test_git_commit_creates_root_commit() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_empty_fixture(workdir.path());
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_log::GitLogProps {
+        from_ref: None,
+        to_ref: None,
+        limit: None,
+        path: None,
+    };
+    let result = git_log::git_log(params, project.clone()).await;
+    assert_eq!(result, serde_json::json!("No commits yet"));
+
+    std::fs::write(workdir.path().join("README.md"), "# project\n").unwrap();
+    let params = git_add::GitAddParams {
+        files: vec!["README.md".to_string()],
+        all: None,
+    };
+    git_add::git_add(params, project.clone()).await;
+
+    let params = git_commit::GitCommitProps {
+        message: "Initial commit".to_string(),
+    };
+    let result = git_commit::git_commit(params, project.clone()).await;
+    assert_eq!(result, serde_json::json!("Root commit created"));
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.parent_count(), 0);
+    assert_eq!(head.message(), Some("Initial commit"));
+
+    let params = git_log::GitLogProps {
+        from_ref: None,
+        to_ref: None,
+        limit: None,
+        path: None,
+    };
+    let result = git_log::git_log(params, project).await;
+    assert!(result.as_str().unwrap().ends_with("\nInitial commit"));
+}
+
+#[tokio::test]
+async fn test_git_commit_uses_configured_author() {
+    use crate::project::CommitAuthor;
+
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_empty_fixture(workdir.path());
+    let mut project = Project::new(workdir.path().to_path_buf());
+    project.set_commit_author(CommitAuthor {
+        name: Some("nixcode-bot".to_string()),
+        email: Some("bot@nixcode.dev".to_string()),
+    });
+    let project = Arc::new(project);
+
+    std::fs::write(workdir.path().join("README.md"), "# project\n").unwrap();
+    let params = git_add::GitAddParams {
+        files: vec!["README.md".to_string()],
+        all: None,
+    };
+    git_add::git_add(params, project.clone()).await;
+
+    let params = git_commit::GitCommitProps {
+        message: "Initial commit".to_string(),
+    };
+    git_commit::git_commit(params, project).await;
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.author().name(), Some("nixcode-bot"));
+    assert_eq!(head.author().email(), Some("bot@nixcode.dev"));
+    assert_eq!(head.committer().name(), Some("nixcode-bot"));
+}
+
+#[tokio::test]
+async fn test_git_amend_changes_message() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_empty_fixture(workdir.path());
+    commit_readme(&repo, "Initial comit\n", "HEAD", &[]);
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_amend::GitAmendParams {
+        message: Some("Initial commit".to_string()),
+        stage_all: false,
+        force: false,
+    };
+    let result = git_amend::git_amend(params, project).await;
+    assert!(result.as_str().unwrap().starts_with("Amended HEAD"));
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.message(), Some("Initial commit"));
+    assert_eq!(head.parent_count(), 0);
+    assert!(head.tree().unwrap().get_name("README.md").is_some());
+}
+
+#[tokio::test]
+async fn test_git_amend_refuses_merge_commit() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_empty_fixture(workdir.path());
+    let root_id = commit_readme(&repo, "# root\n", "HEAD", &[]);
+    let root = repo.find_commit(root_id).unwrap();
+    let side_id = commit_readme(&repo, "# side\n", "refs/heads/side", &[&root]);
+    let side = repo.find_commit(side_id).unwrap();
+    commit_readme(&repo, "# merged\n", "HEAD", &[&root, &side]);
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_amend::GitAmendParams {
+        message: Some("Merge side".to_string()),
+        stage_all: false,
+        force: false,
+    };
+    let result = git_amend::git_amend(params, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!("HEAD is a merge commit, set force to amend it anyway")
+    );
+}
+
+#[tokio::test]
+async fn test_git_amend_refuses_pushed_commit() {
+    let fixture = tempfile::tempdir().unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    create_bare_fixture(&fixture.path().join("origin.git"));
+    let repo = clone_fixture(&fixture.path().join("origin.git"), workdir.path());
+    // Pushed to some remote branch, not to the upstream of the current branch
+    let head = repo.head().unwrap().target().unwrap();
+    repo.reference("refs/remotes/origin/other", head, false, "test")
+        .unwrap();
+    let branch_name = utils::get_head_branch_name(&repo).unwrap();
+    repo.find_reference(&format!("refs/remotes/origin/{}", branch_name))
+        .unwrap()
+        .delete()
+        .unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_amend::GitAmendParams {
+        message: Some("Rewritten".to_string()),
+        stage_all: false,
+        force: false,
+    };
+    let result = git_amend::git_amend(params, project.clone()).await;
+    assert_eq!(
+        result,
+        serde_json::json!("HEAD is already on a remote branch, set force to amend it anyway")
+    );
+
+    let params = git_amend::GitAmendParams {
+        message: Some("Rewritten".to_string()),
+        stage_all: false,
+        force: true,
+    };
+    let result = git_amend::git_amend(params, project).await;
+    assert!(result.as_str().unwrap().starts_with("Amended HEAD"));
+}
+
+/// Clones the bare fixture at `origin` into `path` with a committer identity set
+fn clone_fixture(origin: &Path, path: &Path) -> Repository {
+    let repo = Repository::clone(origin.to_str().unwrap(), path).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "nixcode").unwrap();
+    config.set_str("user.email", "nixcode@example.com").unwrap();
+
+    repo
+}
+
+#[tokio::test]
+async fn test_git_push_unknown_remote() {
+    let fixture = tempfile::tempdir().unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    create_bare_fixture(&fixture.path().join("origin.git"));
+    clone_fixture(&fixture.path().join("origin.git"), workdir.path());
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_push::GitPushParams {
+        remote: Some("upstream".to_string()),
+        branch: None,
+    };
+    let result = git_push::git_push(params, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!("Remote 'upstream' does not exist, available remotes: origin")
+    );
+}
+
+#[tokio::test]
+async fn test_git_push_to_default_remote() {
+    use crate::project::RemoteDefaults;
+
+    let fixture = tempfile::tempdir().unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    let origin = create_bare_fixture(&fixture.path().join("origin.git"));
+    let repo = clone_fixture(&fixture.path().join("origin.git"), workdir.path());
+    repo.remote_rename("origin", "fork").unwrap();
+    // The default branch is used for branches without an upstream
+    let branch_name = utils::get_head_branch_name(&repo).unwrap();
+    let mut branch = repo.find_branch(&branch_name, BranchType::Local).unwrap();
+    branch.set_upstream(None).unwrap();
+    let mut project = Project::new(workdir.path().to_path_buf());
+    project.set_remote_defaults(RemoteDefaults {
+        remote: Some("fork".to_string()),
+        branch: Some("feature".to_string()),
+    });
+    let project = Arc::new(project);
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    commit_readme(&repo, "# changed\n", "HEAD", &[&parent]);
+
+    let params = git_push::GitPushParams {
+        remote: None,
+        branch: None,
+    };
+    let result = git_push::git_push(params, project).await;
+    assert!(result.as_str().unwrap().starts_with("Pushed"));
+
+    let head = repo.head().unwrap().target().unwrap();
+    let pushed = origin.find_reference("refs/heads/feature").unwrap();
+    assert_eq!(pushed.target(), Some(head));
+}
+
+#[test]
+fn test_upstream_branch_wins_over_default_branch() {
+    use crate::project::RemoteDefaults;
+
+    let fixture = tempfile::tempdir().unwrap();
+    let workdir = tempfile::tempdir().unwrap();
+    create_bare_fixture(&fixture.path().join("origin.git"));
+    let repo = clone_fixture(&fixture.path().join("origin.git"), workdir.path());
+    let defaults = RemoteDefaults {
+        remote: None,
+        branch: Some("feature".to_string()),
+    };
+
+    let target = utils::resolve_remote_target(&repo, defaults, None, None).unwrap();
+    let branch_name = utils::get_head_branch_name(&repo).unwrap();
+    assert_eq!(target, ("origin".to_string(), branch_name));
+}
+
+#[tokio::test]
+async fn test_git_tag_checkout_detaches_head() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_empty_fixture(workdir.path());
+    let v1 = commit_readme(&repo, "# v1\n", "HEAD", &[]);
+    let v1 = repo.find_commit(v1).unwrap();
+    repo.tag_lightweight("v1.0.0", v1.as_object(), false)
+        .unwrap();
+    commit_readme(&repo, "# v2\n", "HEAD", &[&v1]);
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_tag_checkout::GitTagCheckoutParams {
+        tag: "v1.0.0".to_string(),
+    };
+    let result = git_tag_checkout::git_tag_checkout(params, project).await;
+    let result = result.as_str().unwrap();
+    assert!(result.starts_with("HEAD is now detached at"));
+
+    let tagged = repo.revparse_single("v1.0.0").unwrap().id();
+    assert!(repo.head_detached().unwrap());
+    assert_eq!(repo.head().unwrap().target(), Some(tagged));
+    let readme = std::fs::read_to_string(workdir.path().join("README.md")).unwrap();
+    assert_eq!(readme, "# v1\n");
+}
+
+#[tokio::test]
+async fn test_git_branches_without_commits() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_empty_fixture(workdir.path());
+    repo.set_head("refs/heads/main").unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_branches::GitBranchesProps { all: None };
+    let result = git_branches::git_branches(params, project.clone()).await;
+    assert_eq!(
+        result,
+        serde_json::json!("Branches:\n* main (no commits yet)")
+    );
+
+    let params = git_branch_create::GitBranchCreateParams {
+        branch_name: "feature".to_string(),
+        switch: None,
+    };
+    let result = git_branch_create::git_branch_create(params, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "Cannot create branch 'feature': No commits yet, create the first commit"
+        )
+    );
+}
+
+#[tokio::test]
+async fn test_git_upstream_set_and_read() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_staged_fixture(workdir.path());
+    let branch = utils::get_head_branch_name(&repo).unwrap();
+    let head = repo.head().unwrap().target().unwrap();
+    repo.remote("origin", "https://example.com/project.git").unwrap();
+    repo.reference(
+        &format!("refs/remotes/origin/{}", branch),
+        head,
+        false,
+        "fetch",
+    )
+    .unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+    let read = || git_upstream::GitUpstreamParams { upstream: None };
+
+    let result = git_upstream::git_upstream(read(), project.clone()).await;
+    assert_eq!(
+        result,
+        serde_json::json!(format!(
+            "Branch '{}' has no upstream, set one with git_upstream (e.g. origin/{})",
+            branch, branch
+        ))
+    );
+
+    let params = git_upstream::GitUpstreamParams {
+        upstream: Some(format!("origin/{}", branch)),
+    };
+    git_upstream::git_upstream(params, project.clone()).await;
+    let result = git_upstream::git_upstream(read(), project.clone()).await;
+    assert_eq!(
+        result,
+        serde_json::json!(format!("Branch '{}' tracks 'origin/{}'", branch, branch))
+    );
+
+    // A remote branch that was not pushed yet can be tracked too
+    let params = git_upstream::GitUpstreamParams {
+        upstream: Some("origin/feature".to_string()),
+    };
+    git_upstream::git_upstream(params, project.clone()).await;
+    assert_eq!(
+        git_upstream::get_upstream(&repo, &branch).as_deref(),
+        Some("origin/feature")
+    );
+
+    // Remote names with slashes are not split at the first one
+    repo.remote("team/fork", "https://example.com/fork.git")
+        .unwrap();
+    let params = git_upstream::GitUpstreamParams {
+        upstream: Some("team/fork/release/1.0".to_string()),
+    };
+    git_upstream::git_upstream(params, project).await;
+    let defaults = crate::project::RemoteDefaults {
+        remote: None,
+        branch: None,
+    };
+    let target = utils::resolve_remote_target(&repo, defaults, None, None).unwrap();
+    assert_eq!(target, ("team/fork".to_string(), "release/1.0".to_string()));
+}
+
+/// Writes README.md, stages it and commits it to `update_ref`
+fn commit_readme(
+    repo: &Repository,
+    content: &str,
+    update_ref: &str,
+    parents: &[&git2::Commit],
+) -> git2::Oid {
+    let signature = Signature::now("nixcode", "nixcode@example.com").unwrap();
+    std::fs::write(repo.workdir().unwrap().join("README.md"), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("README.md")).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+
+    repo.commit(
+        Some(update_ref),
+        &signature,
+        &signature,
+        content.trim(),
+        &tree,
+        parents,
+    )
+    .unwrap()
+}
+
+/// Creates a repository where merging branch `feature` into HEAD left README.md
+/// conflicted
+fn create_conflict_fixture(path: &Path) -> Repository {
+    let repo = Repository::init(path).unwrap();
+    let base = commit_readme(&repo, "base\n", "HEAD", &[]);
+    let base = repo.find_commit(base).unwrap();
+    let feature = commit_readme(&repo, "theirs\n", "refs/heads/feature", &[&base]);
+    commit_readme(&repo, "ours\n", "HEAD", &[&base]);
+
+    let feature = repo.find_annotated_commit(feature).unwrap();
+    repo.merge(&[&feature], None, None).unwrap();
+    drop(feature);
+    drop(base);
+
+    repo
+}
+
+#[tokio::test]
+async fn test_git_resolve_conflict_with_ours() {
+    let workdir = tempfile::tempdir().unwrap();
+    let repo = create_conflict_fixture(workdir.path());
+    assert!(repo.index().unwrap().has_conflicts());
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = git_resolve_conflict::GitResolveConflictParams {
+        path: "README.md".to_string(),
+        side: Some(git_resolve_conflict::ConflictSide::Ours),
+        content: None,
+    };
+    let result = git_resolve_conflict::git_resolve_conflict(params, project.clone()).await;
+
+    assert_eq!(result, serde_json::json!("Resolved README.md with ours"));
+    let readme = std::fs::read_to_string(workdir.path().join("README.md")).unwrap();
+    assert_eq!(readme, "ours\n");
+    let mut index = repo.index().unwrap();
+    index.read(true).unwrap();
+    assert!(!index.has_conflicts());
+    assert!(index.get_path(Path::new("README.md"), 0).is_some());
+
+    let params = git_resolve_conflict::GitResolveConflictParams {
+        path: "README.md".to_string(),
+        side: Some(git_resolve_conflict::ConflictSide::Theirs),
+        content: None,
+    };
+    let result = git_resolve_conflict::git_resolve_conflict(params, project).await;
+    assert_eq!(result, serde_json::json!("No conflict in README.md"));
+}
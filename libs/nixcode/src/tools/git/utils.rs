@@ -1,8 +1,159 @@
-use git2::Repository;
+use core::str;
+
+use git2::{Cred, CredentialType, Diff, Remote, RemoteCallbacks, Repository};
 use std::path::PathBuf;
 
+use super::git_upstream::get_upstream_target;
+use crate::project::RemoteDefaults;
+
 /// Resolves the repository from a given path
 pub fn resolve_repository(path: Option<PathBuf>) -> Option<Repository> {
     let repo_path = path?;
     Repository::open(repo_path).ok()
 }
+
+/// Formats a diff as a patch, cut to `max_chars` characters
+pub fn diff_to_patch(diff: &Diff, max_chars: usize) -> Result<String, String> {
+    let mut output = String::new();
+    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        let content = str::from_utf8(line.content()).unwrap_or("[binary content]\n");
+
+        if let origin @ ('+' | '-' | ' ') = line.origin() {
+            output.push(origin);
+        }
+        output.push_str(content);
+        true
+    })
+    .map_err(|e| format!("Error printing diff: {}", e))?;
+
+    if output.len() > max_chars {
+        let mut end = max_chars;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        output.truncate(end);
+        output.push_str("\n[diff truncated]\n");
+    }
+
+    Ok(output)
+}
+
+/// Result of tools that need a commit when the repository has none yet
+pub const NO_COMMITS_YET: &str = "No commits yet";
+
+/// Returns true when HEAD points to a branch without commits, e.g. in a freshly
+/// initialized repository
+pub fn is_head_unborn(repo: &Repository) -> bool {
+    matches!(repo.head(), Err(e) if e.code() == git2::ErrorCode::UnbornBranch)
+}
+
+/// Name of the branch HEAD points to, also when it has no commits yet
+pub fn get_head_branch_name(repo: &Repository) -> Option<String> {
+    let head = repo.find_reference("HEAD").ok()?;
+    let target = head.symbolic_target()?;
+
+    target.strip_prefix("refs/heads/").map(str::to_string)
+}
+
+/// Finds the remote, failing with the names of the existing remotes so a wrong name
+/// is reported before any network operation
+pub fn find_remote<'r>(repo: &'r Repository, name: &str) -> Result<Remote<'r>, String> {
+    if let Ok(remote) = repo.find_remote(name) {
+        return Ok(remote);
+    }
+
+    let remotes = repo
+        .remotes()
+        .map(|remotes| {
+            remotes
+                .iter()
+                .flatten()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if remotes.is_empty() {
+        return Err(format!(
+            "Remote '{}' does not exist, the repository has no remotes",
+            name
+        ));
+    }
+
+    Err(format!(
+        "Remote '{}' does not exist, available remotes: {}",
+        name,
+        remotes.join(", ")
+    ))
+}
+
+/// Remote and remote branch of a push or pull of the current branch. The remote is the
+/// given one, then the configured default, then the remote of the upstream, then origin.
+/// The branch is the given one, then the upstream branch, then the configured default,
+/// then a branch of the same name. The remote is checked to exist
+pub fn resolve_remote_target(
+    repo: &Repository,
+    defaults: RemoteDefaults,
+    remote: Option<String>,
+    branch: Option<String>,
+) -> Result<(String, String), String> {
+    let Some(branch_name) = get_head_branch_name(repo) else {
+        return Err("HEAD is detached, check out a branch first".to_string());
+    };
+
+    let upstream = get_upstream_target(repo, &branch_name);
+
+    let remote = remote
+        .or(defaults.remote)
+        .or_else(|| upstream.as_ref().map(|(remote, _)| remote.clone()))
+        .unwrap_or_else(|| "origin".to_string());
+    find_remote(repo, &remote)?;
+
+    let upstream_branch = upstream
+        .filter(|(upstream_remote, _)| *upstream_remote == remote)
+        .map(|(_, branch)| branch);
+    let branch = branch
+        .or(upstream_branch)
+        .or(defaults.branch)
+        .unwrap_or(branch_name);
+
+    Ok((remote, branch))
+}
+
+/// Remote callbacks authenticating with the credential types the remote allows: the
+/// ssh agent, GITHUB_TOKEN for HTTPS GitHub remotes, then the git credential helper.
+/// Rejected credentials are not offered again, so a failed login ends the operation
+pub fn remote_callbacks<'a>(url: &str) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+
+    let is_github = url.starts_with("https://") && url.contains("github.com");
+    let token = std::env::var("GITHUB_TOKEN").ok().filter(|_| is_github);
+    let mut attempted = false;
+    callbacks.credentials(move |url, username, allowed| {
+        // Asked first for the user name of ssh urls without one
+        if allowed.contains(CredentialType::USERNAME) {
+            return Cred::username(username.unwrap_or("git"));
+        }
+        if attempted {
+            return Err(git2::Error::from_str("Authentication failed"));
+        }
+        attempted = true;
+
+        if allowed.contains(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username.unwrap_or("git"));
+        }
+        if allowed.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if let Some(token) = &token {
+                return Cred::userpass_plaintext("x-access-token", token);
+            }
+            let config = git2::Config::open_default()?;
+            return Cred::credential_helper(&config, url, username);
+        }
+        if allowed.contains(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+
+        Err(git2::Error::from_str("No supported credential type"))
+    });
+
+    callbacks
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+
+use crate::project::{Project, CANCELLED_MESSAGE, MEMORY_FILE};
+use crate::utils::fs::resolve_path_in_dir;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct AppendMemoryParams {
+    #[schemars(
+        description = "Note to remember for the rest of the task, e.g. a decision, a constraint or a TODO"
+    )]
+    pub note: String,
+}
+
+#[tool("Record a durable note in .nixcode/memory.md, the memory is included in the system prompt")]
+pub async fn append_memory(params: AppendMemoryParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let note = params.note.trim();
+    if note.is_empty() {
+        return json!("Note cannot be empty");
+    }
+
+    let path = match resolve_path_in_dir(&project.get_cwd(), MEMORY_FILE) {
+        Ok(path) => path,
+        Err(e) => return json!(e.to_string()),
+    };
+    if let Err(e) = tokio::fs::create_dir_all(path.parent().unwrap()).await {
+        return json!(format!("Cannot create memory directory: {}", e));
+    }
+
+    let file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await;
+    let mut file = match file {
+        Ok(file) => file,
+        Err(e) => return json!(format!("Cannot open memory file: {}", e)),
+    };
+
+    // Multi-line notes are indented so each note stays one list item
+    let entry = format!("- {}\n", note.replace('\n', "\n  "));
+    // tokio files write in the background, flush so the note is on disk when the tool returns
+    let result = match file.write_all(entry.as_bytes()).await {
+        Ok(()) => file.flush().await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(()) => {
+            project.append_to_memory(&entry);
+            json!("Note saved to memory")
+        }
+        Err(e) => json!(format!("Cannot write memory file: {}", e)),
+    }
+}
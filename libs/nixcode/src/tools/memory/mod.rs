@@ -0,0 +1,4 @@
+pub mod append_memory;
+pub mod read_memory;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::{Project, MEMORY_FILE};
+use crate::utils::fs::resolve_path_in_dir;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct ReadMemoryParams {}
+
+#[tool("Read the notes recorded with append_memory")]
+pub async fn read_memory(_params: ReadMemoryParams, project: Arc<Project>) -> serde_json::Value {
+    let memory = match resolve_path_in_dir(&project.get_cwd(), MEMORY_FILE) {
+        Ok(path) => tokio::fs::read_to_string(path).await,
+        Err(e) => return json!(e.to_string()),
+    };
+    match memory {
+        Ok(memory) if !memory.trim().is_empty() => json!(memory),
+        _ => json!("Memory is empty"),
+    }
+}
@@ -0,0 +1,91 @@
+use super::*;
+use crate::project::Project;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_appended_notes_are_read_back() {
+    let workdir = tempfile::tempdir().unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let result = read_memory::read_memory(read_memory::ReadMemoryParams {}, project.clone()).await;
+    assert_eq!(result, serde_json::json!("Memory is empty"));
+
+    for note in [
+        "Use thiserror for new errors",
+        "TODO: update README\nafter the API change",
+        " ",
+    ] {
+        let params = append_memory::AppendMemoryParams { note: note.into() };
+        append_memory::append_memory(params, project.clone()).await;
+    }
+
+    let result = read_memory::read_memory(read_memory::ReadMemoryParams {}, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!(
+            "- Use thiserror for new errors\n- TODO: update README\n  after the API change\n"
+        )
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_memory_symlink_escaping_project_is_rejected() {
+    let workdir = tempfile::tempdir().unwrap();
+    let outside_dir = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(outside_dir.path(), workdir.path().join(".nixcode")).unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    let params = append_memory::AppendMemoryParams {
+        note: "leaked".into(),
+    };
+    let result = append_memory::append_memory(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("Path must be inside project directory")
+    );
+    assert!(!outside_dir.path().join("memory.md").exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_files_behind_escaping_symlink_are_not_read() {
+    let workdir = tempfile::tempdir().unwrap();
+    let outside_dir = tempfile::tempdir().unwrap();
+    std::fs::write(outside_dir.path().join("memory.md"), "- secret\n").unwrap();
+    std::fs::write(outside_dir.path().join("instructions.md"), "secret").unwrap();
+    std::os::unix::fs::symlink(outside_dir.path(), workdir.path().join(".nixcode")).unwrap();
+    let project = Arc::new(Project::new(workdir.path().to_path_buf()));
+
+    assert_eq!(project.get_memory(), None);
+    assert_eq!(project.get_instructions(), None);
+
+    let result = read_memory::read_memory(read_memory::ReadMemoryParams {}, project).await;
+    assert_eq!(
+        result,
+        serde_json::json!("Path must be inside project directory")
+    );
+}
+
+#[test]
+fn test_system_prompt_memory_is_read_once_and_capped() {
+    let workdir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(workdir.path().join(".nixcode")).unwrap();
+    let notes = (0..2000)
+        .map(|i| format!("- Note {}\n", i))
+        .collect::<String>();
+    std::fs::write(workdir.path().join(crate::project::MEMORY_FILE), notes).unwrap();
+    let project = Project::new(workdir.path().to_path_buf());
+    std::fs::remove_file(workdir.path().join(crate::project::MEMORY_FILE)).unwrap();
+
+    let memory = project.get_memory().unwrap();
+    let (omitted, notes) = memory.split_once('\n').unwrap();
+    assert_eq!(
+        omitted,
+        "[Older notes are left out, use read_memory to see all of them]"
+    );
+    assert!(notes.starts_with("- Note "));
+    assert!(memory.ends_with("- Note 1999\n"));
+    assert!(memory.len() < 8_100);
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::tools::Tool;
+
+/// Full outputs of tool results that were sent to the model as a summary, by tool use id
+pub type FullToolResults = Arc<Mutex<HashMap<String, String>>>;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GetFullToolResultParams {
+    #[schemars(description = "Id of the tool use whose result was summarized")]
+    pub tool_use_id: String,
+}
+
+/// Returns the full output of a tool result that was replaced by a summary
+pub struct GetFullToolResultTool {
+    results: FullToolResults,
+}
+
+impl GetFullToolResultTool {
+    pub fn new(results: FullToolResults) -> Self {
+        Self { results }
+    }
+}
+
+#[async_trait]
+impl Tool for GetFullToolResultTool {
+    fn get_name(&self) -> String {
+        "get_full_tool_result".into()
+    }
+
+    fn get_schema(&self) -> nixcode_llm_sdk::tools::Tool {
+        let schema = schemars::schema_for!(GetFullToolResultParams);
+        let parameters = serde_json::to_value(&schema).unwrap();
+
+        nixcode_llm_sdk::tools::Tool::new(
+            self.get_name(),
+            "Get the full output of a tool result that was summarized".into(),
+            parameters,
+        )
+    }
+
+    async fn execute(
+        &self,
+        params: serde_json::Value,
+        _project: Arc<Project>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let params: GetFullToolResultParams = serde_json::from_value(params)?;
+
+        match self.results.lock().unwrap().get(&params.tool_use_id) {
+            Some(result) => Ok(json!(result)),
+            None => Ok(json!(format!(
+                "No summarized result with tool use id {}",
+                params.tool_use_id
+            ))),
+        }
+    }
+}
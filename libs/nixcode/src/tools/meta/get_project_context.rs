@@ -0,0 +1,55 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+
+/// Maximum number of top-level entries returned
+const MAX_ENTRIES: usize = 100;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GetProjectContextParams {}
+
+/// Top-level entries of `dir` sorted by name, directories end with `/`
+fn list_top_level(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name() != ".git")
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => format!("{}/", name),
+                _ => name,
+            }
+        })
+        .collect();
+    names.sort();
+    names.truncate(MAX_ENTRIES);
+
+    names
+}
+
+#[tool("Get the project directory, git root, detected stack and top-level files, call it before working with paths")]
+pub async fn get_project_context(
+    _params: GetProjectContextParams,
+    project: Arc<Project>,
+) -> serde_json::Value {
+    let cwd = project.get_cwd();
+
+    json!({
+        "cwd": cwd.display().to_string(),
+        "git_root": project.get_repo_path().map(|path| path.display().to_string()),
+        "stack": project.detect_stack(),
+        "has_init_analysis": project.has_init_analysis(),
+        "has_instructions": project.has_instructions(),
+        "entries": list_top_level(&cwd),
+    })
+}
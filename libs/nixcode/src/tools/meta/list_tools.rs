@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::config::Config;
+use crate::project::Project;
+use crate::tools::{Tool, Tools};
+
+/// Lists the tools enabled in the current configuration. Registered tools depend
+/// on the project (git repository, tasks file) and on `[tools.overrides]`, so the
+/// list is captured when the tools are registered.
+pub struct ListToolsTool {
+    tools: Vec<(String, String)>,
+}
+
+impl ListToolsTool {
+    pub fn new(tools: &Tools, config: &Config) -> Self {
+        let mut tools: Vec<(String, String)> = tools
+            .get_enabled_tools(config)
+            .iter()
+            .map(|tool| {
+                (
+                    tool.get_name().to_string(),
+                    tool.get_description().to_string(),
+                )
+            })
+            .collect();
+        tools.sort();
+
+        Self { tools }
+    }
+}
+
+#[async_trait]
+impl Tool for ListToolsTool {
+    fn get_name(&self) -> String {
+        "list_tools".into()
+    }
+
+    fn get_schema(&self) -> nixcode_llm_sdk::tools::Tool {
+        nixcode_llm_sdk::tools::Tool::new(
+            self.get_name(),
+            "List the names and descriptions of the tools available in this project".into(),
+            json!({ "type": "object", "properties": {} }),
+        )
+    }
+
+    async fn execute(
+        &self,
+        _params: serde_json::Value,
+        _project: Arc<Project>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let lines: Vec<String> = self
+            .tools
+            .iter()
+            .map(|(name, description)| format!("{}: {}", name, description))
+            .collect();
+
+        Ok(json!(lines.join("\n")))
+    }
+}
@@ -0,0 +1,5 @@
+pub mod get_full_tool_result;
+pub mod get_project_context;
+pub mod list_tools;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,59 @@
+use super::*;
+use crate::config::Config;
+use crate::project::Project;
+use crate::tools::fs::delete_file::DeleteFileTool;
+use crate::tools::fs::read_text_file::ReadTextFileTool;
+use crate::tools::{Tool, Tools};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_list_tools_reflects_overrides() {
+    let mut tools = Tools::new();
+    tools.add_tool(Arc::new(ReadTextFileTool {}));
+    tools.add_tool(Arc::new(DeleteFileTool {}));
+
+    let mut config = Config::new();
+    config.tools.enabled = true;
+    config.tools.overrides.insert("delete_file".into(), false);
+
+    let tool = list_tools::ListToolsTool::new(&tools, &config);
+    let project = Arc::new(Project::new(PathBuf::from("/tmp")));
+    let result = tool.execute(serde_json::json!({}), project).await.unwrap();
+
+    let listing = result.as_str().unwrap();
+    assert!(listing.starts_with("read_text_file: "));
+    assert!(!listing.contains("delete_file"));
+}
+
+#[tokio::test]
+async fn test_get_project_context() {
+    let dir = tempfile::tempdir().unwrap();
+    git2::Repository::init(dir.path()).unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), "[package]\n").unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::create_dir(dir.path().join(".nixcode")).unwrap();
+    std::fs::write(dir.path().join(".nixcode/instructions.md"), "Be brief.").unwrap();
+
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let mut tools = Tools::new();
+    tools.add_tool(Arc::new(get_project_context::GetProjectContextTool {}));
+
+    // The object result reaches the model as JSON text
+    let result = tools
+        .execute_tool("get_project_context", serde_json::json!({}), project)
+        .await
+        .unwrap();
+    let context: serde_json::Value =
+        serde_json::from_str(&crate::tools::result_to_text(result)).unwrap();
+
+    assert_eq!(context["cwd"], dir.path().display().to_string());
+    assert!(context["git_root"].is_string());
+    assert_eq!(context["stack"], serde_json::json!(["Rust"]));
+    assert_eq!(context["has_init_analysis"], false);
+    assert_eq!(context["has_instructions"], true);
+    assert_eq!(
+        context["entries"],
+        serde_json::json!([".nixcode/", "Cargo.toml", "src/"])
+    );
+}
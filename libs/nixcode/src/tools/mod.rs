@@ -2,13 +2,17 @@ use crate::config::Config;
 use crate::project::Project;
 use async_trait::async_trait;
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
+pub mod docs;
 pub mod fs;
 pub mod git;
 pub mod glob;
+pub mod memory;
+pub mod meta;
 pub mod prompt;
 pub mod search;
+pub mod tasks;
 
 #[async_trait]
 pub trait Tool {
@@ -19,20 +23,55 @@ pub trait Tool {
         params: serde_json::Value,
         project: Arc<Project>,
     ) -> anyhow::Result<serde_json::Value>;
+
+    /// Compact result sent to the model instead of the full output when the details
+    /// are not needed for the next step (e.g. all tests passed)
+    fn summarize_result(&self, _result: &serde_json::Value) -> Option<String> {
+        None
+    }
+
+    /// Project files changed by a call with `params` that returned `result`, listed in
+    /// the turn summary
+    fn changed_paths(&self, _params: &serde_json::Value, _result: &str) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 pub type SafeTool = Arc<dyn Tool + Send + Sync>;
 
+/// Rough number of characters per token used to estimate schema sizes
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimated tokens of the tool definitions (name, description and input schema)
+/// sent with every request
+pub fn estimate_schema_tokens(tools: &[nixcode_llm_sdk::tools::Tool]) -> usize {
+    tools
+        .iter()
+        .map(|tool| serde_json::to_string(tool).map_or(0, |json| json.len()))
+        .sum::<usize>()
+        .div_ceil(CHARS_PER_TOKEN)
+}
+
+/// File tools the agent cannot work without, never left out to fit `tools.max_schema_tokens`
+const CORE_TOOLS: &[&str] = &[
+    "create_file",
+    "delete_file",
+    "delete_text_file_partial",
+    "read_text_file",
+    "update_text_file_partial",
+    "write_text_file",
+];
+
 #[derive(Default, Clone)]
 pub struct Tools {
     pub(crate) hashmap: HashMap<String, SafeTool>,
+    /// Tools last left out to fit the schema budget, to warn only when they change
+    dropped: Arc<Mutex<Vec<String>>>,
 }
 
 impl Tools {
     pub fn new() -> Self {
-        Self {
-            hashmap: HashMap::new(),
-        }
+        Self::default()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -68,6 +107,66 @@ impl Tools {
             .collect()
     }
 
+    /// Enabled tools sent with a request. With `tools.max_schema_tokens` set, the tools
+    /// with the largest schemas are left out until the definitions fit in the budget,
+    /// except for the core file tools.
+    pub fn get_request_tools(&self, config: &Config) -> Vec<nixcode_llm_sdk::tools::Tool> {
+        let mut tools = self.get_enabled_tools(config);
+        tools.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        let Some(max_tokens) = config.tools.max_schema_tokens else {
+            return tools;
+        };
+
+        let mut by_size: Vec<(usize, String)> = tools
+            .iter()
+            .filter(|tool| !CORE_TOOLS.contains(&tool.get_name()))
+            .map(|tool| {
+                let tokens = estimate_schema_tokens(std::slice::from_ref(tool));
+                (tokens, tool.get_name().to_string())
+            })
+            .collect();
+        by_size.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        let mut total_tokens = estimate_schema_tokens(&tools);
+        let mut dropped = Vec::new();
+        for (tokens, name) in by_size {
+            if total_tokens <= max_tokens {
+                break;
+            }
+            total_tokens -= tokens.min(total_tokens);
+            dropped.push(name);
+        }
+
+        let mut last_dropped = self.dropped.lock().unwrap();
+        if !dropped.is_empty() && *last_dropped != dropped {
+            log::warn!(
+                "Tool definitions exceed tools.max_schema_tokens ({}), not sending: {}",
+                max_tokens,
+                dropped.join(", ")
+            );
+        }
+        tools.retain(|tool| !dropped.iter().any(|name| name == tool.get_name()));
+        *last_dropped = dropped;
+
+        tools
+    }
+
+    pub fn summarize_result(&self, name: &str, result: &serde_json::Value) -> Option<String> {
+        self.get_tool(name)?.summarize_result(result)
+    }
+
+    pub fn changed_paths(
+        &self,
+        name: &str,
+        params: &serde_json::Value,
+        result: &str,
+    ) -> Vec<String> {
+        self.get_tool(name)
+            .map(|tool| tool.changed_paths(params, result))
+            .unwrap_or_default()
+    }
+
     pub async fn execute_tool(
         &self,
         name: &str,
@@ -81,3 +180,44 @@ impl Tools {
         }
     }
 }
+
+/// The `path` argument of a call whose result starts with `success`, the changed paths of
+/// tools changing a single file
+pub fn path_if_succeeded(params: &serde_json::Value, result: &str, success: &str) -> Vec<String> {
+    match params["path"].as_str() {
+        Some(path) if result.starts_with(success) => vec![path.to_string()],
+        _ => Vec::new(),
+    }
+}
+
+/// Results shorter than this are sent in full even when the tool can summarize them,
+/// a summary and a get_full_tool_result call would cost more
+pub const MIN_SUMMARIZED_CHARS: usize = 2000;
+
+/// Text of a tool result sent to the model, results that are not a string (e.g. the
+/// object of get_project_context) are sent as JSON text
+pub fn result_to_text(value: serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text,
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::fs::read_text_file::ReadTextFileTool;
+
+    #[test]
+    fn test_tool_schema_is_generated_once() {
+        let first = ReadTextFileTool::cached_schema();
+        let second = ReadTextFileTool::cached_schema();
+        assert!(std::ptr::eq(first, second));
+
+        let tool = ReadTextFileTool {};
+        assert_eq!(
+            serde_json::to_value(tool.get_schema()).unwrap(),
+            serde_json::to_value(first).unwrap()
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::project::Project;
+use crate::utils::fs::resolve_path_in_dir;
 use glob::glob;
 use regex::Regex;
 use serde_json::json;
@@ -31,7 +32,7 @@ pub fn validate_and_resolve_glob(
         return Err(json!("Glob pattern must be a relative path"));
     }
 
-    match crate::utils::fs::join_path(project.get_cwd(), glob_pattern.to_string()) {
+    match resolve_path_in_dir(&project.get_cwd(), glob_pattern) {
         Ok(p) => Ok(p.to_str().unwrap().to_string()),
         Err(e) => Err(json!(e.to_string())),
     }
@@ -78,6 +79,11 @@ pub async fn filter_paths(
 
                 let rel_path = result.unwrap().to_str()?;
 
+                // Symlinks leading outside of the project are skipped
+                if resolve_path_in_dir(&cwd, rel_path).is_err() {
+                    return None;
+                }
+
                 // Check if file should be included based on .git and hidden file filters
                 if !include_git && rel_path.contains(".git/") {
                     return None;
@@ -30,15 +30,55 @@ pub struct ReplaceContentParams {
     )]
     #[serde(default)]
     pub include_hidden: Option<bool>,
+
+    #[schemars(
+        description = "Report the changed line numbers with the lines before and after the replacement (default: false)"
+    )]
+    #[serde(default)]
+    pub verbose: Option<bool>,
+}
+
+/// Maximum number of changed lines reported in verbose mode
+const MAX_REPORTED_CHANGES: usize = 50;
+/// Maximum number of characters of a reported line
+const MAX_SNIPPET_CHARS: usize = 200;
+
+#[derive(Serialize)]
+struct LineChange {
+    line: usize,
+    before: String,
+    after: String,
 }
 
 #[derive(Serialize)]
 struct ReplacementResult {
     path: String,
     matches: usize,
+    changes: Vec<LineChange>,
+}
+
+fn snippet(line: &str) -> String {
+    if line.chars().count() <= MAX_SNIPPET_CHARS {
+        return line.to_string();
+    }
+
+    let cut: String = line.chars().take(MAX_SNIPPET_CHARS).collect();
+    format!("{}...", cut)
+}
+
+/// Files listed in the `path: N replacements` lines of the result
+fn replaced_paths(_params: &serde_json::Value, result: &str) -> Vec<String> {
+    result
+        .lines()
+        .filter_map(|line| line.strip_suffix(" replacements"))
+        .filter_map(|line| line.rsplit_once(": ").map(|(path, _)| path.to_string()))
+        .collect()
 }
 
-#[tool("Replace text content in files based on regex pattern")]
+#[tool(
+    "Replace text content in files based on regex pattern",
+    changed_paths = replaced_paths
+)]
 pub async fn replace_content(
     params: ReplaceContentParams,
     project: Arc<Project>,
@@ -71,13 +111,20 @@ pub async fn replace_content(
     // Process replacements in files
     let regex_pattern = regex.clone();
     let replacement = params.replacement.clone();
+    let verbose = params.verbose.unwrap_or(false);
     let results = tokio::task::spawn_blocking(move || {
         let mut replacement_results = Vec::new();
+        let mut reported_changes = 0;
         let mut total_files_changed = 0;
         let mut total_replacements = 0;
         let mut skipped_files = 0;
 
         for (file_path, rel_path) in filtered_paths {
+            // Files not written yet stay unchanged when the tools are cancelled
+            if project.is_cancelled() {
+                break;
+            }
+
             // Skip directories
             if file_path.is_dir() {
                 continue;
@@ -106,15 +153,25 @@ pub async fn replace_content(
             // Check and perform replacements
             let mut file_changed = false;
             let mut matches_in_file = 0;
+            let mut changes = Vec::new();
             let new_lines: Vec<String> = lines
                 .into_iter()
-                .map(|line| {
+                .enumerate()
+                .map(|(index, line)| {
                     if regex_pattern.is_match(&line) {
                         let new_line = regex_pattern.replace_all(&line, &replacement).to_string();
                         if new_line != line {
                             file_changed = true;
                             matches_in_file += 1;
                             total_replacements += 1;
+                            if verbose && reported_changes < MAX_REPORTED_CHANGES {
+                                reported_changes += 1;
+                                changes.push(LineChange {
+                                    line: index + 1,
+                                    before: snippet(&line),
+                                    after: snippet(&new_line),
+                                });
+                            }
                             return new_line;
                         }
                     }
@@ -127,6 +184,7 @@ pub async fn replace_content(
                 replacement_results.push(ReplacementResult {
                     path: rel_path.clone(),
                     matches: matches_in_file,
+                    changes,
                 });
 
                 total_files_changed += 1;
@@ -166,6 +224,20 @@ pub async fn replace_content(
                 "{}: {} replacements\n",
                 result.path, result.matches
             ));
+
+            for change in &result.changes {
+                result_str.push_str(&format!(
+                    "  line {}:\n  - {}\n  + {}\n",
+                    change.line, change.before, change.after
+                ));
+            }
+        }
+
+        if verbose && total_replacements > MAX_REPORTED_CHANGES {
+            result_str.push_str(&format!(
+                "\n... {} more changed lines not shown\n",
+                total_replacements - MAX_REPORTED_CHANGES
+            ));
         }
 
         if skipped_files > 0 {
@@ -73,3 +73,63 @@ async fn test_search_content() {
     assert!(result.contains("Found"));
     assert!(result.contains("SearchContentParams"));
 }
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_search_content_skips_symlinks_escaping_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let outside_dir = tempfile::tempdir().unwrap();
+    std::fs::write(outside_dir.path().join("secret.txt"), "password").unwrap();
+    std::os::unix::fs::symlink(outside_dir.path(), dir.path().join("escape")).unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "no password here").unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+
+    let params = search_content::SearchContentParams {
+        pattern: "password".to_string(),
+        glob_pattern: "**/*.txt".to_string(),
+        include_gitignored: None,
+        include_hidden: None,
+        offset: None,
+    };
+    let result = search_content::search_content(params, project.clone())
+        .await
+        .to_string();
+    assert!(result.contains("notes.txt"));
+    assert!(!result.contains("secret.txt"));
+
+    let params = search_content::SearchContentParams {
+        pattern: "password".to_string(),
+        glob_pattern: "escape/*.txt".to_string(),
+        include_gitignored: None,
+        include_hidden: None,
+        offset: None,
+    };
+    let result = search_content::search_content(params, project).await;
+    assert_eq!(result, json!("Path must be inside project directory"));
+}
+
+#[tokio::test]
+async fn test_replace_content_verbose_reports_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("lib.rs"),
+        "fn old_name() {}\n\nfn main() {\n    old_name();\n}",
+    )
+    .unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = replace_content::ReplaceContentParams {
+        pattern: "old_name".to_string(),
+        replacement: "new_name".to_string(),
+        glob_pattern: "*.rs".to_string(),
+        include_gitignored: None,
+        include_hidden: None,
+        verbose: Some(true),
+    };
+
+    let result = replace_content::replace_content(params, project).await;
+
+    let result = result.as_str().unwrap();
+    assert!(result.contains("lib.rs: 2 replacements\n"));
+    assert!(result.contains("  line 1:\n  - fn old_name() {}\n  + fn new_name() {}\n"));
+    assert!(result.contains("  line 4:\n  -     old_name();\n  +     new_name();\n"));
+}
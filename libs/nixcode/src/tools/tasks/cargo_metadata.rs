@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::utils::command::{format_command_output, run_command};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct CargoMetadataParams {}
+
+/// Fields of `cargo metadata --format-version=1` used for the summary
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+    workspace_root: String,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    version: String,
+    edition: String,
+    manifest_path: String,
+    features: HashMap<String, Vec<String>>,
+    dependencies: Vec<MetadataDependency>,
+}
+
+#[derive(Deserialize)]
+struct MetadataDependency {
+    name: String,
+    kind: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct PackageSummary {
+    pub name: String,
+    pub version: String,
+    pub edition: String,
+    pub manifest_path: String,
+    pub features: Vec<String>,
+    pub dependencies: Vec<String>,
+    pub dev_dependencies: Vec<String>,
+}
+
+/// Workspace layout with the packages of its members
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CargoMetadataSummary {
+    pub workspace_root: String,
+    /// False for a single package project, true for a (virtual) workspace
+    pub is_workspace: bool,
+    pub members: Vec<String>,
+    pub packages: Vec<PackageSummary>,
+}
+
+/// Parses the JSON output of `cargo metadata --format-version=1 --no-deps`,
+/// manifest paths are made relative to the workspace root
+pub fn parse_cargo_metadata(output: &str) -> Result<CargoMetadataSummary, String> {
+    let metadata: Metadata =
+        serde_json::from_str(output).map_err(|e| format!("Invalid cargo metadata: {}", e))?;
+
+    let root = Path::new(&metadata.workspace_root);
+    let root_manifest = root.join("Cargo.toml");
+
+    let members = metadata
+        .packages
+        .iter()
+        .filter(|package| metadata.workspace_members.contains(&package.id))
+        .collect::<Vec<_>>();
+
+    let has_root_package = members
+        .iter()
+        .any(|package| Path::new(&package.manifest_path) == root_manifest);
+
+    let packages = members
+        .iter()
+        .map(|package| {
+            let mut features = package.features.keys().cloned().collect::<Vec<_>>();
+            features.sort();
+
+            let dependencies_of = |dev: bool| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| (dependency.kind.as_deref() == Some("dev")) == dev)
+                    .map(|dependency| dependency.name.clone())
+                    .collect::<Vec<_>>()
+            };
+
+            let manifest_path = Path::new(&package.manifest_path);
+            PackageSummary {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                edition: package.edition.clone(),
+                manifest_path: manifest_path
+                    .strip_prefix(root)
+                    .unwrap_or(manifest_path)
+                    .display()
+                    .to_string(),
+                features,
+                dependencies: dependencies_of(false),
+                dev_dependencies: dependencies_of(true),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(CargoMetadataSummary {
+        workspace_root: metadata.workspace_root.clone(),
+        is_workspace: !has_root_package || packages.len() > 1,
+        members: packages
+            .iter()
+            .map(|package| package.name.clone())
+            .collect(),
+        packages,
+    })
+}
+
+#[tool("Get the packages of the Cargo workspace with their versions, features and dependencies")]
+pub async fn cargo_metadata(
+    _params: CargoMetadataParams,
+    project: Arc<Project>,
+) -> serde_json::Value {
+    let cwd = project.get_cwd();
+    let command_line = "cargo metadata --format-version=1 --no-deps";
+    let output = match run_command(&cwd, command_line, &project.get_command_options()).await {
+        Ok(output) => output,
+        Err(e) => return json!(format!("Failed to run cargo metadata: {}", e)),
+    };
+
+    if !output.status.success() {
+        return json!(format_command_output(&output));
+    }
+
+    match parse_cargo_metadata(&String::from_utf8_lossy(&output.stdout)) {
+        Ok(summary) => json!(summary),
+        Err(e) => json!(e),
+    }
+}
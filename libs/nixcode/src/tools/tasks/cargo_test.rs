@@ -0,0 +1,144 @@
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::utils::command::{format_command_output, run_command};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct CargoTestParams {
+    #[schemars(description = "Only run tests whose name contains this filter")]
+    #[serde(default)]
+    pub filter: Option<String>,
+
+    #[schemars(description = "Package to test (cargo test -p)")]
+    #[serde(default)]
+    pub package: Option<String>,
+
+    #[schemars(
+        description = "Return pass/fail counts and failing test names instead of the raw output (default: true)"
+    )]
+    #[serde(default)]
+    pub structured: Option<bool>,
+}
+
+/// Counts and failing tests summed over all test binaries of a `cargo test` run
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct CargoTestSummary {
+    pub passed: u32,
+    pub failed: u32,
+    pub ignored: u32,
+    pub failures: Vec<String>,
+}
+
+/// Reads a count like `3 passed` from a summary line part
+fn parse_count(part: &str, label: &str) -> Option<u32> {
+    part.trim()
+        .strip_suffix(label)
+        .and_then(|count| count.trim().parse().ok())
+}
+
+/// Parses the standard `cargo test` output, returns None when no test ran
+/// (e.g. the build failed)
+pub fn parse_cargo_test_output(output: &str) -> Option<CargoTestSummary> {
+    let mut summary = CargoTestSummary::default();
+    let mut has_result = false;
+
+    for line in output.lines() {
+        let line = line.trim();
+
+        if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|line| line.strip_suffix(" ... FAILED"))
+        {
+            summary.failures.push(name.to_string());
+            continue;
+        }
+
+        let Some(result) = line.strip_prefix("test result: ") else {
+            continue;
+        };
+        has_result = true;
+
+        let counts = result.split_once(". ").map_or(result, |(_, counts)| counts);
+        for part in counts.split(';') {
+            if let Some(count) = parse_count(part, "passed") {
+                summary.passed += count;
+            } else if let Some(count) = parse_count(part, "failed") {
+                summary.failed += count;
+            } else if let Some(count) = parse_count(part, "ignored") {
+                summary.ignored += count;
+            }
+        }
+    }
+
+    has_result.then_some(summary)
+}
+
+/// Allows only characters used in test paths and package names, the arguments
+/// are passed to the shell
+pub fn is_valid_argument(argument: &str) -> bool {
+    !argument.is_empty()
+        && argument
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | ':'))
+}
+
+/// Summary of a structured result in which every test passed
+pub fn summarize_cargo_test(result: &serde_json::Value) -> Option<String> {
+    let summary = result.as_object()?;
+    if summary.get("failed")?.as_u64()? > 0 {
+        return None;
+    }
+
+    let passed = summary.get("passed")?.as_u64()?;
+    let ignored = summary.get("ignored")?.as_u64()?;
+    Some(format!(
+        "Tests succeeded: {} passed, {} ignored",
+        passed, ignored
+    ))
+}
+
+#[tool(
+    "Run cargo test, returning passed/failed/ignored counts and the names of failing tests",
+    summarize = summarize_cargo_test
+)]
+pub async fn cargo_test(params: CargoTestParams, project: Arc<Project>) -> serde_json::Value {
+    let mut command_line = String::from("cargo test");
+
+    if let Some(package) = &params.package {
+        if !is_valid_argument(package) {
+            return json!("Invalid package name");
+        }
+        command_line.push_str(&format!(" -p {}", package));
+    }
+
+    if let Some(filter) = &params.filter {
+        if !is_valid_argument(filter) {
+            return json!("Invalid filter, use a test name or module path");
+        }
+        command_line.push_str(&format!(" {}", filter));
+    }
+
+    let cwd = project.get_cwd();
+    let output = match run_command(&cwd, &command_line, &project.get_command_options()).await {
+        Ok(output) => output,
+        Err(e) => return json!(format!("Failed to run cargo test: {}", e)),
+    };
+
+    let raw_output = format_command_output(&output);
+    project.set_last_cargo_output(raw_output.clone());
+
+    if !params.structured.unwrap_or(true) {
+        return json!(raw_output);
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    match parse_cargo_test_output(&stdout) {
+        Some(summary) => json!(summary),
+        None => json!(raw_output),
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::tools::tasks::cargo_test::is_valid_argument;
+use crate::utils::command::{format_command_output, run_command, CommandOptions};
+
+/// Number of least covered files returned with the totals
+const MAX_FILES: usize = 50;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct CoverageParams {
+    #[schemars(description = "Package to measure (-p)")]
+    #[serde(default)]
+    pub package: Option<String>,
+}
+
+/// Cargo subcommands measuring coverage, in order of preference
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CoverageBackend {
+    LlvmCov,
+    Tarpaulin,
+}
+
+impl CoverageBackend {
+    fn get_subcommand(&self) -> &'static str {
+        match self {
+            CoverageBackend::LlvmCov => "llvm-cov",
+            CoverageBackend::Tarpaulin => "tarpaulin",
+        }
+    }
+
+    fn get_command_line(&self) -> &'static str {
+        match self {
+            CoverageBackend::LlvmCov => "cargo llvm-cov --summary-only",
+            CoverageBackend::Tarpaulin => "cargo tarpaulin --skip-clean --out Stdout",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct FileCoverage {
+    pub file: String,
+    pub covered_lines: u64,
+    pub total_lines: u64,
+    pub percent: f64,
+}
+
+/// Line coverage of the whole run, files are sorted from the least covered
+#[derive(Debug, PartialEq, Serialize)]
+pub struct CoverageSummary {
+    pub covered_lines: u64,
+    pub total_lines: u64,
+    pub percent: f64,
+    pub files: Vec<FileCoverage>,
+}
+
+fn get_percent(covered_lines: u64, total_lines: u64) -> f64 {
+    if total_lines == 0 {
+        return 100.0;
+    }
+
+    (covered_lines as f64 * 10000.0 / total_lines as f64).round() / 100.0
+}
+
+impl FileCoverage {
+    fn new(file: &str, covered_lines: u64, total_lines: u64) -> Self {
+        Self {
+            file: file.to_string(),
+            covered_lines,
+            total_lines,
+            percent: get_percent(covered_lines, total_lines),
+        }
+    }
+}
+
+impl CoverageSummary {
+    fn new(mut files: Vec<FileCoverage>) -> Self {
+        let covered_lines = files.iter().map(|file| file.covered_lines).sum();
+        let total_lines = files.iter().map(|file| file.total_lines).sum();
+        files.sort_by(|a, b| a.percent.total_cmp(&b.percent));
+
+        Self {
+            covered_lines,
+            total_lines,
+            percent: get_percent(covered_lines, total_lines),
+            files,
+        }
+    }
+}
+
+/// Parses the table of `cargo llvm-cov --summary-only`, using the lines and
+/// missed lines columns
+pub fn parse_llvm_cov_summary(output: &str) -> Option<CoverageSummary> {
+    let mut lines = output
+        .lines()
+        .skip_while(|line| !line.starts_with("Filename"));
+    let header = lines.next()?.split_whitespace().collect::<Vec<_>>();
+
+    // Column names of two words ("Missed Lines") take two header entries but one
+    // value in the rows
+    let lines_column = header.iter().position(|column| *column == "Lines")?;
+    let lines_column = lines_column
+        - header[..lines_column]
+            .iter()
+            .filter(|column| **column == "Missed")
+            .count();
+
+    let files = lines
+        .filter(|line| !line.starts_with('-') && !line.starts_with("TOTAL"))
+        .filter_map(|line| {
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            let total = columns.get(lines_column)?.parse::<u64>().ok()?;
+            let missed = columns.get(lines_column + 1)?.parse::<u64>().ok()?;
+
+            Some(FileCoverage::new(
+                columns[0],
+                total.saturating_sub(missed),
+                total,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    (!files.is_empty()).then(|| CoverageSummary::new(files))
+}
+
+/// Parses the `Tested/Total Lines` section printed by `cargo tarpaulin`
+pub fn parse_tarpaulin_summary(output: &str) -> Option<CoverageSummary> {
+    let files = output
+        .lines()
+        .skip_while(|line| !line.contains("Tested/Total Lines:"))
+        .skip(1)
+        .map_while(|line| line.strip_prefix("|| "))
+        .filter_map(|line| {
+            let (file, counts) = line.rsplit_once(": ")?;
+            let counts = counts.split_whitespace().next()?;
+            let (covered, total) = counts.split_once('/')?;
+
+            Some(FileCoverage::new(
+                file,
+                covered.parse().ok()?,
+                total.parse().ok()?,
+            ))
+        })
+        .collect::<Vec<_>>();
+
+    (!files.is_empty()).then(|| CoverageSummary::new(files))
+}
+
+/// Returns the first installed coverage subcommand
+async fn detect_backend(cwd: &Path, options: &CommandOptions) -> Option<CoverageBackend> {
+    for backend in [CoverageBackend::LlvmCov, CoverageBackend::Tarpaulin] {
+        let command_line = format!("cargo {} --version", backend.get_subcommand());
+        if let Ok(output) = run_command(cwd, &command_line, options).await {
+            if output.status.success() {
+                return Some(backend);
+            }
+        }
+    }
+
+    None
+}
+
+#[tool("Measure test coverage with cargo llvm-cov or cargo tarpaulin, returning total and per-file line coverage")]
+pub async fn coverage(params: CoverageParams, project: Arc<Project>) -> serde_json::Value {
+    let cwd = project.get_cwd();
+    let options = project.get_command_options();
+
+    let Some(backend) = detect_backend(&cwd, &options).await else {
+        return json!("No coverage tool is installed, install cargo-llvm-cov (cargo install cargo-llvm-cov) or cargo-tarpaulin (cargo install cargo-tarpaulin)");
+    };
+
+    let mut command_line = String::from(backend.get_command_line());
+    if let Some(package) = &params.package {
+        if !is_valid_argument(package) {
+            return json!("Invalid package name");
+        }
+        command_line.push_str(&format!(" -p {}", package));
+    }
+
+    let output = match run_command(&cwd, &command_line, &options).await {
+        Ok(output) => output,
+        Err(e) => {
+            return json!(format!(
+                "Failed to run cargo {}: {}",
+                backend.get_subcommand(),
+                e
+            ))
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let summary = match backend {
+        CoverageBackend::LlvmCov => parse_llvm_cov_summary(&stdout),
+        CoverageBackend::Tarpaulin => parse_tarpaulin_summary(&stdout),
+    };
+
+    match summary {
+        Some(mut summary) => {
+            summary.files.truncate(MAX_FILES);
+            json!(summary)
+        }
+        None => json!(format_command_output(&output)),
+    }
+}
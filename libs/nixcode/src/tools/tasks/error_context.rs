@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::project::Project;
+use crate::utils::fs::resolve_path_in_dir;
+
+/// Lines shown before and after the error line when not given
+const DEFAULT_CONTEXT_LINES: usize = 5;
+
+/// Errors returned at most, later errors are often caused by the first ones
+const MAX_ERRORS: usize = 10;
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct GetErrorContextParams {
+    #[schemars(description = "Lines shown before and after each error line (default: 5)")]
+    #[serde(default)]
+    pub context_lines: Option<usize>,
+}
+
+/// Compiler error with the location of its primary span
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Reads `src/main.rs:3:5` from a ` --> src/main.rs:3:5` line
+fn parse_location(line: &str) -> Option<(String, usize, usize)> {
+    let location = line.trim_start().strip_prefix("--> ")?;
+    let mut parts = location.rsplitn(3, ':');
+    let column = parts.next()?.trim().parse().ok()?;
+    let line = parts.next()?.parse().ok()?;
+    let file = parts.next()?;
+
+    Some((file.to_string(), line, column))
+}
+
+/// Parses the errors of rustc diagnostics printed by cargo, summary lines like
+/// `error: could not compile` have no location and are skipped
+pub fn parse_diagnostics(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut message: Option<&str> = None;
+
+    for line in output.lines() {
+        if line.starts_with("error") && line.contains(": ") {
+            message = Some(line);
+            continue;
+        }
+
+        // Locations of warnings are not reported
+        if line.starts_with("warning") {
+            message = None;
+            continue;
+        }
+
+        let Some((file, line, column)) = parse_location(line) else {
+            continue;
+        };
+
+        if let Some(message) = message.take() {
+            diagnostics.push(Diagnostic {
+                message: message.to_string(),
+                file,
+                line,
+                column,
+            });
+        }
+    }
+
+    diagnostics
+}
+
+/// Numbered lines around `line` (1-based), the error line is marked with `>`
+pub fn format_snippet(content: &str, line: usize, context_lines: usize) -> String {
+    let first = line.saturating_sub(context_lines).max(1);
+    let last = line + context_lines;
+    let width = last.to_string().len();
+
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, text)| (index + 1, text))
+        .filter(|(number, _)| (first..=last).contains(number))
+        .map(|(number, text)| {
+            let marker = if number == line { '>' } else { ' ' };
+            format!("{} {:>width$} | {}", marker, number, text, width = width)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[tool("Get the code around each error of the last cargo run (cargo_test or a cargo task)")]
+pub async fn get_error_context(
+    params: GetErrorContextParams,
+    project: Arc<Project>,
+) -> serde_json::Value {
+    let Some(output) = project.get_last_cargo_output() else {
+        return json!("No cargo command has been run yet");
+    };
+
+    let diagnostics = parse_diagnostics(&output);
+    if diagnostics.is_empty() {
+        return json!("The last cargo run reported no errors with a location");
+    }
+
+    let cwd = project.get_cwd();
+    let context_lines = params.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES);
+    let mut sections = diagnostics
+        .iter()
+        .take(MAX_ERRORS)
+        .map(|diagnostic| {
+            let header = format!(
+                "{}\n--> {}:{}:{}",
+                diagnostic.message, diagnostic.file, diagnostic.line, diagnostic.column
+            );
+
+            let content = resolve_path_in_dir(&cwd, &diagnostic.file)
+                .ok()
+                .and_then(|path| std::fs::read_to_string(path).ok());
+
+            match content {
+                Some(content) => format!(
+                    "{}\n{}",
+                    header,
+                    format_snippet(&content, diagnostic.line, context_lines)
+                ),
+                None => format!("{}\n(file is not readable in the project)", header),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if diagnostics.len() > MAX_ERRORS {
+        sections.push(format!(
+            "{} more errors not shown",
+            diagnostics.len() - MAX_ERRORS
+        ));
+    }
+
+    json!(sections.join("\n\n"))
+}
@@ -0,0 +1,8 @@
+pub mod cargo_metadata;
+pub mod cargo_test;
+pub mod coverage;
+pub mod error_context;
+pub mod run_task;
+pub mod tasks_file;
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+
+use nixcode_macros::tool;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::tasks_file::{TasksFile, TASKS_FILE};
+use crate::project::{Project, CANCELLED_MESSAGE};
+use crate::utils::command::{format_command_output, run_command};
+
+#[derive(JsonSchema, Serialize, Deserialize)]
+pub struct RunTaskParams {
+    #[schemars(description = "Name of the task defined in .nixcode/tasks.toml")]
+    pub task: String,
+}
+
+#[tool("Run a named task (e.g. lint, test) defined by the project in .nixcode/tasks.toml")]
+pub async fn run_task(params: RunTaskParams, project: Arc<Project>) -> serde_json::Value {
+    if project.is_cancelled() {
+        return json!(CANCELLED_MESSAGE);
+    }
+
+    let cwd = project.get_cwd();
+    let tasks = match TasksFile::load(&cwd) {
+        Ok(tasks) => tasks,
+        Err(e) => return json!(e),
+    };
+
+    let command_line = match tasks.get_task(&params.task) {
+        Some(command_line) => command_line,
+        None => {
+            return json!(format!(
+                "Unknown task '{}', tasks available in {}: {}",
+                params.task,
+                TASKS_FILE,
+                tasks.get_task_names().join(", ")
+            ))
+        }
+    };
+
+    match run_command(&cwd, command_line, &project.get_command_options()).await {
+        Ok(output) => {
+            let output = format_command_output(&output);
+            if command_line.trim_start().starts_with("cargo ") {
+                project.set_last_cargo_output(output.clone());
+            }
+            json!(output)
+        }
+        Err(e) => json!(format!("Failed to run task '{}': {}", params.task, e)),
+    }
+}
@@ -0,0 +1,45 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Location of the tasks file, relative to the project root
+pub const TASKS_FILE: &str = ".nixcode/tasks.toml";
+
+/// Named command lines the agent is allowed to run, e.g.
+///
+/// ```toml
+/// [tasks]
+/// lint = "cargo clippy --workspace"
+/// test = "cargo test --workspace"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct TasksFile {
+    #[serde(default)]
+    pub tasks: BTreeMap<String, String>,
+}
+
+impl TasksFile {
+    pub fn get_path(project_dir: &Path) -> PathBuf {
+        project_dir.join(TASKS_FILE)
+    }
+
+    pub fn exists(project_dir: &Path) -> bool {
+        Self::get_path(project_dir).is_file()
+    }
+
+    pub fn load(project_dir: &Path) -> Result<Self, String> {
+        let path = Self::get_path(project_dir);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read {}: {}", TASKS_FILE, e))?;
+
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", TASKS_FILE, e))
+    }
+
+    pub fn get_task(&self, name: &str) -> Option<&String> {
+        self.tasks.get(name)
+    }
+
+    pub fn get_task_names(&self) -> Vec<&str> {
+        self.tasks.keys().map(String::as_str).collect()
+    }
+}
@@ -0,0 +1,383 @@
+use super::*;
+use crate::project::Project;
+use std::path::Path;
+use std::sync::Arc;
+
+fn write_tasks_file(dir: &Path, content: &str) {
+    std::fs::create_dir_all(dir.join(".nixcode")).unwrap();
+    std::fs::write(dir.join(tasks_file::TASKS_FILE), content).unwrap();
+}
+
+#[test]
+fn test_load_tasks_file() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tasks_file(
+        dir.path(),
+        "[tasks]\nlint = \"cargo clippy\"\ntest = \"cargo test\"\n",
+    );
+
+    let tasks = tasks_file::TasksFile::load(dir.path()).unwrap();
+
+    assert_eq!(tasks.get_task("lint").unwrap(), "cargo clippy");
+    assert_eq!(tasks.get_task_names(), vec!["lint", "test"]);
+}
+
+#[tokio::test]
+async fn test_run_named_task() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tasks_file(dir.path(), "[tasks]\ngreet = \"echo hello\"\n");
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = run_task::RunTaskParams {
+        task: String::from("greet"),
+    };
+
+    let result = run_task::run_task(params, project).await;
+
+    let output = result.as_str().unwrap();
+    assert!(output.starts_with("Exit code: 0"));
+    assert!(output.contains("hello"));
+}
+
+#[tokio::test]
+async fn test_run_unknown_task() {
+    let dir = tempfile::tempdir().unwrap();
+    write_tasks_file(dir.path(), "[tasks]\ngreet = \"echo hello\"\n");
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = run_task::RunTaskParams {
+        task: String::from("rm -rf /"),
+    };
+
+    let result = run_task::run_task(params, project).await;
+
+    assert_eq!(
+        result,
+        serde_json::json!("Unknown task 'rm -rf /', tasks available in .nixcode/tasks.toml: greet")
+    );
+}
+
+#[cfg(not(windows))]
+#[tokio::test]
+async fn test_run_command_filters_sensitive_vars() {
+    use crate::utils::command::{run_command, CommandOptions};
+
+    let default_options = CommandOptions::default();
+    assert!(!default_options.is_var_allowed("NIXCODE_TEST_API_KEY"));
+    assert!(default_options.is_var_allowed("NIXCODE_TEST_VISIBLE"));
+
+    let options = CommandOptions {
+        env_allowlist: Some(vec!["PATH".to_string(), "NIXCODE_TEST_API_KEY".to_string()]),
+        ..Default::default()
+    };
+    assert!(options.is_var_allowed("NIXCODE_TEST_API_KEY"));
+    assert!(!options.is_var_allowed("NIXCODE_TEST_VISIBLE"));
+
+    // Only allowed variables of the current environment reach the command
+    let dir = tempfile::tempdir().unwrap();
+    let command_line = "echo \"${PATH:+path} ${HOME:-absent}\"";
+    let output = run_command(dir.path(), command_line, &options)
+        .await
+        .unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "path absent"
+    );
+}
+
+#[test]
+fn test_is_sensitive_var() {
+    use crate::utils::command::is_sensitive_var;
+
+    assert!(is_sensitive_var("ANTHROPIC_API_KEY"));
+    assert!(is_sensitive_var("GITHUB_TOKEN"));
+    assert!(is_sensitive_var("aws_secret_access_key"));
+    assert!(!is_sensitive_var("PATH"));
+    assert!(!is_sensitive_var("TOKENIZERS_PARALLELISM"));
+}
+
+#[cfg(not(windows))]
+#[tokio::test]
+async fn test_run_command_caps_output() {
+    use crate::utils::command::{run_command, CommandOptions};
+
+    let dir = tempfile::tempdir().unwrap();
+    let options = CommandOptions {
+        max_output_bytes: 1000,
+        ..Default::default()
+    };
+
+    // Prints 200 KB, more than a pipe buffer, then exits successfully
+    let command_line = "yes x | head -c 200000; echo end; echo done >&2";
+    let output = run_command(dir.path(), command_line, &options)
+        .await
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("x\nx\n"));
+    assert!(stdout.contains("x\n\n[output truncated, 199004 bytes omitted]\nx\n"));
+    assert!(stdout.ends_with("x\nend\n"));
+    assert!(output.stdout.len() < 1100);
+    assert_eq!(String::from_utf8_lossy(&output.stderr).trim(), "done");
+}
+
+const CARGO_TEST_OUTPUT: &str = r#"
+running 3 tests
+test config::tests::test_load ... ok
+test tools::tests::test_tree ... FAILED
+test tools::tests::test_slow ... ignored
+
+failures:
+
+---- tools::tests::test_tree stdout ----
+thread 'tools::tests::test_tree' panicked at src/tools/tests.rs:10:5
+
+failures:
+    tools::tests::test_tree
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+running 2 tests
+test it_works ... ok
+test it_still_works ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+"#;
+
+#[test]
+fn test_parse_cargo_test_output() {
+    let summary = cargo_test::parse_cargo_test_output(CARGO_TEST_OUTPUT).unwrap();
+
+    assert_eq!(
+        summary,
+        cargo_test::CargoTestSummary {
+            passed: 3,
+            failed: 1,
+            ignored: 1,
+            failures: vec![String::from("tools::tests::test_tree")],
+        }
+    );
+}
+
+#[test]
+fn test_parse_cargo_test_output_without_results() {
+    let output = "error[E0425]: cannot find value `x` in this scope\n";
+
+    assert_eq!(cargo_test::parse_cargo_test_output(output), None);
+}
+
+const CARGO_METADATA_OUTPUT: &str = include_str!("fixtures/cargo_metadata.json");
+
+#[test]
+fn test_parse_cargo_metadata_workspace() {
+    let summary = cargo_metadata::parse_cargo_metadata(CARGO_METADATA_OUTPUT).unwrap();
+
+    assert_eq!(summary.workspace_root, "/work/demo");
+    assert!(summary.is_workspace);
+    assert_eq!(summary.members, vec!["demo-cli", "demo-core"]);
+    assert_eq!(
+        summary.packages[1],
+        cargo_metadata::PackageSummary {
+            name: "demo-core".into(),
+            version: "0.1.3".into(),
+            edition: "2021".into(),
+            manifest_path: "libs/demo-core/Cargo.toml".into(),
+            features: vec!["default".into(), "serde".into(), "std".into()],
+            dependencies: vec!["serde".into()],
+            dev_dependencies: vec![],
+        }
+    );
+    assert_eq!(summary.packages[0].dependencies, vec!["clap", "demo-core"]);
+    assert_eq!(summary.packages[0].dev_dependencies, vec!["tempfile"]);
+}
+
+#[test]
+fn test_parse_cargo_metadata_single_package() {
+    let output = r#"{
+        "packages": [{
+            "id": "path+file:///work/tool#0.1.0", "name": "tool", "version": "0.1.0",
+            "edition": "2024", "manifest_path": "/work/tool/Cargo.toml",
+            "features": {}, "dependencies": []
+        }],
+        "workspace_members": ["path+file:///work/tool#0.1.0"],
+        "workspace_root": "/work/tool"
+    }"#;
+
+    let summary = cargo_metadata::parse_cargo_metadata(output).unwrap();
+
+    assert!(!summary.is_workspace);
+    assert_eq!(summary.packages[0].manifest_path, "Cargo.toml");
+    assert!(cargo_metadata::parse_cargo_metadata("error: could not find `Cargo.toml`").is_err());
+}
+
+#[test]
+fn test_successful_cargo_test_is_summarized() {
+    use crate::tools::Tool;
+
+    let tool = cargo_test::CargoTestTool {};
+    let passed = cargo_test::CargoTestSummary {
+        passed: 42,
+        failed: 0,
+        ignored: 2,
+        failures: vec![],
+    };
+    assert_eq!(
+        tool.summarize_result(&serde_json::json!(passed)).as_deref(),
+        Some("Tests succeeded: 42 passed, 2 ignored")
+    );
+
+    let failed = cargo_test::parse_cargo_test_output(CARGO_TEST_OUTPUT).unwrap();
+    assert_eq!(tool.summarize_result(&serde_json::json!(failed)), None);
+    assert_eq!(tool.summarize_result(&serde_json::json!("Exit code: 101\n")), None);
+}
+
+#[test]
+fn test_parse_llvm_cov_summary() {
+    let output = include_str!("fixtures/llvm_cov_summary.txt");
+    let summary = coverage::parse_llvm_cov_summary(output).unwrap();
+
+    assert_eq!(summary.covered_lines, 240);
+    assert_eq!(summary.total_lines, 410);
+    assert_eq!(summary.percent, 58.54);
+    assert_eq!(
+        summary.files[0],
+        coverage::FileCoverage {
+            file: "src/parser/mod.rs".into(),
+            covered_lines: 0,
+            total_lines: 64,
+            percent: 0.0,
+        }
+    );
+    assert_eq!(summary.files[2].file, "src/config.rs");
+    assert_eq!(summary.files[2].percent, 93.75);
+}
+
+#[test]
+fn test_parse_tarpaulin_summary() {
+    let output = include_str!("fixtures/tarpaulin_summary.txt");
+    let summary = coverage::parse_tarpaulin_summary(output).unwrap();
+
+    assert_eq!(summary.covered_lines, 240);
+    assert_eq!(summary.total_lines, 410);
+    assert_eq!(
+        summary
+            .files
+            .iter()
+            .map(|file| file.file.as_str())
+            .collect::<Vec<_>>(),
+        vec!["src/parser/mod.rs", "src/lib.rs", "src/config.rs"]
+    );
+    assert_eq!(coverage::parse_tarpaulin_summary("error: no such command"), None);
+}
+
+const CARGO_BUILD_ERRORS: &str = r#"   Compiling demo v0.1.0 (/work/demo)
+warning: unused variable: `unused`
+ --> src/lib.rs:2:9
+  |
+2 |     let unused = 1;
+  |         ^^^^^^ help: if this is intentional, prefix it with an underscore: `_unused`
+
+error[E0425]: cannot find value `totl` in this scope
+ --> src/lib.rs:4:5
+  |
+4 |     totl
+  |     ^^^^ help: a local variable with a similar name exists: `total`
+
+error: could not compile `demo` (lib) due to 1 previous error; 1 warning emitted
+"#;
+
+#[test]
+fn test_parse_cargo_diagnostics() {
+    let diagnostics = error_context::parse_diagnostics(CARGO_BUILD_ERRORS);
+
+    assert_eq!(
+        diagnostics,
+        vec![error_context::Diagnostic {
+            message: "error[E0425]: cannot find value `totl` in this scope".into(),
+            file: "src/lib.rs".into(),
+            line: 4,
+            column: 5,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_get_error_context_returns_snippet() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::write(
+        dir.path().join("src/lib.rs"),
+        "pub fn sum() -> i32 {\n    let unused = 1;\n    let total = 2;\n    totl\n}\n",
+    )
+    .unwrap();
+    let project = Arc::new(Project::new(dir.path().to_path_buf()));
+    let params = || error_context::GetErrorContextParams {
+        context_lines: Some(1),
+    };
+
+    let result = error_context::get_error_context(params(), project.clone()).await;
+    assert_eq!(result, serde_json::json!("No cargo command has been run yet"));
+
+    project.set_last_cargo_output(CARGO_BUILD_ERRORS.to_string());
+    let result = error_context::get_error_context(params(), project).await;
+
+    assert_eq!(
+        result.as_str().unwrap(),
+        "error[E0425]: cannot find value `totl` in this scope\n--> src/lib.rs:4:5\n  3 |     let total = 2;\n> 4 |     totl\n  5 | }"
+    );
+}
+
+#[cfg(not(windows))]
+#[tokio::test]
+async fn test_run_command_streams_lines_before_exit() {
+    use crate::events::NixcodeEvent;
+    use crate::utils::command::{run_command, CommandOptions};
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let options = CommandOptions {
+        output_tx: Some(tx),
+        ..Default::default()
+    };
+
+    let cwd = dir.path().to_path_buf();
+    let command = tokio::spawn(async move {
+        run_command(&cwd, "echo Compiling; sleep 1; echo done", &options).await
+    });
+
+    let line = tokio::time::timeout(Duration::from_millis(800), rx.recv())
+        .await
+        .expect("No output before the command finished");
+    assert!(matches!(line, Some(NixcodeEvent::CommandOutput(line)) if line == "Compiling"));
+    assert!(!command.is_finished());
+
+    let output = command.await.unwrap().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "Compiling\ndone\n");
+    assert!(matches!(rx.recv().await, Some(NixcodeEvent::CommandOutput(line)) if line == "done"));
+}
+
+#[cfg(not(windows))]
+#[tokio::test]
+async fn test_run_command_splits_long_streamed_lines() {
+    use crate::events::NixcodeEvent;
+    use crate::utils::command::{run_command, CommandOptions};
+
+    let dir = tempfile::tempdir().unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let options = CommandOptions {
+        output_tx: Some(tx),
+        ..Default::default()
+    };
+
+    run_command(dir.path(), "yes x | head -c 40000 | tr -d '\\n'", &options)
+        .await
+        .unwrap();
+    drop(options);
+
+    let mut streamed = 0;
+    while let Some(NixcodeEvent::CommandOutput(line)) = rx.recv().await {
+        assert!(line.len() <= 4096);
+        streamed += line.len();
+    }
+    assert_eq!(streamed, 20000);
+}
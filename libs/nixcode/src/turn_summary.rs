@@ -0,0 +1,218 @@
+use std::fmt::{Display, Formatter};
+
+use nixcode_llm_sdk::message::content::tools::{ToolResultContent, ToolUseContent};
+use nixcode_llm_sdk::message::content::Content;
+use nixcode_llm_sdk::message::message::Message;
+use serde_json::Value;
+
+use crate::tools::Tools;
+
+/// Command run by a tool and whether it succeeded, None when the result does not tell
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandOutcome {
+    pub command: String,
+    pub succeeded: Option<bool>,
+}
+
+/// Digest of one autonomous turn: the tool calls of a response and their results
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TurnSummary {
+    /// Ids of the summarized tool calls
+    pub tool_use_ids: Vec<String>,
+    pub tools: usize,
+    /// Tool calls that failed to execute
+    pub errors: usize,
+    pub files_changed: Vec<String>,
+    pub commands: Vec<CommandOutcome>,
+}
+
+impl TurnSummary {
+    /// Summarizes the calls, the changed files are reported by the `tools` that ran them
+    pub fn new(calls: &[ToolUseContent], results: &[ToolResultContent], tools: &Tools) -> Self {
+        let mut summary = TurnSummary {
+            tool_use_ids: calls.iter().map(ToolUseContent::get_id).collect(),
+            tools: calls.len(),
+            ..Default::default()
+        };
+
+        for call in calls {
+            let Some(result) = results
+                .iter()
+                .find(|result| result.get_tool_use_id() == call.get_id())
+            else {
+                continue;
+            };
+            if result.is_error() {
+                summary.errors += 1;
+                continue;
+            }
+
+            let (name, input) = call.get_execute_params();
+            let content = result.get_content();
+            for file in tools.changed_paths(&name, &input, &content) {
+                if !summary.files_changed.contains(&file) {
+                    summary.files_changed.push(file);
+                }
+            }
+            if let Some(command) = get_command(&name, &input) {
+                summary.commands.push(CommandOutcome {
+                    command,
+                    succeeded: command_succeeded(&content),
+                });
+            }
+        }
+
+        summary
+    }
+
+    /// Whether `message` is the response whose tool calls are summarized, it may have
+    /// been removed since
+    pub fn is_for(&self, message: &Message) -> bool {
+        message.get_content().iter().any(|content| match content {
+            Content::ToolUse(tool_use) => self.tool_use_ids.contains(&tool_use.get_id()),
+            _ => false,
+        })
+    }
+
+    /// Detail lines shown when the summary is expanded
+    pub fn get_details(&self) -> Vec<String> {
+        let mut details = self
+            .files_changed
+            .iter()
+            .map(|file| format!("changed {}", file))
+            .collect::<Vec<_>>();
+
+        details.extend(self.commands.iter().map(|outcome| {
+            let status = match outcome.succeeded {
+                Some(true) => "ok",
+                Some(false) => "failed",
+                None => "done",
+            };
+            format!("ran {} ({})", outcome.command, status)
+        }));
+
+        details
+    }
+}
+
+impl Display for TurnSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize, noun: &str| match count {
+            1 => format!("1 {}", noun),
+            count => format!("{} {}s", count, noun),
+        };
+
+        write!(f, "{}", plural(self.tools, "tool call"))?;
+        if self.errors > 0 {
+            write!(f, ", {}", plural(self.errors, "error"))?;
+        }
+        if !self.files_changed.is_empty() {
+            write!(f, ", {} changed", plural(self.files_changed.len(), "file"))?;
+        }
+        if !self.commands.is_empty() {
+            let failed = self
+                .commands
+                .iter()
+                .filter(|outcome| outcome.succeeded == Some(false))
+                .count();
+            write!(f, ", {} run", plural(self.commands.len(), "command"))?;
+            if failed > 0 {
+                write!(f, " ({} failed)", failed)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Command line of tools running commands
+fn get_command(name: &str, input: &Value) -> Option<String> {
+    match name {
+        "run_task" => Some(format!("task {}", input["task"].as_str()?)),
+        "cargo_test" => match input["filter"].as_str() {
+            Some(filter) => Some(format!("cargo test {}", filter)),
+            None => Some("cargo test".to_string()),
+        },
+        "coverage" => Some("coverage".to_string()),
+        _ => None,
+    }
+}
+
+fn command_succeeded(content: &str) -> Option<bool> {
+    if let Some(exit_code) = content.strip_prefix("Exit code: ") {
+        return Some(exit_code.starts_with("0\n"));
+    }
+    if content.starts_with("Failed to run") {
+        return Some(false);
+    }
+    if content.starts_with("Tests succeeded") {
+        return Some(true);
+    }
+
+    // Structured cargo test results
+    let result = serde_json::from_str::<Value>(content).ok()?;
+    result["failed"].as_u64().map(|failed| failed == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::fs::delete_file::DeleteFileTool;
+    use crate::tools::fs::update_text_file_partial::UpdateTextFilePartialTool;
+    use crate::tools::fs::write_text_file::WriteTextFileTool;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_turn_summary_lists_changed_files_and_commands() {
+        let calls = vec![
+            ToolUseContent::new("toolu_1", "read_text_file", json!({ "path": "src/lib.rs" })),
+            ToolUseContent::new(
+                "toolu_2",
+                "write_text_file",
+                json!({ "path": "src/lib.rs" }),
+            ),
+            ToolUseContent::new(
+                "toolu_3",
+                "update_text_file_partial",
+                json!({ "path": "a.rs" }),
+            ),
+            ToolUseContent::new("toolu_4", "run_task", json!({ "task": "test" })),
+            ToolUseContent::new("toolu_5", "delete_file", json!({ "path": "b.rs" })),
+        ];
+        let results = vec![
+            calls[0].create_response("fn main() {}"),
+            calls[1].create_response("File updated"),
+            calls[2].create_response("Snippet not found in the file"),
+            calls[3].create_response("Exit code: 101\n\nstderr:\nfailed"),
+            calls[4].create_error_response("Error executing tool"),
+        ];
+
+        let mut tools = Tools::new();
+        tools.add_tool(Arc::new(WriteTextFileTool {}));
+        tools.add_tool(Arc::new(UpdateTextFilePartialTool {}));
+        tools.add_tool(Arc::new(DeleteFileTool {}));
+
+        let summary = TurnSummary::new(&calls, &results, &tools);
+
+        assert_eq!(summary.files_changed, vec!["src/lib.rs".to_string()]);
+        let response = Message::Assistant(vec![Content::new_tool_use(calls[4].clone())]);
+        assert!(summary.is_for(&response));
+        assert!(!summary.is_for(&Message::Assistant(vec![Content::new_text("Done")])));
+        assert_eq!(
+            summary.commands,
+            vec![CommandOutcome {
+                command: "task test".into(),
+                succeeded: Some(false),
+            }]
+        );
+        assert_eq!(
+            summary.to_string(),
+            "5 tool calls, 1 error, 1 file changed, 1 command run (1 failed)"
+        );
+        assert_eq!(
+            summary.get_details(),
+            vec!["changed src/lib.rs", "ran task test (failed)"]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+use crate::events::NixcodeEvent;
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Output, Stdio};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Default number of bytes of stdout (and of stderr) kept from a command
+pub const DEFAULT_MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// Name suffixes of environment variables that usually hold secrets
+const SENSITIVE_VAR_SUFFIXES: [&str; 6] = [
+    "API_KEY",
+    "TOKEN",
+    "SECRET",
+    "SECRET_KEY",
+    "PASSWORD",
+    "ACCESS_KEY",
+];
+
+/// Environment, working directory and output limit of commands run by tools
+#[derive(Debug, Clone)]
+pub struct CommandOptions {
+    /// Only these variables are passed to commands, when not set all variables
+    /// except the sensitive ones are passed
+    pub env_allowlist: Option<Vec<String>>,
+    /// Working directory relative to the project root
+    pub working_dir: Option<PathBuf>,
+    /// Bytes of stdout (and of stderr) kept, half from the start and half from the end,
+    /// the middle is read and dropped
+    pub max_output_bytes: usize,
+    /// Receives each line of stdout and stderr as `CommandOutput` while the command
+    /// runs, independently of `max_output_bytes`
+    pub output_tx: Option<UnboundedSender<NixcodeEvent>>,
+}
+
+impl Default for CommandOptions {
+    fn default() -> Self {
+        Self {
+            env_allowlist: None,
+            working_dir: None,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            output_tx: None,
+        }
+    }
+}
+
+impl CommandOptions {
+    /// Returns true if the variable is passed to commands
+    pub fn is_var_allowed(&self, name: &str) -> bool {
+        match &self.env_allowlist {
+            Some(allowlist) => allowlist.iter().any(|allowed| allowed == name),
+            None => !is_sensitive_var(name),
+        }
+    }
+
+    fn get_env(&self) -> Vec<(OsString, OsString)> {
+        std::env::vars_os()
+            .filter(|(name, _)| name.to_str().is_some_and(|name| self.is_var_allowed(name)))
+            .collect()
+    }
+}
+
+/// Returns true for variables like `ANTHROPIC_API_KEY` or `GITHUB_TOKEN`
+pub fn is_sensitive_var(name: &str) -> bool {
+    let name = name.to_uppercase();
+
+    SENSITIVE_VAR_SUFFIXES
+        .iter()
+        .any(|suffix| name == *suffix || name.ends_with(&format!("_{}", suffix)))
+}
+
+/// Longest line sent while a command runs, longer lines are sent in pieces
+const MAX_STREAMED_LINE_BYTES: usize = 4096;
+
+/// Sends complete lines of a stream as they arrive
+struct LineForwarder<'a> {
+    tx: Option<&'a UnboundedSender<NixcodeEvent>>,
+    partial: Vec<u8>,
+}
+
+impl LineForwarder<'_> {
+    fn push(&mut self, bytes: &[u8]) {
+        let Some(tx) = self.tx else {
+            return;
+        };
+
+        self.partial.extend_from_slice(bytes);
+        while let Some(end) = self.partial.iter().position(|byte| *byte == b'\n') {
+            let line = self.partial.drain(..=end).collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            tx.send(NixcodeEvent::CommandOutput(line.trim_end().to_string()))
+                .ok();
+        }
+
+        // Output without newlines (progress bars, minified files) must not grow unbounded
+        while self.partial.len() >= MAX_STREAMED_LINE_BYTES {
+            let line = self
+                .partial
+                .drain(..MAX_STREAMED_LINE_BYTES)
+                .collect::<Vec<_>>();
+            let line = String::from_utf8_lossy(&line);
+            tx.send(NixcodeEvent::CommandOutput(line.to_string())).ok();
+        }
+    }
+
+    fn finish(&mut self) {
+        if let (Some(tx), false) = (self.tx, self.partial.is_empty()) {
+            let line = String::from_utf8_lossy(&self.partial);
+            tx.send(NixcodeEvent::CommandOutput(line.trim_end().to_string()))
+                .ok();
+        }
+    }
+}
+
+/// Reads the stream to the end (so the process never blocks on a full pipe),
+/// keeping the head and the tail of at most `max_bytes` in total, where errors and
+/// summaries usually are, and noting how much was dropped in between
+async fn read_capped<R: AsyncRead + Unpin>(
+    mut reader: R,
+    max_bytes: usize,
+    output_tx: Option<&UnboundedSender<NixcodeEvent>>,
+) -> std::io::Result<Vec<u8>> {
+    let head_bytes = max_bytes / 2;
+    let tail_bytes = max_bytes - head_bytes;
+    let mut output = Vec::new();
+    let mut tail: VecDeque<u8> = VecDeque::new();
+    let mut buffer = [0u8; 8192];
+    let mut dropped = 0;
+    let mut lines = LineForwarder {
+        tx: output_tx,
+        partial: Vec::new(),
+    };
+
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            lines.finish();
+            break;
+        }
+        lines.push(&buffer[..read]);
+
+        let to_head = read.min(head_bytes - output.len());
+        output.extend_from_slice(&buffer[..to_head]);
+        tail.extend(&buffer[to_head..read]);
+        let excess = tail.len().saturating_sub(tail_bytes);
+        tail.drain(..excess);
+        dropped += excess;
+    }
+
+    if dropped > 0 {
+        output.extend_from_slice(
+            format!("\n[output truncated, {} bytes omitted]\n", dropped).as_bytes(),
+        );
+    }
+    output.extend(tail);
+
+    Ok(output)
+}
+
+/// Runs a command line through the platform shell in the project directory,
+/// with the environment and working directory set by `options`
+pub async fn run_command(
+    cwd: &Path,
+    command_line: &str,
+    options: &CommandOptions,
+) -> std::io::Result<Output> {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = tokio::process::Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    };
+
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    };
+
+    let working_dir = match &options.working_dir {
+        Some(working_dir) => cwd.join(working_dir),
+        None => cwd.to_path_buf(),
+    };
+
+    command
+        .current_dir(working_dir)
+        .env_clear()
+        .envs(options.get_env())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let mut child = command.spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+
+    let (stdout, stderr, status) = tokio::try_join!(
+        read_capped(stdout, options.max_output_bytes, options.output_tx.as_ref()),
+        read_capped(stderr, options.max_output_bytes, options.output_tx.as_ref()),
+        child.wait()
+    )?;
+
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Formats exit code and captured output of a finished command for the LLM
+pub fn format_command_output(output: &Output) -> String {
+    let exit_code = output
+        .status
+        .code()
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "terminated by signal".to_string());
+
+    let mut result = format!("Exit code: {}\n", exit_code);
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.trim().is_empty() {
+        result.push_str(&format!("\nstdout:\n{}", stdout));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.trim().is_empty() {
+        result.push_str(&format!("\nstderr:\n{}", stderr));
+    }
+
+    result
+}
@@ -0,0 +1,61 @@
+use std::path::Path;
+use std::process::Stdio;
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Formatter command for the extension of the file, None for unrecognized file types
+fn get_formatter(path: &Path) -> Option<(&'static str, &'static [&'static str])> {
+    match path.extension()?.to_str()? {
+        "rs" => Some(("rustfmt", &["--emit", "stdout"])),
+        _ => None,
+    }
+}
+
+/// Formats content about to be written to `path`, running the formatter in `root` so
+/// it picks up the project's configuration (e.g. the edition in rustfmt.toml).
+///
+/// Content of unrecognized file types is returned unchanged, as is all content when the
+/// formatter is not installed. An error means the formatter could not parse the content,
+/// which usually points at a syntax error.
+pub async fn format_content(root: &Path, path: &Path, content: &str) -> Result<String, String> {
+    let Some((program, args)) = get_formatter(path) else {
+        return Ok(content.to_string());
+    };
+
+    let child = Command::new(program)
+        .args(args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn();
+
+    let Ok(mut child) = child else {
+        log::debug!("{} is not available, writing unformatted content", program);
+        return Ok(content.to_string());
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(content.as_bytes())
+            .await
+            .map_err(|e| format!("Failed to pass content to {}: {}", program, e))?;
+    }
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} could not parse the content, fix the syntax and try again:\n{}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim_end()
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -1,5 +1,7 @@
 use std::ffi::OsStr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use walkdir::WalkDir;
 
 pub fn join_path(base: impl Into<PathBuf>, path: impl Into<PathBuf>) -> anyhow::Result<PathBuf> {
     let path = path.into();
@@ -21,3 +23,201 @@ pub fn join_path(base: impl Into<PathBuf>, path: impl Into<PathBuf>) -> anyhow::
 
     Ok(base)
 }
+
+/// Joins `path` to `dir` and checks that the result stays inside `dir`, also after
+/// resolving symlinks. For paths that do not exist yet the closest existing ancestor is checked.
+pub fn resolve_path_in_dir(dir: &Path, path: impl Into<PathBuf>) -> anyhow::Result<PathBuf> {
+    let outside = || anyhow::anyhow!("Path must be inside project directory");
+
+    let joined = join_path(dir, path)?;
+    if !joined.starts_with(dir) {
+        return Err(outside());
+    }
+
+    // symlink_metadata does not follow links, so a dangling link is checked (and rejected) too
+    let mut existing = joined.as_path();
+    while existing.symlink_metadata().is_err() {
+        existing = existing.parent().ok_or_else(outside)?;
+    }
+
+    let canonical_dir = dir.canonicalize()?;
+    let canonical = existing.canonicalize().map_err(|_| outside())?;
+    if !canonical.starts_with(&canonical_dir) {
+        return Err(outside());
+    }
+
+    Ok(joined)
+}
+
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes content to a temporary file in the same directory and renames it into place,
+/// so the target is never left partially written. Permissions of an existing file are kept.
+/// A symlink is resolved first, so the file it points to is replaced instead of the link.
+pub async fn write_file_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let is_symlink = tokio::fs::symlink_metadata(path)
+        .await
+        .is_ok_and(|metadata| metadata.file_type().is_symlink());
+    let resolved;
+    let path = if is_symlink {
+        resolved = tokio::fs::canonicalize(path).await?;
+        resolved.as_path()
+    } else {
+        path
+    };
+
+    let parent = path.parent().unwrap_or(Path::new("."));
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp_path = parent.join(format!(
+        ".{}.nixcode-tmp-{}-{}",
+        file_name,
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::SeqCst)
+    ));
+
+    let permissions = tokio::fs::metadata(path)
+        .await
+        .ok()
+        .map(|metadata| metadata.permissions());
+
+    let result = async {
+        let mut file = tokio::fs::File::create(&temp_path).await?;
+        file.write_all(content).await?;
+        file.sync_all().await?;
+        drop(file);
+
+        if let Some(permissions) = permissions {
+            tokio::fs::set_permissions(&temp_path, permissions).await?;
+        }
+
+        tokio::fs::rename(&temp_path, path).await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temp_path).await;
+    }
+
+    result
+}
+
+/// Name of the file with additional, nixcode-specific ignore patterns
+pub const NIXCODE_IGNORE_FILE: &str = ".nixcodeignore";
+
+/// Reads glob patterns from `.nixcodeignore` in the given directory
+pub fn read_nixcode_ignore(base: &Path) -> Vec<glob::Pattern> {
+    let content = std::fs::read_to_string(base.join(NIXCODE_IGNORE_FILE)).unwrap_or_default();
+
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| glob::Pattern::new(line.trim_end_matches('/')).ok())
+        .collect()
+}
+
+/// Checks if relative path (or any of its parent directories) matches one of the patterns
+pub fn is_nixcode_ignored(patterns: &[glob::Pattern], rel_path: &str) -> bool {
+    let mut current = Some(Path::new(rel_path));
+
+    while let Some(path) = current {
+        if path.as_os_str().is_empty() {
+            break;
+        }
+
+        let path_str = path.to_string_lossy();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+
+        if patterns
+            .iter()
+            .any(|pattern| pattern.matches(&path_str) || pattern.matches(&file_name))
+        {
+            return true;
+        }
+
+        current = path.parent();
+    }
+
+    false
+}
+
+/// Filter for walked entries, skipping hidden, gitignored and nixcodeignored paths
+pub struct ProjectEntryFilter {
+    base: PathBuf,
+    repository: Option<git2::Repository>,
+    ignore_patterns: Vec<glob::Pattern>,
+}
+
+impl ProjectEntryFilter {
+    /// Creates a filter for entries under base, `.nixcodeignore` is read from base
+    pub fn new(base: &Path) -> Self {
+        Self {
+            base: base.to_path_buf(),
+            repository: git2::Repository::discover(base).ok(),
+            ignore_patterns: read_nixcode_ignore(base),
+        }
+    }
+
+    pub fn is_visible(&self, entry: &walkdir::DirEntry) -> bool {
+        let rel_path = match entry.path().strip_prefix(&self.base) {
+            Ok(path) => path.to_string_lossy().to_string(),
+            Err(_) => return false,
+        };
+
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            return false;
+        }
+
+        if is_nixcode_ignored(&self.ignore_patterns, &rel_path) {
+            return false;
+        }
+
+        let workdir = self.repository.as_ref().and_then(|repo| repo.workdir());
+        match (&self.repository, workdir) {
+            (Some(repo), Some(workdir)) => {
+                let repo_path = match entry.path().strip_prefix(workdir) {
+                    Ok(path) => path.to_string_lossy().to_string(),
+                    Err(_) => return true,
+                };
+                let repo_path = if entry.file_type().is_dir() {
+                    format!("{}/", repo_path)
+                } else {
+                    repo_path
+                };
+
+                !repo.is_path_ignored(repo_path).unwrap_or(false)
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Lists project files relative to base, skipping hidden, gitignored and nixcodeignored entries
+pub fn list_project_files(base: &Path, limit: usize) -> Vec<String> {
+    let filter = ProjectEntryFilter::new(base);
+
+    WalkDir::new(base)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| filter.is_visible(entry))
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(base)
+                .ok()
+                .map(|path| path.to_string_lossy().to_string())
+        })
+        .take(limit)
+        .collect()
+}
@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+/// Elements whose content is never shown as text
+const SKIPPED_ELEMENTS: [&str; 5] = ["script", "style", "noscript", "svg", "button"];
+
+/// Elements that start a new line in the extracted text
+const BLOCK_ELEMENTS: [&str; 18] = [
+    "p", "div", "section", "pre", "br", "li", "ul", "ol", "h1", "h2", "h3", "h4", "h5", "h6", "tr",
+    "table", "details", "summary",
+];
+
+/// Time limit of fetching a whole page
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Bytes of a page that are read, the rest of larger pages is ignored
+const MAX_HTML_BYTES: usize = 4 * 1024 * 1024;
+
+/// Fetches a page and returns its HTML, failing on non-success status codes
+pub async fn fetch_html(url: &str) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Cannot create HTTP client: {}", e))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Cannot fetch {}: {}", url, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Cannot fetch {}: HTTP {}", url, response.status()));
+    }
+
+    let mut html = Vec::new();
+    while html.len() < MAX_HTML_BYTES {
+        match response.chunk().await {
+            Ok(Some(chunk)) => html.extend_from_slice(&chunk),
+            Ok(None) => break,
+            Err(e) => return Err(format!("Cannot read {}: {}", url, e)),
+        }
+    }
+    html.truncate(MAX_HTML_BYTES);
+
+    Ok(String::from_utf8_lossy(&html).into_owned())
+}
+
+/// Returns the inner HTML of the first element with the given opening tag prefix,
+/// e.g. `<section id="main-content"`
+pub fn find_element<'a>(html: &'a str, opening_tag: &str) -> Option<&'a str> {
+    let start = html.find(opening_tag)?;
+    let name = opening_tag
+        .trim_start_matches('<')
+        .split_whitespace()
+        .next()?;
+    let content_start = start + html[start..].find('>')? + 1;
+
+    // Track nesting of elements with the same name to find the matching closing tag
+    let open = format!("<{}", name);
+    let close = format!("</{}>", name);
+    let mut depth = 1;
+    let mut position = content_start;
+    while depth > 0 {
+        let next_close = html[position..].find(&close)? + position;
+        match html[position..next_close].find(&open) {
+            Some(next_open) => {
+                depth += 1;
+                position += next_open + open.len();
+            }
+            None => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&html[content_start..next_close]);
+                }
+                position = next_close + close.len();
+            }
+        }
+    }
+
+    None
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Converts HTML to plain text: tags are removed, block elements start new lines,
+/// entities are decoded and blank lines are collapsed
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    let mut skipped: Option<String> = None;
+
+    while let Some(tag_start) = rest.find('<') {
+        if skipped.is_none() {
+            text.push_str(&rest[..tag_start]);
+        }
+
+        let Some(tag_length) = rest[tag_start..].find('>') else {
+            rest = "";
+            break;
+        };
+        let tag = &rest[tag_start + 1..tag_start + tag_length];
+        rest = &rest[tag_start + tag_length + 1..];
+
+        let is_closing = tag.starts_with('/');
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+
+        match &skipped {
+            Some(skipped_name) if is_closing && *skipped_name == name => skipped = None,
+            Some(_) => {}
+            None if !is_closing && SKIPPED_ELEMENTS.contains(&name.as_str()) => {
+                skipped = Some(name);
+            }
+            None if BLOCK_ELEMENTS.contains(&name.as_str()) => text.push('\n'),
+            None => {}
+        }
+    }
+
+    if skipped.is_none() {
+        text.push_str(rest);
+    }
+
+    let text = decode_entities(&text);
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(if line.trim().is_empty() { "" } else { line });
+    }
+
+    lines.join("\n").trim().to_string()
+}
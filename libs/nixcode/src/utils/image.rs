@@ -0,0 +1,40 @@
+use base64::Engine;
+use nixcode_llm_sdk::message::content::Content;
+use std::path::Path;
+
+/// Largest image accepted by the providers, the limit applies to the base64 encoded data
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// Media type of a supported image, based on the file extension
+pub fn get_image_media_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// Reads an image file into base64 encoded image content
+pub fn read_image(path: &Path) -> Result<Content, String> {
+    let media_type = get_image_media_type(path)
+        .ok_or_else(|| "Unsupported image type, use png, jpeg, gif or webp".to_string())?;
+
+    let data = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let encoded_len = data.len().div_ceil(3) * 4;
+    if encoded_len > MAX_IMAGE_BYTES {
+        return Err(format!(
+            "Image is too large ({} bytes, {} bytes base64 encoded, max {} bytes encoded)",
+            data.len(),
+            encoded_len,
+            MAX_IMAGE_BYTES
+        ));
+    }
+
+    let data = base64::engine::general_purpose::STANDARD.encode(data);
+
+    Ok(Content::new_image(media_type, data))
+}
@@ -1 +1,5 @@
+pub mod command;
+pub mod format;
 pub mod fs;
+pub mod html;
+pub mod image;